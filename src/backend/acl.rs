@@ -0,0 +1,120 @@
+use super::auth::DEFAULT_USER;
+use super::{now_ms, Backend, ClientInfo};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+// denials of the same kind within this many milliseconds of each other share an entry
+const ACL_LOG_GROUPING_MS: u64 = 60_000;
+
+/// Why a client was denied, as listed by ACL LOG.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclDenial {
+    // wrong credentials given to AUTH or HELLO
+    Auth,
+    // a command run before authenticating
+    Command,
+}
+
+impl AclDenial {
+    pub fn name(self) -> &'static str {
+        match self {
+            AclDenial::Auth => "auth",
+            AclDenial::Command => "command",
+        }
+    }
+}
+
+/// Denials of the same kind, by the same user and on the same object, that happened
+/// close to each other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AclLogEntry {
+    pub entry_id: u64,
+    pub count: u64,
+    pub reason: AclDenial,
+    // the command denied, or AUTH for failed authentications
+    pub object: String,
+    pub username: String,
+    // the CLIENT LIST line of the last client denied
+    pub client_info: String,
+    // unix milliseconds
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Recent denials, newest first, bounded by acllog-max-len.
+#[derive(Debug, Default)]
+pub(crate) struct AclLog {
+    next_id: AtomicU64,
+    entries: Mutex<VecDeque<AclLogEntry>>,
+}
+
+impl Backend {
+    /// Records that a client was denied, as `username` or the default user when None.
+    pub fn record_acl_denial(
+        &self,
+        reason: AclDenial,
+        object: &str,
+        username: Option<&str>,
+        client: Option<ClientInfo>,
+    ) {
+        let max_len = self
+            .config
+            .value("acllog-max-len")
+            .parse()
+            .unwrap_or_default();
+        let username = username.unwrap_or(DEFAULT_USER);
+        let client_info = client.map(|info| info.describe()).unwrap_or_default();
+        let now = now_ms();
+        let mut entries = self
+            .acl_log
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let similar = entries.iter().position(|entry| {
+            entry.reason == reason
+                && entry.object == object
+                && entry.username == username
+                && now.saturating_sub(entry.updated_at) < ACL_LOG_GROUPING_MS
+        });
+        let entry = match similar.and_then(|index| entries.remove(index)) {
+            Some(mut entry) => {
+                entry.count += 1;
+                entry.client_info = client_info;
+                entry.updated_at = now;
+                entry
+            }
+            None => AclLogEntry {
+                entry_id: self.acl_log.next_id.fetch_add(1, Ordering::Relaxed),
+                count: 1,
+                reason,
+                object: object.to_string(),
+                username: username.to_string(),
+                client_info,
+                created_at: now,
+                updated_at: now,
+            },
+        };
+        entries.push_front(entry);
+        entries.truncate(max_len);
+    }
+
+    /// The `count` most recent entries of the ACL log, newest first.
+    pub fn acl_log(&self, count: usize) -> Vec<AclLogEntry> {
+        let entries = self
+            .acl_log
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        entries.iter().take(count).cloned().collect()
+    }
+
+    /// Empties the ACL log.
+    pub fn acl_log_reset(&self) {
+        self.acl_log
+            .entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
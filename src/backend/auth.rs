@@ -0,0 +1,40 @@
+use super::{Backend, BackendError};
+
+// the only user, which requirepass sets the password of
+pub(super) const DEFAULT_USER: &str = "default";
+
+impl Backend {
+    /// Whether clients have to AUTH before running commands, which is the case once
+    /// requirepass is set.
+    pub fn auth_required(&self) -> bool {
+        !self.config.value("requirepass").is_empty()
+    }
+
+    /// Checks the credentials given to AUTH, against the default user when `username`
+    /// is None.
+    pub fn check_credentials(
+        &self,
+        username: Option<&str>,
+        password: &str,
+    ) -> Result<(), BackendError> {
+        let requirepass = self.config.value("requirepass");
+        if requirepass.is_empty() && username.is_none() {
+            return Err(BackendError::NoPasswordConfigured);
+        }
+        let user_matches = username.is_none_or(|username| username == DEFAULT_USER);
+        // with no password set the default user accepts any
+        let password_matches =
+            requirepass.is_empty() || constant_time_eq(password.as_bytes(), requirepass.as_bytes());
+        if user_matches && password_matches {
+            Ok(())
+        } else {
+            Err(BackendError::WrongPass)
+        }
+    }
+}
+
+// compares every byte whatever the first mismatch, so that timing doesn't leak how much
+// of the password a guess got right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
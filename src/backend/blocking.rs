@@ -0,0 +1,116 @@
+use super::Backend;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Registration of a client blocked on a set of keys, removed again when dropped.
+#[derive(Debug)]
+pub struct KeyWaiter {
+    backend: Backend,
+    keys: Vec<String>,
+    notify: Arc<Notify>,
+}
+
+impl KeyWaiter {
+    /// Resolves once one of the keys may have become ready. A wakeup that happens before
+    /// this is awaited is not lost, so the caller can check its keys first.
+    pub async fn ready(&self) {
+        self.notify.notified().await
+    }
+}
+
+impl Drop for KeyWaiter {
+    fn drop(&mut self) {
+        for key in &self.keys {
+            if let Some(mut waiters) = self.backend.db.blocked.get_mut(key) {
+                waiters.retain(|notify| !Arc::ptr_eq(notify, &self.notify));
+            }
+            self.backend
+                .db
+                .blocked
+                .remove_if(key, |_, waiters| waiters.is_empty());
+        }
+        self.backend
+            .stats
+            .blocked_clients
+            .fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Backend {
+    /// Blocks the calling client on `keys` until the returned waiter is dropped.
+    pub fn block_on_keys(&self, keys: &[String]) -> KeyWaiter {
+        let notify = Arc::new(Notify::new());
+        for key in keys {
+            self.db
+                .blocked
+                .entry(key.clone())
+                .or_default()
+                .push(notify.clone());
+        }
+        self.stats.blocked_clients.fetch_add(1, Ordering::Relaxed);
+        KeyWaiter {
+            backend: self.clone(),
+            keys: keys.to_vec(),
+            notify,
+        }
+    }
+
+    // wakes every client blocked on `key`, they retry their command and whoever comes
+    // second may block again
+    pub(crate) fn signal_key_ready(&self, key: &str) {
+        if let Some(waiters) = self.db.blocked.get(key) {
+            for notify in waiters.iter() {
+                notify.notify_one();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ListEnd;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    #[tokio::test]
+    async fn test_push_wakes_blocked_client() {
+        let backend = Backend::new();
+        let waiter = backend.block_on_keys(&["list".to_string()]);
+        assert!(backend.db.blocked.contains_key("list"));
+
+        // the wakeup is kept until the waiter gets to await it
+        backend
+            .push("list".to_string(), ListEnd::Right, vec![b"a".into()])
+            .unwrap();
+        assert!(timeout(Duration::from_secs(1), waiter.ready())
+            .await
+            .is_ok());
+        assert!(timeout(Duration::from_millis(10), waiter.ready())
+            .await
+            .is_err());
+
+        drop(waiter);
+        assert!(!backend.db.blocked.contains_key("list"));
+    }
+
+    #[tokio::test]
+    async fn test_swapdb_wakes_blocked_clients() {
+        let backend = Backend::new();
+        let other = backend.select(1).unwrap();
+        let waiter = backend.block_on_keys(&["list".to_string()]);
+        let other_waiter = other.block_on_keys(&["list".to_string()]);
+
+        // both go look at the keys of the database now in place of theirs
+        backend.swap_dbs(0, 1).unwrap();
+        assert_eq!(backend.db_index(), 1);
+        assert_eq!(other.db_index(), 0);
+        assert!(timeout(Duration::from_secs(1), waiter.ready())
+            .await
+            .is_ok());
+        assert!(timeout(Duration::from_secs(1), other_waiter.ready())
+            .await
+            .is_ok());
+    }
+}
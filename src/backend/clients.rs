@@ -0,0 +1,677 @@
+use super::pubsub::Outbox;
+use super::{AclDenial, Backend, BackendError, TrackingOptions};
+use crate::{RespFrame, SimpleString};
+use dashmap::DashMap;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, mpsc, Notify};
+
+/// Metadata of a connected client, as listed by CLIENT LIST.
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub id: u64,
+    pub addr: SocketAddr,
+    pub laddr: SocketAddr,
+    // empty until the client names itself
+    pub name: String,
+    pub created_at: Instant,
+    pub last_interaction: Instant,
+    // lowercase name of the last command sent, or being executed
+    pub last_command: String,
+    pub db: usize,
+    // in MONITOR mode
+    pub monitor: bool,
+}
+
+impl ClientInfo {
+    /// A line of CLIENT LIST describing the client, without the trailing newline.
+    pub fn describe(&self) -> String {
+        let mut line = String::new();
+        // writing to a String never fails
+        let _ = write!(
+            line,
+            "id={} addr={} laddr={} name={} age={} idle={} flags={} db={} cmd={}",
+            self.id,
+            self.addr,
+            self.laddr,
+            self.name,
+            self.created_at.elapsed().as_secs(),
+            self.last_interaction.elapsed().as_secs(),
+            if self.monitor { "O" } else { "N" },
+            self.db,
+            if self.last_command.is_empty() {
+                "NULL"
+            } else {
+                &self.last_command
+            },
+        );
+        line
+    }
+}
+
+/// Which clients CLIENT KILL closes. Every filter that is set has to match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientFilter {
+    pub id: Option<u64>,
+    // "ip:port" of the client
+    pub addr: Option<String>,
+    // "ip:port" of the server side of the connection
+    pub laddr: Option<String>,
+    // clients connected for longer than this
+    pub max_age: Option<Duration>,
+}
+
+impl ClientFilter {
+    fn matches(&self, info: &ClientInfo) -> bool {
+        self.id.is_none_or(|id| id == info.id)
+            && self
+                .addr
+                .as_ref()
+                .is_none_or(|addr| *addr == info.addr.to_string())
+            && self
+                .laddr
+                .as_ref()
+                .is_none_or(|laddr| *laddr == info.laddr.to_string())
+            && self
+                .max_age
+                .is_none_or(|max_age| info.created_at.elapsed() > max_age)
+    }
+}
+
+/// Which commands CLIENT PAUSE holds back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseMode {
+    // commands that may write to the keyspace
+    Write,
+    All,
+}
+
+/// Whether a client gets replies, as set by CLIENT REPLY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyMode {
+    On,
+    Off,
+    // no reply to CLIENT REPLY SKIP itself and to the command after it
+    Skip,
+}
+
+/// Every connected client, keyed by id.
+#[derive(Debug)]
+pub(crate) struct ClientRegistry {
+    clients: DashMap<u64, ClientInfo>,
+    // signals the task of a connection to close it
+    kills: DashMap<u64, Arc<Notify>>,
+    next_id: AtomicU64,
+    // until when commands are held back, and which ones
+    pause: Mutex<Option<(Instant, PauseMode)>>,
+    // wakes up the clients held back when the pause is lifted or changed
+    unpaused: Notify,
+}
+
+impl Default for ClientRegistry {
+    fn default() -> Self {
+        Self {
+            clients: DashMap::new(),
+            kills: DashMap::new(),
+            next_id: AtomicU64::new(1),
+            pause: Mutex::new(None),
+            unpaused: Notify::new(),
+        }
+    }
+}
+
+// RESP version of new connections. Unlike redis, which starts clients on RESP2, the server
+// has always replied with RESP3 types, so clients have to ask for RESP2 with HELLO 2
+const DEFAULT_PROTOCOL: u8 = 3;
+
+/// State of a connection, registered as a client for as long as it is alive.
+#[derive(Debug)]
+pub struct Connection {
+    backend: Backend,
+    id: u64,
+    killed: Arc<Notify>,
+    replies_off: bool,
+    // replies left to skip, counting the one to CLIENT REPLY SKIP
+    skipped_replies: u8,
+    // the feed of MONITOR, once the client asked for it
+    monitor: Option<broadcast::Receiver<String>>,
+    // index of the database selected with SELECT
+    db: usize,
+    // whether the client may run commands, which takes an AUTH once requirepass is set
+    authenticated: bool,
+    // RESP version negotiated with HELLO
+    protocol: u8,
+    // set by QUIT, the connection is closed once the reply is sent
+    closing: bool,
+    // replies to send before the one to the command executed, when it has several
+    queued_replies: Vec<RespFrame>,
+    // pub/sub messages for the client, sent through `outbox`
+    outbox: Outbox,
+    inbox: mpsc::UnboundedReceiver<RespFrame>,
+    // channels subscribed to with SUBSCRIBE
+    channels: BTreeSet<String>,
+    // glob patterns subscribed to with PSUBSCRIBE
+    patterns: BTreeSet<String>,
+    // shard channels subscribed to with SSUBSCRIBE
+    shard_channels: BTreeSet<String>,
+    // set while CLIENT TRACKING is on, invalidations are sent through `outbox` too
+    tracking: Option<TrackingOptions>,
+    // commands queued since MULTI, None outside of a transaction
+    transaction: Option<Vec<RespFrame>>,
+    // set when a command could not be queued, EXEC then discards the transaction
+    transaction_failed: bool,
+}
+
+impl Connection {
+    pub fn new(backend: &Backend, addr: SocketAddr, laddr: SocketAddr) -> Self {
+        let registry = &backend.clients;
+        let id = registry.next_id.fetch_add(1, Ordering::Relaxed);
+        let now = Instant::now();
+        let info = ClientInfo {
+            id,
+            addr,
+            laddr,
+            name: String::new(),
+            created_at: now,
+            last_interaction: now,
+            last_command: String::new(),
+            db: 0,
+            monitor: false,
+        };
+        let killed = Arc::new(Notify::new());
+        registry.clients.insert(id, info);
+        registry.kills.insert(id, killed.clone());
+        backend.stats.record_connection();
+        let (outbox, inbox) = mpsc::unbounded_channel();
+        Self {
+            backend: backend.clone(),
+            id,
+            killed,
+            replies_off: false,
+            skipped_replies: 0,
+            monitor: None,
+            db: 0,
+            authenticated: !backend.auth_required(),
+            protocol: DEFAULT_PROTOCOL,
+            closing: false,
+            queued_replies: Vec::new(),
+            outbox,
+            inbox,
+            channels: BTreeSet::new(),
+            patterns: BTreeSet::new(),
+            shard_channels: BTreeSet::new(),
+            tracking: None,
+            transaction: None,
+            transaction_failed: false,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Resolves once the client is killed, even if that happened before this is
+    /// awaited. The connection should be closed then.
+    pub fn killed(&self) -> impl std::future::Future<Output = ()> + 'static {
+        let killed = self.killed.clone();
+        async move { killed.notified().await }
+    }
+
+    /// Records that the client sent `command`.
+    pub fn record_command(&self, command: &str) {
+        if let Some(mut info) = self.backend.clients.clients.get_mut(&self.id) {
+            info.last_interaction = Instant::now();
+            info.last_command = command.to_string();
+        }
+    }
+
+    /// Names the client, or clears its name when `name` is empty.
+    pub fn set_name(&self, name: String) {
+        if let Some(mut info) = self.backend.clients.clients.get_mut(&self.id) {
+            info.name = name;
+        }
+    }
+
+    /// Index of the database the commands of the client run against.
+    pub fn db(&self) -> usize {
+        self.db
+    }
+
+    /// Switches the client to database `index`, which the caller checked exists.
+    pub fn select(&mut self, index: usize) {
+        self.db = index;
+        if let Some(mut info) = self.backend.clients.clients.get_mut(&self.id) {
+            info.db = index;
+        }
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    /// Authenticates the client with the credentials given to AUTH. A failed attempt
+    /// leaves the client as it was.
+    pub fn authenticate(
+        &mut self,
+        username: Option<&str>,
+        password: &str,
+    ) -> Result<(), BackendError> {
+        if let Err(e) = self.backend.check_credentials(username, password) {
+            if matches!(e, BackendError::WrongPass) {
+                self.backend
+                    .record_acl_denial(AclDenial::Auth, "AUTH", username, self.info());
+            }
+            return Err(e);
+        }
+        self.authenticated = true;
+        Ok(())
+    }
+
+    /// Closes the connection once the reply to the command being executed is sent.
+    pub fn close_after_reply(&mut self) {
+        self.closing = true;
+    }
+
+    pub fn is_closing(&self) -> bool {
+        self.closing
+    }
+
+    /// Queues a reply to send ahead of the one to the command being executed, for
+    /// commands like SUBSCRIBE that reply once per argument.
+    pub fn queue_reply(&mut self, frame: RespFrame) {
+        self.queued_replies.push(frame);
+    }
+
+    /// Replies queued by the command just executed, in order.
+    pub fn take_queued_replies(&mut self) -> Vec<RespFrame> {
+        std::mem::take(&mut self.queued_replies)
+    }
+
+    /// Subscribes the client to `channel`. Returns how many channels and patterns it is
+    /// subscribed to.
+    pub fn subscribe(&mut self, channel: &str) -> usize {
+        if self.channels.insert(channel.to_string()) {
+            self.backend
+                .subscribe_channel(channel, self.id, self.outbox.clone());
+        }
+        self.subscription_count()
+    }
+
+    /// Unsubscribes the client from `channel`. Returns how many channels and patterns it
+    /// is still subscribed to.
+    pub fn unsubscribe(&mut self, channel: &str) -> usize {
+        if self.channels.remove(channel) {
+            self.backend.unsubscribe_channel(channel, self.id);
+        }
+        self.subscription_count()
+    }
+
+    /// Channels the client is subscribed to, sorted by name.
+    pub fn channels(&self) -> Vec<String> {
+        self.channels.iter().cloned().collect()
+    }
+
+    /// Subscribes the client to the channels matching the glob `pattern`. Returns how many
+    /// channels and patterns it is subscribed to.
+    pub fn psubscribe(&mut self, pattern: &str) -> usize {
+        if self.patterns.insert(pattern.to_string()) {
+            self.backend
+                .subscribe_pattern(pattern, self.id, self.outbox.clone());
+        }
+        self.subscription_count()
+    }
+
+    /// Unsubscribes the client from `pattern`. Returns how many channels and patterns it
+    /// is still subscribed to.
+    pub fn punsubscribe(&mut self, pattern: &str) -> usize {
+        if self.patterns.remove(pattern) {
+            self.backend.unsubscribe_pattern(pattern, self.id);
+        }
+        self.subscription_count()
+    }
+
+    /// Patterns the client is subscribed to, sorted.
+    pub fn patterns(&self) -> Vec<String> {
+        self.patterns.iter().cloned().collect()
+    }
+
+    /// How many channels and patterns the client is subscribed to.
+    pub fn subscription_count(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    /// Subscribes the client to the shard channel `channel`. Returns how many shard
+    /// channels it is subscribed to.
+    pub fn ssubscribe(&mut self, channel: &str) -> usize {
+        if self.shard_channels.insert(channel.to_string()) {
+            self.backend
+                .subscribe_shard_channel(channel, self.id, self.outbox.clone());
+        }
+        self.shard_subscription_count()
+    }
+
+    /// Unsubscribes the client from the shard channel `channel`. Returns how many shard
+    /// channels it is still subscribed to.
+    pub fn sunsubscribe(&mut self, channel: &str) -> usize {
+        if self.shard_channels.remove(channel) {
+            self.backend.unsubscribe_shard_channel(channel, self.id);
+        }
+        self.shard_subscription_count()
+    }
+
+    /// Shard channels the client is subscribed to, sorted by name.
+    pub fn shard_channels(&self) -> Vec<String> {
+        self.shard_channels.iter().cloned().collect()
+    }
+
+    pub fn shard_subscription_count(&self) -> usize {
+        self.shard_channels.len()
+    }
+
+    /// Whether the client is subscribed to any channel, pattern or shard channel.
+    pub fn is_subscribed(&self) -> bool {
+        self.subscription_count() + self.shard_subscription_count() > 0
+    }
+
+    /// The next frame pushed to the client outside of replies: a line of the MONITOR
+    /// feed or a message published to a channel it is subscribed to.
+    pub async fn next_push(&mut self) -> RespFrame {
+        tokio::select! {
+            line = next_monitor_line(&mut self.monitor) => SimpleString::new(line).into(),
+            // the connection holds a sender, so the channel is never closed
+            Some(frame) = self.inbox.recv() => frame,
+        }
+    }
+
+    /// Turns tracking on with `options`, replacing the ones it was on with if any.
+    pub fn enable_tracking(&mut self, options: TrackingOptions) -> Result<(), BackendError> {
+        self.backend.enable_tracking(
+            self.id,
+            options.clone(),
+            self.outbox.clone(),
+            self.protocol,
+        )?;
+        self.tracking = Some(options);
+        Ok(())
+    }
+
+    pub fn disable_tracking(&mut self) {
+        if self.tracking.take().is_some() {
+            self.backend.disable_tracking(self.id);
+        }
+    }
+
+    /// Options tracking is on with, None while it is off.
+    pub fn tracking(&self) -> Option<&TrackingOptions> {
+        self.tracking.as_ref()
+    }
+
+    /// Whether the keys read by the client are to be remembered, which broadcasting mode
+    /// does without.
+    pub fn is_tracking_reads(&self) -> bool {
+        self.tracking.as_ref().is_some_and(|options| !options.bcast)
+    }
+
+    /// Remembers that the client read `keys`, so that it is told once they change.
+    pub fn track_reads(&self, keys: impl IntoIterator<Item = String>) {
+        if self.is_tracking_reads() {
+            self.backend.track_keys(self.id, keys);
+        }
+    }
+
+    /// Opens a transaction, queuing the commands that follow until EXEC or DISCARD.
+    /// Returns false if one is already open.
+    pub fn begin_transaction(&mut self) -> bool {
+        if self.transaction.is_some() {
+            return false;
+        }
+        self.transaction = Some(Vec::new());
+        true
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    /// Queues `frame` to be executed by EXEC, if a transaction is open.
+    pub fn queue_command(&mut self, frame: RespFrame) {
+        if let Some(queued) = &mut self.transaction {
+            queued.push(frame);
+        }
+    }
+
+    /// Marks the open transaction as failed, for a command that could not be queued.
+    pub fn fail_transaction(&mut self) {
+        self.transaction_failed = self.transaction.is_some();
+    }
+
+    /// Whether a command of the open transaction could not be queued.
+    pub fn is_transaction_failed(&self) -> bool {
+        self.transaction_failed
+    }
+
+    /// Closes the open transaction, returning its queued commands, None if no
+    /// transaction is open.
+    pub fn take_transaction(&mut self) -> Option<Vec<RespFrame>> {
+        self.transaction_failed = false;
+        self.transaction.take()
+    }
+
+    /// RESP version the replies to the client are encoded with.
+    pub fn protocol(&self) -> u8 {
+        self.protocol
+    }
+
+    /// Switches the client to RESP `version`, which the caller checked is 2 or 3.
+    pub fn set_protocol(&mut self, version: u8) {
+        self.protocol = version;
+        if self.tracking.is_some() {
+            self.backend.set_tracking_protocol(self.id, version);
+        }
+    }
+
+    pub fn set_reply_mode(&mut self, mode: ReplyMode) {
+        match mode {
+            ReplyMode::On => {
+                self.replies_off = false;
+                self.skipped_replies = 0;
+            }
+            ReplyMode::Off => self.replies_off = true,
+            ReplyMode::Skip if !self.replies_off => self.skipped_replies = 2,
+            ReplyMode::Skip => {}
+        }
+    }
+
+    /// Whether the reply to the command just executed is to be sent, given the reply
+    /// mode. Called once per command.
+    pub fn should_reply(&mut self) -> bool {
+        if self.replies_off {
+            return false;
+        }
+        if self.skipped_replies > 0 {
+            self.skipped_replies -= 1;
+            return false;
+        }
+        true
+    }
+
+    /// Switches the client into MONITOR mode, receiving every command processed from
+    /// then on.
+    pub fn start_monitor(&mut self) {
+        if self.monitor.is_none() {
+            self.monitor = Some(self.backend.subscribe_monitor());
+        }
+        if let Some(mut info) = self.backend.clients.clients.get_mut(&self.id) {
+            info.monitor = true;
+        }
+    }
+
+    pub fn is_monitor(&self) -> bool {
+        self.monitor.is_some()
+    }
+
+    /// The next line of the MONITOR feed, never resolving unless the client is in MONITOR
+    /// mode. Lines the client fell too far behind on are skipped.
+    pub async fn next_monitor_line(&mut self) -> String {
+        next_monitor_line(&mut self.monitor).await
+    }
+
+    /// Puts the connection back in the state of a new one: replies on, out of MONITOR
+    /// mode, on the first database and default protocol, subscribed to nothing, tracking
+    /// off, out of any transaction and no longer authenticated if a password is required.
+    /// Its name is kept.
+    pub fn reset(&mut self) {
+        self.disable_tracking();
+        self.take_transaction();
+        for channel in self.channels() {
+            self.unsubscribe(&channel);
+        }
+        for pattern in self.patterns() {
+            self.punsubscribe(&pattern);
+        }
+        for channel in self.shard_channels() {
+            self.sunsubscribe(&channel);
+        }
+        self.set_reply_mode(ReplyMode::On);
+        self.protocol = DEFAULT_PROTOCOL;
+        self.authenticated = !self.backend.auth_required();
+        self.select(0);
+        self.monitor = None;
+        if let Some(mut info) = self.backend.clients.clients.get_mut(&self.id) {
+            info.monitor = false;
+        }
+    }
+
+    /// Metadata of this client.
+    pub fn info(&self) -> Option<ClientInfo> {
+        self.backend.client(self.id)
+    }
+}
+
+async fn next_monitor_line(monitor: &mut Option<broadcast::Receiver<String>>) -> String {
+    let Some(monitor) = monitor else {
+        return std::future::pending().await;
+    };
+    loop {
+        match monitor.recv().await {
+            Ok(line) => return line,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            // the sender lives as long as the backend
+            Err(broadcast::error::RecvError::Closed) => std::future::pending().await,
+        }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        for channel in &self.channels {
+            self.backend.unsubscribe_channel(channel, self.id);
+        }
+        for pattern in &self.patterns {
+            self.backend.unsubscribe_pattern(pattern, self.id);
+        }
+        for channel in &self.shard_channels {
+            self.backend.unsubscribe_shard_channel(channel, self.id);
+        }
+        if self.tracking.is_some() {
+            self.backend.disable_tracking(self.id);
+        }
+        self.backend.clients.clients.remove(&self.id);
+        self.backend.clients.kills.remove(&self.id);
+        self.backend.stats.record_disconnection();
+    }
+}
+
+impl Backend {
+    pub fn client(&self, id: u64) -> Option<ClientInfo> {
+        self.clients.clients.get(&id).map(|info| info.clone())
+    }
+
+    /// Every connected client, sorted by id.
+    pub fn client_list(&self) -> Vec<ClientInfo> {
+        let mut clients = self
+            .clients
+            .clients
+            .iter()
+            .map(|info| info.value().clone())
+            .collect::<Vec<_>>();
+        clients.sort_by_key(|info| info.id);
+        clients
+    }
+
+    /// Closes the clients matching `filter`, except for the one with id `skip`. Returns
+    /// how many were killed.
+    pub fn kill_clients(&self, filter: &ClientFilter, skip: Option<u64>) -> usize {
+        let ids = self
+            .clients
+            .clients
+            .iter()
+            .filter(|info| Some(info.id) != skip && filter.matches(info))
+            .map(|info| info.id)
+            .collect::<Vec<_>>();
+        ids.iter()
+            .filter(|id| match self.clients.kills.get(id) {
+                Some(killed) => {
+                    killed.notify_one();
+                    true
+                }
+                None => false,
+            })
+            .count()
+    }
+
+    /// Holds back the commands of every client selected by `mode` for `duration`. A
+    /// pause that is already in effect is only ever extended, and made stricter.
+    pub fn pause_clients(&self, duration: Duration, mode: PauseMode) {
+        let mut pause = self.clients.pause.lock().unwrap_or_else(|e| e.into_inner());
+        let mut until = Instant::now() + duration;
+        let mut mode = mode;
+        if let Some((current_until, current_mode)) = *pause {
+            if current_until > Instant::now() {
+                until = until.max(current_until);
+                if current_mode == PauseMode::All {
+                    mode = PauseMode::All;
+                }
+            }
+        }
+        *pause = Some((until, mode));
+        self.clients.unpaused.notify_waiters();
+    }
+
+    /// Lifts the pause, letting the clients held back go on.
+    pub fn unpause_clients(&self) {
+        let mut pause = self.clients.pause.lock().unwrap_or_else(|e| e.into_inner());
+        *pause = None;
+        self.clients.unpaused.notify_waiters();
+    }
+
+    /// Resolves once a command that writes to the keyspace or not, according to
+    /// `write`, is no longer held back by CLIENT PAUSE.
+    pub async fn wait_unpaused(&self, write: bool) {
+        loop {
+            // registered before the pause is checked so that lifting it in between is
+            // not missed
+            let unpaused = self.clients.unpaused.notified();
+            tokio::pin!(unpaused);
+            unpaused.as_mut().enable();
+
+            let pause = *self.clients.pause.lock().unwrap_or_else(|e| e.into_inner());
+            let until = match pause {
+                Some((until, mode))
+                    if until > Instant::now() && (write || mode == PauseMode::All) =>
+                {
+                    until
+                }
+                _ => return,
+            };
+            tokio::select! {
+                _ = tokio::time::sleep_until(until.into()) => {}
+                _ = unpaused => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,510 @@
+use super::notify::{keyspace_event_flags, parse_keyspace_events};
+use super::{Backend, BackendError};
+use crate::glob_match;
+use anyhow::Context;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+use std::{fs, io};
+use tracing::level_filters::LevelFilter;
+
+const LOG_LEVELS: &[&str] = &["debug", "verbose", "notice", "warning", "nothing"];
+const MAXMEMORY_POLICIES: &[&str] = &[
+    "volatile-lru",
+    "volatile-lfu",
+    "volatile-random",
+    "volatile-ttl",
+    "allkeys-lru",
+    "allkeys-lfu",
+    "allkeys-random",
+    "noeviction",
+];
+
+/// A parameter of the runtime configuration.
+struct Parameter {
+    name: &'static str,
+    default: &'static str,
+    // immutable parameters can only be set by the config file
+    mutable: bool,
+    // validates a value, returning it the way CONFIG GET replies with it
+    parse: fn(&str) -> Result<String, String>,
+}
+
+// sorted by name
+const PARAMETERS: &[Parameter] = &[
+    Parameter {
+        name: "acllog-max-len",
+        default: "128",
+        mutable: true,
+        parse: parse_int::<0, { i64::MAX }>,
+    },
+    Parameter {
+        name: "active-expire-effort",
+        default: "1",
+        mutable: true,
+        parse: parse_int::<1, 10>,
+    },
+    Parameter {
+        name: "databases",
+        default: "16",
+        mutable: false,
+        parse: parse_int::<1, { i32::MAX as i64 }>,
+    },
+    Parameter {
+        name: "dbfilename",
+        default: "dump.rdb",
+        mutable: true,
+        parse: parse_filename,
+    },
+    Parameter {
+        name: "dir",
+        default: ".",
+        mutable: true,
+        parse: parse_dir,
+    },
+    Parameter {
+        name: "hz",
+        default: "10",
+        mutable: true,
+        parse: parse_hz,
+    },
+    Parameter {
+        name: "latency-monitor-threshold",
+        default: "0",
+        mutable: true,
+        parse: parse_int::<0, { i64::MAX }>,
+    },
+    Parameter {
+        name: "loglevel",
+        default: "notice",
+        mutable: true,
+        parse: |value| parse_enum(value, LOG_LEVELS),
+    },
+    Parameter {
+        name: "maxmemory",
+        default: "0",
+        mutable: true,
+        parse: parse_memory,
+    },
+    Parameter {
+        name: "maxmemory-policy",
+        default: "noeviction",
+        mutable: true,
+        parse: |value| parse_enum(value, MAXMEMORY_POLICIES),
+    },
+    Parameter {
+        name: "notify-keyspace-events",
+        default: "",
+        mutable: true,
+        parse: parse_keyspace_events,
+    },
+    Parameter {
+        name: "port",
+        default: "6379",
+        mutable: false,
+        parse: parse_int::<0, 65535>,
+    },
+    Parameter {
+        name: "requirepass",
+        default: "",
+        mutable: true,
+        parse: |value| Ok(value.to_string()),
+    },
+    Parameter {
+        name: "save",
+        default: "3600 1 300 100 60 10000",
+        mutable: true,
+        parse: parse_save,
+    },
+    Parameter {
+        name: "timeout",
+        default: "0",
+        mutable: true,
+        parse: parse_int::<0, { i32::MAX as i64 }>,
+    },
+];
+
+/// Values of the runtime configuration, along with parsed copies of the ones read on
+/// hot paths.
+#[derive(Debug)]
+pub(crate) struct Config {
+    values: RwLock<HashMap<&'static str, String>>,
+    // file the configuration was loaded from, rewritten by CONFIG REWRITE
+    file: RwLock<Option<PathBuf>>,
+    // seconds, 0 to never close idle clients
+    timeout: AtomicU64,
+    // index into LOG_LEVELS
+    loglevel: AtomicU8,
+    // classes of notify-keyspace-events, checked on every write
+    keyspace_events: AtomicU32,
+    // bytes, 0 for no limit, checked before every command that may use more memory
+    maxmemory: AtomicU64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let values = PARAMETERS
+            .iter()
+            .map(|p| (p.name, p.default.to_string()))
+            .collect();
+        Self {
+            values: RwLock::new(values),
+            file: RwLock::new(None),
+            timeout: AtomicU64::new(0),
+            loglevel: AtomicU8::new(2),
+            keyspace_events: AtomicU32::new(0),
+            maxmemory: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Config {
+    // number of databases, which can't change once clients connect
+    pub(super) fn databases(&self) -> usize {
+        self.value("databases").parse().unwrap_or(16)
+    }
+
+    pub(super) fn value(&self, name: &str) -> String {
+        let values = self.values.read().unwrap_or_else(|e| e.into_inner());
+        values.get(name).cloned().unwrap_or_default()
+    }
+
+    pub(super) fn keyspace_events(&self) -> u32 {
+        self.keyspace_events.load(Ordering::Relaxed)
+    }
+}
+
+impl Backend {
+    /// Parameters whose name matches any of `patterns`, case-insensitively, sorted by
+    /// name.
+    pub fn config_get(&self, patterns: &[String]) -> Vec<(&'static str, String)> {
+        let values = self.config.values.read().unwrap_or_else(|e| e.into_inner());
+        PARAMETERS
+            .iter()
+            .filter(|p| {
+                patterns
+                    .iter()
+                    .any(|pattern| glob_match(pattern.as_bytes(), p.name.as_bytes(), true))
+            })
+            .map(|p| (p.name, values.get(p.name).cloned().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Sets every parameter in `pairs`, or none of them if any is unknown, immutable or
+    /// invalid. The new values take effect right away.
+    pub fn config_set(&self, pairs: &[(String, String)]) -> Result<(), BackendError> {
+        self.set_parameters(pairs, false)
+    }
+
+    fn set_parameters(
+        &self,
+        pairs: &[(String, String)],
+        allow_immutable: bool,
+    ) -> Result<(), BackendError> {
+        let mut parsed = BTreeMap::new();
+        for (name, value) in pairs {
+            let name = name.to_ascii_lowercase();
+            let Some(parameter) = PARAMETERS.iter().find(|p| p.name == name) else {
+                return Err(BackendError::UnknownConfig(name));
+            };
+            let failed = |reason: &str| BackendError::ConfigSet(name.clone(), reason.to_string());
+            if !parameter.mutable && !allow_immutable {
+                return Err(failed("can't set immutable config"));
+            }
+            let value = (parameter.parse)(value).map_err(|reason| failed(&reason))?;
+            if parsed.insert(parameter.name, value).is_some() {
+                return Err(failed("duplicate parameter"));
+            }
+        }
+
+        let mut values = self
+            .config
+            .values
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        for (name, value) in parsed {
+            self.apply_config(name, &value);
+            values.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Loads the configuration from a file of `name value` lines, where `#` starts a
+    /// comment, and remembers it for CONFIG REWRITE.
+    pub fn load_config(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("can't read config file {}", path.display()))?;
+        for (i, line) in content.lines().enumerate() {
+            let Some((name, value)) = parse_config_line(line) else {
+                continue;
+            };
+            self.set_parameters(&[(name, value)], true)
+                .with_context(|| format!("{}:{}: bad directive", path.display(), i + 1))?;
+        }
+        let mut file = self.config.file.write().unwrap_or_else(|e| e.into_inner());
+        *file = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    /// Writes the current configuration back to the file it was loaded from. Lines of
+    /// the file are updated in place, keeping comments and their order, and parameters
+    /// changed from their defaults are appended.
+    pub fn config_rewrite(&self) -> Result<(), BackendError> {
+        let path = self
+            .config
+            .file
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let path = path.ok_or(BackendError::NoConfigFile)?;
+        let failed = |e: io::Error| BackendError::ConfigRewrite(e.to_string());
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(failed(e)),
+        };
+        let values = self.config.values.read().unwrap_or_else(|e| e.into_inner());
+        let mut written = HashSet::new();
+        let mut lines = Vec::new();
+        for line in content.lines() {
+            let parameter = parse_config_line(line)
+                .and_then(|(name, _)| PARAMETERS.iter().find(|p| p.name == name));
+            match parameter {
+                // later occurrences of a parameter would be overridden anyway
+                Some(p) if !written.insert(p.name) => {}
+                Some(p) => lines.push(format_config_line(p.name, &values[p.name])),
+                None => lines.push(line.to_string()),
+            }
+        }
+        for p in PARAMETERS {
+            if !written.contains(p.name) && values[p.name] != p.default {
+                lines.push(format_config_line(p.name, &values[p.name]));
+            }
+        }
+        drop(values);
+
+        // replace the file at once so that a failure doesn't leave it half written
+        let mut content = lines.join("\n");
+        content.push('\n');
+        let tmp = path.with_extension("rewrite.tmp");
+        fs::write(&tmp, content).map_err(failed)?;
+        fs::rename(&tmp, &path).map_err(failed)
+    }
+
+    /// Port the server listens on.
+    pub fn port(&self) -> u16 {
+        self.config.value("port").parse().unwrap_or(6379)
+    }
+
+    // makes a newly set value take effect
+    fn apply_config(&self, name: &str, value: &str) {
+        let config = &self.config;
+        match name {
+            "hz" | "active-expire-effort" => {
+                let (mut hz, mut effort) = self.active_expire_config();
+                let value = value.parse().unwrap_or_default();
+                if name == "hz" {
+                    hz = value;
+                } else {
+                    effort = value;
+                }
+                self.configure_active_expire(hz, effort);
+            }
+            "timeout" => {
+                let timeout = value.parse().unwrap_or_default();
+                config.timeout.store(timeout, Ordering::Relaxed);
+            }
+            "latency-monitor-threshold" => {
+                let threshold = value.parse().unwrap_or_default();
+                self.latency.threshold.store(threshold, Ordering::Relaxed);
+            }
+            "databases" => {
+                // the value was validated to be at least 1
+                let count = value.parse().unwrap_or(1);
+                self.resize_dbs(count);
+            }
+            "loglevel" => {
+                let level = LOG_LEVELS.iter().position(|l| *l == value).unwrap_or(2);
+                config.loglevel.store(level as u8, Ordering::Relaxed);
+            }
+            "notify-keyspace-events" => {
+                let flags = keyspace_event_flags(value).unwrap_or_default();
+                config.keyspace_events.store(flags, Ordering::Relaxed);
+            }
+            "maxmemory" => {
+                let maxmemory = value.parse().unwrap_or_default();
+                config.maxmemory.store(maxmemory, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+
+    /// How long a client may stay idle before it is disconnected, if at all.
+    pub fn client_timeout(&self) -> Option<Duration> {
+        match self.config.timeout.load(Ordering::Relaxed) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
+
+    /// Most verbose level of the server logs, mapped from the redis log levels.
+    pub fn log_level(&self) -> LevelFilter {
+        match self.config.loglevel.load(Ordering::Relaxed) {
+            0 => LevelFilter::DEBUG,
+            1 => LevelFilter::INFO,
+            2 => LevelFilter::WARN,
+            3 => LevelFilter::ERROR,
+            _ => LevelFilter::OFF,
+        }
+    }
+
+    /// Memory limit in bytes, 0 for no limit.
+    pub fn maxmemory(&self) -> u64 {
+        self.config.maxmemory.load(Ordering::Relaxed)
+    }
+
+    /// Policy reported for maxmemory. No key is ever evicted to make room, so every
+    /// policy behaves like noeviction.
+    pub fn maxmemory_policy(&self) -> String {
+        self.config.value("maxmemory-policy")
+    }
+
+    /// File snapshots are written to and loaded from, dbfilename in dir.
+    pub fn snapshot_path(&self) -> PathBuf {
+        Path::new(&self.config.value("dir")).join(self.config.value("dbfilename"))
+    }
+
+    /// Whether any save point is configured, in which case SHUTDOWN saves by default.
+    pub fn save_configured(&self) -> bool {
+        !self.config.value("save").is_empty()
+    }
+
+    // the `seconds changes` pairs of the save config
+    pub(super) fn save_points(&self) -> Vec<(u64, u64)> {
+        let numbers: Vec<u64> = self
+            .config
+            .value("save")
+            .split_whitespace()
+            .filter_map(|n| n.parse().ok())
+            .collect();
+        numbers
+            .chunks_exact(2)
+            .map(|rule| (rule[0], rule[1]))
+            .collect()
+    }
+}
+
+// name and value of a line of the config file, unless it's blank or a comment
+fn parse_config_line(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (name, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+    Some((name.to_ascii_lowercase(), value.to_string()))
+}
+
+fn format_config_line(name: &str, value: &str) -> String {
+    if value.is_empty() {
+        format!("{} \"\"", name)
+    } else {
+        format!("{} {}", name, value)
+    }
+}
+
+fn parse_int<const MIN: i64, const MAX: i64>(value: &str) -> Result<String, String> {
+    let n: i64 = value
+        .parse()
+        .map_err(|_| "argument couldn't be parsed into an integer".to_string())?;
+    if !(MIN..=MAX).contains(&n) {
+        return Err(format!(
+            "argument must be between {} and {} inclusive",
+            MIN, MAX
+        ));
+    }
+    Ok(n.to_string())
+}
+
+// like redis, out of range frequencies are clamped rather than refused
+fn parse_hz(value: &str) -> Result<String, String> {
+    let hz: i64 = parse_int::<0, { i32::MAX as i64 }>(value)?
+        .parse()
+        .unwrap_or_default();
+    Ok(hz.clamp(1, 500).to_string())
+}
+
+fn parse_enum(value: &str, allowed: &[&str]) -> Result<String, String> {
+    let value = value.to_ascii_lowercase();
+    if allowed.contains(&value.as_str()) {
+        Ok(value)
+    } else {
+        Err(format!(
+            "argument(s) must be one of the following: {}",
+            allowed.join(", ")
+        ))
+    }
+}
+
+// a number of bytes, optionally followed by a unit such as kb, m or gb
+fn parse_memory(value: &str) -> Result<String, String> {
+    let value = value.to_ascii_lowercase();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (digits, unit) = value.split_at(split);
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" => 1000,
+        "kb" => 1024,
+        "m" => 1000 * 1000,
+        "mb" => 1024 * 1024,
+        "g" => 1000 * 1000 * 1000,
+        "gb" => 1024 * 1024 * 1024,
+        _ => return Err("argument must be a memory value".to_string()),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .map(|bytes| bytes.to_string())
+        .ok_or_else(|| "argument must be a memory value".to_string())
+}
+
+fn parse_filename(value: &str) -> Result<String, String> {
+    if value.is_empty() || value.contains('/') {
+        return Err("dbfilename can't be a path, just a filename".to_string());
+    }
+    Ok(value.to_string())
+}
+
+fn parse_dir(value: &str) -> Result<String, String> {
+    if !Path::new(value).is_dir() {
+        return Err("No such file or directory".to_string());
+    }
+    Ok(value.to_string())
+}
+
+// pairs of "seconds changes", snapshotting after that many changes in that many seconds
+fn parse_save(value: &str) -> Result<String, String> {
+    let numbers = value
+        .split_whitespace()
+        .map(|n| n.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()
+        .filter(|numbers| numbers.len() % 2 == 0)
+        .filter(|numbers| numbers.chunks(2).all(|rule| rule[0] > 0))
+        .ok_or_else(|| "Invalid save parameters".to_string())?;
+    Ok(numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
@@ -0,0 +1,100 @@
+use super::object::KeyAccess;
+use super::zset::SortedSet;
+use super::{Backend, BackendError};
+use crate::RespFrame;
+use dashmap::DashMap;
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Keys of one of the logical databases clients pick with SELECT.
+#[derive(Debug, Default)]
+pub(crate) struct Db {
+    // position in the databases, which changes when SWAPDB moves it
+    pub(crate) index: AtomicUsize,
+    pub(crate) map: DashMap<String, RespFrame>,
+    pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
+    // absolute expiration time of a key, in unix milliseconds
+    pub(crate) expire: DashMap<String, u64>,
+    // absolute expiration time of individual hash fields, in unix milliseconds
+    pub(crate) hexpire: DashMap<String, DashMap<String, u64>>,
+    // when and how often each key was accessed, for OBJECT
+    pub(crate) access: DashMap<String, KeyAccess>,
+    pub(crate) list: DashMap<String, VecDeque<RespFrame>>,
+    pub(crate) set: DashMap<String, HashSet<Vec<u8>>>,
+    pub(crate) zset: DashMap<String, SortedSet>,
+    // clients blocked on a key, woken up when it is written to
+    pub(crate) blocked: DashMap<String, Vec<Arc<Notify>>>,
+}
+
+impl Db {
+    pub(crate) fn new(index: usize) -> Self {
+        Db {
+            index: AtomicUsize::new(index),
+            ..Default::default()
+        }
+    }
+}
+
+impl Backend {
+    /// Number of databases, as set by the databases config.
+    pub fn databases(&self) -> usize {
+        self.dbs.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// A handle on database `index`, sharing everything but the keyspace with this one.
+    pub fn select(&self, index: usize) -> Result<Backend, BackendError> {
+        let dbs = self.dbs.read().unwrap_or_else(|e| e.into_inner());
+        let db = dbs.get(index).ok_or(BackendError::DbOutOfRange)?;
+        Ok(Backend {
+            inner: self.inner.clone(),
+            db: db.clone(),
+        })
+    }
+
+    /// Exchanges the keys of databases `a` and `b`, so that clients that selected one
+    /// see the keys of the other from their next command on. Clients blocked on keys of
+    /// either are woken up to look at the keys now in their place.
+    pub fn swap_dbs(&self, a: usize, b: usize) -> Result<(), BackendError> {
+        let _guard = self.exclusive_lock();
+        let mut dbs = self.dbs.write().unwrap_or_else(|e| e.into_inner());
+        if a >= dbs.len() || b >= dbs.len() {
+            return Err(BackendError::DbOutOfRange);
+        }
+        dbs.swap(a, b);
+        dbs[a].index.store(a, Ordering::Relaxed);
+        dbs[b].index.store(b, Ordering::Relaxed);
+        for db in [&dbs[a], &dbs[b]] {
+            for waiters in db.blocked.iter() {
+                waiters.iter().for_each(|notify| notify.notify_one());
+            }
+        }
+        Ok(())
+    }
+
+    // a handle on each database, in order
+    pub(super) fn all_dbs(&self) -> Vec<Backend> {
+        let dbs = self.dbs.read().unwrap_or_else(|e| e.into_inner());
+        dbs.iter()
+            .map(|db| Backend {
+                inner: self.inner.clone(),
+                db: db.clone(),
+            })
+            .collect()
+    }
+
+    // index of the database this handle is on, which changes when SWAPDB moves it
+    pub(crate) fn db_index(&self) -> usize {
+        self.db.index.load(Ordering::Relaxed)
+    }
+
+    // grows or shrinks the databases to `count`, which only happens while loading the
+    // config so no client has selected a database that goes away
+    pub(super) fn resize_dbs(&self, count: usize) {
+        let mut dbs = self.dbs.write().unwrap_or_else(|e| e.into_inner());
+        dbs.truncate(count);
+        let len = dbs.len();
+        dbs.extend((len..count).map(|index| Arc::new(Db::new(index))));
+    }
+}
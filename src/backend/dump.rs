@@ -0,0 +1,278 @@
+use super::notify::NOTIFY_GENERIC;
+use super::{now_ms, string_bytes, value::Value, Backend, BackendError, KeyAccess, SortedSet};
+use crate::{BulkString, RespFrame};
+use dashmap::DashMap;
+use std::collections::{HashSet, VecDeque};
+
+// bumped whenever the layout of a payload changes, older payloads are rejected
+const DUMP_VERSION: u16 = 1;
+
+const TYPE_STRING: u8 = 0;
+const TYPE_LIST: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_ZSET: u8 = 3;
+const TYPE_HASH: u8 = 4;
+
+/// How RESTORE writes the value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RestoreOptions {
+    // REPLACE: overwrite the key if it exists
+    pub replace: bool,
+    // absolute expiration time in unix milliseconds
+    pub expire_at: Option<u64>,
+    // IDLETIME: seconds since the value was last accessed
+    pub idle_time: Option<u64>,
+    // FREQ: access frequency counter
+    pub freq: Option<u8>,
+}
+
+impl Backend {
+    // length of the value at `key` once serialized, without the version and checksum
+    pub(super) fn serialized_length(&self, key: &str) -> Option<usize> {
+        let value = self.clone_value(key)?;
+        encode_value(&value).map(|payload| payload.len())
+    }
+
+    /// Serializes the value at `key`, None if the key does not exist.
+    ///
+    /// A payload is a type tag and the value, followed by the format version and a
+    /// CRC-64 of everything before it, all integers being little endian.
+    pub fn dump(&self, key: &str) -> Option<Vec<u8>> {
+        self.lookup_read(key)?;
+        self.expire_fields_if_needed(key);
+        dump_value(&self.clone_value(key)?)
+    }
+
+    /// Recreates the value serialized by DUMP in `payload` at `key`.
+    pub fn restore(
+        &self,
+        key: &str,
+        payload: &[u8],
+        options: RestoreOptions,
+    ) -> Result<(), BackendError> {
+        let value = decode_payload(payload).ok_or(BackendError::BadPayload)?;
+        let _guard = self.shared_lock();
+        if self.contains_key(key) {
+            if !options.replace {
+                return Err(BackendError::BusyKey);
+            }
+            self.delete_key(key);
+        }
+        // a deadline that already passed leaves the key deleted
+        if options.expire_at.is_some_and(|at| at <= now_ms()) {
+            return Ok(());
+        }
+        self.insert_value(key.to_string(), value);
+        if let Some(at) = options.expire_at {
+            self.db.expire.insert(key.to_string(), at);
+        }
+        let access = KeyAccess::restored(now_ms(), options.idle_time, options.freq);
+        self.db.access.insert(key.to_string(), access);
+        self.notify(NOTIFY_GENERIC, "restore", key);
+        self.signal_key_ready(key);
+        Ok(())
+    }
+}
+
+// payload of DUMP for `value`, None if it holds something that is not a string
+pub(super) fn dump_value(value: &Value) -> Option<Vec<u8>> {
+    let mut payload = encode_value(value)?;
+    payload.extend_from_slice(&DUMP_VERSION.to_le_bytes());
+    let checksum = crc64(&payload);
+    payload.extend_from_slice(&checksum.to_le_bytes());
+    Some(payload)
+}
+
+fn encode_value(value: &Value) -> Option<Vec<u8>> {
+    let mut out = vec![];
+    match value {
+        Value::String(frame) => {
+            out.push(TYPE_STRING);
+            put_bytes(&mut out, &string_bytes(frame).ok()?);
+        }
+        Value::List(list) => {
+            out.push(TYPE_LIST);
+            put_len(&mut out, list.len());
+            for element in list {
+                put_bytes(&mut out, &string_bytes(element).ok()?);
+            }
+        }
+        Value::Set(set) => {
+            out.push(TYPE_SET);
+            put_len(&mut out, set.len());
+            for member in set {
+                put_bytes(&mut out, member);
+            }
+        }
+        Value::ZSet(zset) => {
+            out.push(TYPE_ZSET);
+            put_len(&mut out, zset.len());
+            for (member, score) in zset.iter() {
+                put_bytes(&mut out, member);
+                out.extend_from_slice(&score.to_le_bytes());
+            }
+        }
+        Value::Hash(hmap, ttls) => {
+            out.push(TYPE_HASH);
+            put_len(&mut out, hmap.len());
+            for entry in hmap.iter() {
+                put_bytes(&mut out, entry.key().as_bytes());
+                put_bytes(&mut out, &string_bytes(entry.value()).ok()?);
+                // 0 for a field without TTL
+                let ttl = ttls
+                    .as_ref()
+                    .and_then(|ttls| ttls.get(entry.key()).map(|at| *at))
+                    .unwrap_or(0);
+                out.extend_from_slice(&ttl.to_le_bytes());
+            }
+        }
+    }
+    Some(out)
+}
+
+pub(super) fn decode_payload(payload: &[u8]) -> Option<Value> {
+    let (body, checksum) = payload.split_at_checked(payload.len().checked_sub(8)?)?;
+    if crc64(body).to_le_bytes() != checksum {
+        return None;
+    }
+    let (body, version) = body.split_at_checked(body.len().checked_sub(2)?)?;
+    if version != DUMP_VERSION.to_le_bytes() {
+        return None;
+    }
+
+    let mut reader = Reader(body);
+    let value = match reader.u8()? {
+        TYPE_STRING => Value::String(BulkString::new(reader.bytes()?).into()),
+        TYPE_LIST => {
+            let len = reader.len()?;
+            let mut list = VecDeque::new();
+            for _ in 0..len {
+                list.push_back(BulkString::new(reader.bytes()?).into());
+            }
+            Value::List(list)
+        }
+        TYPE_SET => {
+            let len = reader.len()?;
+            let mut set = HashSet::new();
+            for _ in 0..len {
+                set.insert(reader.bytes()?);
+            }
+            Value::Set(set)
+        }
+        TYPE_ZSET => {
+            let len = reader.len()?;
+            let mut zset = SortedSet::default();
+            for _ in 0..len {
+                let member = reader.bytes()?;
+                let score = f64::from_le_bytes(reader.array()?);
+                if score.is_nan() {
+                    return None;
+                }
+                zset.insert(member, score);
+            }
+            Value::ZSet(zset)
+        }
+        TYPE_HASH => {
+            let len = reader.len()?;
+            let hmap = DashMap::new();
+            let ttls = DashMap::new();
+            for _ in 0..len {
+                let field = String::from_utf8(reader.bytes()?).ok()?;
+                let value: RespFrame = BulkString::new(reader.bytes()?).into();
+                match u64::from_le_bytes(reader.array()?) {
+                    0 => {}
+                    at => {
+                        ttls.insert(field.clone(), at);
+                    }
+                }
+                hmap.insert(field, value);
+            }
+            let ttls = (!ttls.is_empty()).then_some(ttls);
+            Value::Hash(hmap, ttls)
+        }
+        _ => return None,
+    };
+    // an empty collection never exists in the keyspace
+    if !reader.0.is_empty() || value.len() == 0 {
+        return None;
+    }
+    Some(value)
+}
+
+pub(super) fn put_len(out: &mut Vec<u8>, len: usize) {
+    out.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+pub(super) fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    put_len(out, bytes.len());
+    out.extend_from_slice(bytes);
+}
+
+// reads a payload front to back, None once it runs short
+pub(super) struct Reader<'a>(pub(super) &'a [u8]);
+
+impl Reader<'_> {
+    pub(super) fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let (head, rest) = self.0.split_first_chunk::<N>()?;
+        self.0 = rest;
+        Some(*head)
+    }
+
+    pub(super) fn u8(&mut self) -> Option<u8> {
+        self.array::<1>().map(|[b]| b)
+    }
+
+    pub(super) fn len(&mut self) -> Option<usize> {
+        usize::try_from(u64::from_le_bytes(self.array()?)).ok()
+    }
+
+    pub(super) fn bytes(&mut self) -> Option<Vec<u8>> {
+        let len = self.len()?;
+        let (head, rest) = self.0.split_at_checked(len)?;
+        self.0 = rest;
+        Some(head.to_vec())
+    }
+}
+
+// CRC-64/Jones, the variant redis checksums its payloads with
+pub(super) fn crc64(data: &[u8]) -> u64 {
+    const POLY: u64 = 0x95ac_9329_ac4b_c9b5;
+    let mut crc = 0u64;
+    for byte in data {
+        crc ^= *byte as u64;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc64_check_value() {
+        // the check value of CRC-64/Jones as listed in redis' crc64.c
+        assert_eq!(crc64(b"123456789"), 0xe9c6_d914_c4b8_d9ca);
+    }
+
+    #[test]
+    fn test_decode_rejects_corrupted_payloads() {
+        let mut payload = encode_value(&Value::Set(HashSet::from([b"a".to_vec()]))).unwrap();
+        payload.extend_from_slice(&DUMP_VERSION.to_le_bytes());
+        let checksum = crc64(&payload);
+        payload.extend_from_slice(&checksum.to_le_bytes());
+        assert!(decode_payload(&payload).is_some());
+
+        let mut corrupted = payload.clone();
+        corrupted[1] ^= 1;
+        assert!(decode_payload(&corrupted).is_none());
+        assert!(decode_payload(&payload[..payload.len() - 1]).is_none());
+        assert!(decode_payload(&[]).is_none());
+    }
+}
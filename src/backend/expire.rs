@@ -0,0 +1,236 @@
+use super::notify::{NOTIFY_EXPIRED, NOTIFY_GENERIC};
+use super::{now_ms, Backend, ExpireCondition, LatencyEvent};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+// keys sampled per loop of the active expire cycle at the lowest effort
+const ACTIVE_EXPIRE_KEYS_PER_LOOP: u64 = 20;
+// share of each period the cycle may run for at the lowest effort, in percent
+const ACTIVE_EXPIRE_TIME_PERC: u64 = 25;
+// percentage of expired keys in a sample under which the cycle stops at the lowest effort
+const ACTIVE_EXPIRE_ACCEPTABLE_STALE: u64 = 10;
+
+// tuning of the active expire cycle, read on every run so it can be changed live
+#[derive(Debug)]
+pub(crate) struct ActiveExpireConfig {
+    // runs per second, from 1 to 500
+    hz: AtomicU64,
+    // from 1 to 10, higher values spend more CPU to keep fewer expired keys around
+    effort: AtomicU64,
+    // turned off by DEBUG SET-ACTIVE-EXPIRE, leaving expired keys to lazy expiration
+    enabled: AtomicBool,
+}
+
+impl Default for ActiveExpireConfig {
+    fn default() -> Self {
+        Self {
+            hz: AtomicU64::new(10),
+            effort: AtomicU64::new(1),
+            enabled: AtomicBool::new(true),
+        }
+    }
+}
+
+impl Backend {
+    /// Sets the TTL of `key` to `expire_at`, in unix milliseconds, if `condition` holds. A
+    /// deadline that has already passed deletes the key right away. Returns whether the
+    /// key exists and the condition was met.
+    pub fn expire(&self, key: &str, expire_at: u64, condition: Option<ExpireCondition>) -> bool {
+        let _guard = self.shared_lock();
+        if !self.contains_key(key) {
+            return false;
+        }
+        let current = self.db.expire.get(key).map(|at| *at);
+        if !condition.is_none_or(|c| c.allows(current, expire_at)) {
+            return false;
+        }
+        if expire_at <= now_ms() {
+            self.delete_key(key);
+            self.notify(NOTIFY_GENERIC, "del", key);
+        } else {
+            self.db.expire.insert(key.to_string(), expire_at);
+            self.notify(NOTIFY_GENERIC, "expire", key);
+        }
+        true
+    }
+
+    /// Removes the TTL of `key`, returning whether it had one.
+    pub fn persist(&self, key: &str) -> bool {
+        let _guard = self.shared_lock();
+        let persisted = self.contains_key(key) && self.db.expire.remove(key).is_some();
+        if persisted {
+            self.notify(NOTIFY_GENERIC, "persist", key);
+        }
+        persisted
+    }
+
+    /// Remaining TTL of `key` in milliseconds, -1 if it has none and -2 if the key does
+    /// not exist.
+    pub fn pttl(&self, key: &str) -> i64 {
+        match self.pexpiretime(key) {
+            at @ (-2 | -1) => at,
+            at => (at as u64).saturating_sub(now_ms()) as i64,
+        }
+    }
+
+    /// Absolute expiration time of `key` in unix milliseconds, -1 if it has no TTL and -2
+    /// if the key does not exist.
+    pub fn pexpiretime(&self, key: &str) -> i64 {
+        if self.lookup_read(key).is_none() {
+            return -2;
+        }
+        match self.db.expire.get(key) {
+            Some(at) => *at as i64,
+            None => -1,
+        }
+    }
+
+    /// Changes how often the active expire cycle runs and how hard it works, clamping
+    /// both to the ranges redis accepts.
+    pub fn configure_active_expire(&self, hz: u64, effort: u64) {
+        let config = &self.active_expire;
+        config.hz.store(hz.clamp(1, 500), Ordering::Relaxed);
+        config.effort.store(effort.clamp(1, 10), Ordering::Relaxed);
+    }
+
+    /// Current `(hz, effort)` of the active expire cycle.
+    pub fn active_expire_config(&self) -> (u64, u64) {
+        let config = &self.active_expire;
+        (
+            config.hz.load(Ordering::Relaxed),
+            config.effort.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Turns the active expire cycle on or off.
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.active_expire.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Spawns the task running the active expire cycle `hz` times per second. The task
+    /// ends once every other handle to the backend is dropped.
+    pub fn start_active_expire(&self) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            loop {
+                let hz = match inner.upgrade() {
+                    Some(inner) => inner.active_expire.hz.load(Ordering::Relaxed),
+                    None => return,
+                };
+                tokio::time::sleep(Duration::from_millis(1000 / hz)).await;
+                match inner.upgrade() {
+                    Some(inner) if inner.active_expire.enabled.load(Ordering::Relaxed) => {
+                        Backend::from_inner(inner).active_expire_cycle()
+                    }
+                    Some(_) => 0,
+                    None => return,
+                };
+            }
+        })
+    }
+
+    /// Deletes expired keys that nobody reads, like redis' activeExpireCycle: in each
+    /// database in turn, random keys with a TTL are sampled until few enough of them turn
+    /// out to be expired or the time budget for this run is spent. Returns the number of
+    /// keys removed.
+    pub fn active_expire_cycle(&self) -> usize {
+        let hz = self.active_expire.hz.load(Ordering::Relaxed);
+        let effort = self.active_expire.effort.load(Ordering::Relaxed) - 1;
+        let keys_per_loop = ACTIVE_EXPIRE_KEYS_PER_LOOP + ACTIVE_EXPIRE_KEYS_PER_LOOP / 4 * effort;
+        let time_perc = ACTIVE_EXPIRE_TIME_PERC + 2 * effort;
+        let acceptable_stale = ACTIVE_EXPIRE_ACCEPTABLE_STALE - effort;
+        let time_limit = Duration::from_micros(time_perc * 1_000_000 / hz / 100);
+
+        let start = Instant::now();
+        let (mut sampled, mut expired) = (0, 0);
+        'dbs: for db in self.all_dbs() {
+            loop {
+                let keys = db.sample_ttl_keys(keys_per_loop as usize);
+                if keys.is_empty() {
+                    break;
+                }
+                let removed = {
+                    // like the commands of clients, keys don't expire in the middle of EXEC
+                    let _command = self.command_lock();
+                    let _guard = db.shared_lock();
+                    keys.iter().filter(|key| db.expire_if_needed(key)).count()
+                };
+                sampled += keys.len();
+                expired += removed;
+                if removed as u64 * 100 <= keys.len() as u64 * acceptable_stale {
+                    break;
+                }
+                if start.elapsed() > time_limit {
+                    self.stats
+                        .expired_time_cap_reached
+                        .fetch_add(1, Ordering::Relaxed);
+                    break 'dbs;
+                }
+            }
+        }
+
+        self.record_latency(LatencyEvent::ExpireCycle, start.elapsed());
+        let stats = &self.stats;
+        let elapsed = start.elapsed().as_micros() as u64;
+        stats
+            .expire_cycle_micros
+            .fetch_add(elapsed, Ordering::Relaxed);
+        // like redis, the stale estimate is a moving average weighted towards history
+        let current = if sampled > 0 {
+            expired as f64 / sampled as f64
+        } else {
+            0.0
+        };
+        let _ =
+            stats
+                .expired_stale_perc
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                    Some((current * 0.05 + f64::from_bits(bits) * 0.95).to_bits())
+                });
+        expired
+    }
+
+    // picks up to `count` distinct random keys among the ones with a TTL. The map can't be
+    // indexed, so it is walked up to the last key picked
+    fn sample_ttl_keys(&self, count: usize) -> Vec<String> {
+        let len = self.db.expire.len();
+        let mut picks =
+            rand::seq::index::sample(&mut rand::thread_rng(), len, count.min(len)).into_vec();
+        picks.sort_unstable();
+        let Some(&last) = picks.last() else {
+            return Vec::new();
+        };
+        let mut picks = picks.into_iter().peekable();
+        // the map may change while it is walked, which only makes the sample smaller
+        self.db
+            .expire
+            .iter()
+            .take(last + 1)
+            .enumerate()
+            .filter_map(|(i, entry)| {
+                if picks.next_if_eq(&i).is_some() {
+                    Some(entry.key().clone())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Lazily removes `key` if its TTL has elapsed, returning true if it was removed.
+    pub(super) fn expire_if_needed(&self, key: &str) -> bool {
+        let now = now_ms();
+        // the expire lock must be released before touching the value maps
+        if self.db.expire.remove_if(key, |_, at| *at <= now).is_none() {
+            return false;
+        }
+        self.db.map.remove(key);
+        self.remove_collection(key);
+        self.db.access.remove(key);
+        self.stats.expired_keys.fetch_add(1, Ordering::Relaxed);
+        self.notify(NOTIFY_EXPIRED, "expired", key);
+        true
+    }
+}
@@ -0,0 +1,248 @@
+use super::notify::NOTIFY_GENERIC;
+use super::{now_ms, scan, Backend, BackendError, KeyType};
+use crate::glob_match;
+use rand::Rng;
+use std::sync::Arc;
+
+impl Backend {
+    /// Removes `keys` whatever their type, returning how many existed.
+    pub fn del(&self, keys: &[String]) -> usize {
+        let _guard = self.shared_lock();
+        keys.iter()
+            .filter(|key| {
+                self.expire_if_needed(key);
+                let deleted = self.delete_key(key);
+                if deleted {
+                    self.notify(NOTIFY_GENERIC, "del", key);
+                }
+                deleted
+            })
+            .count()
+    }
+
+    /// Number of `keys` that exist, a key given several times being counted every time.
+    pub fn exists(&self, keys: &[String]) -> usize {
+        keys.iter()
+            .filter(|key| self.lookup_read(key).is_some())
+            .count()
+    }
+
+    /// Moves the value at `key` and its TTL to `new_key`, replacing whatever was there
+    /// unless `nx` is set. Returns whether the value was moved.
+    pub fn rename(&self, key: &str, new_key: &str, nx: bool) -> Result<bool, BackendError> {
+        let _guard = self.exclusive_lock();
+        if !self.contains_key(key) {
+            return Err(BackendError::NoSuchKey);
+        }
+        if nx && self.contains_key(new_key) {
+            return Ok(false);
+        }
+        if key == new_key {
+            return Ok(true);
+        }
+        let Some(value) = self.take_value(key) else {
+            return Err(BackendError::NoSuchKey);
+        };
+        let ttl = self.db.expire.remove(key);
+        let access = self.db.access.remove(key);
+        self.notify(NOTIFY_GENERIC, "rename_from", key);
+        self.expire_if_needed(new_key);
+        self.delete_key(new_key);
+        self.insert_value(new_key.to_string(), value);
+        if let Some((_, at)) = ttl {
+            self.db.expire.insert(new_key.to_string(), at);
+        }
+        if let Some((_, access)) = access {
+            self.db.access.insert(new_key.to_string(), access);
+        }
+        self.notify(NOTIFY_GENERIC, "rename_to", new_key);
+        self.signal_key_ready(new_key);
+        Ok(true)
+    }
+
+    /// Moves `key` with its TTL to database `db`, unless a key of that name already exists
+    /// there. Returns whether the key was moved.
+    pub fn move_key(&self, key: &str, db: usize) -> Result<bool, BackendError> {
+        let target = self.select(db)?;
+        if Arc::ptr_eq(&self.db, &target.db) {
+            return Err(BackendError::SameObject);
+        }
+        let _guard = self.exclusive_lock();
+        if !self.contains_key(key) || target.contains_key(key) {
+            return Ok(false);
+        }
+        let Some(value) = self.take_value(key) else {
+            return Ok(false);
+        };
+        let ttl = self.db.expire.remove(key);
+        let access = self.db.access.remove(key);
+        self.notify(NOTIFY_GENERIC, "move_from", key);
+        target.insert_value(key.to_string(), value);
+        if let Some((_, at)) = ttl {
+            target.db.expire.insert(key.to_string(), at);
+        }
+        if let Some((_, access)) = access {
+            target.db.access.insert(key.to_string(), access);
+        }
+        target.notify(NOTIFY_GENERIC, "move_to", key);
+        target.signal_key_ready(key);
+        Ok(true)
+    }
+
+    /// Copies the value at `source` and its TTL to `destination` in database `db`, or in
+    /// this one, replacing whatever was there only if `replace` is set. Returns whether
+    /// the value was copied.
+    pub fn copy(
+        &self,
+        source: &str,
+        destination: &str,
+        db: Option<usize>,
+        replace: bool,
+    ) -> Result<bool, BackendError> {
+        let target = match db {
+            Some(index) => self.select(index)?,
+            None => self.clone(),
+        };
+        if source == destination && Arc::ptr_eq(&self.db, &target.db) {
+            return Err(BackendError::SameObject);
+        }
+        let _guard = self.exclusive_lock();
+        if !self.contains_key(source) {
+            return Ok(false);
+        }
+        if target.contains_key(destination) {
+            if !replace {
+                return Ok(false);
+            }
+            target.delete_key(destination);
+        }
+        let Some(value) = self.clone_value(source) else {
+            return Ok(false);
+        };
+        target.insert_value(destination.to_string(), value);
+        if let Some(at) = self.db.expire.get(source).map(|at| *at) {
+            target.db.expire.insert(destination.to_string(), at);
+        }
+        target.notify(NOTIFY_GENERIC, "copy_to", destination);
+        target.signal_key_ready(destination);
+        Ok(true)
+    }
+
+    /// Number of `keys` that exist, counting as an access to each of them.
+    pub fn touch(&self, keys: &[String]) -> usize {
+        self.exists(keys)
+    }
+
+    /// Number of live keys. The type maps keep count of their entries, so only the keys
+    /// with a TTL are walked to leave out the expired ones not removed yet.
+    pub fn dbsize(&self) -> usize {
+        let total = self.db.map.len()
+            + self.db.hmap.len()
+            + self.db.list.len()
+            + self.db.set.len()
+            + self.db.zset.len();
+        let now = now_ms();
+        let expired = self
+            .db
+            .expire
+            .iter()
+            .filter(|at| *at.value() <= now)
+            .count();
+        total.saturating_sub(expired)
+    }
+
+    /// Type of the value stored at `key`, None if the key does not exist.
+    pub fn type_of(&self, key: &str) -> Option<KeyType> {
+        self.lookup_read(key)
+    }
+
+    /// A live key picked uniformly at random, None if the keyspace is empty.
+    pub fn random_key(&self) -> Option<String> {
+        // expired keys are removed as they are drawn, so this ends once a live key shows
+        // up or the keyspace runs out
+        loop {
+            let key = self.pick_key()?;
+            if !self.expire_if_needed(&key) {
+                return Some(key);
+            }
+        }
+    }
+
+    // any key, expired or not: an index is drawn over all the type maps, then the map
+    // holding that index is walked up to it
+    fn pick_key(&self) -> Option<String> {
+        let lens = [
+            self.db.map.len(),
+            self.db.hmap.len(),
+            self.db.list.len(),
+            self.db.set.len(),
+            self.db.zset.len(),
+        ];
+        let total = lens.iter().sum::<usize>();
+        if total == 0 {
+            return None;
+        }
+        let mut index = rand::thread_rng().gen_range(0..total);
+        let map = lens.iter().position(|len| {
+            let found = index < *len;
+            if !found {
+                index -= len;
+            }
+            found
+        })?;
+        // a map that shrank in the meantime is simply drawn from again
+        let key = match map {
+            0 => self.db.map.iter().nth(index).map(|e| e.key().clone()),
+            1 => self.db.hmap.iter().nth(index).map(|e| e.key().clone()),
+            2 => self.db.list.iter().nth(index).map(|e| e.key().clone()),
+            3 => self.db.set.iter().nth(index).map(|e| e.key().clone()),
+            _ => self.db.zset.iter().nth(index).map(|e| e.key().clone()),
+        };
+        key.or_else(|| self.pick_key())
+    }
+
+    /// Names of the live keys matching the glob `pattern`.
+    pub fn keys(&self, pattern: &[u8]) -> Vec<String> {
+        let matching = self
+            .key_names()
+            .into_iter()
+            .filter(|key| glob_match(pattern, key.as_bytes(), false))
+            .collect::<Vec<_>>();
+        // every map guard is released by now, so expired keys can be removed
+        matching
+            .into_iter()
+            .filter(|key| !self.expire_if_needed(key))
+            .collect()
+    }
+
+    /// One page of an iteration over the keyspace, keeping only the keys of `key_type`
+    /// if given. See [`scan::scan`] for the cursor guarantees.
+    pub fn scan(&self, cursor: u64, count: usize, key_type: Option<KeyType>) -> (u64, Vec<String>) {
+        let (cursor, keys) = scan::scan(self.key_names(), cursor, count, |key| key.as_str());
+        let keys = keys
+            .into_iter()
+            .filter(|key| {
+                !self.expire_if_needed(key)
+                    && key_type.is_none_or(|key_type| self.key_type(key) == Some(key_type))
+            })
+            .collect();
+        (cursor, keys)
+    }
+
+    // names of the keys of every type, including the ones whose TTL has elapsed
+    pub(super) fn key_names(&self) -> Vec<String> {
+        let mut names = Vec::with_capacity(
+            self.db.map.len()
+                + self.db.hmap.len()
+                + self.db.list.len()
+                + self.db.set.len()
+                + self.db.zset.len(),
+        );
+        names.extend(self.db.map.iter().map(|e| e.key().clone()));
+        names.extend(self.db.hmap.iter().map(|e| e.key().clone()));
+        names.extend(self.db.list.iter().map(|e| e.key().clone()));
+        names.extend(self.db.set.iter().map(|e| e.key().clone()));
+        names.extend(self.db.zset.iter().map(|e| e.key().clone()));
+        names
+    }
+}
@@ -0,0 +1,160 @@
+use super::{now_ms, Backend};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// samples kept per event, like redis
+const LATENCY_HISTORY_LEN: usize = 160;
+
+/// Class of operation whose latency spikes are tracked by LATENCY.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LatencyEvent {
+    // commands that are not flagged as fast
+    Command,
+    FastCommand,
+    // a run of the active expire cycle
+    ExpireCycle,
+    // writing a snapshot of the keyspace
+    Snapshot,
+}
+
+impl LatencyEvent {
+    const ALL: [LatencyEvent; 4] = [
+        LatencyEvent::Command,
+        LatencyEvent::FastCommand,
+        LatencyEvent::ExpireCycle,
+        LatencyEvent::Snapshot,
+    ];
+
+    /// Name of the event as reported by LATENCY.
+    pub fn name(self) -> &'static str {
+        match self {
+            LatencyEvent::Command => "command",
+            LatencyEvent::FastCommand => "fast-command",
+            LatencyEvent::ExpireCycle => "expire-cycle",
+            LatencyEvent::Snapshot => "snapshot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|event| event.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// A latency spike, keeping the worst one when several happen within a second.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySample {
+    // unix seconds
+    pub time: u64,
+    // milliseconds
+    pub latency: u64,
+}
+
+/// Latest and worst spike of an event, as listed by LATENCY LATEST.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyReport {
+    pub event: LatencyEvent,
+    pub latest: LatencySample,
+    // worst spike since the event was last reset, in milliseconds
+    pub max: u64,
+}
+
+#[derive(Debug, Default)]
+struct LatencyHistory {
+    samples: VecDeque<LatencySample>,
+    max: u64,
+}
+
+/// Recent latency spikes of each event.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyMonitor {
+    // milliseconds from which an operation counts as a spike, 0 to track nothing
+    pub(super) threshold: AtomicU64,
+    events: Mutex<BTreeMap<LatencyEvent, LatencyHistory>>,
+}
+
+impl Backend {
+    /// Records that `event` took `elapsed`, if that reaches the
+    /// latency-monitor-threshold.
+    pub fn record_latency(&self, event: LatencyEvent, elapsed: Duration) {
+        let threshold = self.latency.threshold.load(Ordering::Relaxed);
+        let latency = elapsed.as_millis() as u64;
+        if threshold == 0 || latency < threshold {
+            return;
+        }
+        let time = now_ms() / 1000;
+        let mut events = self
+            .latency
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let history = events.entry(event).or_default();
+        history.max = history.max.max(latency);
+        match history.samples.back_mut() {
+            Some(last) if last.time == time => last.latency = last.latency.max(latency),
+            _ => {
+                if history.samples.len() == LATENCY_HISTORY_LEN {
+                    history.samples.pop_front();
+                }
+                history.samples.push_back(LatencySample { time, latency });
+            }
+        }
+    }
+
+    /// Latest spike of every event that had one.
+    pub fn latency_latest(&self) -> Vec<LatencyReport> {
+        let events = self
+            .latency
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        events
+            .iter()
+            .filter_map(|(event, history)| {
+                Some(LatencyReport {
+                    event: *event,
+                    latest: *history.samples.back()?,
+                    max: history.max,
+                })
+            })
+            .collect()
+    }
+
+    /// Recent spikes of `event`, oldest first.
+    pub fn latency_history(&self, event: LatencyEvent) -> Vec<LatencySample> {
+        let events = self
+            .latency
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        events
+            .get(&event)
+            .map(|history| history.samples.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Forgets the spikes of `events`, or of every event when empty. Returns how many
+    /// events had spikes.
+    pub fn latency_reset(&self, events: &[LatencyEvent]) -> usize {
+        let mut tracked = self
+            .latency
+            .events
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if events.is_empty() {
+            let count = tracked.len();
+            tracked.clear();
+            return count;
+        }
+        let mut events = events.to_vec();
+        events.sort();
+        events.dedup();
+        events
+            .iter()
+            .filter(|event| tracked.remove(event).is_some())
+            .count()
+    }
+}
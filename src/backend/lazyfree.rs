@@ -0,0 +1,96 @@
+use super::notify::NOTIFY_GENERIC;
+use super::Backend;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use std::mem;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+// collections with more elements than this are freed in the background
+const LAZYFREE_THRESHOLD: usize = 64;
+
+lazy_static! {
+    // values unlinked from the keyspace are dropped on this thread
+    static ref LAZY_FREE: Sender<Box<dyn Send>> = {
+        let (tx, rx) = mpsc::channel::<Box<dyn Send>>();
+        thread::Builder::new()
+            .name("lazy-free".into())
+            .spawn(move || rx.into_iter().for_each(drop))
+            .expect("failed to spawn the lazy free thread");
+        tx
+    };
+}
+
+impl Backend {
+    /// Like DEL, but large values are freed on a background thread instead of the caller.
+    pub fn unlink(&self, keys: &[String]) -> usize {
+        let _guard = self.shared_lock();
+        keys.iter()
+            .filter(|key| {
+                self.expire_if_needed(key);
+                let Some(value) = self.take_value(key) else {
+                    return false;
+                };
+                self.db.expire.remove(*key);
+                self.db.access.remove(*key);
+                self.notify(NOTIFY_GENERIC, "del", key);
+                if value.len() > LAZYFREE_THRESHOLD {
+                    // only fails if the thread is gone, dropping inline is fine then
+                    let _ = LAZY_FREE.send(Box::new(value));
+                }
+                true
+            })
+            .count()
+    }
+
+    /// Removes every key of this database. With `lazy` the tables holding the keyspace are
+    /// swapped for empty ones and freed whole on a background thread, so that a huge
+    /// keyspace does not stall the caller.
+    pub fn flush(&self, lazy: bool) {
+        let _guard = self.exclusive_lock();
+        self.flush_unlocked(lazy);
+        self.invalidate_all();
+    }
+
+    /// Like [`Backend::flush`], for every database at once.
+    pub fn flush_all(&self, lazy: bool) {
+        let _guard = self.exclusive_lock();
+        for db in self.all_dbs() {
+            db.flush_unlocked(lazy);
+        }
+        self.invalidate_all();
+    }
+
+    fn flush_unlocked(&self, lazy: bool) {
+        let db = &self.db;
+        if lazy {
+            free_lazily(&db.map);
+            free_lazily(&db.hmap);
+            free_lazily(&db.list);
+            free_lazily(&db.set);
+            free_lazily(&db.zset);
+            free_lazily(&db.expire);
+            free_lazily(&db.hexpire);
+            free_lazily(&db.access);
+            return;
+        }
+        self.db.map.clear();
+        self.db.hmap.clear();
+        self.db.list.clear();
+        self.db.set.clear();
+        self.db.zset.clear();
+        self.db.expire.clear();
+        self.db.hexpire.clear();
+        self.db.access.clear();
+    }
+}
+
+// swaps an empty table in for each shard of `map`, in time proportional to the number of
+// shards, and hands the old ones to the lazy free thread
+fn free_lazily<V: Send + 'static>(map: &DashMap<String, V>) {
+    for shard in map.shards() {
+        let table = mem::take(&mut *shard.write());
+        // only fails if the thread is gone, dropping inline is fine then
+        let _ = LAZY_FREE.send(Box::new(table));
+    }
+}
@@ -0,0 +1,321 @@
+use super::notify::{NOTIFY_GENERIC, NOTIFY_LIST};
+use super::{string_bytes, Backend, BackendError, KeyType};
+use crate::RespFrame;
+
+/// End of a list that elements are pushed to or popped from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEnd {
+    // LEFT: the head of the list
+    Left,
+    // RIGHT: the tail of the list
+    Right,
+}
+
+impl Backend {
+    /// Pushes `elements` one after the other onto `end` of the list at `key`, creating the
+    /// list if needed. Returns the new length of the list.
+    pub fn push(
+        &self,
+        key: String,
+        end: ListEnd,
+        elements: Vec<RespFrame>,
+    ) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.push_unlocked(key, end, elements)
+    }
+
+    fn push_unlocked(
+        &self,
+        key: String,
+        end: ListEnd,
+        elements: Vec<RespFrame>,
+    ) -> Result<usize, BackendError> {
+        self.check_type(&key, KeyType::List)?;
+        let mut list = self.db.list.entry(key).or_default();
+        for element in elements {
+            match end {
+                ListEnd::Left => list.push_front(element),
+                ListEnd::Right => list.push_back(element),
+            }
+        }
+        let len = list.len();
+        let key = list.key().clone();
+        drop(list);
+        let event = match end {
+            ListEnd::Left => "lpush",
+            ListEnd::Right => "rpush",
+        };
+        self.notify(NOTIFY_LIST, event, &key);
+        self.signal_key_ready(&key);
+        Ok(len)
+    }
+
+    /// Pops up to `count` elements from `end` of the list at `key`, deleting the key once
+    /// the list is empty. Returns None if the key does not exist.
+    pub fn pop(
+        &self,
+        key: &str,
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<Vec<RespFrame>>, BackendError> {
+        let _guard = self.shared_lock();
+        self.pop_unlocked(key, end, count)
+    }
+
+    fn pop_unlocked(
+        &self,
+        key: &str,
+        end: ListEnd,
+        count: usize,
+    ) -> Result<Option<Vec<RespFrame>>, BackendError> {
+        self.check_type(key, KeyType::List)?;
+        let popped: Vec<_> = match self.db.list.get_mut(key) {
+            Some(mut list) => {
+                let len = list.len();
+                let count = count.min(len);
+                match end {
+                    ListEnd::Left => list.drain(..count).collect(),
+                    ListEnd::Right => list.drain(len - count..).rev().collect(),
+                }
+            }
+            None => return Ok(None),
+        };
+        if !popped.is_empty() {
+            let event = match end {
+                ListEnd::Left => "lpop",
+                ListEnd::Right => "rpop",
+            };
+            self.notify(NOTIFY_LIST, event, key);
+        }
+        self.remove_list_if_empty(key);
+        Ok(Some(popped))
+    }
+
+    /// Atomically pops an element from `from` of the list at `source` and pushes it onto
+    /// `to` of the list at `destination`, returning the moved element. Returns None if
+    /// `source` does not exist.
+    pub fn lmove(
+        &self,
+        source: &str,
+        destination: String,
+        from: ListEnd,
+        to: ListEnd,
+    ) -> Result<Option<RespFrame>, BackendError> {
+        let _guard = self.exclusive_lock();
+        self.check_type(source, KeyType::List)?;
+        if !self.db.list.contains_key(source) {
+            return Ok(None);
+        }
+        // check the destination before popping so that a type error loses nothing
+        self.check_type(&destination, KeyType::List)?;
+        let Some(element) = self
+            .pop_unlocked(source, from, 1)?
+            .and_then(|mut elements| elements.pop())
+        else {
+            return Ok(None);
+        };
+        self.push_unlocked(destination, to, vec![element.clone()])?;
+        Ok(Some(element))
+    }
+
+    /// Elements of the list at `key` between `start` and `stop` inclusive, negative
+    /// indexes counting from the tail. Out of range indexes are clamped to the list.
+    pub fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<RespFrame>, BackendError> {
+        self.check_read(key, KeyType::List)?;
+        let Some(list) = self.db.list.get(key) else {
+            return Ok(vec![]);
+        };
+        Ok(match list_range(list.len(), start, stop) {
+            Some((start, stop)) => list.range(start..=stop).cloned().collect(),
+            None => vec![],
+        })
+    }
+
+    /// Indexes of the elements of the list at `key` equal to `element`. Matching starts
+    /// with the `rank`-th match, counted from the tail if negative, and stops after
+    /// `count` matches (0 for all of them) or once `maxlen` elements (0 for no limit)
+    /// were compared.
+    pub fn lpos(
+        &self,
+        key: &str,
+        element: &RespFrame,
+        rank: i64,
+        count: usize,
+        maxlen: usize,
+    ) -> Result<Vec<usize>, BackendError> {
+        self.check_read(key, KeyType::List)?;
+        let Some(list) = self.db.list.get(key) else {
+            return Ok(vec![]);
+        };
+        let element = string_bytes(element)?;
+        let len = list.len();
+        let indexes: Box<dyn Iterator<Item = usize>> = if rank < 0 {
+            Box::new((0..len).rev())
+        } else {
+            Box::new(0..len)
+        };
+        let count = if count == 0 { usize::MAX } else { count };
+        let maxlen = if maxlen == 0 { usize::MAX } else { maxlen };
+        Ok(indexes
+            .take(maxlen)
+            .filter(|&i| element_eq(&list[i], &element))
+            .skip(rank.unsigned_abs() as usize - 1)
+            .take(count)
+            .collect())
+    }
+
+    /// Length of the list at `key`, 0 if the key does not exist.
+    pub fn llen(&self, key: &str) -> Result<usize, BackendError> {
+        self.check_read(key, KeyType::List)?;
+        Ok(self.db.list.get(key).map_or(0, |list| list.len()))
+    }
+
+    /// Element at `index` of the list at `key`, negative indexes counting from the tail.
+    pub fn lindex(&self, key: &str, index: i64) -> Result<Option<RespFrame>, BackendError> {
+        self.check_read(key, KeyType::List)?;
+        let Some(list) = self.db.list.get(key) else {
+            return Ok(None);
+        };
+        Ok(list_index(list.len(), index).and_then(|i| list.get(i).cloned()))
+    }
+
+    /// Inserts `element` before or after the first occurrence of `pivot` in the list at
+    /// `key`. Returns the new length, 0 if the key does not exist and -1 if `pivot` was
+    /// not found.
+    pub fn linsert(
+        &self,
+        key: &str,
+        before: bool,
+        pivot: &RespFrame,
+        element: RespFrame,
+    ) -> Result<i64, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::List)?;
+        let Some(mut list) = self.db.list.get_mut(key) else {
+            return Ok(0);
+        };
+        let pivot = string_bytes(pivot)?;
+        let Some(index) = list.iter().position(|e| element_eq(e, &pivot)) else {
+            return Ok(-1);
+        };
+        list.insert(if before { index } else { index + 1 }, element);
+        let len = list.len();
+        drop(list);
+        self.notify(NOTIFY_LIST, "linsert", key);
+        Ok(len as i64)
+    }
+
+    /// Replaces the element at `index` of the list at `key`, negative indexes counting
+    /// from the tail.
+    pub fn lset(&self, key: &str, index: i64, element: RespFrame) -> Result<(), BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::List)?;
+        let Some(mut list) = self.db.list.get_mut(key) else {
+            return Err(BackendError::NoSuchKey);
+        };
+        let index = list_index(list.len(), index).ok_or(BackendError::IndexOutOfRange)?;
+        list[index] = element;
+        drop(list);
+        self.notify(NOTIFY_LIST, "lset", key);
+        Ok(())
+    }
+
+    /// Removes occurrences of `element` from the list at `key`: the first `count` from the
+    /// head if positive, the last `-count` from the tail if negative, all of them if 0.
+    /// Returns the number of removed elements.
+    pub fn lrem(&self, key: &str, count: i64, element: &RespFrame) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::List)?;
+        let element = string_bytes(element)?;
+        let removed = match self.db.list.get_mut(key) {
+            Some(mut list) => {
+                let limit = match count {
+                    0 => usize::MAX,
+                    n => n.unsigned_abs() as usize,
+                };
+                let mut removed = 0;
+                let mut keep = |e: &RespFrame| {
+                    if removed < limit && element_eq(e, &element) {
+                        removed += 1;
+                        return false;
+                    }
+                    true
+                };
+                if count < 0 {
+                    // walk from the tail so that the last occurrences go first
+                    let mut kept: Vec<_> = list.drain(..).rev().filter(|e| keep(e)).collect();
+                    kept.reverse();
+                    list.extend(kept);
+                } else {
+                    list.retain(|e| keep(e));
+                }
+                removed
+            }
+            None => return Ok(0),
+        };
+        if removed > 0 {
+            self.notify(NOTIFY_LIST, "lrem", key);
+        }
+        self.remove_list_if_empty(key);
+        Ok(removed)
+    }
+
+    /// Trims the list at `key` to the elements between `start` and `stop` inclusive, with
+    /// the same index rules as LRANGE. The key is deleted if nothing is left.
+    pub fn ltrim(&self, key: &str, start: i64, stop: i64) -> Result<(), BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::List)?;
+        match self.db.list.get_mut(key) {
+            Some(mut list) => match list_range(list.len(), start, stop) {
+                Some((start, stop)) => {
+                    list.truncate(stop + 1);
+                    list.drain(..start);
+                }
+                None => list.clear(),
+            },
+            None => return Ok(()),
+        }
+        self.notify(NOTIFY_LIST, "ltrim", key);
+        self.remove_list_if_empty(key);
+        Ok(())
+    }
+
+    fn remove_list_if_empty(&self, key: &str) {
+        if self
+            .db
+            .list
+            .remove_if(key, |_, list| list.is_empty())
+            .is_some()
+        {
+            self.db.expire.remove(key);
+            self.notify(NOTIFY_GENERIC, "del", key);
+        }
+    }
+}
+
+// resolves a possibly negative `index` into a list of `len` elements
+fn list_index(len: usize, index: i64) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+// clamps `start` and `stop` to a list of `len` elements, None if the range is empty
+pub(super) fn list_range(len: usize, start: i64, stop: i64) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    (start <= stop).then_some((start as usize, stop as usize))
+}
+
+// list elements are compared by their string representation
+fn element_eq(element: &RespFrame, value: &[u8]) -> bool {
+    string_bytes(element).is_ok_and(|element| element == value)
+}
@@ -0,0 +1,402 @@
+use super::object::KeyAccess;
+use super::zset::SortedSet;
+use super::{Backend, ClientInfo, KeyType};
+use crate::RespFrame;
+use dashmap::DashMap;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// elements looked at by MEMORY USAGE when no SAMPLES are given
+pub const DEFAULT_MEMORY_SAMPLES: usize = 5;
+
+// per-entry bookkeeping of a hash table besides the key and value, an estimate of the
+// control bytes and load factor of the std and dashmap tables
+const TABLE_ENTRY_OVERHEAD: usize = 8;
+
+// below this MEMORY DOCTOR doesn't look for issues, like redis
+const DOCTOR_MIN_MEMORY: usize = 5 * 1024 * 1024;
+
+// bytes currently allocated, and the most ever allocated at once, by CountingAllocator
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, keeping count of the bytes allocated through it for MEMORY
+/// STATS and INFO. Only takes effect once registered with `#[global_allocator]`.
+#[derive(Debug, Default)]
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            record_allocation(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+            record_allocation(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn record_allocation(size: usize) {
+    let allocated = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_ALLOCATED.fetch_max(allocated, Ordering::Relaxed);
+}
+
+/// Bytes currently allocated by the process, None unless CountingAllocator is the
+/// global allocator.
+pub fn allocated_memory() -> Option<usize> {
+    match ALLOCATED.load(Ordering::Relaxed) {
+        0 => None,
+        allocated => Some(allocated),
+    }
+}
+
+/// Resident set size of the process in bytes, where /proc is available.
+pub fn resident_memory() -> Option<usize> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: usize = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
+/// Breakdown of the memory used by the server, as reported by MEMORY STATS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryStats {
+    pub peak_allocated: usize,
+    pub total_allocated: usize,
+    // allocated by the time the keyspace was created
+    pub startup_allocated: usize,
+    // metadata of the connected clients
+    pub clients: usize,
+    // TTLs and access times of the keys
+    pub keyspace_overhead: usize,
+    pub keys: usize,
+    // estimated bytes of the keys of each type and their values
+    pub dataset_by_type: Vec<(KeyType, usize)>,
+    // false when the totals are estimates because allocations are not counted
+    pub accounted: bool,
+}
+
+impl MemoryStats {
+    /// Memory not used by the keys and their values.
+    pub fn overhead(&self) -> usize {
+        self.startup_allocated + self.clients + self.keyspace_overhead
+    }
+
+    /// Memory used by the keys and their values.
+    pub fn dataset(&self) -> usize {
+        self.total_allocated.saturating_sub(self.overhead())
+    }
+}
+
+/// Memory allocated when the backend was created, the baseline of MEMORY STATS.
+#[derive(Debug)]
+pub(crate) struct MemoryAccounting {
+    startup_allocated: usize,
+}
+
+impl Default for MemoryAccounting {
+    fn default() -> Self {
+        Self {
+            startup_allocated: allocated_memory().unwrap_or(0),
+        }
+    }
+}
+
+impl Backend {
+    /// Estimated bytes used by `key` and its value, looking at up to `samples` elements
+    /// of a collection, all of them when 0, and extrapolating to the rest.
+    pub fn memory_usage(&self, key: &str, samples: usize) -> Option<usize> {
+        let key_type = self.peek_type(key)?;
+        let mut size = string_size(key) + TABLE_ENTRY_OVERHEAD;
+        if self.db.expire.contains_key(key) {
+            size += expire_entry_size(key);
+        }
+        size += match key_type {
+            KeyType::String => frame_size(self.db.map.get(key)?.value()),
+            KeyType::List => list_size(self.db.list.get(key)?.value(), samples),
+            KeyType::Set => set_size(self.db.set.get(key)?.value(), samples),
+            KeyType::ZSet => zset_size(self.db.zset.get(key)?.value(), samples),
+            KeyType::Hash => {
+                let ttls = self.db.hexpire.get(key);
+                hash_size(self.db.hmap.get(key)?.value(), ttls.as_deref(), samples)
+            }
+        };
+        Some(size)
+    }
+
+    /// Whether more memory is allocated than maxmemory allows. Never the case unless
+    /// allocations are counted.
+    pub fn out_of_memory(&self) -> bool {
+        let maxmemory = self.maxmemory() as usize;
+        maxmemory > 0 && allocated_memory().is_some_and(|allocated| allocated > maxmemory)
+    }
+
+    /// Memory used by the server, the keys of every database included.
+    pub fn memory_stats(&self) -> MemoryStats {
+        let samples = DEFAULT_MEMORY_SAMPLES;
+        let mut keys = 0;
+        let mut keyspace_overhead = 0;
+        let mut dataset_by_type = [
+            KeyType::String,
+            KeyType::Hash,
+            KeyType::List,
+            KeyType::Set,
+            KeyType::ZSet,
+        ]
+        .map(|key_type| (key_type, 0))
+        .to_vec();
+        for backend in self.all_dbs() {
+            let db = &backend.db;
+            let mut add = |key_type, sizes: &mut dyn Iterator<Item = (usize, usize)>| {
+                let bytes = sizes
+                    .map(|(key_len, size)| {
+                        keys += 1;
+                        size_of::<String>() + key_len + TABLE_ENTRY_OVERHEAD + size
+                    })
+                    .sum::<usize>();
+                if let Some((_, total)) = dataset_by_type.iter_mut().find(|(t, _)| *t == key_type) {
+                    *total += bytes;
+                }
+            };
+            add(
+                KeyType::String,
+                &mut db
+                    .map
+                    .iter()
+                    .map(|e| (e.key().len(), frame_size(e.value()))),
+            );
+            add(
+                KeyType::Hash,
+                &mut db.hmap.iter().map(|e| {
+                    let ttls = db.hexpire.get(e.key());
+                    (
+                        e.key().len(),
+                        hash_size(e.value(), ttls.as_deref(), samples),
+                    )
+                }),
+            );
+            add(
+                KeyType::List,
+                &mut db
+                    .list
+                    .iter()
+                    .map(|e| (e.key().len(), list_size(e.value(), samples))),
+            );
+            add(
+                KeyType::Set,
+                &mut db
+                    .set
+                    .iter()
+                    .map(|e| (e.key().len(), set_size(e.value(), samples))),
+            );
+            add(
+                KeyType::ZSet,
+                &mut db
+                    .zset
+                    .iter()
+                    .map(|e| (e.key().len(), zset_size(e.value(), samples))),
+            );
+
+            keyspace_overhead +=
+                db.expire
+                    .iter()
+                    .map(|e| expire_entry_size(e.key()))
+                    .chain(db.access.iter().map(|e| {
+                        string_size(e.key()) + size_of::<KeyAccess>() + TABLE_ENTRY_OVERHEAD
+                    }))
+                    .sum::<usize>();
+        }
+        let clients = self
+            .client_list()
+            .iter()
+            .map(|info| {
+                size_of::<ClientInfo>()
+                    + info.name.len()
+                    + info.last_command.len()
+                    + TABLE_ENTRY_OVERHEAD
+            })
+            .sum();
+
+        let mut stats = MemoryStats {
+            peak_allocated: PEAK_ALLOCATED.load(Ordering::Relaxed),
+            total_allocated: allocated_memory().unwrap_or(0),
+            startup_allocated: self.memory.startup_allocated,
+            clients,
+            keyspace_overhead,
+            keys,
+            dataset_by_type,
+            accounted: allocated_memory().is_some(),
+        };
+        if !stats.accounted {
+            let dataset: usize = stats.dataset_by_type.iter().map(|(_, bytes)| bytes).sum();
+            stats.total_allocated = stats.overhead() + dataset;
+            stats.peak_allocated = stats.total_allocated;
+        }
+        stats
+    }
+
+    /// Report of likely memory issues, as replied by MEMORY DOCTOR.
+    pub fn memory_doctor(&self) -> String {
+        let stats = self.memory_stats();
+        if stats.total_allocated < DOCTOR_MIN_MEMORY {
+            return "This instance is empty or is using very little memory, the issues \
+                    detector can't be used in these conditions. Please fill it with some \
+                    data and try again."
+                .to_string();
+        }
+
+        let mut issues = Vec::new();
+        if stats.peak_allocated > stats.total_allocated / 2 * 3 {
+            issues.push(
+                "Peak memory: In the past this instance used more than 150% the memory \
+                 that is currently using. The allocator is normally not able to release \
+                 memory after a peak, so you can expect to see a big fragmentation ratio, \
+                 however this is actually harmless and is only due to the memory peak.",
+            );
+        }
+        if resident_memory().is_some_and(|rss| rss > stats.total_allocated / 10 * 14) {
+            issues.push(
+                "High process RSS overhead: The resident set size of the process is more \
+                 than 140% of the memory allocated for data and bookkeeping. This is \
+                 usually due to fragmentation after many keys were deleted or shrunk.",
+            );
+        }
+        let connected = self.client_list().len();
+        if connected > 0 && stats.clients / connected > 200 * 1024 {
+            issues.push(
+                "Big client buffers: The clients use on average more than 200k of memory \
+                 each. This may be due to big command arguments or large client names.",
+            );
+        }
+        let maxmemory = self.maxmemory() as usize;
+        if maxmemory > 0 && stats.total_allocated > maxmemory / 10 * 9 {
+            issues.push(
+                "Close to maxmemory: More than 90% of the maxmemory limit is in use. \
+                 Consider raising maxmemory or expiring keys that are no longer needed.",
+            );
+        }
+        if stats.keys > 0 && stats.keyspace_overhead > stats.dataset() {
+            issues.push(
+                "Keyspace overhead: Tracking the TTLs and access times of the keys takes \
+                 more memory than the keys themselves, which is typical of many tiny \
+                 keys. Consider grouping small values into hashes.",
+            );
+        }
+
+        if issues.is_empty() {
+            return "I can't find any memory issue in this instance.".to_string();
+        }
+        let mut report = String::from("I detected a few issues in this instance:\n\n");
+        for issue in issues {
+            // writing to a String never fails
+            let _ = write!(report, " * {}\n\n", issue);
+        }
+        report
+    }
+}
+
+fn hash_size(
+    hash: &DashMap<String, RespFrame>,
+    ttls: Option<&DashMap<String, u64>>,
+    samples: usize,
+) -> usize {
+    let entry = size_of::<String>() + TABLE_ENTRY_OVERHEAD;
+    let fields = hash
+        .iter()
+        .map(|field| field.key().len() + frame_size(field.value()));
+    let ttls = ttls.map_or(0, |ttls| {
+        let entry = size_of::<String>() + size_of::<u64>() + TABLE_ENTRY_OVERHEAD;
+        let fields = ttls.iter().map(|ttl| ttl.key().len());
+        extrapolate(fields, ttls.len(), samples, entry)
+    });
+    size_of_val(hash) + extrapolate(fields, hash.len(), samples, entry) + ttls
+}
+
+fn list_size(list: &VecDeque<RespFrame>, samples: usize) -> usize {
+    // the elements' own heap data, the frames themselves live in the ring buffer
+    let elements = list
+        .iter()
+        .map(|element| frame_size(element) - size_of::<RespFrame>());
+    size_of_val(list)
+        + list.capacity() * size_of::<RespFrame>()
+        + extrapolate(elements, list.len(), samples, 0)
+}
+
+fn set_size(set: &HashSet<Vec<u8>>, samples: usize) -> usize {
+    let entry = size_of::<Vec<u8>>() + TABLE_ENTRY_OVERHEAD;
+    let members = set.iter().map(|member| member.len());
+    size_of_val(set) + extrapolate(members, set.len(), samples, entry)
+}
+
+fn zset_size(zset: &SortedSet, samples: usize) -> usize {
+    // members are stored in both the score lookup and the ordered index
+    let entry = 2 * (size_of::<Vec<u8>>() + TABLE_ENTRY_OVERHEAD) + 2 * size_of::<f64>();
+    let members = zset.iter().map(|(member, _)| 2 * member.len());
+    size_of_val(zset) + extrapolate(members, zset.len(), samples, entry)
+}
+
+// total size of `len` elements of which `sizes` yields the variable part, each adding
+// `fixed` bytes, from the first `samples` of them when not 0
+fn extrapolate(
+    sizes: impl Iterator<Item = usize>,
+    len: usize,
+    samples: usize,
+    fixed: usize,
+) -> usize {
+    let limit = if samples == 0 { len } else { samples };
+    let (mut sampled, mut total) = (0, 0);
+    for size in sizes.take(limit) {
+        sampled += 1;
+        total += size;
+    }
+    let variable = (total * len).checked_div(sampled).unwrap_or(0);
+    variable + fixed * len
+}
+
+fn expire_entry_size(key: &str) -> usize {
+    string_size(key) + size_of::<u64>() + TABLE_ENTRY_OVERHEAD
+}
+
+fn string_size(s: &str) -> usize {
+    size_of::<String>() + s.len()
+}
+
+// the frame and whatever it owns on the heap
+fn frame_size(frame: &RespFrame) -> usize {
+    size_of::<RespFrame>()
+        + match frame {
+            RespFrame::BulkString(s) => s.0.capacity(),
+            RespFrame::SimpleString(s) => s.0.capacity(),
+            RespFrame::Error(e) => e.0.capacity(),
+            RespFrame::Array(array) => array.0.iter().map(frame_size).sum(),
+            RespFrame::Set(set) => set.0.iter().map(frame_size).sum(),
+            RespFrame::Map(map) => map
+                .iter()
+                .map(|(key, value)| string_size(key) + frame_size(value))
+                .sum(),
+            _ => 0,
+        }
+}
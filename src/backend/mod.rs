@@ -1,36 +1,267 @@
-use crate::RespFrame;
+mod acl;
+mod auth;
+mod blocking;
+mod clients;
+mod config;
+mod db;
+mod dump;
+mod expire;
+mod generic;
+mod latency;
+mod lazyfree;
+mod list;
+mod memory;
+mod monitor;
+mod notify;
+mod object;
+mod pubsub;
+mod replication;
+mod scan;
+mod set;
+mod shutdown;
+mod snapshot;
+mod sort;
+mod stats;
+mod tracking;
+mod value;
+mod zset;
+
+pub use acl::{AclDenial, AclLogEntry};
+pub use blocking::KeyWaiter;
+pub use clients::{ClientFilter, ClientInfo, Connection, PauseMode, ReplyMode};
+pub use dump::RestoreOptions;
+pub use latency::{LatencyEvent, LatencyReport, LatencySample};
+pub use list::ListEnd;
+pub use memory::{
+    allocated_memory, resident_memory, CountingAllocator, MemoryStats, DEFAULT_MEMORY_SAMPLES,
+};
+pub use set::SetOp;
+pub use snapshot::{BgSaveStatus, PersistenceStats};
+pub use sort::SortOptions;
+pub use stats::{CommandStats, ExpireStats, KeyspaceStats, ServerStats};
+pub use tracking::TrackingOptions;
+pub use zset::{format_score, Aggregate, ScoreCondition, ScoredMember, ZRangeBy, ZSetEnd};
+
+use crate::{BulkString, RespFrame, SimpleError};
+use acl::AclLog;
+use clients::ClientRegistry;
+use config::Config;
 use dashmap::DashMap;
+use db::Db;
+use expire::ActiveExpireConfig;
+use latency::LatencyMonitor;
+use memory::MemoryAccounting;
+use monitor::MONITOR_BACKLOG;
+use notify::{NOTIFY_GENERIC, NOTIFY_HASH, NOTIFY_KEY_MISS, NOTIFY_STRING};
+use object::KeyAccess;
+use pubsub::PubSub;
+use rand::seq::{IteratorRandom, SliceRandom};
+use shutdown::ShutdownSignal;
+use snapshot::SnapshotState;
+use stats::Stats;
 use std::ops::Deref;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::broadcast;
+use tracking::Tracking;
+use zset::SortedSet;
+
+// same limit as redis' default proto-max-bulk-len
+const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
 
+/// Handle on the server state and on the keyspace of one of its databases.
 #[derive(Debug, Clone)]
-pub struct Backend(Arc<BackendInner>);
+pub struct Backend {
+    inner: Arc<BackendInner>,
+    // the database commands run against through this handle
+    pub(crate) db: Arc<Db>,
+}
 
 #[derive(Debug)]
 pub struct BackendInner {
-    pub(crate) map: DashMap<String, RespFrame>,
-    pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
+    // every database, the first one being the default one
+    pub(crate) dbs: RwLock<Vec<Arc<Db>>>,
+    pub(crate) active_expire: ActiveExpireConfig,
+    pub(crate) stats: Stats,
+    pub(crate) config: Config,
+    pub(crate) clients: ClientRegistry,
+    // single-key writes share this lock, multi-key writes take it exclusively
+    // so that they are atomic across shards and databases
+    pub(crate) keyspace_lock: RwLock<()>,
+    // the commands of clients share this lock, EXEC takes it exclusively so that no
+    // command of another client runs in the middle of a transaction
+    pub(crate) transaction_lock: RwLock<()>,
+    pub(crate) memory: MemoryAccounting,
+    pub(crate) latency: LatencyMonitor,
+    pub(crate) shutdown: ShutdownSignal,
+    pub(crate) snapshot: SnapshotState,
+    // lines of MONITOR, one per command processed
+    pub(crate) monitors: broadcast::Sender<String>,
+    pub(crate) acl_log: AclLog,
+    pub(crate) pubsub: PubSub,
+    // clients tracking the keys they cache with CLIENT TRACKING
+    pub(crate) tracking: Tracking,
+}
+
+/// Type of the value stored at a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    String,
+    Hash,
+    List,
+    Set,
+    ZSet,
+}
+
+impl KeyType {
+    /// Name of the type as replied by TYPE.
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyType::String => "string",
+            KeyType::Hash => "hash",
+            KeyType::List => "list",
+            KeyType::Set => "set",
+            KeyType::ZSet => "zset",
+        }
+    }
+}
+
+/// Condition a write must satisfy before it is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    // NX: only set the key if it does not already exist
+    IfNotExists,
+    // XX: only set the key if it already exists
+    IfExists,
+}
+
+/// Condition the current TTL must satisfy before a new one is applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpireCondition {
+    // NX: only set the TTL if there is none yet
+    IfNoTtl,
+    // XX: only set the TTL if there already is one
+    IfHasTtl,
+    // GT: only set the TTL if it is later than the current one
+    IfGreater,
+    // LT: only set the TTL if it is earlier than the current one
+    IfLess,
+}
+
+impl ExpireCondition {
+    /// Whether `expire_at` may replace the `current` deadline. No deadline at all counts
+    /// as an infinite TTL.
+    pub fn allows(self, current: Option<u64>, expire_at: u64) -> bool {
+        match (self, current) {
+            (ExpireCondition::IfNoTtl, current) => current.is_none(),
+            (ExpireCondition::IfHasTtl, current) => current.is_some(),
+            (ExpireCondition::IfGreater, Some(at)) => expire_at > at,
+            (ExpireCondition::IfGreater, None) => false,
+            (ExpireCondition::IfLess, Some(at)) => expire_at < at,
+            (ExpireCondition::IfLess, None) => true,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BackendError {
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotInteger,
+    #[error("ERR increment or decrement would overflow")]
+    Overflow,
+    #[error("ERR value is not a valid float")]
+    NotFloat,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NotFinite,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR hash value is not an integer")]
+    HashNotInteger,
+    #[error("ERR hash value is not a float")]
+    HashNotFloat,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("ERR resulting score is not a number (NaN)")]
+    ScoreNaN,
+    #[error("ERR One or more scores can't be converted into double")]
+    SortNotFloat,
+    #[error("ERR source and destination objects are the same")]
+    SameObject,
+    #[error("ERR DUMP payload version or checksum are wrong")]
+    BadPayload,
+    #[error("BUSYKEY Target key name already exists.")]
+    BusyKey,
+    #[error("ERR Unknown option or number of arguments for CONFIG SET - '{0}'")]
+    UnknownConfig(String),
+    #[error("ERR CONFIG SET failed (possibly related to argument '{0}') - {1}")]
+    ConfigSet(String, String),
+    #[error("ERR The server is running without a config file")]
+    NoConfigFile,
+    #[error("ERR Rewriting config file: {0}")]
+    ConfigRewrite(String),
+    #[error("ERR Failed saving the snapshot: {0}")]
+    SnapshotFailed(String),
+    #[error("ERR Background save already in progress")]
+    BgSaveInProgress,
+    #[error("OOM command not allowed when used memory > 'maxmemory'.")]
+    OutOfMemory,
+    #[error("ERR Errors trying to SHUTDOWN. Check logs.")]
+    ShutdownFailed,
+    #[error("ERR DB index is out of range")]
+    DbOutOfRange,
+    #[error("ERR AUTH <password> called without any password configured for the default user. Are you sure your configuration is correct?")]
+    NoPasswordConfigured,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPass,
+    #[error("NOAUTH Authentication required.")]
+    NoAuth,
+    #[error("ERR The client ID you want redirect to does not exist")]
+    NoSuchRedirect,
+    #[error("ERR You can't switch BCAST mode on/off before disabling tracking for this client, and then re-enabling it with a different mode.")]
+    TrackingModeSwitch,
 }
 
 impl Deref for Backend {
     type Target = BackendInner;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.inner
     }
 }
 
 impl Default for Backend {
     fn default() -> Self {
-        Self(Arc::new(BackendInner::default()))
+        Self::from_inner(Arc::new(BackendInner::default()))
     }
 }
 
 impl Default for BackendInner {
     fn default() -> Self {
+        let config = Config::default();
+        let dbs = (0..config.databases())
+            .map(|index| Arc::new(Db::new(index)))
+            .collect();
         Self {
-            map: DashMap::new(),
-            hmap: DashMap::new(),
+            dbs: RwLock::new(dbs),
+            active_expire: ActiveExpireConfig::default(),
+            stats: Stats::default(),
+            config,
+            clients: ClientRegistry::default(),
+            keyspace_lock: RwLock::new(()),
+            transaction_lock: RwLock::new(()),
+            memory: MemoryAccounting::default(),
+            latency: LatencyMonitor::default(),
+            shutdown: ShutdownSignal::default(),
+            snapshot: SnapshotState::default(),
+            monitors: broadcast::channel(MONITOR_BACKLOG).0,
+            acl_log: AclLog::default(),
+            pubsub: PubSub::default(),
+            tracking: Tracking::default(),
         }
     }
 }
@@ -40,26 +271,846 @@ impl Backend {
         Self::default()
     }
 
+    // a handle on the first database
+    fn from_inner(inner: Arc<BackendInner>) -> Self {
+        let db = inner.dbs.read().unwrap_or_else(|e| e.into_inner())[0].clone();
+        Self { inner, db }
+    }
+
     pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+        self.lookup_read(key);
+        self.db.map.get(key).map(|v| v.value().clone())
     }
 
     pub fn set(&self, key: String, value: RespFrame) {
-        self.map.insert(key, value);
+        let _guard = self.shared_lock();
+        self.set_unlocked(key, value);
+    }
+
+    fn set_unlocked(&self, key: String, value: RespFrame) {
+        self.db.expire.remove(&key);
+        self.remove_collection(&key);
+        self.record_access(&key);
+        self.db.map.insert(key.clone(), value);
+        self.notify(NOTIFY_STRING, "set", &key);
+    }
+
+    /// Writes `value` under `key` if `condition` holds. `expire_at` replaces the key's TTL
+    /// unless `keep_ttl` is set. Returns whether the write happened and the previous value.
+    pub fn set_with(
+        &self,
+        key: String,
+        value: RespFrame,
+        condition: Option<SetCondition>,
+        expire_at: Option<u64>,
+        keep_ttl: bool,
+    ) -> (bool, Option<RespFrame>) {
+        // a collection at the key is overwritten, so nothing is rejected
+        self.set_checked(key, value, condition, expire_at, keep_ttl, false)
+            .unwrap_or_default()
+    }
+
+    // set_with, failing with WrongType instead of overwriting a collection at the key
+    // when `reject_collection` is set
+    fn set_checked(
+        &self,
+        key: String,
+        value: RespFrame,
+        condition: Option<SetCondition>,
+        expire_at: Option<u64>,
+        keep_ttl: bool,
+        reject_collection: bool,
+    ) -> Result<(bool, Option<RespFrame>), BackendError> {
+        let _guard = self.shared_lock();
+        self.expire_if_needed(&key);
+        let exists_as_collection = self.key_type(&key).is_some_and(|t| t != KeyType::String);
+        if exists_as_collection && reject_collection {
+            return Err(BackendError::WrongType);
+        }
+        // hold the entry lock so the check and the write are atomic
+        let entry = self.db.map.entry(key);
+        let old = match &entry {
+            dashmap::Entry::Occupied(e) => Some(e.get().clone()),
+            dashmap::Entry::Vacant(_) => None,
+        };
+        let exists = old.is_some() || exists_as_collection;
+        let allowed = match condition {
+            Some(SetCondition::IfNotExists) => !exists,
+            Some(SetCondition::IfExists) => exists,
+            None => true,
+        };
+        if !allowed {
+            return Ok((false, old));
+        }
+
+        let key = entry.key().clone();
+        entry.insert(value);
+        if exists_as_collection {
+            self.remove_collection(&key);
+        }
+        self.record_access(&key);
+        self.notify(NOTIFY_STRING, "set", &key);
+        if !keep_ttl {
+            match expire_at {
+                Some(at) => {
+                    self.db.expire.insert(key.clone(), at);
+                    self.notify(NOTIFY_GENERIC, "expire", &key);
+                }
+                None => {
+                    self.db.expire.remove(&key);
+                }
+            }
+        }
+        Ok((true, old))
+    }
+
+    /// Sets `key` only if it does not exist yet, returning whether the value was written.
+    pub fn set_nx(&self, key: String, value: RespFrame) -> bool {
+        let (written, _) = self.set_with(key, value, Some(SetCondition::IfNotExists), None, false);
+        written
+    }
+
+    /// Atomically replaces the value at `key`, returning the previous one.
+    pub fn getset(&self, key: String, value: RespFrame) -> Result<Option<RespFrame>, BackendError> {
+        let (_, old) = self.set_checked(key, value, None, None, false, true)?;
+        Ok(old)
+    }
+
+    /// Atomically removes `key`, returning the value it held.
+    pub fn getdel(&self, key: &str) -> Result<Option<RespFrame>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::String)?;
+        let old = self.db.map.remove(key).map(|(_, v)| v);
+        if old.is_some() {
+            self.db.expire.remove(key);
+            self.notify(NOTIFY_GENERIC, "del", key);
+        }
+        Ok(old)
+    }
+
+    /// Returns the value at `key`, setting its TTL to `expire_at` or removing it when None.
+    pub fn getex(
+        &self,
+        key: &str,
+        expire_at: Option<u64>,
+    ) -> Result<Option<RespFrame>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::String)?;
+        let Some(value) = self.db.map.get(key) else {
+            return Ok(None);
+        };
+        match expire_at {
+            Some(at) => {
+                self.db.expire.insert(key.to_string(), at);
+                self.notify(NOTIFY_GENERIC, "expire", key);
+            }
+            None => {
+                if self.db.expire.remove(key).is_some() {
+                    self.notify(NOTIFY_GENERIC, "persist", key);
+                }
+            }
+        }
+        Ok(Some(value.value().clone()))
+    }
+
+    /// Bytes of the string at `key`.
+    pub fn get_string(&self, key: &str) -> Result<Option<Vec<u8>>, BackendError> {
+        self.check_read(key, KeyType::String)?;
+        self.db
+            .map
+            .get(key)
+            .map(|v| string_bytes(v.value()))
+            .transpose()
+    }
+
+    /// Byte length of the string at `key`, 0 if the key does not exist.
+    pub fn strlen(&self, key: &str) -> Result<usize, BackendError> {
+        self.check_read(key, KeyType::String)?;
+        match self.db.map.get(key).as_deref() {
+            Some(RespFrame::BulkString(s)) => Ok(s.len()),
+            Some(RespFrame::SimpleString(s)) => Ok(s.len()),
+            Some(RespFrame::Integer(n)) => Ok(n.to_string().len()),
+            Some(_) => Err(BackendError::WrongType),
+            None => Ok(0),
+        }
+    }
+
+    /// Appends `value` to the string at `key` in place, returning the new length.
+    pub fn append(&self, key: String, value: &[u8]) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::String)?;
+        let mut entry = self
+            .db
+            .map
+            .entry(key)
+            .or_insert_with(|| BulkString::new(vec![]).into());
+        let s = string_mut(entry.value_mut())?;
+        s.extend_from_slice(value);
+        let len = s.len();
+        self.notify(NOTIFY_STRING, "append", entry.key());
+        Ok(len)
+    }
+
+    /// Substring of the string at `key` between `start` and `end` inclusive, negative
+    /// offsets counting from the end of the string.
+    pub fn getrange(&self, key: &str, start: i64, end: i64) -> Result<Vec<u8>, BackendError> {
+        self.check_read(key, KeyType::String)?;
+        let Some(value) = self.db.map.get(key) else {
+            return Ok(vec![]);
+        };
+        let bytes = string_bytes(value.value())?;
+        let len = bytes.len() as i64;
+        let start = if start < 0 {
+            (len + start).max(0)
+        } else {
+            start
+        };
+        let end = if end < 0 { len + end } else { end.min(len - 1) };
+        if start > end || end < 0 {
+            return Ok(vec![]);
+        }
+        Ok(bytes[start as usize..=end as usize].to_vec())
+    }
+
+    /// Overwrites the string at `key` from `offset` with `value`, zero-padding the string
+    /// if it is shorter than `offset`. Returns the new length.
+    pub fn setrange(
+        &self,
+        key: String,
+        offset: usize,
+        value: &[u8],
+    ) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::String)?;
+        if offset + value.len() > MAX_STRING_LEN {
+            return Err(BackendError::StringTooLong);
+        }
+        if value.is_empty() {
+            return self.strlen(&key);
+        }
+        let mut entry = self
+            .db
+            .map
+            .entry(key)
+            .or_insert_with(|| BulkString::new(vec![]).into());
+        let s = string_mut(entry.value_mut())?;
+        if s.len() < offset + value.len() {
+            s.resize(offset + value.len(), 0);
+        }
+        s[offset..offset + value.len()].copy_from_slice(value);
+        let len = s.len();
+        self.notify(NOTIFY_STRING, "setrange", entry.key());
+        Ok(len)
+    }
+
+    /// Atomically adds `delta` to the integer stored at `key`, treating a missing key as 0.
+    pub fn incr_by(&self, key: String, delta: i64) -> Result<i64, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::String)?;
+        let mut entry = self.db.map.entry(key).or_insert_with(|| 0.into());
+        let current = frame_to_i64(entry.value())?;
+        let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+        *entry.value_mut() = BulkString::new(value.to_string()).into();
+        self.notify(NOTIFY_STRING, "incrby", entry.key());
+        Ok(value)
+    }
+
+    /// Atomically adds `delta` to the float stored at `key`, treating a missing key as 0.
+    pub fn incr_by_float(&self, key: String, delta: f64) -> Result<f64, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::String)?;
+        let mut entry = self.db.map.entry(key).or_insert_with(|| 0.into());
+        let current = frame_to_f64(entry.value())?;
+        let value = current + delta;
+        if !value.is_finite() {
+            return Err(BackendError::NotFinite);
+        }
+        *entry.value_mut() = BulkString::new(format_float(value)).into();
+        self.notify(NOTIFY_STRING, "incrbyfloat", entry.key());
+        Ok(value)
+    }
+
+    /// Sets all `pairs` atomically.
+    pub fn mset(&self, pairs: Vec<(String, RespFrame)>) {
+        let _guard = self.exclusive_lock();
+        for (key, value) in pairs {
+            self.set_unlocked(key, value);
+        }
+    }
+
+    /// Sets all `pairs` atomically, but only if none of the keys exist.
+    pub fn msetnx(&self, pairs: Vec<(String, RespFrame)>) -> bool {
+        let _guard = self.exclusive_lock();
+        if pairs.iter().any(|(key, _)| self.contains_key(key)) {
+            return false;
+        }
+        for (key, value) in pairs {
+            self.set_unlocked(key, value);
+        }
+        true
     }
 
     pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
+        self.lookup_read(key);
+        self.expire_fields_if_needed(key);
+        self.db
+            .hmap
             .get(key)
             .and_then(|v| v.get(field).map(|v| v.value().clone()))
     }
 
     pub fn hset(&self, key: String, field: String, value: RespFrame) {
-        let hmap = self.hmap.entry(key).or_default();
+        let _guard = self.shared_lock();
+        self.expire_if_needed(&key);
+        self.expire_fields_if_needed(&key);
+        let hmap = self.db.hmap.entry(key).or_default();
+        self.persist_field(hmap.key(), &field);
         hmap.insert(field, value);
+        self.notify(NOTIFY_HASH, "hset", hmap.key());
+    }
+
+    /// Sets all `pairs` in the hash at `key`, returning the number of newly created fields.
+    pub fn hmset(
+        &self,
+        key: String,
+        pairs: Vec<(String, RespFrame)>,
+    ) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(&key)?;
+        let hmap = self.db.hmap.entry(key).or_default();
+        // overwriting a field also clears its TTL
+        let created = pairs
+            .into_iter()
+            .filter(|(field, value)| {
+                self.persist_field(hmap.key(), field);
+                hmap.insert(field.clone(), value.clone()).is_none()
+            })
+            .count();
+        self.notify(NOTIFY_HASH, "hset", hmap.key());
+        Ok(created)
     }
 
     pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+        self.lookup_read(key);
+        self.expire_fields_if_needed(key);
+        self.db.hmap.get(key).map(|v| v.clone())
+    }
+
+    pub fn hexists(&self, key: &str, field: &str) -> Result<bool, BackendError> {
+        self.check_hash_read(key)?;
+        Ok(self
+            .db
+            .hmap
+            .get(key)
+            .is_some_and(|hmap| hmap.contains_key(field)))
+    }
+
+    /// Sets `field` in the hash at `key` only if it does not exist yet, returning whether
+    /// the value was written.
+    pub fn hsetnx(
+        &self,
+        key: String,
+        field: String,
+        value: RespFrame,
+    ) -> Result<bool, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(&key)?;
+        let hmap = self.db.hmap.entry(key).or_default();
+        // the field entry lock makes the check and the write atomic
+        let written = match hmap.entry(field) {
+            dashmap::Entry::Occupied(_) => false,
+            dashmap::Entry::Vacant(e) => {
+                e.insert(value);
+                true
+            }
+        };
+        if written {
+            self.notify(NOTIFY_HASH, "hset", hmap.key());
+        }
+        Ok(written)
+    }
+
+    /// Atomically adds `delta` to the integer in `field` of the hash at `key`, treating a
+    /// missing field as 0.
+    pub fn hincr_by(&self, key: String, field: String, delta: i64) -> Result<i64, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(&key)?;
+        let hmap = self.db.hmap.entry(key).or_default();
+        let mut entry = hmap.entry(field).or_insert_with(|| 0.into());
+        let current = frame_to_i64(entry.value()).map_err(|_| BackendError::HashNotInteger)?;
+        let value = current.checked_add(delta).ok_or(BackendError::Overflow)?;
+        *entry.value_mut() = BulkString::new(value.to_string()).into();
+        self.notify(NOTIFY_HASH, "hincrby", hmap.key());
+        Ok(value)
+    }
+
+    /// Atomically adds `delta` to the float in `field` of the hash at `key`, treating a
+    /// missing field as 0.
+    pub fn hincr_by_float(
+        &self,
+        key: String,
+        field: String,
+        delta: f64,
+    ) -> Result<f64, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(&key)?;
+        let hmap = self.db.hmap.entry(key).or_default();
+        let mut entry = hmap.entry(field).or_insert_with(|| 0.into());
+        let current = frame_to_f64(entry.value()).map_err(|_| BackendError::HashNotFloat)?;
+        let value = current + delta;
+        if !value.is_finite() {
+            return Err(BackendError::NotFinite);
+        }
+        *entry.value_mut() = BulkString::new(format_float(value)).into();
+        self.notify(NOTIFY_HASH, "hincrbyfloat", hmap.key());
+        Ok(value)
+    }
+
+    /// Random fields of the hash at `key` with their values. A positive `count` returns
+    /// distinct fields, a negative one may repeat the same field.
+    pub fn hrandfield(
+        &self,
+        key: &str,
+        count: i64,
+    ) -> Result<Vec<(String, RespFrame)>, BackendError> {
+        self.check_hash_read(key)?;
+        let Some(hmap) = self.db.hmap.get(key) else {
+            return Ok(vec![]);
+        };
+        let mut rng = rand::thread_rng();
+        let pairs = hmap.iter().map(|v| (v.key().clone(), v.value().clone()));
+        if count >= 0 {
+            // the sample is allocated upfront, a count above the size takes the whole hash
+            let count = (count as usize).min(hmap.len());
+            let mut fields = pairs.choose_multiple(&mut rng, count);
+            fields.shuffle(&mut rng);
+            return Ok(fields);
+        }
+        let pairs = pairs.collect::<Vec<_>>();
+        Ok((0..count.unsigned_abs())
+            .filter_map(|_| pairs.choose(&mut rng).cloned())
+            .collect())
     }
+
+    /// One page of a cursor based iteration over the hash at `key`.
+    pub fn hscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+    ) -> Result<(u64, Vec<(String, RespFrame)>), BackendError> {
+        self.check_hash_read(key)?;
+        let Some(hmap) = self.db.hmap.get(key) else {
+            return Ok((0, vec![]));
+        };
+        let pairs = hmap.iter().map(|v| (v.key().clone(), v.value().clone()));
+        Ok(scan::scan(pairs, cursor, count, |(field, _)| {
+            field.as_str()
+        }))
+    }
+
+    /// Values of `fields` in the hash at `key`, in the same order.
+    pub fn hmget(
+        &self,
+        key: &str,
+        fields: &[String],
+    ) -> Result<Vec<Option<RespFrame>>, BackendError> {
+        self.check_hash_read(key)?;
+        let hmap = self.db.hmap.get(key);
+        Ok(fields
+            .iter()
+            .map(|field| {
+                hmap.as_ref()
+                    .and_then(|hmap| hmap.get(field).map(|v| v.value().clone()))
+            })
+            .collect())
+    }
+
+    /// Byte length of the value of `field` in the hash at `key`, 0 if the field or key
+    /// does not exist.
+    pub fn hstrlen(&self, key: &str, field: &str) -> Result<usize, BackendError> {
+        self.check_hash_read(key)?;
+        match self
+            .db
+            .hmap
+            .get(key)
+            .and_then(|hmap| hmap.get(field).map(|v| v.clone()))
+        {
+            Some(value) => Ok(string_bytes(&value)?.len()),
+            None => Ok(0),
+        }
+    }
+
+    /// Number of fields in the hash at `key`.
+    pub fn hlen(&self, key: &str) -> Result<usize, BackendError> {
+        self.check_hash_read(key)?;
+        Ok(self.db.hmap.get(key).map_or(0, |hmap| hmap.len()))
+    }
+
+    pub fn hkeys(&self, key: &str) -> Result<Vec<String>, BackendError> {
+        self.check_hash_read(key)?;
+        Ok(self.db.hmap.get(key).map_or(vec![], |hmap| {
+            hmap.iter().map(|v| v.key().clone()).collect()
+        }))
+    }
+
+    pub fn hvals(&self, key: &str) -> Result<Vec<RespFrame>, BackendError> {
+        self.check_hash_read(key)?;
+        Ok(self.db.hmap.get(key).map_or(vec![], |hmap| {
+            hmap.iter().map(|v| v.value().clone()).collect()
+        }))
+    }
+
+    /// Removes `fields` from the hash at `key`, deleting the key once the hash is empty.
+    /// Returns the number of fields removed.
+    pub fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(key)?;
+        let removed = match self.db.hmap.get(key) {
+            Some(hmap) => fields
+                .iter()
+                .filter(|field| {
+                    self.persist_field(key, field);
+                    hmap.remove(*field).is_some()
+                })
+                .count(),
+            None => return Ok(0),
+        };
+        if removed > 0 {
+            self.notify(NOTIFY_HASH, "hdel", key);
+        }
+        self.remove_hash_if_empty(key);
+        Ok(removed)
+    }
+
+    /// Sets the deadline of `fields` in the hash at `key` to `expire_at` if `condition`
+    /// holds. The reply for each field is -2 if it does not exist, 0 if the condition
+    /// was not met, 1 if the TTL was set and 2 if `expire_at` is already in the past, in
+    /// which case the field is deleted.
+    pub fn hexpire(
+        &self,
+        key: &str,
+        fields: &[String],
+        expire_at: u64,
+        condition: Option<ExpireCondition>,
+    ) -> Result<Vec<i64>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(key)?;
+        let Some(hmap) = self.db.hmap.get(key) else {
+            return Ok(vec![-2; fields.len()]);
+        };
+        let now = now_ms();
+        let ttls = self.db.hexpire.entry(key.to_string()).or_default();
+        let replies: Vec<_> = fields
+            .iter()
+            .map(|field| {
+                if !hmap.contains_key(field) {
+                    return -2;
+                }
+                let current = ttls.get(field).map(|at| *at);
+                if !condition.is_none_or(|c| c.allows(current, expire_at)) {
+                    return 0;
+                }
+                if expire_at <= now {
+                    ttls.remove(field);
+                    hmap.remove(field);
+                    return 2;
+                }
+                ttls.insert(field.clone(), expire_at);
+                1
+            })
+            .collect();
+        drop(ttls);
+        drop(hmap);
+        self.db.hexpire.remove_if(key, |_, ttls| ttls.is_empty());
+        if replies.contains(&1) {
+            self.notify(NOTIFY_HASH, "hexpire", key);
+        }
+        if replies.contains(&2) {
+            self.notify(NOTIFY_HASH, "hdel", key);
+        }
+        self.remove_hash_if_empty(key);
+        Ok(replies)
+    }
+
+    /// Absolute deadline of `fields` in the hash at `key` in unix milliseconds, -1 for a
+    /// field without TTL and -2 for a missing field.
+    pub fn hexpiretime(&self, key: &str, fields: &[String]) -> Result<Vec<i64>, BackendError> {
+        self.check_hash_read(key)?;
+        let hmap = self.db.hmap.get(key);
+        let ttls = self.db.hexpire.get(key);
+        Ok(fields
+            .iter()
+            .map(|field| {
+                if !hmap.as_ref().is_some_and(|hmap| hmap.contains_key(field)) {
+                    return -2;
+                }
+                ttls.as_ref()
+                    .and_then(|ttls| ttls.get(field).map(|at| *at as i64))
+                    .unwrap_or(-1)
+            })
+            .collect())
+    }
+
+    /// Removes the TTL of `fields` in the hash at `key`. The reply for each field is -2
+    /// if it does not exist, -1 if it has no TTL and 1 if the TTL was removed.
+    pub fn hpersist(&self, key: &str, fields: &[String]) -> Result<Vec<i64>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_hash(key)?;
+        let hmap = self.db.hmap.get(key);
+        let replies: Vec<_> = fields
+            .iter()
+            .map(|field| {
+                if !hmap.as_ref().is_some_and(|hmap| hmap.contains_key(field)) {
+                    -2
+                } else if self.persist_field(key, field) {
+                    1
+                } else {
+                    -1
+                }
+            })
+            .collect();
+        if replies.contains(&1) {
+            self.notify(NOTIFY_HASH, "hpersist", key);
+        }
+        Ok(replies)
+    }
+
+    // expires `key` and its fields if needed and makes sure it does not hold a non-hash
+    // value
+    fn check_hash(&self, key: &str) -> Result<(), BackendError> {
+        self.check_type(key, KeyType::Hash)?;
+        self.expire_fields_if_needed(key);
+        Ok(())
+    }
+
+    // like check_hash, for commands that only read the hash
+    fn check_hash_read(&self, key: &str) -> Result<(), BackendError> {
+        self.check_read(key, KeyType::Hash)?;
+        self.expire_fields_if_needed(key);
+        Ok(())
+    }
+
+    // like check_type, for commands that only read `key`
+    fn check_read(&self, key: &str, expected: KeyType) -> Result<(), BackendError> {
+        match self.lookup_read(key) {
+            Some(actual) if actual != expected => Err(BackendError::WrongType),
+            _ => Ok(()),
+        }
+    }
+
+    // expires `key` if needed and returns its type, counting a keyspace hit or miss like
+    // the reads of redis do
+    fn lookup_read(&self, key: &str) -> Option<KeyType> {
+        self.expire_if_needed(key);
+        let key_type = self.key_type(key);
+        self.stats.record_lookup(key_type.is_some());
+        match key_type {
+            Some(_) => self.record_access(key),
+            None => self.notify(NOTIFY_KEY_MISS, "keymiss", key),
+        }
+        key_type
+    }
+
+    // expires `key` if needed and makes sure it does not hold a value of another type.
+    // Counts as an access since the key is about to be written.
+    fn check_type(&self, key: &str, expected: KeyType) -> Result<(), BackendError> {
+        self.expire_if_needed(key);
+        self.record_access(key);
+        match self.key_type(key) {
+            Some(actual) if actual != expected => Err(BackendError::WrongType),
+            _ => Ok(()),
+        }
+    }
+
+    fn key_type(&self, key: &str) -> Option<KeyType> {
+        if self.db.map.contains_key(key) {
+            Some(KeyType::String)
+        } else if self.db.hmap.contains_key(key) {
+            Some(KeyType::Hash)
+        } else if self.db.list.contains_key(key) {
+            Some(KeyType::List)
+        } else if self.db.set.contains_key(key) {
+            Some(KeyType::Set)
+        } else if self.db.zset.contains_key(key) {
+            Some(KeyType::ZSet)
+        } else {
+            None
+        }
+    }
+
+    // removes `key` whatever its type, returning whether it existed
+    fn delete_key(&self, key: &str) -> bool {
+        let existed = self.key_type(key).is_some();
+        self.db.map.remove(key);
+        self.remove_collection(key);
+        self.db.expire.remove(key);
+        self.db.access.remove(key);
+        existed
+    }
+
+    // drops the non-string value at `key`, leaving its TTL alone
+    fn remove_collection(&self, key: &str) {
+        self.db.hmap.remove(key);
+        self.db.hexpire.remove(key);
+        self.db.list.remove(key);
+        self.db.set.remove(key);
+        self.db.zset.remove(key);
+    }
+
+    fn remove_hash_if_empty(&self, key: &str) {
+        if self
+            .db
+            .hmap
+            .remove_if(key, |_, hmap| hmap.is_empty())
+            .is_some()
+        {
+            self.db.expire.remove(key);
+            self.db.hexpire.remove(key);
+            self.notify(NOTIFY_GENERIC, "del", key);
+        }
+    }
+
+    // removes the TTL of `field` in the hash at `key`, returning whether it had one
+    fn persist_field(&self, key: &str, field: &str) -> bool {
+        let removed = match self.db.hexpire.get(key) {
+            Some(ttls) => ttls.remove(field).is_some(),
+            None => return false,
+        };
+        self.db.hexpire.remove_if(key, |_, ttls| ttls.is_empty());
+        removed
+    }
+
+    /// Lazily removes the fields of the hash at `key` whose TTL has elapsed, deleting the
+    /// key once the hash is empty.
+    fn expire_fields_if_needed(&self, key: &str) {
+        let now = now_ms();
+        let mut expired = vec![];
+        // like the key TTLs, the field TTLs are released before touching the hash
+        match self.db.hexpire.get(key) {
+            Some(ttls) => ttls.retain(|field, at| {
+                if *at <= now {
+                    expired.push(field.clone());
+                }
+                *at > now
+            }),
+            None => return,
+        }
+        self.db.hexpire.remove_if(key, |_, ttls| ttls.is_empty());
+        if expired.is_empty() {
+            return;
+        }
+        if let Some(hmap) = self.db.hmap.get(key) {
+            for field in &expired {
+                hmap.remove(field);
+            }
+        }
+        self.notify(NOTIFY_HASH, "hexpired", key);
+        self.remove_hash_if_empty(key);
+    }
+
+    fn contains_key(&self, key: &str) -> bool {
+        self.expire_if_needed(key);
+        self.key_type(key).is_some()
+    }
+
+    /// Held while the command of a client runs, which then waits for any transaction
+    /// being executed.
+    pub fn command_lock(&self) -> RwLockReadGuard<'_, ()> {
+        self.transaction_lock
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Held while EXEC runs the commands of a transaction, keeping those of other
+    /// clients out until it is done.
+    pub fn transaction_lock(&self) -> RwLockWriteGuard<'_, ()> {
+        self.transaction_lock
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn shared_lock(&self) -> RwLockReadGuard<'_, ()> {
+        // the lock guards no data, so a poisoned lock is still usable
+        self.keyspace_lock.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn exclusive_lock(&self) -> RwLockWriteGuard<'_, ()> {
+        self.keyspace_lock
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl From<BackendError> for RespFrame {
+    fn from(e: BackendError) -> Self {
+        SimpleError::new(e.to_string()).into()
+    }
+}
+
+fn string_bytes(frame: &RespFrame) -> Result<Vec<u8>, BackendError> {
+    match frame {
+        RespFrame::BulkString(s) => Ok(s.to_vec()),
+        RespFrame::SimpleString(s) => Ok(s.as_bytes().to_vec()),
+        RespFrame::Integer(n) => Ok(n.to_string().into_bytes()),
+        _ => Err(BackendError::WrongType),
+    }
+}
+
+// string values are normally stored as BulkString, any other scalar is converted
+// first so that it can be edited in place
+fn string_mut(frame: &mut RespFrame) -> Result<&mut Vec<u8>, BackendError> {
+    if !matches!(frame, RespFrame::BulkString(_)) {
+        *frame = BulkString::new(string_bytes(frame)?).into();
+    }
+    match frame {
+        RespFrame::BulkString(s) => Ok(&mut s.0),
+        _ => unreachable!("frame was converted to a BulkString"),
+    }
+}
+
+fn frame_to_i64(frame: &RespFrame) -> Result<i64, BackendError> {
+    match frame {
+        RespFrame::Integer(n) => Ok(*n),
+        RespFrame::BulkString(s) => std::str::from_utf8(s)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or(BackendError::NotInteger),
+        RespFrame::SimpleString(s) => s.parse().map_err(|_| BackendError::NotInteger),
+        _ => Err(BackendError::WrongType),
+    }
+}
+
+fn frame_to_f64(frame: &RespFrame) -> Result<f64, BackendError> {
+    let value = match frame {
+        RespFrame::Integer(n) => Some(*n as f64),
+        RespFrame::Double(n) => Some(*n),
+        RespFrame::BulkString(s) => std::str::from_utf8(s).ok().and_then(|s| s.parse().ok()),
+        RespFrame::SimpleString(s) => s.parse().ok(),
+        _ => return Err(BackendError::WrongType),
+    };
+    value
+        .filter(|v: &f64| v.is_finite())
+        .ok_or(BackendError::NotFloat)
+}
+
+/// Formats a float the way redis replies to INCRBYFLOAT: plain notation, at most 17
+/// significant digits and no trailing zeros.
+pub fn format_float(value: f64) -> String {
+    // Display already yields the shortest representation that round-trips,
+    // which never needs more than 17 significant digits
+    format!("{}", value)
+}
+
+/// Current unix time in milliseconds.
+pub fn now_ms() -> u64 {
+    now_us() / 1000
+}
+
+/// Current unix time in microseconds, the clock expiration and TIME are based on.
+pub fn now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or_default()
 }
@@ -0,0 +1,59 @@
+use super::{now_us, Backend, ClientInfo};
+use std::fmt::Write;
+use tokio::sync::broadcast;
+
+// lines a monitor may fall behind by before it misses some
+pub(crate) const MONITOR_BACKLOG: usize = 1024;
+
+impl Backend {
+    /// Whether any client is in MONITOR mode, so that the dispatch path only builds the
+    /// feed when someone reads it.
+    pub fn has_monitors(&self) -> bool {
+        self.monitors.receiver_count() > 0
+    }
+
+    /// Sends a command of `client` with arguments `args` to every monitor, as a line like
+    /// `1339518083.107412 [0 127.0.0.1:60866] "keys" "*"`.
+    pub fn feed_monitors(&self, client: &ClientInfo, args: &[Vec<u8>]) {
+        let now = now_us();
+        let mut line = format!(
+            "{}.{:06} [{} {}]",
+            now / 1_000_000,
+            now % 1_000_000,
+            client.db,
+            client.addr
+        );
+        for arg in args {
+            line.push(' ');
+            push_repr(&mut line, arg);
+        }
+        // there is no one to send it to when the last monitor just left
+        let _ = self.monitors.send(line);
+    }
+
+    pub(super) fn subscribe_monitor(&self) -> broadcast::Receiver<String> {
+        self.monitors.subscribe()
+    }
+}
+
+// `bytes` quoted and escaped the way redis prints arguments
+fn push_repr(out: &mut String, bytes: &[u8]) {
+    out.push('"');
+    for &byte in bytes {
+        match byte {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            byte if byte.is_ascii_graphic() || byte == b' ' => out.push(byte as char),
+            byte => {
+                // writing to a String never fails
+                let _ = write!(out, "\\x{:02x}", byte);
+            }
+        }
+    }
+    out.push('"');
+}
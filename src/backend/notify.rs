@@ -0,0 +1,102 @@
+use super::Backend;
+
+// classes of keyspace events, one per character of notify-keyspace-events
+pub(super) const NOTIFY_KEYSPACE: u32 = 1 << 0;
+pub(super) const NOTIFY_KEYEVENT: u32 = 1 << 1;
+pub(super) const NOTIFY_GENERIC: u32 = 1 << 2;
+pub(super) const NOTIFY_STRING: u32 = 1 << 3;
+pub(super) const NOTIFY_LIST: u32 = 1 << 4;
+pub(super) const NOTIFY_SET: u32 = 1 << 5;
+pub(super) const NOTIFY_HASH: u32 = 1 << 6;
+pub(super) const NOTIFY_ZSET: u32 = 1 << 7;
+pub(super) const NOTIFY_EXPIRED: u32 = 1 << 8;
+pub(super) const NOTIFY_EVICTED: u32 = 1 << 9;
+pub(super) const NOTIFY_STREAM: u32 = 1 << 10;
+pub(super) const NOTIFY_KEY_MISS: u32 = 1 << 11;
+pub(super) const NOTIFY_MODULE: u32 = 1 << 12;
+// the classes the A alias stands for, leaving out key misses like redis. The new keys
+// class of redis is not supported, as no event is published when a key is created
+const NOTIFY_ALL: u32 = NOTIFY_GENERIC
+    | NOTIFY_STRING
+    | NOTIFY_LIST
+    | NOTIFY_SET
+    | NOTIFY_HASH
+    | NOTIFY_ZSET
+    | NOTIFY_EXPIRED
+    | NOTIFY_EVICTED
+    | NOTIFY_STREAM
+    | NOTIFY_MODULE;
+
+// characters of each class, in the order CONFIG GET lists them
+const CLASSES: &[(char, u32)] = &[
+    ('g', NOTIFY_GENERIC),
+    ('$', NOTIFY_STRING),
+    ('l', NOTIFY_LIST),
+    ('s', NOTIFY_SET),
+    ('h', NOTIFY_HASH),
+    ('z', NOTIFY_ZSET),
+    ('x', NOTIFY_EXPIRED),
+    ('e', NOTIFY_EVICTED),
+    ('t', NOTIFY_STREAM),
+    ('d', NOTIFY_MODULE),
+    ('K', NOTIFY_KEYSPACE),
+    ('E', NOTIFY_KEYEVENT),
+    ('m', NOTIFY_KEY_MISS),
+];
+
+/// Classes enabled by a notify-keyspace-events value, None if it holds an unknown
+/// character.
+pub(super) fn keyspace_event_flags(value: &str) -> Option<u32> {
+    value.chars().try_fold(0, |flags, c| {
+        let class = match c {
+            'A' => NOTIFY_ALL,
+            c => CLASSES.iter().find(|(name, _)| *name == c)?.1,
+        };
+        Some(flags | class)
+    })
+}
+
+// validates notify-keyspace-events, replying with the classes in canonical order and
+// the A alias whenever all of its classes are set
+pub(super) fn parse_keyspace_events(value: &str) -> Result<String, String> {
+    let flags = keyspace_event_flags(value)
+        .ok_or_else(|| "Invalid event class character. Use 'Ag$lshzxeKEtmd'.".to_string())?;
+    let mut names = String::new();
+    let mut remaining = flags;
+    if flags & NOTIFY_ALL == NOTIFY_ALL {
+        names.push('A');
+        remaining &= !NOTIFY_ALL;
+    }
+    names.extend(
+        CLASSES
+            .iter()
+            .filter(|(_, class)| remaining & class != 0)
+            .map(|(name, _)| name),
+    );
+    Ok(names)
+}
+
+impl Backend {
+    /// Publishes `event` on `key` of this database if its class is enabled by
+    /// notify-keyspace-events: the event name to `__keyspace@<db>__:<key>` and the key
+    /// name to `__keyevent@<db>__:<event>`. Every event but a key miss means the key
+    /// changed, so it is also invalidated for the clients tracking it and counted as a
+    /// write for the save points.
+    pub(super) fn notify(&self, class: u32, event: &str, key: &str) {
+        if class != NOTIFY_KEY_MISS {
+            self.invalidate_key(key);
+            self.record_change();
+        }
+        let flags = self.config.keyspace_events();
+        if flags & class == 0 {
+            return;
+        }
+        let db = self.db_index();
+        if flags & NOTIFY_KEYSPACE != 0 {
+            self.publish(&format!("__keyspace@{}__:{}", db, key), event.as_bytes());
+        }
+        if flags & NOTIFY_KEYEVENT != 0 {
+            self.publish(&format!("__keyevent@{}__:{}", db, event), key.as_bytes());
+        }
+    }
+}
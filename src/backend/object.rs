@@ -0,0 +1,211 @@
+use super::{frame_to_i64, now_ms, string_bytes, Backend, KeyType};
+use rand::Rng;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+// counter given to new keys so that they are not the first ones considered cold
+const LFU_INIT_VAL: u8 = 5;
+// the higher, the more accesses it takes to increment the counter
+const LFU_LOG_FACTOR: f64 = 10.0;
+// the counter is decremented once per elapsed period without access
+const LFU_DECAY_MS: u64 = 60 * 1000;
+// collections up to these sizes would be stored compactly by redis
+const LISTPACK_MAX_ENTRIES: usize = 128;
+const LISTPACK_MAX_VALUE: usize = 64;
+const INTSET_MAX_ENTRIES: usize = 512;
+// strings up to this length are allocated along with their object by redis
+const EMBSTR_MAX_LEN: usize = 44;
+// integers below this are shared objects in redis
+const SHARED_INTEGERS: i64 = 10000;
+
+/// When a key was last accessed and how often, like the LRU and LFU fields of a redis
+/// object.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct KeyAccess {
+    // unix milliseconds
+    last_access: u64,
+    // logarithmic access counter
+    counter: u8,
+}
+
+impl KeyAccess {
+    fn new(now: u64) -> Self {
+        Self {
+            last_access: now,
+            counter: LFU_INIT_VAL,
+        }
+    }
+
+    // metadata of a restored key, as given by RESTORE IDLETIME and FREQ
+    pub fn restored(now: u64, idle_time: Option<u64>, freq: Option<u8>) -> Self {
+        Self {
+            last_access: now.saturating_sub(idle_time.unwrap_or(0).saturating_mul(1000)),
+            counter: freq.unwrap_or(LFU_INIT_VAL),
+        }
+    }
+
+    fn touch(&mut self, now: u64) {
+        self.counter = self.decayed_counter(now);
+        if self.counter < u8::MAX {
+            let base = self.counter.saturating_sub(LFU_INIT_VAL) as f64;
+            let p = 1.0 / (base * LFU_LOG_FACTOR + 1.0);
+            if rand::thread_rng().gen::<f64>() < p {
+                self.counter += 1;
+            }
+        }
+        self.last_access = now;
+    }
+
+    fn decayed_counter(&self, now: u64) -> u8 {
+        let periods = now.saturating_sub(self.last_access) / LFU_DECAY_MS;
+        self.counter
+            .saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+}
+
+impl Backend {
+    /// Name of the encoding redis would use for the value at `key`, None if the key does
+    /// not exist.
+    pub fn object_encoding(&self, key: &str) -> Option<&'static str> {
+        let small = |len: usize, max_value: usize| {
+            len <= LISTPACK_MAX_ENTRIES && max_value <= LISTPACK_MAX_VALUE
+        };
+        let encoding = match self.peek_type(key)? {
+            KeyType::String => {
+                let value = self.db.map.get(key)?;
+                let bytes = string_bytes(value.value()).ok()?;
+                if bytes.len() <= 20 && frame_to_i64(value.value()).is_ok() {
+                    "int"
+                } else if bytes.len() <= EMBSTR_MAX_LEN {
+                    "embstr"
+                } else {
+                    "raw"
+                }
+            }
+            KeyType::Hash => {
+                let hmap = self.db.hmap.get(key)?;
+                let max_value = hmap
+                    .iter()
+                    .map(|e| {
+                        let value = string_bytes(e.value()).map_or(0, |v| v.len());
+                        e.key().len().max(value)
+                    })
+                    .max()
+                    .unwrap_or(0);
+                if small(hmap.len(), max_value) {
+                    "listpack"
+                } else {
+                    "hashtable"
+                }
+            }
+            KeyType::List => {
+                let list = self.db.list.get(key)?;
+                let max_value = list
+                    .iter()
+                    .map(|v| string_bytes(v).map_or(0, |v| v.len()))
+                    .max()
+                    .unwrap_or(0);
+                if small(list.len(), max_value) {
+                    "listpack"
+                } else {
+                    "quicklist"
+                }
+            }
+            KeyType::Set => {
+                let set = self.db.set.get(key)?;
+                let integers = set.iter().all(|member| {
+                    std::str::from_utf8(member).is_ok_and(|s| s.parse::<i64>().is_ok())
+                });
+                let max_value = set.iter().map(|member| member.len()).max().unwrap_or(0);
+                if integers && set.len() <= INTSET_MAX_ENTRIES {
+                    "intset"
+                } else if small(set.len(), max_value) {
+                    "listpack"
+                } else {
+                    "hashtable"
+                }
+            }
+            KeyType::ZSet => {
+                let zset = self.db.zset.get(key)?;
+                let max_value = zset.iter().map(|(member, _)| member.len()).max();
+                if small(zset.len(), max_value.unwrap_or(0)) {
+                    "listpack"
+                } else {
+                    "skiplist"
+                }
+            }
+        };
+        Some(encoding)
+    }
+
+    /// Number of references to the value at `key`. Values are never shared, except small
+    /// integers which redis shares between all keys.
+    pub fn object_refcount(&self, key: &str) -> Option<i64> {
+        self.peek_type(key)?;
+        let shared = self
+            .db
+            .map
+            .get(key)
+            .and_then(|value| frame_to_i64(value.value()).ok())
+            .is_some_and(|n| (0..SHARED_INTEGERS).contains(&n));
+        Some(if shared { i32::MAX as i64 } else { 1 })
+    }
+
+    /// Seconds since `key` was last read or written.
+    pub fn object_idletime(&self, key: &str) -> Option<u64> {
+        self.peek_type(key)?;
+        let now = now_ms();
+        let last_access = self.db.access.get(key).map_or(now, |a| a.last_access);
+        Some(now.saturating_sub(last_access) / 1000)
+    }
+
+    /// Logarithmic access frequency counter of `key`, decayed over time.
+    pub fn object_freq(&self, key: &str) -> Option<u8> {
+        self.peek_type(key)?;
+        let freq = self
+            .db
+            .access
+            .get(key)
+            .map_or(LFU_INIT_VAL, |a| a.decayed_counter(now_ms()));
+        Some(freq)
+    }
+
+    /// Internal details of the value at `key`, in the format of redis' DEBUG OBJECT.
+    pub fn debug_object(&self, key: &str) -> Option<String> {
+        let encoding = self.object_encoding(key)?;
+        let refcount = self.object_refcount(key)?;
+        let idle = self.object_idletime(key)?;
+        let last_access = self.db.access.get(key).map_or(now_ms(), |a| a.last_access);
+        // values have no stable address here, a hash of the key stands in for it so that
+        // clients parsing the reply still find the field
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Some(format!(
+            "Value at:{:#x} refcount:{} encoding:{} serializedlength:{} lru:{} lru_seconds_idle:{}",
+            hasher.finish(),
+            refcount,
+            encoding,
+            self.serialized_length(key).unwrap_or(0),
+            // redis' LRU clock: seconds, wrapping at 24 bits
+            last_access / 1000 % (1 << 24),
+            idle,
+        ))
+    }
+
+    // updates the access metadata of `key`, which exists or is about to be written
+    pub(super) fn record_access(&self, key: &str) {
+        let now = now_ms();
+        match self.db.access.get_mut(key) {
+            Some(mut access) => access.touch(now),
+            None => {
+                self.db.access.insert(key.to_string(), KeyAccess::new(now));
+            }
+        }
+    }
+
+    // type of `key` without counting as an access to it
+    pub(super) fn peek_type(&self, key: &str) -> Option<KeyType> {
+        self.expire_if_needed(key);
+        self.key_type(key)
+    }
+}
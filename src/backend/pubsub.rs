@@ -0,0 +1,177 @@
+use super::Backend;
+use crate::{glob_match, BulkString, RespFrame, RespPush};
+use dashmap::DashMap;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+
+/// Frames the server pushes to a connection outside of replies, like pub/sub messages.
+/// Built as pushes, which RESP2 clients receive as plain arrays.
+/// Unbounded like the output buffers of redis clients without limits.
+pub(crate) type Outbox = mpsc::UnboundedSender<RespFrame>;
+
+// outboxes of the subscribers, keyed by client id
+type Subscribers = HashMap<u64, Outbox>;
+
+/// Subscribers of every channel and pattern that has some.
+#[derive(Debug, Default)]
+pub(crate) struct PubSub {
+    channels: DashMap<String, Subscribers>,
+    // glob patterns subscribed to with PSUBSCRIBE
+    patterns: DashMap<String, Subscribers>,
+    // shard channels subscribed to with SSUBSCRIBE, unrelated to the channels of the
+    // same name
+    shard_channels: DashMap<String, Subscribers>,
+}
+
+impl Backend {
+    pub(super) fn subscribe_channel(&self, channel: &str, id: u64, outbox: Outbox) {
+        subscribe(&self.pubsub.channels, channel, id, outbox);
+    }
+
+    pub(super) fn unsubscribe_channel(&self, channel: &str, id: u64) {
+        unsubscribe(&self.pubsub.channels, channel, id);
+    }
+
+    pub(super) fn subscribe_pattern(&self, pattern: &str, id: u64, outbox: Outbox) {
+        subscribe(&self.pubsub.patterns, pattern, id, outbox);
+    }
+
+    pub(super) fn unsubscribe_pattern(&self, pattern: &str, id: u64) {
+        unsubscribe(&self.pubsub.patterns, pattern, id);
+    }
+
+    pub(super) fn subscribe_shard_channel(&self, channel: &str, id: u64, outbox: Outbox) {
+        subscribe(&self.pubsub.shard_channels, channel, id, outbox);
+    }
+
+    pub(super) fn unsubscribe_shard_channel(&self, channel: &str, id: u64) {
+        unsubscribe(&self.pubsub.shard_channels, channel, id);
+    }
+
+    // sends `frame` to client `id` only, if it is subscribed to `channel`
+    pub(super) fn send_to_subscriber(&self, channel: &str, id: u64, frame: RespFrame) {
+        if let Some(outbox) = self
+            .pubsub
+            .channels
+            .get(channel)
+            .and_then(|subscribers| subscribers.get(&id).cloned())
+        {
+            let _ = outbox.send(frame);
+        }
+    }
+
+    /// Channels with at least one subscriber, sorted, only those matching the glob
+    /// `pattern` if given.
+    pub fn pubsub_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        list(&self.pubsub.channels, pattern)
+    }
+
+    /// How many clients are subscribed to `channel`, not counting pattern subscriptions.
+    pub fn pubsub_numsub(&self, channel: &str) -> usize {
+        count(&self.pubsub.channels, channel)
+    }
+
+    /// Shard channels with at least one subscriber, sorted, only those matching the glob
+    /// `pattern` if given.
+    pub fn pubsub_shard_channels(&self, pattern: Option<&str>) -> Vec<String> {
+        list(&self.pubsub.shard_channels, pattern)
+    }
+
+    /// How many clients are subscribed to the shard channel `channel`.
+    pub fn pubsub_shard_numsub(&self, channel: &str) -> usize {
+        count(&self.pubsub.shard_channels, channel)
+    }
+
+    /// How many distinct patterns clients are subscribed to.
+    pub fn pubsub_numpat(&self) -> usize {
+        self.pubsub.patterns.len()
+    }
+
+    /// Sends `message` to the subscribers of `channel` and of the patterns matching it.
+    /// Returns how many received it, counting a client once per subscription like redis
+    /// does when its channel and patterns overlap.
+    pub fn publish(&self, channel: &str, message: &[u8]) -> usize {
+        let mut receivers = 0;
+        if let Some(subscribers) = self.pubsub.channels.get(channel) {
+            let frame: RespFrame = RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ])
+            .into();
+            receivers += deliver(&subscribers, &frame);
+        }
+        for entry in self.pubsub.patterns.iter() {
+            if !glob_match(entry.key().as_bytes(), channel.as_bytes(), false) {
+                continue;
+            }
+            let frame: RespFrame = RespPush::new(vec![
+                BulkString::new("pmessage").into(),
+                BulkString::new(entry.key().as_str()).into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ])
+            .into();
+            receivers += deliver(entry.value(), &frame);
+        }
+        receivers
+    }
+
+    /// Sends `message` to the subscribers of the shard channel `channel`, which patterns
+    /// never match. Returns how many received it.
+    pub fn spublish(&self, channel: &str, message: &[u8]) -> usize {
+        let Some(subscribers) = self.pubsub.shard_channels.get(channel) else {
+            return 0;
+        };
+        let frame: RespFrame = RespPush::new(vec![
+            BulkString::new("smessage").into(),
+            BulkString::new(channel).into(),
+            BulkString::new(message).into(),
+        ])
+        .into();
+        deliver(&subscribers, &frame)
+    }
+}
+
+fn subscribe(registry: &DashMap<String, Subscribers>, name: &str, id: u64, outbox: Outbox) {
+    registry
+        .entry(name.to_string())
+        .or_default()
+        .insert(id, outbox);
+}
+
+fn unsubscribe(registry: &DashMap<String, Subscribers>, name: &str, id: u64) {
+    if let Some(mut subscribers) = registry.get_mut(name) {
+        subscribers.remove(&id);
+    }
+    // channels and patterns are only listed while someone is subscribed to them
+    registry.remove_if(name, |_, subscribers| subscribers.is_empty());
+}
+
+// names in `registry`, sorted, only those matching the glob `pattern` if given
+fn list(registry: &DashMap<String, Subscribers>, pattern: Option<&str>) -> Vec<String> {
+    let mut names = registry
+        .iter()
+        .map(|entry| entry.key().clone())
+        .filter(|name| {
+            pattern.is_none_or(|pattern| glob_match(pattern.as_bytes(), name.as_bytes(), false))
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn count(registry: &DashMap<String, Subscribers>, name: &str) -> usize {
+    registry
+        .get(name)
+        .map_or(0, |subscribers| subscribers.len())
+}
+
+// sends `frame` to every subscriber, returning how many got it
+fn deliver(subscribers: &Subscribers, frame: &RespFrame) -> usize {
+    subscribers
+        .values()
+        // the connection of a subscriber may be closing
+        .filter(|outbox| outbox.send(frame.clone()).is_ok())
+        .count()
+}
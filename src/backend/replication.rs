@@ -0,0 +1,19 @@
+use super::Backend;
+
+// replicas can't attach to this server, so it never propagates its writes and no one
+// acknowledges them
+impl Backend {
+    /// Offset of the replication stream, which stays at 0 as no write is propagated.
+    pub fn repl_offset(&self) -> u64 {
+        0
+    }
+
+    pub fn connected_replicas(&self) -> usize {
+        0
+    }
+
+    /// How many replicas acknowledged the replication stream up to `offset`.
+    pub fn replicas_acked(&self, _offset: u64) -> usize {
+        0
+    }
+}
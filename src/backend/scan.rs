@@ -0,0 +1,82 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// One page of a cursor based iteration over `items`.
+///
+/// Elements are visited in the order of a fixed hash of their name and the cursor is the
+/// hash of the next element to visit, so a full iteration returns every element that was
+/// present from start to end even if the collection changes in between. Elements sharing
+/// a hash are always returned in the same page. A returned cursor of 0 ends the iteration.
+pub(crate) fn scan<T, K>(
+    items: impl IntoIterator<Item = T>,
+    cursor: u64,
+    count: usize,
+    name: impl Fn(&T) -> &K,
+) -> (u64, Vec<T>)
+where
+    K: Hash + ?Sized,
+{
+    let mut remaining = items
+        .into_iter()
+        .map(|item| (scan_hash(name(&item)), item))
+        .filter(|(hash, _)| *hash >= cursor)
+        .collect::<Vec<_>>();
+    remaining.sort_by_key(|(hash, _)| *hash);
+
+    let mut end = count.max(1).min(remaining.len());
+    while end < remaining.len() && remaining[end].0 == remaining[end - 1].0 {
+        end += 1;
+    }
+    let next = remaining.get(end).map_or(0, |(hash, _)| *hash);
+    remaining.truncate(end);
+    (next, remaining.into_iter().map(|(_, item)| item).collect())
+}
+
+// DefaultHasher::new() uses fixed keys, so the order is stable across calls;
+// 0 is reserved for the start and the end of an iteration
+fn scan_hash<K: Hash + ?Sized>(name: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish().max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_visits_every_element_once() {
+        let items = (0..100).map(|i| format!("field:{}", i)).collect::<Vec<_>>();
+        let mut seen = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let (next, page) = scan(items.iter(), cursor, 10, |s| s.as_str());
+            assert!(page.len() <= 10);
+            seen.extend(page);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        seen.sort();
+        let mut expected = items.iter().collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn test_scan_survives_removals() {
+        let mut items = (0..50).map(|i| format!("field:{}", i)).collect::<Vec<_>>();
+        let (cursor, first) = scan(items.iter().cloned(), 0, 20, |s| s.as_str());
+        // drop an element that was already returned
+        items.retain(|item| item != &first[0]);
+        let mut seen = first;
+        let mut cursor = cursor;
+        while cursor != 0 {
+            let (next, page) = scan(items.iter().cloned(), cursor, 20, |s| s.as_str());
+            seen.extend(page);
+            cursor = next;
+        }
+        assert_eq!(seen.len(), 50);
+    }
+}
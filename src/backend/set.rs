@@ -0,0 +1,275 @@
+use super::notify::{NOTIFY_GENERIC, NOTIFY_SET};
+use super::{scan, Backend, BackendError, KeyType};
+use rand::seq::{IteratorRandom, SliceRandom};
+use std::collections::HashSet;
+
+/// How the sets of several keys are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetOp {
+    // members of all the sets
+    Inter,
+    // members of any of the sets
+    Union,
+    // members of the first set that are in none of the others
+    Diff,
+}
+
+impl Backend {
+    /// Adds `members` to the set at `key`, creating the set if needed. Returns the number
+    /// of members that were not in the set yet.
+    pub fn sadd(&self, key: String, members: Vec<Vec<u8>>) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::Set)?;
+        let mut set = self.db.set.entry(key).or_default();
+        let added = members
+            .into_iter()
+            .filter(|member| set.insert(member.clone()))
+            .count();
+        if added > 0 {
+            self.notify(NOTIFY_SET, "sadd", set.key());
+        }
+        Ok(added)
+    }
+
+    /// Removes `members` from the set at `key`, deleting the key once the set is empty.
+    /// Returns the number of members removed.
+    pub fn srem(&self, key: &str, members: &[Vec<u8>]) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::Set)?;
+        let removed = match self.db.set.get_mut(key) {
+            Some(mut set) => members.iter().filter(|member| set.remove(*member)).count(),
+            None => return Ok(0),
+        };
+        if removed > 0 {
+            self.notify(NOTIFY_SET, "srem", key);
+        }
+        self.remove_set_if_empty(key);
+        Ok(removed)
+    }
+
+    pub fn smembers(&self, key: &str) -> Result<Vec<Vec<u8>>, BackendError> {
+        self.check_read(key, KeyType::Set)?;
+        Ok(self
+            .db
+            .set
+            .get(key)
+            .map_or(vec![], |set| set.iter().cloned().collect()))
+    }
+
+    pub fn sismember(&self, key: &str, member: &[u8]) -> Result<bool, BackendError> {
+        self.check_read(key, KeyType::Set)?;
+        Ok(self.db.set.get(key).is_some_and(|set| set.contains(member)))
+    }
+
+    /// Membership of each of `members` in the set at `key`, in the same order.
+    pub fn smismember(&self, key: &str, members: &[Vec<u8>]) -> Result<Vec<bool>, BackendError> {
+        self.check_read(key, KeyType::Set)?;
+        let set = self.db.set.get(key);
+        Ok(members
+            .iter()
+            .map(|member| set.as_ref().is_some_and(|set| set.contains(member)))
+            .collect())
+    }
+
+    /// Atomically moves `member` from the set at `source` to the set at `destination`,
+    /// returning whether `member` was in `source`.
+    pub fn smove(
+        &self,
+        source: &str,
+        destination: String,
+        member: Vec<u8>,
+    ) -> Result<bool, BackendError> {
+        let _guard = self.exclusive_lock();
+        self.check_type(source, KeyType::Set)?;
+        self.check_type(&destination, KeyType::Set)?;
+        if source == destination {
+            return Ok(self
+                .db
+                .set
+                .get(source)
+                .is_some_and(|set| set.contains(&member)));
+        }
+        let removed = self
+            .db
+            .set
+            .get_mut(source)
+            .is_some_and(|mut set| set.remove(&member));
+        if !removed {
+            return Ok(false);
+        }
+        self.notify(NOTIFY_SET, "srem", source);
+        self.remove_set_if_empty(source);
+        self.db
+            .set
+            .entry(destination.clone())
+            .or_default()
+            .insert(member);
+        self.notify(NOTIFY_SET, "sadd", &destination);
+        Ok(true)
+    }
+
+    /// One page of a cursor based iteration over the set at `key`.
+    pub fn sscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+    ) -> Result<(u64, Vec<Vec<u8>>), BackendError> {
+        self.check_read(key, KeyType::Set)?;
+        let Some(set) = self.db.set.get(key) else {
+            return Ok((0, vec![]));
+        };
+        let members = set.iter().cloned();
+        Ok(scan::scan(members, cursor, count, |member| {
+            member.as_slice()
+        }))
+    }
+
+    /// Number of members of the set at `key`, 0 if the key does not exist.
+    pub fn scard(&self, key: &str) -> Result<usize, BackendError> {
+        self.check_read(key, KeyType::Set)?;
+        Ok(self.db.set.get(key).map_or(0, |set| set.len()))
+    }
+
+    /// Removes and returns up to `count` random members of the set at `key`, deleting the
+    /// key once the set is empty.
+    pub fn spop(&self, key: &str, count: usize) -> Result<Vec<Vec<u8>>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::Set)?;
+        let popped = match self.db.set.get_mut(key) {
+            Some(mut set) => {
+                let mut rng = rand::thread_rng();
+                // the sample is allocated upfront, a count above the size takes the whole set
+                let count = count.min(set.len());
+                let popped = set.iter().cloned().choose_multiple(&mut rng, count);
+                for member in &popped {
+                    set.remove(member);
+                }
+                popped
+            }
+            None => return Ok(vec![]),
+        };
+        if !popped.is_empty() {
+            self.notify(NOTIFY_SET, "spop", key);
+        }
+        self.remove_set_if_empty(key);
+        Ok(popped)
+    }
+
+    /// Random members of the set at `key`. A positive `count` returns distinct members, a
+    /// negative one may repeat the same member.
+    pub fn srandmember(&self, key: &str, count: i64) -> Result<Vec<Vec<u8>>, BackendError> {
+        self.check_read(key, KeyType::Set)?;
+        let Some(set) = self.db.set.get(key) else {
+            return Ok(vec![]);
+        };
+        let mut rng = rand::thread_rng();
+        if count >= 0 {
+            // the sample is allocated upfront, a count above the size takes the whole set
+            let count = (count as usize).min(set.len());
+            let mut members = set.iter().cloned().choose_multiple(&mut rng, count);
+            members.shuffle(&mut rng);
+            return Ok(members);
+        }
+        let members = set.iter().collect::<Vec<_>>();
+        Ok((0..count.unsigned_abs())
+            .filter_map(|_| members.choose(&mut rng).map(|member| member.to_vec()))
+            .collect())
+    }
+
+    /// Combines the sets at `keys` with `op`, missing keys counting as empty sets.
+    pub fn scombine(&self, op: SetOp, keys: &[String]) -> Result<Vec<Vec<u8>>, BackendError> {
+        Ok(self.scombine_unlocked(op, keys)?.into_iter().collect())
+    }
+
+    /// Stores the combination of the sets at `keys` in `destination`, replacing whatever
+    /// it held and deleting it if the result is empty. Returns the size of the result.
+    pub fn scombine_store(
+        &self,
+        op: SetOp,
+        destination: String,
+        keys: &[String],
+    ) -> Result<usize, BackendError> {
+        let _guard = self.exclusive_lock();
+        let result = self.scombine_unlocked(op, keys)?;
+        let len = result.len();
+        let existed = self.delete_key(&destination);
+        if !result.is_empty() {
+            self.db.set.insert(destination.clone(), result);
+            let event = match op {
+                SetOp::Inter => "sinterstore",
+                SetOp::Union => "sunionstore",
+                SetOp::Diff => "sdiffstore",
+            };
+            self.notify(NOTIFY_SET, event, &destination);
+        } else if existed {
+            self.notify(NOTIFY_GENERIC, "del", &destination);
+        }
+        Ok(len)
+    }
+
+    /// Size of the intersection of the sets at `keys`, counting stops once `limit` members
+    /// (0 for no limit) were found.
+    pub fn sintercard(&self, keys: &[String], limit: usize) -> Result<usize, BackendError> {
+        // holding several shard guards at once is only safe while no writer can run
+        let _guard = self.exclusive_lock();
+        for key in keys {
+            self.check_read(key, KeyType::Set)?;
+        }
+        let Some(mut sets) = keys
+            .iter()
+            .map(|key| self.db.set.get(key))
+            .collect::<Option<Vec<_>>>()
+        else {
+            return Ok(0);
+        };
+        // walk the smallest set and probe the others
+        sets.sort_by_key(|set| set.len());
+        let limit = if limit == 0 { usize::MAX } else { limit };
+        Ok(sets[0]
+            .iter()
+            .filter(|member| sets[1..].iter().all(|set| set.contains(*member)))
+            .take(limit)
+            .count())
+    }
+
+    fn scombine_unlocked(
+        &self,
+        op: SetOp,
+        keys: &[String],
+    ) -> Result<HashSet<Vec<u8>>, BackendError> {
+        // type check every key before combining anything
+        for key in keys {
+            self.check_read(key, KeyType::Set)?;
+        }
+        let sets = keys.iter().map(|key| {
+            self.db
+                .set
+                .get(key)
+                .map(|set| set.clone())
+                .unwrap_or_default()
+        });
+        let mut result = HashSet::new();
+        for (i, set) in sets.enumerate() {
+            match op {
+                _ if i == 0 => result = set,
+                SetOp::Inter => result.retain(|member| set.contains(member)),
+                SetOp::Union => result.extend(set),
+                SetOp::Diff => result.retain(|member| !set.contains(member)),
+            }
+        }
+        Ok(result)
+    }
+
+    fn remove_set_if_empty(&self, key: &str) {
+        if self
+            .db
+            .set
+            .remove_if(key, |_, set| set.is_empty())
+            .is_some()
+        {
+            self.db.expire.remove(key);
+            self.notify(NOTIFY_GENERIC, "del", key);
+        }
+    }
+}
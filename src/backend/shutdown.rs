@@ -0,0 +1,63 @@
+use super::{Backend, BackendError, ClientFilter};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+
+// how often the server checks whether every connection closed while draining them
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Tells the accept loop and the connections that the server is stopping.
+#[derive(Debug)]
+pub(crate) struct ShutdownSignal {
+    requested: watch::Sender<bool>,
+}
+
+impl Default for ShutdownSignal {
+    fn default() -> Self {
+        Self {
+            requested: watch::Sender::new(false),
+        }
+    }
+}
+
+impl Backend {
+    /// Stops the server, first saving a snapshot if `save` says so, or by default when
+    /// save points are configured. A failed save cancels the shutdown unless `force` is
+    /// set.
+    pub fn shutdown(&self, save: Option<bool>, force: bool) -> Result<(), BackendError> {
+        if save.unwrap_or_else(|| self.save_configured()) {
+            if let Err(e) = self.write_snapshot_now() {
+                if !force {
+                    return Err(e);
+                }
+            }
+        }
+        self.shutdown.requested.send_replace(true);
+        Ok(())
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        *self.shutdown.requested.borrow()
+    }
+
+    /// Resolves once a shutdown is requested, right away if it already was.
+    pub async fn shutdown_requested(&self) {
+        let mut requested = self.shutdown.requested.subscribe();
+        // the sender lives as long as the backend, so this can't fail
+        let _ = requested.wait_for(|requested| *requested).await;
+    }
+
+    /// Closes every connection and waits for up to `timeout` for them to be gone.
+    /// Returns whether they all closed in time.
+    pub async fn drain_clients(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        self.kill_clients(&ClientFilter::default(), None);
+        while !self.client_list().is_empty() {
+            if Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+        }
+        true
+    }
+}
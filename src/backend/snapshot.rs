@@ -0,0 +1,373 @@
+use super::dump::{crc64, decode_payload, dump_value, put_bytes, put_len, Reader};
+use super::value::Value;
+use super::{now_ms, Backend, BackendError, LatencyEvent};
+use anyhow::Context;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use std::{fs, io};
+use tokio::task::JoinHandle;
+
+const SNAPSHOT_MAGIC: &[u8] = b"SREDIS";
+// bumped whenever the layout of a snapshot changes, older snapshots are rejected
+const SNAPSHOT_VERSION: u16 = 2;
+// how often the save points are checked, like the serverCron of redis
+const SAVE_SCHEDULE_INTERVAL: Duration = Duration::from_secs(1);
+
+// a key as written to a snapshot, with the index of its database and its absolute
+// expiration time in unix milliseconds
+type SnapshotEntry = (usize, String, Option<u64>, Value);
+
+/// Outcome of asking for a background save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BgSaveStatus {
+    Started,
+    // another one is in progress, this one starts once it is done
+    Scheduled,
+}
+
+/// State of the snapshots, as reported by LASTSAVE and INFO persistence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PersistenceStats {
+    // unix seconds of the last successful save, or of the start of the server
+    pub last_save: u64,
+    // writes to the keyspace the last successful save doesn't have
+    pub changes_since_last_save: u64,
+    pub bgsave_in_progress: bool,
+    pub last_bgsave_ok: bool,
+}
+
+#[derive(Debug)]
+pub(crate) struct SnapshotState {
+    last_save: AtomicU64,
+    changes: AtomicU64,
+    bgsave_in_progress: AtomicBool,
+    bgsave_scheduled: AtomicBool,
+    last_bgsave_ok: AtomicBool,
+}
+
+impl Default for SnapshotState {
+    fn default() -> Self {
+        Self {
+            last_save: AtomicU64::new(now_ms() / 1000),
+            changes: AtomicU64::new(0),
+            bgsave_in_progress: AtomicBool::new(false),
+            bgsave_scheduled: AtomicBool::new(false),
+            last_bgsave_ok: AtomicBool::new(true),
+        }
+    }
+}
+
+impl Backend {
+    /// Writes every key to the snapshot file, replacing it only once the new snapshot is
+    /// complete. Fails while a background save is in progress.
+    ///
+    /// A snapshot is a header, then the database, name, expiration time or 0 and DUMP
+    /// payload of each key, followed by a CRC-64 of everything before it.
+    pub fn save_snapshot(&self) -> Result<(), BackendError> {
+        if self.snapshot.bgsave_in_progress.load(Ordering::Relaxed) {
+            return Err(BackendError::BgSaveInProgress);
+        }
+        self.write_snapshot_now()
+    }
+
+    // saves right away even if a background save is in progress, for SHUTDOWN
+    pub(super) fn write_snapshot_now(&self) -> Result<(), BackendError> {
+        let start = Instant::now();
+        let (entries, changes) = self.snapshot_entries();
+        write_snapshot(&self.snapshot_path(), "tmp", &entries)
+            .map_err(|e| BackendError::SnapshotFailed(e.to_string()))?;
+        self.saved(changes);
+        self.record_latency(LatencyEvent::Snapshot, start.elapsed());
+        Ok(())
+    }
+
+    /// Saves a snapshot of the keyspace as it is now on a blocking task, so that clients
+    /// can go on meanwhile. When another background save is in progress, fails unless
+    /// `schedule` asks to run this one after it.
+    pub fn bgsave(&self, schedule: bool) -> Result<BgSaveStatus, BackendError> {
+        let state = &self.snapshot;
+        if state.bgsave_in_progress.swap(true, Ordering::AcqRel) {
+            if !schedule {
+                return Err(BackendError::BgSaveInProgress);
+            }
+            state.bgsave_scheduled.store(true, Ordering::Relaxed);
+            return Ok(BgSaveStatus::Scheduled);
+        }
+        self.spawn_bgsave();
+        Ok(BgSaveStatus::Started)
+    }
+
+    fn spawn_bgsave(&self) {
+        let start = Instant::now();
+        let (entries, changes) = self.snapshot_entries();
+        let backend = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let saved = write_snapshot(&backend.snapshot_path(), "bgtmp", &entries).is_ok();
+            let state = &backend.snapshot;
+            if saved {
+                backend.saved(changes);
+                backend.record_latency(LatencyEvent::Snapshot, start.elapsed());
+            }
+            state.last_bgsave_ok.store(saved, Ordering::Relaxed);
+            if state.bgsave_scheduled.swap(false, Ordering::AcqRel) {
+                backend.spawn_bgsave();
+            } else {
+                state.bgsave_in_progress.store(false, Ordering::Release);
+            }
+        });
+    }
+
+    // a save of the keyspace as it was after `changes` writes completed
+    fn saved(&self, changes: u64) {
+        let state = &self.snapshot;
+        state.last_save.store(now_ms() / 1000, Ordering::Relaxed);
+        // the writes made while saving are left for the next save
+        state.changes.fetch_sub(changes, Ordering::Relaxed);
+    }
+
+    // counts a write to the keyspace for the save points
+    pub(super) fn record_change(&self) {
+        self.snapshot.changes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Spawns the task starting a background save whenever one of the save points is
+    /// reached. The task ends once every other handle to the backend is dropped.
+    pub fn start_save_schedule(&self) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(SAVE_SCHEDULE_INTERVAL).await;
+                match inner.upgrade() {
+                    Some(inner) => Backend::from_inner(inner).save_if_due(),
+                    None => return,
+                };
+            }
+        })
+    }
+
+    /// Starts a background save if, for any `seconds changes` pair of the save config,
+    /// at least that many writes happened and that many seconds passed since the last
+    /// save. Returns whether a save was started.
+    pub fn save_if_due(&self) -> bool {
+        let state = &self.snapshot;
+        let changes = state.changes.load(Ordering::Relaxed);
+        let elapsed = (now_ms() / 1000).saturating_sub(state.last_save.load(Ordering::Relaxed));
+        let due = self
+            .save_points()
+            .into_iter()
+            .any(|(seconds, min_changes)| changes >= min_changes && elapsed >= seconds);
+        // a save already in progress will take care of the writes
+        due && self.bgsave(false).is_ok()
+    }
+
+    pub fn persistence_stats(&self) -> PersistenceStats {
+        let state = &self.snapshot;
+        PersistenceStats {
+            last_save: state.last_save.load(Ordering::Relaxed),
+            changes_since_last_save: state.changes.load(Ordering::Relaxed),
+            bgsave_in_progress: state.bgsave_in_progress.load(Ordering::Acquire),
+            last_bgsave_ok: state.last_bgsave_ok.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Loads the keys of the snapshot file, if there is one, leaving out those that
+    /// expired in the meantime. Returns how many keys were loaded.
+    pub fn load_snapshot(&self) -> anyhow::Result<usize> {
+        let path = self.snapshot_path();
+        let data = match fs::read(&path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(e).with_context(|| format!("can't read {}", path.display()));
+            }
+        };
+        let entries = decode_snapshot(&data)
+            .with_context(|| format!("{} is not a valid snapshot", path.display()))?;
+
+        let dbs = self.all_dbs();
+        if let Some((index, ..)) = entries.iter().find(|(index, ..)| *index >= dbs.len()) {
+            anyhow::bail!(
+                "{} has keys in database {} but only {} databases are configured",
+                path.display(),
+                index,
+                dbs.len()
+            );
+        }
+
+        let _guard = self.exclusive_lock();
+        let now = now_ms();
+        let mut loaded = 0;
+        for (index, key, expire_at, value) in entries {
+            if expire_at.is_some_and(|at| at <= now) {
+                continue;
+            }
+            let db = &dbs[index];
+            db.delete_key(&key);
+            if let Some(at) = expire_at {
+                db.db.expire.insert(key.clone(), at);
+            }
+            db.insert_value(key, value);
+            loaded += 1;
+        }
+        // the keys just loaded are already saved
+        self.snapshot.changes.store(0, Ordering::Relaxed);
+        Ok(loaded)
+    }
+
+    // copy of every key of every database that hasn't expired, taken at a single point
+    // in time, along with the number of writes made by then
+    fn snapshot_entries(&self) -> (Vec<SnapshotEntry>, u64) {
+        let _guard = self.exclusive_lock();
+        let now = now_ms();
+        let mut entries = vec![];
+        for (index, db) in self.all_dbs().into_iter().enumerate() {
+            entries.extend(db.key_names().into_iter().filter_map(|key| {
+                let expire_at = db.db.expire.get(&key).map(|at| *at);
+                if expire_at.is_some_and(|at| at <= now) {
+                    return None;
+                }
+                let value = db.clone_value(&key)?;
+                Some((index, key, expire_at, value))
+            }));
+        }
+        (entries, self.snapshot.changes.load(Ordering::Relaxed))
+    }
+}
+
+// writes to a file with the `tmp` extension first, so that a save in the foreground and one
+// in the background don't clobber each other
+fn write_snapshot(path: &Path, tmp: &str, entries: &[SnapshotEntry]) -> io::Result<()> {
+    let mut data = SNAPSHOT_MAGIC.to_vec();
+    data.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+    for (index, key, expire_at, value) in entries {
+        let payload = dump_value(value)
+            .ok_or_else(|| io::Error::other(format!("can't serialize the value at {}", key)))?;
+        put_len(&mut data, *index);
+        put_bytes(&mut data, key.as_bytes());
+        data.extend_from_slice(&expire_at.unwrap_or(0).to_le_bytes());
+        put_bytes(&mut data, &payload);
+    }
+    let checksum = crc64(&data);
+    data.extend_from_slice(&checksum.to_le_bytes());
+
+    let tmp = path.with_extension(tmp);
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)
+}
+
+fn decode_snapshot(data: &[u8]) -> Option<Vec<SnapshotEntry>> {
+    let (body, checksum) = data.split_at_checked(data.len().checked_sub(8)?)?;
+    if crc64(body).to_le_bytes() != checksum {
+        return None;
+    }
+    let body = body.strip_prefix(SNAPSHOT_MAGIC)?;
+    let mut reader = Reader(body);
+    if u16::from_le_bytes(reader.array()?) != SNAPSHOT_VERSION {
+        return None;
+    }
+    let mut entries = vec![];
+    while !reader.0.is_empty() {
+        let index = reader.len()?;
+        let key = String::from_utf8(reader.bytes()?).ok()?;
+        let expire_at = match u64::from_le_bytes(reader.array()?) {
+            0 => None,
+            at => Some(at),
+        };
+        let value = decode_payload(&reader.bytes()?)?;
+        entries.push((index, key, expire_at, value));
+    }
+    Some(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, ListEnd, RespFrame};
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let dir =
+            std::env::temp_dir().join(format!("simple-redis-snapshot-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let backend = Backend::new();
+        let dir_value = dir.to_string_lossy().to_string();
+        backend
+            .config_set(&[("dir".to_string(), dir_value.clone())])
+            .unwrap();
+        backend.set("key".to_string(), BulkString::new("value").into());
+        backend
+            .push(
+                "list".to_string(),
+                ListEnd::Right,
+                vec![BulkString::new("a").into()],
+            )
+            .unwrap();
+        backend.set("volatile".to_string(), BulkString::new("1").into());
+        backend.expire("volatile", now_ms() + 60_000, None);
+        backend
+            .select(3)
+            .unwrap()
+            .set("key".to_string(), BulkString::new("other").into());
+        backend.save_snapshot().unwrap();
+
+        let restored = Backend::new();
+        restored
+            .config_set(&[("dir".to_string(), dir_value)])
+            .unwrap();
+        assert_eq!(restored.load_snapshot().unwrap(), 4);
+        assert_eq!(
+            restored.get("key"),
+            Some(RespFrame::from(BulkString::new("value")))
+        );
+        assert_eq!(restored.dump("list"), backend.dump("list"));
+        assert!(restored.pttl("volatile") > 0);
+        assert_eq!(
+            restored.select(3).unwrap().get("key"),
+            Some(RespFrame::from(BulkString::new("other")))
+        );
+
+        // a corrupted snapshot is rejected rather than partially loaded
+        let path = restored.snapshot_path();
+        let mut data = fs::read(&path).unwrap();
+        data[10] ^= 1;
+        fs::write(&path, data).unwrap();
+        assert!(restored.load_snapshot().is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_points() {
+        let dir = std::env::temp_dir().join(format!("simple-redis-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let backend = Backend::new();
+        backend
+            .config_set(&[
+                ("dir".to_string(), dir.to_string_lossy().to_string()),
+                ("save".to_string(), "10 2".to_string()),
+            ])
+            .unwrap();
+        backend.set("a".to_string(), BulkString::new("1").into());
+        assert_eq!(backend.persistence_stats().changes_since_last_save, 1);
+        backend
+            .snapshot
+            .last_save
+            .store(now_ms() / 1000 - 10, Ordering::Relaxed);
+        assert!(!backend.save_if_due());
+
+        backend.set("b".to_string(), BulkString::new("2").into());
+        assert!(backend.save_if_due());
+        while backend.persistence_stats().bgsave_in_progress {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let stats = backend.persistence_stats();
+        assert!(stats.last_bgsave_ok);
+        assert_eq!(stats.changes_since_last_save, 0);
+        // not due again until enough time passed since this save
+        backend.set("c".to_string(), BulkString::new("3").into());
+        backend.set("d".to_string(), BulkString::new("4").into());
+        assert!(!backend.save_if_due());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
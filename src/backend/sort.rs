@@ -0,0 +1,167 @@
+use super::notify::{NOTIFY_GENERIC, NOTIFY_LIST};
+use super::{string_bytes, Backend, BackendError, KeyType};
+use crate::{BulkString, RespFrame};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+
+/// Options of SORT.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    // BY: sort by the values of the keys built from this pattern, a pattern without `*`
+    // skips sorting
+    pub by: Option<String>,
+    // LIMIT offset count
+    pub limit: Option<(i64, i64)>,
+    // GET: reply with the values of the keys built from these patterns, `#` being the
+    // element itself
+    pub get: Vec<String>,
+    pub desc: bool,
+    // ALPHA: compare lexicographically instead of as numbers
+    pub alpha: bool,
+}
+
+// weight an element is sorted by
+#[derive(Debug, PartialEq, PartialOrd)]
+enum Weight {
+    Alpha(Option<Vec<u8>>),
+    Number(f64),
+}
+
+impl Backend {
+    /// Elements of the list, set or sorted set at `key` sorted according to `options`.
+    /// Elements looked up with GET patterns that point to nothing are None.
+    pub fn sort(
+        &self,
+        key: &str,
+        options: &SortOptions,
+    ) -> Result<Vec<Option<Vec<u8>>>, BackendError> {
+        let mut elements = match self.lookup_read(key) {
+            Some(KeyType::List) => self
+                .db
+                .list
+                .get(key)
+                .map_or(Ok(vec![]), |list| list.iter().map(string_bytes).collect())?,
+            Some(KeyType::Set) => self
+                .db
+                .set
+                .get(key)
+                .map_or(vec![], |set| set.iter().cloned().collect()),
+            Some(KeyType::ZSet) => self.db.zset.get(key).map_or(vec![], |zset| {
+                zset.iter().map(|(member, _)| member.to_vec()).collect()
+            }),
+            Some(_) => return Err(BackendError::WrongType),
+            None => vec![],
+        };
+
+        let sort = options.by.as_ref().is_none_or(|by| by.contains('*'));
+        if sort {
+            let mut weighted = elements
+                .into_iter()
+                .map(|element| Ok((self.sort_weight(&element, options)?, element)))
+                .collect::<Result<Vec<_>, BackendError>>()?;
+            // equal weights fall back to the elements themselves
+            weighted.sort_by(|(a, x), (b, y)| {
+                let ordering = a.partial_cmp(b).unwrap_or(Ordering::Equal).then(x.cmp(y));
+                if options.desc {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+            elements = weighted.into_iter().map(|(_, element)| element).collect();
+        }
+
+        let (offset, count) = options.limit.unwrap_or((0, -1));
+        let count = if count < 0 {
+            usize::MAX
+        } else {
+            count as usize
+        };
+        let elements = elements
+            .into_iter()
+            .skip(offset.max(0) as usize)
+            .take(count);
+        if options.get.is_empty() {
+            return Ok(elements.map(Some).collect());
+        }
+        Ok(elements
+            .flat_map(|element| {
+                options
+                    .get
+                    .iter()
+                    .map(|pattern| self.sort_lookup(pattern, &element))
+                    .collect::<Vec<_>>()
+            })
+            .collect())
+    }
+
+    /// Stores the result of SORT as a list at `destination`, replacing whatever it held
+    /// and deleting it if the result is empty. Returns the length of the list.
+    pub fn sort_store(
+        &self,
+        key: &str,
+        options: &SortOptions,
+        destination: String,
+    ) -> Result<usize, BackendError> {
+        let _guard = self.exclusive_lock();
+        let elements = self.sort(key, options)?;
+        let len = elements.len();
+        let existed = self.delete_key(&destination);
+        if !elements.is_empty() {
+            // lookups that point to nothing are stored as empty strings
+            let list = elements
+                .into_iter()
+                .map(|element| BulkString::new(element.unwrap_or_default()).into())
+                .collect::<VecDeque<RespFrame>>();
+            self.db.list.insert(destination.clone(), list);
+            self.notify(NOTIFY_LIST, "sortstore", &destination);
+            self.signal_key_ready(&destination);
+        } else if existed {
+            self.notify(NOTIFY_GENERIC, "del", &destination);
+        }
+        Ok(len)
+    }
+
+    fn sort_weight(&self, element: &[u8], options: &SortOptions) -> Result<Weight, BackendError> {
+        let value = match &options.by {
+            Some(by) => self.sort_lookup(by, element),
+            None => Some(element.to_vec()),
+        };
+        if options.alpha {
+            return Ok(Weight::Alpha(value));
+        }
+        let Some(value) = value else {
+            return Ok(Weight::Number(0.0));
+        };
+        std::str::from_utf8(&value)
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+            .filter(|value| !value.is_nan())
+            .map(Weight::Number)
+            .ok_or(BackendError::SortNotFloat)
+    }
+
+    // value of the key built by replacing the first `*` of `pattern` with `element`, or of
+    // a hash field when the pattern ends with `->field`
+    fn sort_lookup(&self, pattern: &str, element: &[u8]) -> Option<Vec<u8>> {
+        if pattern == "#" {
+            return Some(element.to_vec());
+        }
+        let star = pattern.find('*')?;
+        let (key, field) = match pattern[star + 1..].find("->") {
+            Some(arrow) if star + arrow + 3 < pattern.len() => {
+                let (key, field) = pattern.split_at(star + 1 + arrow);
+                (key, Some(&field[2..]))
+            }
+            _ => (pattern, None),
+        };
+        let element = std::str::from_utf8(element).ok()?;
+        let key = key.replacen('*', element, 1);
+        match field {
+            Some(field) => self
+                .hget(&key, field)
+                .and_then(|value| string_bytes(&value).ok()),
+            None => self.get_string(&key).ok().flatten(),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use super::Backend;
+use dashmap::DashMap;
+use rand::Rng;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Lookups of keys by read commands, as reported by INFO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyspaceStats {
+    pub keyspace_hits: u64,
+    pub keyspace_misses: u64,
+}
+
+/// Counters of the expiration subsystem, as reported by INFO.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExpireStats {
+    // keys removed because their TTL elapsed, lazily or by the active cycle
+    pub expired_keys: u64,
+    // running estimate of the percentage of keys with a TTL that are already expired
+    pub expired_stale_perc: f64,
+    // cycles stopped early because they ran out of time
+    pub expired_time_cap_reached_count: u64,
+    pub expire_cycle_cpu_milliseconds: u64,
+}
+
+/// Clients and commands served, as reported by INFO.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerStats {
+    pub uptime: Duration,
+    // random id of this run of the server, as replicas of redis would know it by
+    pub run_id: String,
+    pub connected_clients: u64,
+    pub blocked_clients: u64,
+    pub total_connections_received: u64,
+    pub total_commands_processed: u64,
+}
+
+/// Calls of a command, as reported by INFO commandstats.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandStats {
+    pub calls: u64,
+    // total time spent executing the command, in microseconds
+    pub usec: u64,
+    // calls refused before executing, like malformed ones
+    pub rejected_calls: u64,
+    // calls that executed and replied with an error
+    pub failed_calls: u64,
+}
+
+/// Registry of the counters every subsystem feeds, read back by INFO.
+#[derive(Debug)]
+pub(crate) struct Stats {
+    started_at: Instant,
+    run_id: String,
+    keyspace_hits: AtomicU64,
+    keyspace_misses: AtomicU64,
+    pub(super) expired_keys: AtomicU64,
+    // f64 bits
+    pub(super) expired_stale_perc: AtomicU64,
+    pub(super) expired_time_cap_reached: AtomicU64,
+    pub(super) expire_cycle_micros: AtomicU64,
+    connected_clients: AtomicU64,
+    pub(super) blocked_clients: AtomicU64,
+    total_connections_received: AtomicU64,
+    total_commands_processed: AtomicU64,
+    // keyed by lowercase command name
+    commands: DashMap<String, CommandStats>,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            started_at: Instant::now(),
+            run_id: (0..40)
+                .map(|_| char::from_digit(rng.gen_range(0..16), 16).unwrap_or('0'))
+                .collect(),
+            keyspace_hits: AtomicU64::new(0),
+            keyspace_misses: AtomicU64::new(0),
+            expired_keys: AtomicU64::new(0),
+            expired_stale_perc: AtomicU64::new(0),
+            expired_time_cap_reached: AtomicU64::new(0),
+            expire_cycle_micros: AtomicU64::new(0),
+            connected_clients: AtomicU64::new(0),
+            blocked_clients: AtomicU64::new(0),
+            total_connections_received: AtomicU64::new(0),
+            total_commands_processed: AtomicU64::new(0),
+            commands: DashMap::new(),
+        }
+    }
+}
+
+impl Stats {
+    pub fn record_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.keyspace_hits
+        } else {
+            &self.keyspace_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_connection(&self) {
+        self.connected_clients.fetch_add(1, Ordering::Relaxed);
+        self.total_connections_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_disconnection(&self) {
+        self.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Counts a call to `name` that took `elapsed` and possibly replied with an error.
+    pub fn record_command(&self, name: &str, elapsed: Duration, failed: bool) {
+        self.total_commands_processed
+            .fetch_add(1, Ordering::Relaxed);
+        let mut stats = self.commands.entry(name.to_string()).or_default();
+        stats.calls += 1;
+        stats.usec += elapsed.as_micros() as u64;
+        stats.failed_calls += failed as u64;
+    }
+
+    /// Counts a call to `name` refused before it could execute.
+    pub fn record_rejected_command(&self, name: &str) {
+        let mut stats = self.commands.entry(name.to_string()).or_default();
+        stats.rejected_calls += 1;
+    }
+
+    // zeroes the counters, leaving the gauges of current clients alone
+    fn reset(&self) {
+        let counters = [
+            &self.keyspace_hits,
+            &self.keyspace_misses,
+            &self.expired_keys,
+            &self.expired_stale_perc,
+            &self.expired_time_cap_reached,
+            &self.expire_cycle_micros,
+            &self.total_connections_received,
+            &self.total_commands_processed,
+        ];
+        for counter in counters {
+            counter.store(0, Ordering::Relaxed);
+        }
+        self.commands.clear();
+    }
+}
+
+impl Backend {
+    pub fn keyspace_stats(&self) -> KeyspaceStats {
+        KeyspaceStats {
+            keyspace_hits: self.stats.keyspace_hits.load(Ordering::Relaxed),
+            keyspace_misses: self.stats.keyspace_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn expire_stats(&self) -> ExpireStats {
+        let stats = &self.stats;
+        ExpireStats {
+            expired_keys: stats.expired_keys.load(Ordering::Relaxed),
+            expired_stale_perc: f64::from_bits(stats.expired_stale_perc.load(Ordering::Relaxed)),
+            expired_time_cap_reached_count: stats.expired_time_cap_reached.load(Ordering::Relaxed),
+            expire_cycle_cpu_milliseconds: stats.expire_cycle_micros.load(Ordering::Relaxed) / 1000,
+        }
+    }
+
+    pub fn server_stats(&self) -> ServerStats {
+        let stats = &self.stats;
+        ServerStats {
+            uptime: stats.started_at.elapsed(),
+            run_id: stats.run_id.clone(),
+            connected_clients: stats.connected_clients.load(Ordering::Relaxed),
+            blocked_clients: stats.blocked_clients.load(Ordering::Relaxed),
+            total_connections_received: stats.total_connections_received.load(Ordering::Relaxed),
+            total_commands_processed: stats.total_commands_processed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Calls of every command called so far, sorted by name.
+    pub fn command_stats(&self) -> Vec<(String, CommandStats)> {
+        let mut commands = self
+            .stats
+            .commands
+            .iter()
+            .map(|e| (e.key().clone(), *e.value()))
+            .collect::<Vec<_>>();
+        commands.sort_by(|a, b| a.0.cmp(&b.0));
+        commands
+    }
+
+    /// Zeroes the statistics reported by INFO, as done by CONFIG RESETSTAT.
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
+
+    /// Number of live keys of this database and of the ones with a TTL.
+    pub fn keyspace_size(&self) -> (usize, usize) {
+        let now = super::now_ms();
+        let volatile = self.db.expire.iter().filter(|at| *at.value() > now).count();
+        (self.dbsize(), volatile)
+    }
+}
@@ -0,0 +1,152 @@
+use super::pubsub::Outbox;
+use super::{Backend, BackendError};
+use crate::{BulkString, RespArray, RespFrame, RespNull, RespPush};
+use dashmap::DashMap;
+use std::collections::HashSet;
+
+// channel redirected invalidations are published on, for RESP2 clients to subscribe to
+const INVALIDATE_CHANNEL: &str = "__redis__:invalidate";
+
+/// How a client tracks the keys it caches, as set by CLIENT TRACKING.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackingOptions {
+    // client the invalidations are sent to instead, as messages on __redis__:invalidate
+    pub redirect: Option<u64>,
+    // broadcasting mode: every key under `prefixes` is invalidated, read or not
+    pub bcast: bool,
+    // prefixes of the keys tracked in broadcasting mode, every key when empty
+    pub prefixes: Vec<String>,
+}
+
+// a client with tracking on
+#[derive(Debug)]
+struct Tracker {
+    options: TrackingOptions,
+    outbox: Outbox,
+    // only RESP3 clients get invalidations pushed, RESP2 ones need a redirection
+    protocol: u8,
+}
+
+/// Clients with tracking on and the keys they read.
+#[derive(Debug, Default)]
+pub(crate) struct Tracking {
+    trackers: DashMap<u64, Tracker>,
+    // ids of the clients that read each key since it was last invalidated, which may
+    // include clients that turned tracking off since
+    keys: DashMap<String, HashSet<u64>>,
+}
+
+impl Backend {
+    pub(super) fn enable_tracking(
+        &self,
+        id: u64,
+        options: TrackingOptions,
+        outbox: Outbox,
+        protocol: u8,
+    ) -> Result<(), BackendError> {
+        if let Some(redirect) = options.redirect {
+            if self.client(redirect).is_none() {
+                return Err(BackendError::NoSuchRedirect);
+            }
+        }
+        if let Some(tracker) = self.tracking.trackers.get(&id) {
+            if tracker.options.bcast != options.bcast {
+                return Err(BackendError::TrackingModeSwitch);
+            }
+        }
+        let tracker = Tracker {
+            options,
+            outbox,
+            protocol,
+        };
+        self.tracking.trackers.insert(id, tracker);
+        Ok(())
+    }
+
+    pub(super) fn disable_tracking(&self, id: u64) {
+        self.tracking.trackers.remove(&id);
+    }
+
+    pub(super) fn set_tracking_protocol(&self, id: u64, protocol: u8) {
+        if let Some(mut tracker) = self.tracking.trackers.get_mut(&id) {
+            tracker.protocol = protocol;
+        }
+    }
+
+    // remembers that client `id` read `keys`, to invalidate them once they change
+    pub(super) fn track_keys(&self, id: u64, keys: impl IntoIterator<Item = String>) {
+        for key in keys {
+            self.tracking.keys.entry(key).or_default().insert(id);
+        }
+    }
+
+    /// Tells the clients that read `key`, and the broadcasting ones tracking a prefix
+    /// of it, that their copy is stale. Clients have to read it again to be told next
+    /// time, like with redis.
+    pub(super) fn invalidate_key(&self, key: &str) {
+        if self.tracking.trackers.is_empty() {
+            return;
+        }
+        let readers = self
+            .tracking
+            .keys
+            .remove(key)
+            .map(|(_, readers)| readers)
+            .unwrap_or_default();
+        for tracker in self.tracking.trackers.iter() {
+            let options = &tracker.options;
+            let tracked = if options.bcast {
+                options.prefixes.is_empty()
+                    || options
+                        .prefixes
+                        .iter()
+                        .any(|prefix| key.starts_with(prefix))
+            } else {
+                readers.contains(tracker.key())
+            };
+            if tracked {
+                self.send_invalidation(&tracker, Some(key));
+            }
+        }
+    }
+
+    /// Tells every tracking client to drop everything it cached, once the keyspace was
+    /// flushed.
+    pub(super) fn invalidate_all(&self) {
+        self.tracking.keys.clear();
+        for tracker in self.tracking.trackers.iter() {
+            self.send_invalidation(&tracker, None);
+        }
+    }
+
+    // invalidates `key`, or every key when None, in the cache of `tracker`
+    fn send_invalidation(&self, tracker: &Tracker, key: Option<&str>) {
+        let keys: RespFrame = match key {
+            Some(key) => RespArray::new(vec![BulkString::new(key).into()]).into(),
+            None => RespNull.into(),
+        };
+        match tracker.options.redirect {
+            Some(redirect) if self.client(redirect).is_some() => {
+                let message = RespPush::new(vec![
+                    BulkString::new("message").into(),
+                    BulkString::new(INVALIDATE_CHANNEL).into(),
+                    keys,
+                ]);
+                self.send_to_subscriber(INVALIDATE_CHANNEL, redirect, message.into());
+            }
+            // the client is told its invalidations are lost, if it can be
+            Some(redirect) if tracker.protocol >= 3 => {
+                let push = RespPush::new(vec![
+                    BulkString::new("tracking-redir-broken").into(),
+                    RespFrame::Integer(redirect as i64),
+                ]);
+                let _ = tracker.outbox.send(push.into());
+            }
+            None if tracker.protocol >= 3 => {
+                let push = RespPush::new(vec![BulkString::new("invalidate").into(), keys]);
+                let _ = tracker.outbox.send(push.into());
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use super::{Backend, SortedSet};
+use crate::RespFrame;
+use dashmap::DashMap;
+use std::collections::{HashSet, VecDeque};
+
+/// A value lifted out of the keyspace, so that it can be moved to another key, copied or
+/// freed elsewhere.
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    String(RespFrame),
+    // the fields and their TTLs, if any has one
+    Hash(DashMap<String, RespFrame>, Option<DashMap<String, u64>>),
+    List(VecDeque<RespFrame>),
+    Set(HashSet<Vec<u8>>),
+    ZSet(SortedSet),
+}
+
+impl Value {
+    /// Number of elements, 1 for a string.
+    pub fn len(&self) -> usize {
+        match self {
+            Value::String(_) => 1,
+            Value::Hash(hmap, _) => hmap.len(),
+            Value::List(list) => list.len(),
+            Value::Set(set) => set.len(),
+            Value::ZSet(zset) => zset.len(),
+        }
+    }
+}
+
+impl Backend {
+    // removes the value at `key`, leaving its TTL alone
+    pub(super) fn take_value(&self, key: &str) -> Option<Value> {
+        if let Some((_, value)) = self.db.map.remove(key) {
+            return Some(Value::String(value));
+        }
+        if let Some((_, hmap)) = self.db.hmap.remove(key) {
+            let ttls = self.db.hexpire.remove(key).map(|(_, ttls)| ttls);
+            return Some(Value::Hash(hmap, ttls));
+        }
+        if let Some((_, list)) = self.db.list.remove(key) {
+            return Some(Value::List(list));
+        }
+        if let Some((_, set)) = self.db.set.remove(key) {
+            return Some(Value::Set(set));
+        }
+        self.db.zset.remove(key).map(|(_, zset)| Value::ZSet(zset))
+    }
+
+    // deep copy of the value at `key`, leaving its TTL alone
+    pub(super) fn clone_value(&self, key: &str) -> Option<Value> {
+        if let Some(value) = self.db.map.get(key) {
+            return Some(Value::String(value.clone()));
+        }
+        if let Some(hmap) = self.db.hmap.get(key) {
+            let ttls = self.db.hexpire.get(key).map(|ttls| ttls.clone());
+            return Some(Value::Hash(hmap.clone(), ttls));
+        }
+        if let Some(list) = self.db.list.get(key) {
+            return Some(Value::List(list.clone()));
+        }
+        if let Some(set) = self.db.set.get(key) {
+            return Some(Value::Set(set.clone()));
+        }
+        self.db.zset.get(key).map(|zset| Value::ZSet(zset.clone()))
+    }
+
+    // stores `value` under `key`, which must not hold anything yet
+    pub(super) fn insert_value(&self, key: String, value: Value) {
+        match value {
+            Value::String(value) => {
+                self.db.map.insert(key, value);
+            }
+            Value::Hash(hmap, ttls) => {
+                if let Some(ttls) = ttls {
+                    self.db.hexpire.insert(key.clone(), ttls);
+                }
+                self.db.hmap.insert(key, hmap);
+            }
+            Value::List(list) => {
+                self.db.list.insert(key, list);
+            }
+            Value::Set(set) => {
+                self.db.set.insert(key, set);
+            }
+            Value::ZSet(zset) => {
+                self.db.zset.insert(key, zset);
+            }
+        }
+    }
+}
@@ -0,0 +1,709 @@
+use super::notify::{NOTIFY_GENERIC, NOTIFY_ZSET};
+use super::{list::list_range, scan, Backend, BackendError, KeyType, SetCondition, SetOp};
+use rand::seq::{IteratorRandom, SliceRandom};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::ops::{Bound, RangeBounds};
+
+/// Condition a new score must satisfy against the current score of a member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreCondition {
+    // GT: only update the member if the new score is greater
+    IfGreater,
+    // LT: only update the member if the new score is less
+    IfLess,
+}
+
+impl ScoreCondition {
+    fn allows(self, current: f64, score: f64) -> bool {
+        match self {
+            ScoreCondition::IfGreater => score > current,
+            ScoreCondition::IfLess => score < current,
+        }
+    }
+}
+
+/// A member of a sorted set along with its score.
+pub type ScoredMember = (Vec<u8>, f64);
+
+/// How the scores of a member found in several sorted sets are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            // inf + -inf counts as 0, like redis does
+            Aggregate::Sum => nan_to_zero(a + b),
+            Aggregate::Min => a.min(b),
+            Aggregate::Max => a.max(b),
+        }
+    }
+}
+
+/// End of a sorted set that members are popped from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZSetEnd {
+    // MIN: the members with the lowest scores
+    Min,
+    // MAX: the members with the highest scores
+    Max,
+}
+
+/// How ZRANGE selects the members of a sorted set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZRangeBy {
+    // indexes in score order, negative ones counting from the end
+    Rank(i64, i64),
+    Score(Bound<f64>, Bound<f64>),
+    // only meaningful when all the members share the same score
+    Lex(Bound<Vec<u8>>, Bound<Vec<u8>>),
+}
+
+/// Members of a sorted set, ordered by score and then lexicographically. Ranks are not
+/// indexed: finding one walks the members from the nearest end, which is linear unlike the
+/// skip list of redis, while score and lex ranges seek to their first member.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SortedSet {
+    scores: HashMap<Vec<u8>, f64>,
+    ordered: BTreeSet<(Score, Vec<u8>)>,
+}
+
+// a score with a total order so that it can key the BTreeSet, NaN is never stored
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Score(f64);
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl SortedSet {
+    pub(crate) fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub(crate) fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Sets the score of `member`, returning its previous score.
+    pub(crate) fn insert(&mut self, member: Vec<u8>, score: f64) -> Option<f64> {
+        // total_cmp orders -0 before 0, redis treats them as the same score
+        let score = score + 0.0;
+        let previous = self.scores.insert(member.clone(), score);
+        if let Some(previous) = previous {
+            self.ordered.remove(&(Score(previous), member.clone()));
+        }
+        self.ordered.insert((Score(score), member));
+        previous
+    }
+
+    /// Removes `member`, returning its score.
+    pub(crate) fn remove(&mut self, member: &[u8]) -> Option<f64> {
+        let (member, score) = self.scores.remove_entry(member)?;
+        self.ordered.remove(&(Score(score), member));
+        Some(score)
+    }
+
+    /// Members with their scores, lowest score first.
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = (&[u8], f64)> {
+        self.ordered.iter().map(entry)
+    }
+
+    /// Rank of `member` in score order, counting the members on its shorter side.
+    pub(crate) fn rank(&self, member: &[u8]) -> Option<usize> {
+        let key = (Score(self.score(member)?), member.to_vec());
+        let mut below = self
+            .ordered
+            .range((Bound::Unbounded, Bound::Excluded(&key)));
+        let mut above = self
+            .ordered
+            .range((Bound::Excluded(&key), Bound::Unbounded));
+        let mut walked = 0;
+        loop {
+            if below.next().is_none() {
+                return Some(walked);
+            }
+            if above.next().is_none() {
+                return Some(self.len() - 1 - walked);
+            }
+            walked += 1;
+        }
+    }
+
+    /// Members selected by `range` with their scores, highest score first if `rev`.
+    pub(crate) fn select<'a>(
+        &'a self,
+        range: &'a ZRangeBy,
+        rev: bool,
+    ) -> Box<dyn Iterator<Item = (&'a [u8], f64)> + 'a> {
+        match range {
+            ZRangeBy::Rank(start, stop) => {
+                let len = self.len();
+                let Some((start, stop)) = list_range(len, *start, *stop) else {
+                    return Box::new(std::iter::empty());
+                };
+                let count = stop - start + 1;
+                if start <= len - 1 - stop {
+                    return Box::new(directed(self.iter(), rev).skip(start).take(count));
+                }
+                // closer to the other end, walked from there
+                let mut members = directed(self.iter(), !rev)
+                    .skip(len - 1 - stop)
+                    .take(count)
+                    .collect::<Vec<_>>();
+                members.reverse();
+                Box::new(members.into_iter())
+            }
+            ZRangeBy::Score(min, max) => {
+                let Some(keys) = score_keys(min, max) else {
+                    return Box::new(std::iter::empty());
+                };
+                let members = self.ordered.range(keys).map(entry);
+                Box::new(directed(members, rev).filter(|(_, score)| (*min, *max).contains(score)))
+            }
+            ZRangeBy::Lex(min, max) => {
+                if !lex_range_valid(min, max) {
+                    return Box::new(std::iter::empty());
+                }
+                // members sharing one score are ordered by name, so the range can be sought
+                let members: Box<dyn DoubleEndedIterator<Item = (&[u8], f64)>> =
+                    match self.common_score() {
+                        Some(score) => Box::new(
+                            self.ordered
+                                .range((lex_key(score, min), lex_key(score, max)))
+                                .map(entry),
+                        ),
+                        None => Box::new(self.iter()),
+                    };
+                let bounds = (
+                    min.as_ref().map(Vec::as_slice),
+                    max.as_ref().map(Vec::as_slice),
+                );
+                Box::new(
+                    directed(members, rev)
+                        .filter(move |(member, _)| RangeBounds::<[u8]>::contains(&bounds, *member)),
+                )
+            }
+        }
+    }
+
+    // the score of every member, if they all share one
+    fn common_score(&self) -> Option<Score> {
+        let (first, _) = self.ordered.first()?;
+        let (last, _) = self.ordered.last()?;
+        (first == last).then_some(*first)
+    }
+}
+
+fn entry((score, member): &(Score, Vec<u8>)) -> (&[u8], f64) {
+    (member.as_slice(), score.0)
+}
+
+// `members` in reverse if `rev`
+fn directed<'a, I>(members: I, rev: bool) -> Box<dyn Iterator<Item = (&'a [u8], f64)> + 'a>
+where
+    I: DoubleEndedIterator<Item = (&'a [u8], f64)> + 'a,
+{
+    if rev {
+        Box::new(members.rev())
+    } else {
+        Box::new(members)
+    }
+}
+
+type OrderedBound = Bound<(Score, Vec<u8>)>;
+
+// keys spanning the ordered members scoring between `min` and `max`, along with the ones
+// scoring exactly an excluded bound. None if no score is in between
+fn score_keys(min: &Bound<f64>, max: &Bound<f64>) -> Option<(OrderedBound, OrderedBound)> {
+    // total_cmp orders -0 before 0, which members never score
+    let lower = match min {
+        Bound::Included(min) | Bound::Excluded(min) => Some(min + 0.0),
+        Bound::Unbounded => None,
+    };
+    let upper = match max {
+        Bound::Included(max) | Bound::Excluded(max) if *max != f64::INFINITY => {
+            Some((max + 0.0).next_up())
+        }
+        _ => None,
+    };
+    if let (Some(lower), Some(upper)) = (lower, upper) {
+        if lower >= upper {
+            return None;
+        }
+    }
+    // the empty member comes first among the members of a score
+    Some((
+        lower.map_or(Bound::Unbounded, |lower| {
+            Bound::Included((Score(lower), vec![]))
+        }),
+        upper.map_or(Bound::Unbounded, |upper| {
+            Bound::Excluded((Score(upper), vec![]))
+        }),
+    ))
+}
+
+// whether some member can be between `min` and `max`
+fn lex_range_valid(min: &Bound<Vec<u8>>, max: &Bound<Vec<u8>>) -> bool {
+    match (min, max) {
+        (Bound::Included(min), Bound::Included(max)) => min <= max,
+        (
+            Bound::Included(min) | Bound::Excluded(min),
+            Bound::Included(max) | Bound::Excluded(max),
+        ) => min < max,
+        _ => true,
+    }
+}
+
+fn lex_key(score: Score, bound: &Bound<Vec<u8>>) -> OrderedBound {
+    bound.as_ref().map(|member| (score, member.clone()))
+}
+
+impl Backend {
+    /// Sets the score of each member of `members` in the sorted set at `key`, creating the
+    /// set if needed. `condition` restricts the update to new or existing members and
+    /// `comparison` to existing members whose score would grow or shrink. Returns the
+    /// number of members added and the number of existing members whose score changed.
+    pub fn zadd(
+        &self,
+        key: String,
+        members: Vec<(f64, Vec<u8>)>,
+        condition: Option<SetCondition>,
+        comparison: Option<ScoreCondition>,
+    ) -> Result<(usize, usize), BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::ZSet)?;
+        let mut zset = self.db.zset.entry(key).or_default();
+        let (mut added, mut updated) = (0, 0);
+        for (score, member) in members {
+            match zset.score(&member) {
+                Some(current) => {
+                    if condition == Some(SetCondition::IfNotExists)
+                        || comparison.is_some_and(|c| !c.allows(current, score))
+                    {
+                        continue;
+                    }
+                    if current != score {
+                        zset.insert(member, score);
+                        updated += 1;
+                    }
+                }
+                None => {
+                    if condition == Some(SetCondition::IfExists) {
+                        continue;
+                    }
+                    zset.insert(member, score);
+                    added += 1;
+                }
+            }
+        }
+        let key = zset.key().clone();
+        drop(zset);
+        if added + updated > 0 {
+            self.notify(NOTIFY_ZSET, "zadd", &key);
+        }
+        self.remove_zset_if_empty(&key);
+        if added > 0 {
+            self.signal_key_ready(&key);
+        }
+        Ok((added, updated))
+    }
+
+    /// Adds `delta` to the score of `member` in the sorted set at `key`, treating a missing
+    /// member as 0, under the same conditions as ZADD. Returns the new score, None if a
+    /// condition prevented the update.
+    pub fn zincr_by(
+        &self,
+        key: String,
+        member: Vec<u8>,
+        delta: f64,
+        condition: Option<SetCondition>,
+        comparison: Option<ScoreCondition>,
+    ) -> Result<Option<f64>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(&key, KeyType::ZSet)?;
+        let mut zset = self.db.zset.entry(key).or_default();
+        let score = match zset.score(&member) {
+            Some(_) if condition == Some(SetCondition::IfNotExists) => None,
+            Some(current) => {
+                // inf + -inf, the member exists so no empty set is left behind
+                let score = current + delta;
+                if score.is_nan() {
+                    return Err(BackendError::ScoreNaN);
+                }
+                comparison
+                    .is_none_or(|c| c.allows(current, score))
+                    .then_some(score)
+            }
+            None if condition == Some(SetCondition::IfExists) => None,
+            None => Some(delta),
+        };
+        let added = score.is_some_and(|score| zset.insert(member, score).is_none());
+        let key = zset.key().clone();
+        drop(zset);
+        if score.is_some() {
+            self.notify(NOTIFY_ZSET, "zincr", &key);
+        }
+        self.remove_zset_if_empty(&key);
+        if added {
+            self.signal_key_ready(&key);
+        }
+        Ok(score)
+    }
+
+    pub fn zscore(&self, key: &str, member: &[u8]) -> Result<Option<f64>, BackendError> {
+        self.check_read(key, KeyType::ZSet)?;
+        Ok(self.db.zset.get(key).and_then(|zset| zset.score(member)))
+    }
+
+    /// Members of the sorted set at `key` selected by `range` with their scores, in
+    /// descending order if `rev`. Ranks are counted in that order too. `limit` skips
+    /// `offset` matching members and returns at most `count` of them, all of them if
+    /// `count` is negative.
+    pub fn zrange(
+        &self,
+        key: &str,
+        range: &ZRangeBy,
+        rev: bool,
+        limit: Option<(i64, i64)>,
+    ) -> Result<Vec<ScoredMember>, BackendError> {
+        self.check_read(key, KeyType::ZSet)?;
+        let Some(zset) = self.db.zset.get(key) else {
+            return Ok(vec![]);
+        };
+        let members = zset.select(range, rev);
+        let (offset, count) = limit.unwrap_or((0, -1));
+        if offset < 0 {
+            return Ok(vec![]);
+        }
+        let count = if count < 0 {
+            usize::MAX
+        } else {
+            count as usize
+        };
+        Ok(members
+            .skip(offset as usize)
+            .take(count)
+            .map(|(member, score)| (member.to_vec(), score))
+            .collect())
+    }
+
+    /// Pops up to `count` members with their scores from `end` of the sorted set at `key`,
+    /// deleting the key once the set is empty.
+    pub fn zpop(
+        &self,
+        key: &str,
+        end: ZSetEnd,
+        count: usize,
+    ) -> Result<Vec<ScoredMember>, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::ZSet)?;
+        let popped = match self.db.zset.get_mut(key) {
+            Some(mut zset) => {
+                let members: Box<dyn Iterator<Item = (&[u8], f64)>> = match end {
+                    ZSetEnd::Min => Box::new(zset.iter()),
+                    ZSetEnd::Max => Box::new(zset.iter().rev()),
+                };
+                let popped = members
+                    .take(count)
+                    .map(|(member, score)| (member.to_vec(), score))
+                    .collect::<Vec<_>>();
+                for (member, _) in &popped {
+                    zset.remove(member);
+                }
+                popped
+            }
+            None => return Ok(vec![]),
+        };
+        if !popped.is_empty() {
+            let event = match end {
+                ZSetEnd::Min => "zpopmin",
+                ZSetEnd::Max => "zpopmax",
+            };
+            self.notify(NOTIFY_ZSET, event, key);
+        }
+        self.remove_zset_if_empty(key);
+        Ok(popped)
+    }
+
+    /// Removes `members` from the sorted set at `key`, deleting the key once the set is
+    /// empty. Returns the number of members removed.
+    pub fn zrem(&self, key: &str, members: &[Vec<u8>]) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::ZSet)?;
+        let removed = match self.db.zset.get_mut(key) {
+            Some(mut zset) => members
+                .iter()
+                .filter(|member| zset.remove(member).is_some())
+                .count(),
+            None => return Ok(0),
+        };
+        if removed > 0 {
+            self.notify(NOTIFY_ZSET, "zrem", key);
+        }
+        self.remove_zset_if_empty(key);
+        Ok(removed)
+    }
+
+    /// Removes the members of the sorted set at `key` selected by `range`, deleting the key
+    /// once the set is empty. Returns the number of members removed.
+    pub fn zremrange(&self, key: &str, range: &ZRangeBy) -> Result<usize, BackendError> {
+        let _guard = self.shared_lock();
+        self.check_type(key, KeyType::ZSet)?;
+        let removed = match self.db.zset.get_mut(key) {
+            Some(mut zset) => {
+                let members = zset
+                    .select(range, false)
+                    .map(|(member, _)| member.to_vec())
+                    .collect::<Vec<_>>();
+                for member in &members {
+                    zset.remove(member);
+                }
+                members.len()
+            }
+            None => return Ok(0),
+        };
+        if removed > 0 {
+            let event = match range {
+                ZRangeBy::Rank(..) => "zremrangebyrank",
+                ZRangeBy::Score(..) => "zremrangebyscore",
+                ZRangeBy::Lex(..) => "zremrangebylex",
+            };
+            self.notify(NOTIFY_ZSET, event, key);
+        }
+        self.remove_zset_if_empty(key);
+        Ok(removed)
+    }
+
+    /// Combines the sorted sets at `keys` with `op`, ordered by score. Plain sets count as
+    /// sorted sets whose members all score 1 and missing keys as empty sets. The scores
+    /// of `keys[i]` are multiplied by `weights[i]`, 1 when missing, and `aggregate` merges
+    /// the scores of a member found in several sets.
+    pub fn zcombine(
+        &self,
+        op: SetOp,
+        keys: &[String],
+        weights: &[f64],
+        aggregate: Aggregate,
+    ) -> Result<Vec<ScoredMember>, BackendError> {
+        let result = self.zcombine_unlocked(op, keys, weights, aggregate)?;
+        Ok(result
+            .iter()
+            .map(|(member, score)| (member.to_vec(), score))
+            .collect())
+    }
+
+    /// Stores the combination of the sorted sets at `keys` in `destination`, replacing
+    /// whatever it held and deleting it if the result is empty. Returns the size of the
+    /// result.
+    pub fn zcombine_store(
+        &self,
+        op: SetOp,
+        destination: String,
+        keys: &[String],
+        weights: &[f64],
+        aggregate: Aggregate,
+    ) -> Result<usize, BackendError> {
+        let _guard = self.exclusive_lock();
+        let result = self.zcombine_unlocked(op, keys, weights, aggregate)?;
+        let len = result.len();
+        let existed = self.delete_key(&destination);
+        if !result.is_empty() {
+            self.db.zset.insert(destination.clone(), result);
+            let event = match op {
+                SetOp::Inter => "zinterstore",
+                SetOp::Union => "zunionstore",
+                SetOp::Diff => "zdiffstore",
+            };
+            self.notify(NOTIFY_ZSET, event, &destination);
+            self.signal_key_ready(&destination);
+        } else if existed {
+            self.notify(NOTIFY_GENERIC, "del", &destination);
+        }
+        Ok(len)
+    }
+
+    fn zcombine_unlocked(
+        &self,
+        op: SetOp,
+        keys: &[String],
+        weights: &[f64],
+        aggregate: Aggregate,
+    ) -> Result<SortedSet, BackendError> {
+        // type check every key before combining anything
+        for key in keys {
+            if !matches!(
+                self.lookup_read(key),
+                None | Some(KeyType::ZSet | KeyType::Set)
+            ) {
+                return Err(BackendError::WrongType);
+            }
+        }
+        let inputs = keys.iter().enumerate().map(|(i, key)| {
+            let weight = weights.get(i).copied().unwrap_or(1.0);
+            let weighted = |score: f64| nan_to_zero(score * weight);
+            if let Some(zset) = self.db.zset.get(key) {
+                zset.iter()
+                    .map(|(member, score)| (member.to_vec(), weighted(score)))
+                    .collect()
+            } else if let Some(set) = self.db.set.get(key) {
+                set.iter()
+                    .map(|member| (member.clone(), weighted(1.0)))
+                    .collect()
+            } else {
+                HashMap::new()
+            }
+        });
+        let mut result = HashMap::new();
+        for (i, members) in inputs.enumerate() {
+            match op {
+                _ if i == 0 => result = members,
+                SetOp::Inter => result.retain(|member, score| match members.get(member) {
+                    Some(other) => {
+                        *score = aggregate.apply(*score, *other);
+                        true
+                    }
+                    None => false,
+                }),
+                SetOp::Union => {
+                    for (member, other) in members {
+                        result
+                            .entry(member)
+                            .and_modify(|score| *score = aggregate.apply(*score, other))
+                            .or_insert(other);
+                    }
+                }
+                SetOp::Diff => result.retain(|member, _| !members.contains_key(member)),
+            }
+        }
+        let mut zset = SortedSet::default();
+        for (member, score) in result {
+            zset.insert(member, score);
+        }
+        Ok(zset)
+    }
+
+    /// Rank of `member` in the sorted set at `key` along with its score, counted from the
+    /// highest score if `rev`. Returns None if the member does not exist.
+    pub fn zrank(
+        &self,
+        key: &str,
+        member: &[u8],
+        rev: bool,
+    ) -> Result<Option<(usize, f64)>, BackendError> {
+        self.check_read(key, KeyType::ZSet)?;
+        let Some(zset) = self.db.zset.get(key) else {
+            return Ok(None);
+        };
+        let (Some(score), Some(rank)) = (zset.score(member), zset.rank(member)) else {
+            return Ok(None);
+        };
+        Ok(Some(match rev {
+            true => (zset.len() - 1 - rank, score),
+            false => (rank, score),
+        }))
+    }
+
+    /// One page of a cursor based iteration over the sorted set at `key`, members coming
+    /// with their scores.
+    pub fn zscan(
+        &self,
+        key: &str,
+        cursor: u64,
+        count: usize,
+    ) -> Result<(u64, Vec<ScoredMember>), BackendError> {
+        self.check_read(key, KeyType::ZSet)?;
+        let Some(zset) = self.db.zset.get(key) else {
+            return Ok((0, vec![]));
+        };
+        let members = zset.iter().map(|(member, score)| (member.to_vec(), score));
+        Ok(scan::scan(members, cursor, count, |(member, _)| {
+            member.as_slice()
+        }))
+    }
+
+    /// Random members of the sorted set at `key` with their scores. A positive `count`
+    /// returns distinct members, a negative one may repeat the same member.
+    pub fn zrandmember(&self, key: &str, count: i64) -> Result<Vec<ScoredMember>, BackendError> {
+        self.check_read(key, KeyType::ZSet)?;
+        let Some(zset) = self.db.zset.get(key) else {
+            return Ok(vec![]);
+        };
+        let mut rng = rand::thread_rng();
+        let to_owned = |(member, score): (&[u8], f64)| (member.to_vec(), score);
+        if count >= 0 {
+            // the sample is allocated upfront, a count above the size takes the whole set
+            let count = (count as usize).min(zset.len());
+            let mut members = zset.iter().choose_multiple(&mut rng, count);
+            members.shuffle(&mut rng);
+            return Ok(members.into_iter().map(to_owned).collect());
+        }
+        let members = zset.iter().collect::<Vec<_>>();
+        Ok((0..count.unsigned_abs())
+            .filter_map(|_| members.choose(&mut rng).copied().map(to_owned))
+            .collect())
+    }
+
+    /// Number of members of the sorted set at `key`, 0 if the key does not exist.
+    pub fn zcard(&self, key: &str) -> Result<usize, BackendError> {
+        self.check_read(key, KeyType::ZSet)?;
+        Ok(self.db.zset.get(key).map_or(0, |zset| zset.len()))
+    }
+
+    fn remove_zset_if_empty(&self, key: &str) {
+        if self
+            .db
+            .zset
+            .remove_if(key, |_, zset| zset.is_empty())
+            .is_some()
+        {
+            self.db.expire.remove(key);
+            self.notify(NOTIFY_GENERIC, "del", key);
+        }
+    }
+}
+
+fn nan_to_zero(score: f64) -> f64 {
+    if score.is_nan() {
+        0.0
+    } else {
+        score
+    }
+}
+
+/// Formats a score the way redis replies with doubles: the shortest digits that round-trip,
+/// in scientific notation when the exponent is below -4 or above 16 like `%.17g` does.
+pub fn format_score(score: f64) -> String {
+    if !score.is_finite() {
+        return format!("{}", score);
+    }
+    let scientific = format!("{:e}", score);
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("{:e} always writes an exponent");
+    let exponent: i32 = exponent.parse().expect("{:e} writes a valid exponent");
+    if (-4..17).contains(&exponent) {
+        return format!("{}", score);
+    }
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+}
@@ -0,0 +1,1301 @@
+use super::server::REDIS_VERSION;
+use super::{
+    extract_args, extract_bytes, extract_db_index, extract_integer, extract_string,
+    validate_command, validate_variadic_command, Auth, Client, ClientSubcommand, CommandError,
+    CommandExecutor, ConnectionCommand, Hello, Monitor, Ping, Quit, Reset, Select, RESP_OK,
+};
+
+use crate::{
+    Backend, BackendError, BulkString, ClientFilter, ClientInfo, Connection, PauseMode, ReplyMode,
+    RespArray, RespFrame, RespMap, RespNull, SimpleError, SimpleString, TrackingOptions,
+};
+use std::time::Duration;
+
+impl CommandExecutor for Client {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.run(None, backend)
+    }
+}
+
+impl ConnectionCommand for Client {
+    fn execute_on(&self, connection: &mut Connection, backend: &Backend) -> RespFrame {
+        self.run(Some(connection), backend)
+    }
+}
+
+impl Client {
+    // subcommands about the client sending them fail without a connection
+    fn run(&self, connection: Option<&mut Connection>, backend: &Backend) -> RespFrame {
+        match &self.subcommand {
+            ClientSubcommand::List(ids) => return client_list(backend, ids),
+            ClientSubcommand::Kill {
+                filter,
+                skip_me,
+                legacy,
+            } => {
+                let skip = connection
+                    .filter(|_| *skip_me)
+                    .map(|connection| connection.id());
+                return client_kill(backend, filter, skip, *legacy);
+            }
+            ClientSubcommand::Pause { timeout, mode } => {
+                backend.pause_clients(*timeout, *mode);
+                return RESP_OK.clone();
+            }
+            ClientSubcommand::Unpause => {
+                backend.unpause_clients();
+                return RESP_OK.clone();
+            }
+            _ => {}
+        }
+
+        let Some(connection) = connection else {
+            return SimpleError::new(format!(
+                "ERR CLIENT {} needs a connection",
+                self.subcommand.name()
+            ))
+            .into();
+        };
+        match &self.subcommand {
+            ClientSubcommand::Info => match connection.info() {
+                Some(info) => describe(&[info]),
+                None => BulkString::new("").into(),
+            },
+            ClientSubcommand::Id => RespFrame::Integer(connection.id() as i64),
+            ClientSubcommand::SetName(name) => {
+                connection.set_name(name.clone());
+                RESP_OK.clone()
+            }
+            ClientSubcommand::GetName => match connection.info() {
+                Some(info) if !info.name.is_empty() => BulkString::new(info.name).into(),
+                _ => RespFrame::Null(RespNull),
+            },
+            // only sent back when turning replies on
+            ClientSubcommand::Reply(mode) => {
+                connection.set_reply_mode(*mode);
+                RESP_OK.clone()
+            }
+            ClientSubcommand::Tracking(Some(options)) => {
+                match connection.enable_tracking(options.clone()) {
+                    Ok(()) => RESP_OK.clone(),
+                    Err(e) => e.into(),
+                }
+            }
+            ClientSubcommand::Tracking(None) => {
+                connection.disable_tracking();
+                RESP_OK.clone()
+            }
+            // -1 while tracking is off and 0 when invalidations are not redirected
+            ClientSubcommand::GetRedir => match connection.tracking() {
+                Some(options) => options.redirect.map_or(0, |id| id as i64).into(),
+                None => RespFrame::Integer(-1),
+            },
+            ClientSubcommand::TrackingInfo => tracking_info(connection, backend),
+            _ => unreachable!("handled without a connection"),
+        }
+    }
+}
+
+impl ClientSubcommand {
+    fn name(&self) -> &'static str {
+        match self {
+            ClientSubcommand::List(_) => "LIST",
+            ClientSubcommand::Info => "INFO",
+            ClientSubcommand::Id => "ID",
+            ClientSubcommand::SetName(_) => "SETNAME",
+            ClientSubcommand::GetName => "GETNAME",
+            ClientSubcommand::Kill { .. } => "KILL",
+            ClientSubcommand::Pause { .. } => "PAUSE",
+            ClientSubcommand::Unpause => "UNPAUSE",
+            ClientSubcommand::Reply(_) => "REPLY",
+            ClientSubcommand::Tracking(_) => "TRACKING",
+            ClientSubcommand::GetRedir => "GETREDIR",
+            ClientSubcommand::TrackingInfo => "TRACKINGINFO",
+        }
+    }
+}
+
+fn client_list(backend: &Backend, ids: &[u64]) -> RespFrame {
+    let clients = backend
+        .client_list()
+        .into_iter()
+        .filter(|info| ids.is_empty() || ids.contains(&info.id))
+        .collect::<Vec<_>>();
+    describe(&clients)
+}
+
+fn client_kill(
+    backend: &Backend,
+    filter: &ClientFilter,
+    skip: Option<u64>,
+    legacy: bool,
+) -> RespFrame {
+    let killed = backend.kill_clients(filter, skip);
+    match legacy {
+        false => RespFrame::Integer(killed as i64),
+        true if killed > 0 => RESP_OK.clone(),
+        true => SimpleError::new("ERR No such client").into(),
+    }
+}
+
+// flags, redirection and prefixes of the tracking of `connection`
+fn tracking_info(connection: &Connection, backend: &Backend) -> RespFrame {
+    let options = connection.tracking();
+    let mut flags = vec![];
+    match options {
+        Some(options) => {
+            flags.push("on");
+            if options.bcast {
+                flags.push("bcast");
+            }
+            if options
+                .redirect
+                .is_some_and(|id| backend.client(id).is_none())
+            {
+                flags.push("broken_redirect");
+            }
+        }
+        None => flags.push("off"),
+    }
+    let redirect = options.and_then(|options| options.redirect);
+    let prefixes = options.map_or(vec![], |options| options.prefixes.clone());
+    let mut map = RespMap::new();
+    map.insert(
+        "flags".to_string(),
+        RespArray::new(
+            flags
+                .into_iter()
+                .map(|flag| BulkString::new(flag).into())
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+    );
+    map.insert(
+        "redirect".to_string(),
+        redirect.map_or(-1, |id| id as i64).into(),
+    );
+    map.insert(
+        "prefixes".to_string(),
+        RespArray::new(
+            prefixes
+                .into_iter()
+                .map(|prefix| BulkString::new(prefix).into())
+                .collect::<Vec<_>>(),
+        )
+        .into(),
+    );
+    map.into()
+}
+
+// one line per client, each ending with a newline
+fn describe(clients: &[ClientInfo]) -> RespFrame {
+    let lines = clients
+        .iter()
+        .map(|info| info.describe() + "\n")
+        .collect::<String>();
+    BulkString::new(lines).into()
+}
+
+impl TryFrom<RespArray> for Client {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["client"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "LIST" => {
+                let mut ids = Vec::new();
+                if let Some(option) = args.next() {
+                    if !extract_string(Some(option))?.eq_ignore_ascii_case("ID") {
+                        return Err(CommandError::InvalidArgument("syntax error".into()));
+                    }
+                    for id in args.by_ref() {
+                        match extract_integer(Some(id))? {
+                            id if id > 0 => ids.push(id as u64),
+                            _ => {
+                                return Err(CommandError::InvalidArgument(
+                                    "Invalid client ID".into(),
+                                ))
+                            }
+                        }
+                    }
+                    if ids.is_empty() {
+                        return Err(CommandError::InvalidArgument("syntax error".into()));
+                    }
+                }
+                ClientSubcommand::List(ids)
+            }
+            "INFO" => ClientSubcommand::Info,
+            "ID" => ClientSubcommand::Id,
+            "GETNAME" => ClientSubcommand::GetName,
+            "SETNAME" => ClientSubcommand::SetName(extract_client_name(args.next())?),
+            "KILL" => parse_kill(&mut args)?,
+            "UNPAUSE" => ClientSubcommand::Unpause,
+            "TRACKING" => parse_tracking(&mut args)?,
+            "GETREDIR" => ClientSubcommand::GetRedir,
+            "TRACKINGINFO" => ClientSubcommand::TrackingInfo,
+            "REPLY" => match extract_string(args.next())?.to_ascii_uppercase().as_str() {
+                "ON" => ClientSubcommand::Reply(ReplyMode::On),
+                "OFF" => ClientSubcommand::Reply(ReplyMode::Off),
+                "SKIP" => ClientSubcommand::Reply(ReplyMode::Skip),
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            },
+            "PAUSE" => {
+                let timeout = match extract_integer(args.next()) {
+                    Ok(ms) if ms >= 0 => Duration::from_millis(ms as u64),
+                    Ok(_) => {
+                        return Err(CommandError::InvalidArgument("timeout is negative".into()))
+                    }
+                    Err(_) => {
+                        return Err(CommandError::InvalidArgument(
+                            "timeout is not an integer or out of range".into(),
+                        ))
+                    }
+                };
+                let mode = match args.next() {
+                    None => PauseMode::All,
+                    Some(mode) => match extract_string(Some(mode))?.to_ascii_uppercase().as_str() {
+                        "ALL" => PauseMode::All,
+                        "WRITE" => PauseMode::Write,
+                        _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+                    },
+                };
+                ClientSubcommand::Pause { timeout, mode }
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try CLIENT HELP.",
+                    name
+                )))
+            }
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument(format!(
+                "wrong number of arguments for 'client|{}' command",
+                name.to_ascii_lowercase()
+            )));
+        }
+        Ok(Client { subcommand })
+    }
+}
+
+// parses either `addr` alone or `filter value [filter value ...]`
+fn parse_kill(
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<ClientSubcommand, CommandError> {
+    let args = args
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let syntax_error = || CommandError::InvalidArgument("syntax error".into());
+    if let [addr] = args.as_slice() {
+        return Ok(ClientSubcommand::Kill {
+            filter: ClientFilter {
+                addr: Some(addr.clone()),
+                ..Default::default()
+            },
+            skip_me: false,
+            legacy: true,
+        });
+    }
+    if args.is_empty() || args.len() % 2 != 0 {
+        return Err(syntax_error());
+    }
+
+    let mut filter = ClientFilter::default();
+    let mut skip_me = true;
+    for pair in args.chunks(2) {
+        let value = &pair[1];
+        match pair[0].to_ascii_uppercase().as_str() {
+            "ID" => match value.parse::<u64>() {
+                Ok(id) if id > 0 => filter.id = Some(id),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "client-id should be greater than 0".into(),
+                    ))
+                }
+            },
+            "ADDR" => filter.addr = Some(value.clone()),
+            "LADDR" => filter.laddr = Some(value.clone()),
+            "MAXAGE" => {
+                let max_age = value.parse::<u64>().map_err(|_| syntax_error())?;
+                filter.max_age = Some(Duration::from_secs(max_age));
+            }
+            "SKIPME" => match value.to_ascii_lowercase().as_str() {
+                "yes" => skip_me = true,
+                "no" => skip_me = false,
+                _ => return Err(syntax_error()),
+            },
+            _ => return Err(syntax_error()),
+        }
+    }
+    Ok(ClientSubcommand::Kill {
+        filter,
+        skip_me,
+        legacy: false,
+    })
+}
+
+// parses `ON|OFF [REDIRECT id] [PREFIX prefix ...] [BCAST]`
+fn parse_tracking(
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<ClientSubcommand, CommandError> {
+    let syntax_error = || CommandError::InvalidArgument("syntax error".into());
+    let on = match extract_string(args.next())?.to_ascii_uppercase().as_str() {
+        "ON" => true,
+        "OFF" => false,
+        _ => return Err(syntax_error()),
+    };
+    let mut options = TrackingOptions::default();
+    while let Some(option) = args.next() {
+        match extract_string(Some(option))?.to_ascii_uppercase().as_str() {
+            "REDIRECT" => match extract_integer(args.next())? {
+                id if id > 0 => options.redirect = Some(id as u64),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "The client ID you want redirect to does not exist".into(),
+                    ))
+                }
+            },
+            "PREFIX" => options.prefixes.push(extract_string(args.next())?),
+            "BCAST" => options.bcast = true,
+            _ => return Err(syntax_error()),
+        }
+    }
+    if !options.prefixes.is_empty() && !options.bcast {
+        return Err(CommandError::InvalidArgument(
+            "PREFIX option requires BCAST mode to be enabled".into(),
+        ));
+    }
+    Ok(ClientSubcommand::Tracking(on.then_some(options)))
+}
+
+impl CommandExecutor for Monitor {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR MONITOR needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Monitor {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        connection.start_monitor();
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Monitor {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["monitor"], 0)?;
+        Ok(Monitor)
+    }
+}
+
+impl CommandExecutor for Reset {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR RESET needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Reset {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        connection.reset();
+        SimpleString::new("RESET").into()
+    }
+}
+
+impl TryFrom<RespArray> for Reset {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["reset"], 0)?;
+        Ok(Reset)
+    }
+}
+
+impl CommandExecutor for Quit {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR QUIT needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Quit {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        connection.close_after_reply();
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Quit {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        // like redis, arguments are ignored
+        validate_variadic_command(&value, &["quit"], 0)?;
+        Ok(Quit)
+    }
+}
+
+impl CommandExecutor for Ping {
+    fn execute(self, _: &Backend) -> RespFrame {
+        self.pong()
+    }
+}
+
+impl ConnectionCommand for Ping {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        // like redis, RESP2 subscribers get a reply shaped like a message to tell it apart
+        // from the ones they are waiting for
+        if connection.is_subscribed() && connection.protocol() == 2 {
+            let message = self.message.clone().unwrap_or_default();
+            return RespArray::new(vec![
+                BulkString::new("pong").into(),
+                BulkString::new(message).into(),
+            ])
+            .into();
+        }
+        self.pong()
+    }
+}
+
+impl Ping {
+    fn pong(&self) -> RespFrame {
+        match &self.message {
+            Some(message) => BulkString::new(message.clone()).into(),
+            None => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["ping"], 0)?;
+        if value.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "wrong number of arguments for 'ping' command".into(),
+            ));
+        }
+        let mut args = extract_args(value, 1)?.into_iter();
+        let message = args
+            .next()
+            .map(|arg| extract_bytes(Some(arg)))
+            .transpose()?;
+        Ok(Ping { message })
+    }
+}
+
+impl CommandExecutor for Select {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR SELECT needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Select {
+    fn execute_on(&self, connection: &mut Connection, backend: &Backend) -> RespFrame {
+        match backend.select(self.index) {
+            Ok(_) => {
+                connection.select(self.index);
+                RESP_OK.clone()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Select {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["select"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Select {
+            index: extract_db_index(args.next())?,
+        })
+    }
+}
+
+// a client name given to CLIENT SETNAME or HELLO
+fn extract_client_name(arg: Option<RespFrame>) -> Result<String, CommandError> {
+    let name = extract_string(arg)?;
+    // names show up in CLIENT LIST, which separates fields with spaces
+    if name.bytes().any(|b| !(b'!'..=b'~').contains(&b)) {
+        return Err(CommandError::InvalidArgument(
+            "Client names cannot contain spaces, newlines or special characters.".into(),
+        ));
+    }
+    Ok(name)
+}
+
+impl CommandExecutor for Auth {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR AUTH needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Auth {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        match connection.authenticate(self.username.as_deref(), &self.password) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Auth {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["auth"], 1)?;
+
+        let mut args = extract_args(value, 1)?;
+        if args.len() > 2 {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        let password = extract_string(args.pop())?;
+        let username = args
+            .pop()
+            .map(|arg| extract_string(Some(arg)))
+            .transpose()?;
+        Ok(Auth { username, password })
+    }
+}
+
+impl CommandExecutor for Hello {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR HELLO needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Hello {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        let protocol = match self.protover {
+            None => connection.protocol(),
+            Some(version @ (2 | 3)) => version as u8,
+            Some(_) => {
+                return SimpleError::new("NOPROTO sorry, this protocol version is not supported.")
+                    .into()
+            }
+        };
+        if let Some((username, password)) = &self.auth {
+            if let Err(e) = connection.authenticate(Some(username), password) {
+                return e.into();
+            }
+        }
+        if !connection.is_authenticated() {
+            return BackendError::NoAuth.into();
+        }
+        if let Some(name) = &self.setname {
+            connection.set_name(name.clone());
+        }
+        connection.set_protocol(protocol);
+
+        let mut map = RespMap::new();
+        map.insert("server".to_string(), BulkString::new("redis").into());
+        map.insert("version".to_string(), BulkString::new(REDIS_VERSION).into());
+        map.insert("proto".to_string(), (protocol as i64).into());
+        map.insert("id".to_string(), (connection.id() as i64).into());
+        map.insert("mode".to_string(), BulkString::new("standalone").into());
+        map.insert("role".to_string(), BulkString::new("master").into());
+        map.insert("modules".to_string(), RespArray::new(vec![]).into());
+        map.into()
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["hello"], 0)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut hello = Hello {
+            protover: None,
+            auth: None,
+            setname: None,
+        };
+        let Some(protover) = args.next() else {
+            return Ok(hello);
+        };
+        hello.protover = Some(extract_integer(Some(protover)).map_err(|_| {
+            CommandError::InvalidArgument(
+                "Protocol version is not an integer or out of range".into(),
+            )
+        })?);
+        while let Some(option) = args.next() {
+            match extract_string(Some(option))?.to_ascii_uppercase().as_str() {
+                "AUTH" => {
+                    let username = extract_string(args.next())?;
+                    let password = extract_string(args.next())?;
+                    hello.auth = Some((username, password));
+                }
+                "SETNAME" => hello.setname = Some(extract_client_name(args.next())?),
+                option => {
+                    return Err(CommandError::InvalidArgument(format!(
+                        "Syntax error in HELLO option '{}'",
+                        option
+                    )))
+                }
+            }
+        }
+        Ok(hello)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{cmd::Command, RespDecode, RespPush};
+
+    use super::*;
+
+    #[test]
+    fn test_client_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nclient\r\n$4\r\nlist\r\n$2\r\nID\r\n$1\r\n3\r\n$1\r\n5\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Client = frame.try_into()?;
+        assert_eq!(cmd.subcommand, ClientSubcommand::List(vec![3, 5]));
+
+        buf.extend_from_slice(b"*2\r\n$6\r\nclient\r\n$4\r\nINFO\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Client = frame.try_into()?;
+        assert_eq!(cmd.subcommand, ClientSubcommand::Info);
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nclient\r\n$4\r\ninfo\r\n$1\r\nx\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Client::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_list_and_info() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut first = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let second = Connection::new(&backend, "127.0.0.1:50001".parse()?, laddr);
+        first.record_command("client");
+
+        let cmd = Client {
+            subcommand: ClientSubcommand::Info,
+        };
+        let RespFrame::BulkString(info) = cmd.execute_on(&mut first, &backend) else {
+            panic!("CLIENT INFO replies with a bulk string");
+        };
+        let info = String::from_utf8(info.to_vec())?;
+        assert!(info.starts_with(&format!(
+            "id={} addr=127.0.0.1:50000 laddr=127.0.0.1:6379 name= age=0 idle=0",
+            first.id()
+        )));
+        assert!(info.ends_with(" db=0 cmd=client\n"));
+
+        let list = |ids: Vec<u64>| -> Result<Vec<String>> {
+            let cmd = Client {
+                subcommand: ClientSubcommand::List(ids),
+            };
+            let RespFrame::BulkString(list) = cmd.execute(&backend) else {
+                panic!("CLIENT LIST replies with a bulk string");
+            };
+            Ok(String::from_utf8(list.to_vec())?
+                .lines()
+                .map(String::from)
+                .collect())
+        };
+        assert_eq!(list(vec![])?.len(), 2);
+        let only_second = list(vec![second.id()])?;
+        assert_eq!(only_second.len(), 1);
+        assert!(only_second[0].ends_with("cmd=NULL"));
+
+        drop(second);
+        assert_eq!(list(vec![])?.len(), 1);
+        assert_eq!(backend.server_stats().connected_clients, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_names() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let mut execute = |subcommand| Client { subcommand }.execute_on(&mut connection, &backend);
+
+        assert_eq!(
+            execute(ClientSubcommand::GetName),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            execute(ClientSubcommand::SetName("worker-1".to_string())),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            execute(ClientSubcommand::GetName),
+            BulkString::new("worker-1").into()
+        );
+        let RespFrame::Integer(id) = execute(ClientSubcommand::Id) else {
+            panic!("CLIENT ID replies with an integer");
+        };
+        let list = Client {
+            subcommand: ClientSubcommand::List(vec![id as u64]),
+        }
+        .execute(&backend);
+        let RespFrame::BulkString(list) = list else {
+            panic!("CLIENT LIST replies with a bulk string");
+        };
+        assert!(String::from_utf8(list.to_vec())?.contains(" name=worker-1 "));
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nclient\r\n$7\r\nsetname\r\n$3\r\na b\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Client::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut killer = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let victim = Connection::new(&backend, "127.0.0.1:50001".parse()?, laddr);
+        let bystander = Connection::new(&backend, "10.0.0.1:50002".parse()?, laddr);
+
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*6\r\n$6\r\nclient\r\n$4\r\nkill\r\n$5\r\nLADDR\r\n$14\r\n127.0.0.1:6379\r\n$6\r\nSKIPME\r\n$3\r\nyes\r\n");
+        let cmd = Client::try_from(RespArray::decode(&mut buf)?)?;
+        // everyone shares the local address, but the killer skips itself
+        assert_eq!(cmd.execute_on(&mut killer, &backend), RespFrame::Integer(2));
+        tokio::time::timeout(Duration::from_secs(1), victim.killed()).await?;
+        tokio::time::timeout(Duration::from_secs(1), bystander.killed()).await?;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), killer.killed())
+                .await
+                .is_err()
+        );
+
+        let kill = |addr: &str| {
+            let mut buf = BytesMut::new();
+            buf.extend_from_slice(
+                format!(
+                    "*3\r\n$6\r\nclient\r\n$4\r\nkill\r\n${}\r\n{}\r\n",
+                    addr.len(),
+                    addr
+                )
+                .as_bytes(),
+            );
+            Client::try_from(RespArray::decode(&mut buf).unwrap()).unwrap()
+        };
+        assert_eq!(
+            kill("10.0.0.1:50002").execute_on(&mut killer, &backend),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            kill("10.0.0.9:1").execute_on(&mut killer, &backend),
+            SimpleError::new("ERR No such client").into()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_pause() -> Result<()> {
+        let backend = Backend::new();
+        let execute = |subcommand| Client { subcommand }.execute(&backend);
+        let pause = |mode| ClientSubcommand::Pause {
+            timeout: Duration::from_secs(10),
+            mode,
+        };
+        let quickly = Duration::from_millis(50);
+
+        assert_eq!(execute(pause(PauseMode::Write)), RESP_OK.clone());
+        // reads go on, writes wait
+        tokio::time::timeout(quickly, backend.wait_unpaused(false)).await?;
+        assert!(tokio::time::timeout(quickly, backend.wait_unpaused(true))
+            .await
+            .is_err());
+
+        let writer = tokio::spawn({
+            let backend = backend.clone();
+            async move { backend.wait_unpaused(true).await }
+        });
+        assert_eq!(execute(ClientSubcommand::Unpause), RESP_OK.clone());
+        tokio::time::timeout(Duration::from_secs(1), writer).await??;
+
+        // the pause ends by itself once the timeout elapses
+        let short = ClientSubcommand::Pause {
+            timeout: Duration::from_millis(20),
+            mode: PauseMode::All,
+        };
+        assert_eq!(execute(short), RESP_OK.clone());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(5), backend.wait_unpaused(false))
+                .await
+                .is_err()
+        );
+        tokio::time::timeout(Duration::from_secs(1), backend.wait_unpaused(false)).await?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_reply() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        // whether the reply to each command is sent
+        let mut replies = |subcommand: Option<ClientSubcommand>| {
+            if let Some(subcommand) = subcommand {
+                Client { subcommand }.execute_on(&mut connection, &backend);
+            }
+            connection.should_reply()
+        };
+
+        assert!(replies(None));
+        assert!(!replies(Some(ClientSubcommand::Reply(ReplyMode::Skip))));
+        assert!(!replies(None));
+        assert!(replies(None));
+
+        assert!(!replies(Some(ClientSubcommand::Reply(ReplyMode::Off))));
+        assert!(!replies(None));
+        assert!(!replies(Some(ClientSubcommand::Reply(ReplyMode::Skip))));
+        assert!(replies(Some(ClientSubcommand::Reply(ReplyMode::On))));
+        assert!(replies(None));
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_tracking_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$6\r\nclient\r\n$8\r\ntracking\r\n$2\r\non\r\n$5\r\nBCAST\r\n$6\r\nPREFIX\r\n$5\r\nuser:\r\n$5\r\nbcast\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Client = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            ClientSubcommand::Tracking(Some(TrackingOptions {
+                redirect: None,
+                bcast: true,
+                prefixes: vec!["user:".to_string()],
+            }))
+        );
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nclient\r\n$8\r\ntracking\r\n$3\r\noff\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Client = frame.try_into()?;
+        assert_eq!(cmd.subcommand, ClientSubcommand::Tracking(None));
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nclient\r\n$8\r\ntracking\r\n$2\r\non\r\n$6\r\nprefix\r\n$5\r\nuser:\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Client::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_tracking() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut tracker = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let execute = |connection: &mut Connection, subcommand| {
+            Client { subcommand }.execute_on(connection, &backend)
+        };
+        let invalidate = |keys: RespFrame| -> RespFrame {
+            RespPush::new(vec![BulkString::new("invalidate").into(), keys]).into()
+        };
+        let keys =
+            |key: &str| -> RespFrame { RespArray::new(vec![BulkString::new(key).into()]).into() };
+
+        assert_eq!(
+            execute(&mut tracker, ClientSubcommand::GetRedir),
+            RespFrame::Integer(-1)
+        );
+        let on = ClientSubcommand::Tracking(Some(TrackingOptions::default()));
+        assert_eq!(execute(&mut tracker, on), RESP_OK.clone());
+        assert_eq!(
+            execute(&mut tracker, ClientSubcommand::GetRedir),
+            RespFrame::Integer(0)
+        );
+
+        // a key read is invalidated once, until it is read again
+        tracker.track_reads(["cached".to_string()]);
+        backend.set("cached".to_string(), BulkString::new("1").into());
+        backend.set("cached".to_string(), BulkString::new("2").into());
+        backend.set("unread".to_string(), BulkString::new("3").into());
+        assert_eq!(tracker.next_push().await, invalidate(keys("cached")));
+        // flushing invalidates everything at once
+        backend.flush(false);
+        assert_eq!(tracker.next_push().await, invalidate(RespNull.into()));
+
+        // broadcasting mode can't be switched on while tracking is on
+        let bcast = || {
+            ClientSubcommand::Tracking(Some(TrackingOptions {
+                redirect: None,
+                bcast: true,
+                prefixes: vec!["user:".to_string()],
+            }))
+        };
+        assert_eq!(
+            execute(&mut tracker, bcast()),
+            BackendError::TrackingModeSwitch.into()
+        );
+        assert_eq!(
+            execute(&mut tracker, ClientSubcommand::Tracking(None)),
+            RESP_OK.clone()
+        );
+        assert_eq!(execute(&mut tracker, bcast()), RESP_OK.clone());
+        backend.set("session:1".to_string(), BulkString::new("1").into());
+        backend.set("user:1".to_string(), BulkString::new("1").into());
+        assert_eq!(tracker.next_push().await, invalidate(keys("user:1")));
+
+        // RESP2 clients get invalidations redirected to a subscriber of __redis__:invalidate
+        let mut subscriber = Connection::new(&backend, "127.0.0.1:50001".parse()?, laddr);
+        subscriber.subscribe("__redis__:invalidate");
+        let mut redirected = Connection::new(&backend, "127.0.0.1:50002".parse()?, laddr);
+        redirected.set_protocol(2);
+        let on = ClientSubcommand::Tracking(Some(TrackingOptions {
+            redirect: Some(subscriber.id()),
+            ..Default::default()
+        }));
+        assert_eq!(execute(&mut redirected, on), RESP_OK.clone());
+        redirected.track_reads(["cached".to_string()]);
+        backend.set("cached".to_string(), BulkString::new("4").into());
+        assert_eq!(
+            subscriber.next_push().await,
+            RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new("__redis__:invalidate").into(),
+                keys("cached"),
+            ])
+            .into()
+        );
+        let mut info = RespMap::new();
+        info.insert(
+            "flags".to_string(),
+            RespArray::new(vec![BulkString::new("on").into()]).into(),
+        );
+        info.insert("redirect".to_string(), (subscriber.id() as i64).into());
+        info.insert("prefixes".to_string(), RespArray::new(vec![]).into());
+        assert_eq!(
+            execute(&mut redirected, ClientSubcommand::TrackingInfo),
+            info.into()
+        );
+
+        let missing = ClientSubcommand::Tracking(Some(TrackingOptions {
+            redirect: Some(1000),
+            ..Default::default()
+        }));
+        assert_eq!(
+            execute(&mut redirected, missing),
+            BackendError::NoSuchRedirect.into()
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_monitor() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut monitor = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let client = Connection::new(&backend, "127.0.0.1:50001".parse()?, laddr);
+        assert!(!backend.has_monitors());
+        assert_eq!(
+            Monitor.execute(&backend),
+            SimpleError::new("ERR MONITOR needs a connection").into()
+        );
+
+        assert_eq!(Monitor.execute_on(&mut monitor, &backend), RESP_OK.clone());
+        assert!(backend.has_monitors());
+        assert!(monitor.info().unwrap().describe().contains(" flags=O "));
+
+        let info = client.info().unwrap();
+        backend.feed_monitors(
+            &info,
+            &[b"set".to_vec(), b"k\"ey".to_vec(), b"a\nb".to_vec()],
+        );
+        let line = monitor.next_monitor_line().await;
+        assert!(line.ends_with(r#" [0 127.0.0.1:50001] "set" "k\"ey" "a\nb""#));
+
+        // like redis, passwords are redacted, including those of HELLO
+        for (args, shown) in [
+            (
+                vec!["auth", "user", "secret"],
+                r#""auth" "(redacted)" "(redacted)""#,
+            ),
+            (
+                vec!["hello", "3", "setname", "auth", "auth", "user", "secret"],
+                r#""hello" "3" "setname" "auth" "auth" "(redacted)" "(redacted)""#,
+            ),
+        ] {
+            let mut args: Vec<Vec<u8>> = args.into_iter().map(|arg| arg.into()).collect();
+            let cmd = Command::try_from(RespFrame::from(RespArray::new(
+                args.iter()
+                    .map(|arg| BulkString::new(arg.clone()).into())
+                    .collect::<Vec<RespFrame>>(),
+            )))?;
+            cmd.redact(&mut args);
+            backend.feed_monitors(&info, &args);
+            let line = monitor.next_monitor_line().await;
+            assert!(line.ends_with(shown));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reset() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$5\r\nreset\r\n$3\r\nall\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Reset::try_from(frame).is_err());
+
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        connection.set_name("pooled".to_string());
+        connection.set_reply_mode(ReplyMode::Off);
+        connection.start_monitor();
+        connection.subscribe("news");
+
+        assert_eq!(
+            Reset.execute_on(&mut connection, &backend),
+            SimpleString::new("RESET").into()
+        );
+        assert!(connection.should_reply());
+        assert!(!connection.is_monitor());
+        assert!(!backend.has_monitors());
+        assert_eq!(connection.subscription_count(), 0);
+        assert_eq!(backend.publish("news", b"hello"), 0);
+        let info = connection.info().unwrap();
+        assert_eq!(info.name, "pooled");
+        assert!(!info.monitor);
+        Ok(())
+    }
+
+    #[test]
+    fn test_quit() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\nquit\r\n$3\r\nnow\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let _: Quit = frame.try_into()?;
+
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        assert!(!connection.is_closing());
+        assert_eq!(Quit.execute_on(&mut connection, &backend), RESP_OK.clone());
+        assert!(connection.is_closing());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ping() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\nping\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Ping = frame.try_into()?;
+        assert_eq!(cmd.message, Some(b"hello".to_vec()));
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nping\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Ping::try_from(frame).is_err());
+
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let ping = |message: Option<&str>| Ping {
+            message: message.map(|message| message.as_bytes().to_vec()),
+        };
+        assert_eq!(
+            ping(None).execute_on(&mut connection, &backend),
+            SimpleString::new("PONG").into()
+        );
+        assert_eq!(
+            ping(Some("hello")).execute_on(&mut connection, &backend),
+            BulkString::new("hello").into()
+        );
+        // RESP3 subscribers get the usual reply, RESP2 ones one shaped like a message
+        connection.subscribe("news");
+        assert_eq!(
+            ping(None).execute_on(&mut connection, &backend),
+            SimpleString::new("PONG").into()
+        );
+        connection.set_protocol(2);
+        assert_eq!(
+            ping(None).execute_on(&mut connection, &backend),
+            RespArray::new(vec![
+                BulkString::new("pong").into(),
+                BulkString::new("").into(),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_select() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nselect\r\n$1\r\n3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Select = frame.try_into()?;
+        assert_eq!(cmd.index, 3);
+
+        buf.extend_from_slice(b"*2\r\n$6\r\nselect\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Select::try_from(frame).is_err());
+
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        assert_eq!(
+            Select { index: 3 }.execute_on(&mut connection, &backend),
+            RESP_OK.clone()
+        );
+        assert_eq!(connection.db(), 3);
+        assert!(connection.info().unwrap().describe().contains(" db=3 "));
+        assert_eq!(
+            Select { index: 16 }.execute_on(&mut connection, &backend),
+            SimpleError::new("ERR DB index is out of range").into()
+        );
+        assert_eq!(connection.db(), 3);
+
+        Reset.execute_on(&mut connection, &backend);
+        assert_eq!(connection.db(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_auth() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nauth\r\n$7\r\ndefault\r\n$6\r\nsecret\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Auth = frame.try_into()?;
+        assert_eq!(cmd.username.as_deref(), Some("default"));
+        assert_eq!(cmd.password, "secret");
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nauth\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\nc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Auth::try_from(frame).is_err());
+
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let auth = |username: Option<&str>, password: &str| Auth {
+            username: username.map(String::from),
+            password: password.to_string(),
+        };
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        assert!(connection.is_authenticated());
+        assert_eq!(
+            auth(None, "secret").execute_on(&mut connection, &backend),
+            BackendError::NoPasswordConfigured.into()
+        );
+
+        backend.config_set(&[("requirepass".to_string(), "secret".to_string())])?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50001".parse()?, laddr);
+        assert!(!connection.is_authenticated());
+        assert_eq!(
+            auth(None, "guess").execute_on(&mut connection, &backend),
+            BackendError::WrongPass.into()
+        );
+        assert_eq!(
+            auth(Some("admin"), "secret").execute_on(&mut connection, &backend),
+            BackendError::WrongPass.into()
+        );
+        assert!(!connection.is_authenticated());
+        assert_eq!(
+            auth(Some("default"), "secret").execute_on(&mut connection, &backend),
+            RESP_OK.clone()
+        );
+        assert!(connection.is_authenticated());
+
+        // RESET logs the client out again
+        Reset.execute_on(&mut connection, &backend);
+        assert!(!connection.is_authenticated());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*7\r\n$5\r\nhello\r\n$1\r\n2\r\n$4\r\nauth\r\n$7\r\ndefault\r\n$6\r\nsecret\r\n$7\r\nsetname\r\n$6\r\nworker\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Hello = frame.try_into()?;
+        assert_eq!(cmd.protover, Some(2));
+        assert_eq!(
+            cmd.auth,
+            Some(("default".to_string(), "secret".to_string()))
+        );
+        assert_eq!(cmd.setname.as_deref(), Some("worker"));
+
+        buf.extend_from_slice(b"*2\r\n$5\r\nhello\r\n$5\r\nthree\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Hello::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*3\r\n$5\r\nhello\r\n$1\r\n3\r\n$4\r\nauth\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Hello::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello() -> Result<()> {
+        let backend = Backend::new();
+        backend.config_set(&[("requirepass".to_string(), "secret".to_string())])?;
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let hello = |protover: Option<i64>, password: Option<&str>| Hello {
+            protover,
+            auth: password.map(|password| ("default".to_string(), password.to_string())),
+            setname: Some("worker".to_string()),
+        };
+
+        assert_eq!(
+            hello(Some(4), Some("secret")).execute_on(&mut connection, &backend),
+            SimpleError::new("NOPROTO sorry, this protocol version is not supported.").into()
+        );
+        assert_eq!(
+            hello(Some(2), None).execute_on(&mut connection, &backend),
+            BackendError::NoAuth.into()
+        );
+        assert_eq!(
+            hello(Some(2), Some("guess")).execute_on(&mut connection, &backend),
+            BackendError::WrongPass.into()
+        );
+        // failed attempts change nothing
+        assert_eq!(connection.protocol(), 3);
+        assert_eq!(connection.info().map(|info| info.name), Some(String::new()));
+
+        let RespFrame::Map(reply) =
+            hello(Some(2), Some("secret")).execute_on(&mut connection, &backend)
+        else {
+            panic!("HELLO replies with a map");
+        };
+        assert_eq!(reply.get("proto"), Some(&RespFrame::Integer(2)));
+        assert_eq!(
+            reply.get("id"),
+            Some(&RespFrame::Integer(connection.id() as i64))
+        );
+        assert!(connection.is_authenticated());
+        assert_eq!(connection.protocol(), 2);
+        assert_eq!(
+            connection.info().map(|info| info.name),
+            Some("worker".into())
+        );
+
+        // without a version the protocol is left as it is
+        let RespFrame::Map(reply) = hello(None, None).execute_on(&mut connection, &backend) else {
+            panic!("HELLO replies with a map");
+        };
+        assert_eq!(reply.get("proto"), Some(&RespFrame::Integer(2)));
+
+        Reset.execute_on(&mut connection, &backend);
+        assert_eq!(connection.protocol(), 3);
+        Ok(())
+    }
+}
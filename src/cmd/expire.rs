@@ -0,0 +1,434 @@
+use super::{
+    extract_args, extract_integer, extract_string, validate_command, validate_variadic_command,
+    CommandError, CommandExecutor, Expire, ExpireAt, ExpireTime, PExpire, PExpireAt, PExpireTime,
+    PTtl, Persist, Ttl,
+};
+
+use crate::{now_ms, Backend, ExpireCondition, RespArray, RespFrame};
+
+impl CommandExecutor for Expire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire_at = (now_ms() as i64).saturating_add(self.seconds.saturating_mul(1000));
+        expire(backend, &self.key, expire_at, self.condition)
+    }
+}
+
+impl CommandExecutor for PExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire_at = (now_ms() as i64).saturating_add(self.milliseconds);
+        expire(backend, &self.key, expire_at, self.condition)
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire_at = self.unix_time_seconds.saturating_mul(1000);
+        expire(backend, &self.key, expire_at, self.condition)
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        expire(
+            backend,
+            &self.key,
+            self.unix_time_milliseconds,
+            self.condition,
+        )
+    }
+}
+
+impl CommandExecutor for Persist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.pexpiretime(&self.key) {
+            at @ (-2 | -1) => RespFrame::Integer(at),
+            at => RespFrame::Integer(at / 1000),
+        }
+    }
+}
+
+impl CommandExecutor for PExpireTime {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.pexpiretime(&self.key))
+    }
+}
+
+impl CommandExecutor for Ttl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // redis rounds the remaining milliseconds to the nearest second
+        match backend.pttl(&self.key) {
+            ttl @ (-2 | -1) => RespFrame::Integer(ttl),
+            ttl => RespFrame::Integer((ttl + 500) / 1000),
+        }
+    }
+}
+
+impl CommandExecutor for PTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.pttl(&self.key))
+    }
+}
+
+// `expire_at` is in unix milliseconds, a deadline in the past deletes the key
+fn expire(
+    backend: &Backend,
+    key: &str,
+    expire_at: i64,
+    condition: Option<ExpireCondition>,
+) -> RespFrame {
+    let expire_at = expire_at.max(0) as u64;
+    RespFrame::Integer(backend.expire(key, expire_at, condition) as i64)
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition) = parse_expire(value, "expire", 1000, now_ms())?;
+        Ok(Expire {
+            key,
+            seconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition) = parse_expire(value, "pexpire", 1, now_ms())?;
+        Ok(PExpire {
+            key,
+            milliseconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, unix_time_seconds, condition) = parse_expire(value, "expireat", 1000, 0)?;
+        Ok(ExpireAt {
+            key,
+            unix_time_seconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, unix_time_milliseconds, condition) = parse_expire(value, "pexpireat", 1, 0)?;
+        Ok(PExpireAt {
+            key,
+            unix_time_milliseconds,
+            condition,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Persist {
+            key: parse_key(value, "persist")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(ExpireTime {
+            key: parse_key(value, "expiretime")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireTime {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PExpireTime {
+            key: parse_key(value, "pexpiretime")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Ttl {
+            key: parse_key(value, "ttl")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(PTtl {
+            key: parse_key(value, "pttl")?,
+        })
+    }
+}
+
+fn parse_key(value: RespArray, name: &'static str) -> Result<String, CommandError> {
+    validate_command(&value, &[name], 1)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    extract_string(args.next())
+}
+
+// parses `key time [NX | XX | GT | LT]` where time is given in `unit` milliseconds
+// relative to `base`, and must not overflow once turned into a deadline
+fn parse_expire(
+    value: RespArray,
+    name: &'static str,
+    unit: i64,
+    base: u64,
+) -> Result<(String, i64, Option<ExpireCondition>), CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let time = extract_integer(args.next())?;
+    let condition = match args.next() {
+        Some(arg) => Some(extract_expire_condition(arg)?),
+        None => None,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+    match time
+        .checked_mul(unit)
+        .and_then(|ms| ms.checked_add(base as i64))
+    {
+        Some(_) => Ok((key, time, condition)),
+        None => Err(CommandError::InvalidArgument(format!(
+            "invalid expire time in '{}' command",
+            name
+        ))),
+    }
+}
+
+fn extract_expire_condition(frame: RespFrame) -> Result<ExpireCondition, CommandError> {
+    let option = extract_string(Some(frame))?;
+    match option.to_ascii_uppercase().as_str() {
+        "NX" => Ok(ExpireCondition::IfNoTtl),
+        "XX" => Ok(ExpireCondition::IfHasTtl),
+        "GT" => Ok(ExpireCondition::IfGreater),
+        "LT" => Ok(ExpireCondition::IfLess),
+        _ => Err(CommandError::InvalidArgument(format!(
+            "Unsupported option {}",
+            option
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::RespDecode;
+
+    use super::*;
+
+    #[test]
+    fn test_expire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$2\r\n-5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Expire = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.seconds, -5);
+
+        // the deadline would overflow
+        buf.extend_from_slice(b"*3\r\n$6\r\nexpire\r\n$3\r\nkey\r\n$19\r\n9223372036854775807\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Expire, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*3\r\n$4\r\npttl\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<PTtl, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*4\r\n$8\r\nexpireat\r\n$3\r\nkey\r\n$2\r\n10\r\n$2\r\ngt\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ExpireAt = frame.try_into()?;
+        assert_eq!(cmd.condition, Some(ExpireCondition::IfGreater));
+
+        buf.extend_from_slice(b"*4\r\n$7\r\npexpire\r\n$3\r\nkey\r\n$2\r\n10\r\n$2\r\nYY\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<PExpire, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_ttl_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), RespFrame::Integer(1));
+
+        let cmd = Ttl {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+        let cmd = PTtl {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+
+        let cmd = Expire {
+            key: "key".to_string(),
+            seconds: 100,
+            condition: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Ttl {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(100));
+        let cmd = PTtl {
+            key: "key".to_string(),
+        };
+        let RespFrame::Integer(ttl) = cmd.execute(&backend) else {
+            panic!("PTTL replies with an integer");
+        };
+        assert!(ttl > 99_000 && ttl <= 100_000);
+
+        let cmd = PExpire {
+            key: "missing".to_string(),
+            milliseconds: 100,
+            condition: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        // a negative TTL deletes the key
+        let cmd = PExpire {
+            key: "key".to_string(),
+            milliseconds: -1,
+            condition: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("key"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expireat_persist_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), RespFrame::Integer(1));
+
+        let cmd = ExpireTime {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        let at = now_ms() as i64 / 1000 + 100;
+        let cmd = ExpireAt {
+            key: "key".to_string(),
+            unix_time_seconds: at,
+            condition: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = ExpireTime {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(at));
+        let cmd = PExpireTime {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(at * 1000));
+
+        let cmd = Persist {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Persist {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = PExpireTime {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-1));
+
+        // a timestamp in the past deletes the key
+        let cmd = PExpireAt {
+            key: "key".to_string(),
+            unix_time_milliseconds: 1,
+            condition: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = ExpireTime {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expire_conditions() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), RespFrame::Integer(1));
+
+        // XX and GT need an existing TTL, no TTL counting as an infinite one
+        for condition in [ExpireCondition::IfHasTtl, ExpireCondition::IfGreater] {
+            let cmd = Expire {
+                key: "key".to_string(),
+                seconds: 100,
+                condition: Some(condition),
+            };
+            assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        }
+        let cmd = Expire {
+            key: "key".to_string(),
+            seconds: 100,
+            condition: Some(ExpireCondition::IfNoTtl),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Expire {
+            key: "key".to_string(),
+            seconds: 200,
+            condition: Some(ExpireCondition::IfNoTtl),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        // LT only shortens the TTL and GT only extends it
+        let cmd = Expire {
+            key: "key".to_string(),
+            seconds: 200,
+            condition: Some(ExpireCondition::IfLess),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = Expire {
+            key: "key".to_string(),
+            seconds: 200,
+            condition: Some(ExpireCondition::IfGreater),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        let cmd = Ttl {
+            key: "key".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(200));
+        Ok(())
+    }
+}
@@ -0,0 +1,1213 @@
+use super::{
+    extract_args, extract_bytes, extract_cursor, extract_db_index, extract_integer, extract_string,
+    parse_keys, parse_scan_option, scan_matches, scan_reply, validate_command,
+    validate_variadic_command, BlockingCommand, CommandError, CommandExecutor, Copy, DbSize, Del,
+    Dump, Exists, FlushAll, FlushDb, Keys, Move, Object, ObjectSubcommand, RandomKey, Rename,
+    RenameNx, Restore, Scan, Touch, Type, Unlink, Wait, RESP_OK,
+};
+
+use std::time::Duration;
+
+use crate::{now_ms, Backend, BulkString, KeyType, RespArray, RespFrame, RespNull, RestoreOptions};
+
+impl CommandExecutor for Del {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.del(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Unlink {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.unlink(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Exists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.exists(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Touch {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.touch(&self.keys) as i64)
+    }
+}
+
+impl CommandExecutor for Object {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let key = &self.key;
+        let reply = match self.subcommand {
+            ObjectSubcommand::Encoding => backend
+                .object_encoding(key)
+                .map(|encoding| BulkString::new(encoding).into()),
+            ObjectSubcommand::RefCount => backend.object_refcount(key).map(RespFrame::Integer),
+            ObjectSubcommand::IdleTime => backend
+                .object_idletime(key)
+                .map(|idle| RespFrame::Integer(idle as i64)),
+            ObjectSubcommand::Freq => backend
+                .object_freq(key)
+                .map(|freq| RespFrame::Integer(freq as i64)),
+        };
+        reply.unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for Dump {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.dump(&self.key) {
+            Some(payload) => BulkString::new(payload).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for Restore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire_at = match self.ttl {
+            0 => None,
+            ttl if self.absttl => Some(ttl),
+            ttl => Some(now_ms().saturating_add(ttl)),
+        };
+        let options = RestoreOptions {
+            replace: self.replace,
+            expire_at,
+            idle_time: self.idle_time,
+            freq: self.freq,
+        };
+        match backend.restore(&self.key, &self.payload, options) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for DbSize {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.dbsize() as i64)
+    }
+}
+
+impl CommandExecutor for FlushDb {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flush(self.lazy);
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for FlushAll {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.flush_all(self.lazy);
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for Keys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let keys = backend
+            .keys(self.pattern.as_bytes())
+            .into_iter()
+            .map(|key| BulkString::new(key).into())
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(keys).into()
+    }
+}
+
+impl CommandExecutor for Scan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, keys) = backend.scan(self.cursor, self.count, self.key_type);
+        let keys = keys
+            .into_iter()
+            .filter(|key| scan_matches(&self.pattern, key.as_bytes()))
+            .map(|key| BulkString::new(key).into())
+            .collect();
+        scan_reply(cursor, keys)
+    }
+}
+
+impl CommandExecutor for Type {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let name = backend.type_of(&self.key).map_or("none", KeyType::name);
+        RespFrame::SimpleString(name.into())
+    }
+}
+
+impl CommandExecutor for Rename {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, &self.new_key, false) {
+            Ok(_) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RenameNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.rename(&self.key, &self.new_key, true) {
+            Ok(renamed) => RespFrame::Integer(renamed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for RandomKey {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.random_key() {
+            Some(key) => BulkString::new(key).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for Copy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.copy(&self.source, &self.destination, self.db, self.replace) {
+            Ok(copied) => RespFrame::Integer(copied as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Move {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.move_key(&self.key, self.db) {
+            Ok(moved) => RespFrame::Integer(moved as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Wait {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or_else(|| self.timed_out())
+    }
+}
+
+impl BlockingCommand for Wait {
+    // waits for acknowledgements rather than for keys
+    fn keys(&self) -> &[String] {
+        &[]
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        let acked = backend.replicas_acked(backend.repl_offset());
+        (acked >= self.numreplicas).then_some(RespFrame::Integer(acked as i64))
+    }
+
+    fn timed_out(&self) -> RespFrame {
+        RespFrame::Integer(0)
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Del {
+            keys: parse_keys(value, "del")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Unlink {
+            keys: parse_keys(value, "unlink")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Exists {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Exists {
+            keys: parse_keys(value, "exists")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Touch {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(Touch {
+            keys: parse_keys(value, "touch")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Object {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["object"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "ENCODING" => ObjectSubcommand::Encoding,
+            "REFCOUNT" => ObjectSubcommand::RefCount,
+            "IDLETIME" => ObjectSubcommand::IdleTime,
+            "FREQ" => ObjectSubcommand::Freq,
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try OBJECT HELP.",
+                    name
+                )))
+            }
+        };
+        Ok(Object {
+            subcommand,
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Dump {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dump"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Dump {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Restore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["restore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let ttl = match extract_integer(args.next())? {
+            n if n < 0 => {
+                return Err(CommandError::InvalidArgument(
+                    "Invalid TTL value, must be >= 0".into(),
+                ))
+            }
+            n => n as u64,
+        };
+        let mut restore = Restore {
+            key,
+            ttl,
+            payload: extract_bytes(args.next())?,
+            replace: false,
+            absttl: false,
+            idle_time: None,
+            freq: None,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            match option.as_str() {
+                "REPLACE" => restore.replace = true,
+                "ABSTTL" => restore.absttl = true,
+                "IDLETIME" if restore.freq.is_none() => match extract_integer(args.next())? {
+                    n if n < 0 => {
+                        return Err(CommandError::InvalidArgument(
+                            "Invalid IDLETIME value, must be >= 0".into(),
+                        ))
+                    }
+                    n => restore.idle_time = Some(n as u64),
+                },
+                "FREQ" if restore.idle_time.is_none() => {
+                    match u8::try_from(extract_integer(args.next())?) {
+                        Ok(n) => restore.freq = Some(n),
+                        Err(_) => {
+                            return Err(CommandError::InvalidArgument(
+                                "Invalid FREQ value, must be >= 0 and <= 255".into(),
+                            ))
+                        }
+                    }
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        Ok(restore)
+    }
+}
+
+impl TryFrom<RespArray> for DbSize {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["dbsize"], 0)?;
+        Ok(DbSize)
+    }
+}
+
+impl TryFrom<RespArray> for FlushDb {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(FlushDb {
+            lazy: parse_flush(value, "flushdb")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for FlushAll {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(FlushAll {
+            lazy: parse_flush(value, "flushall")?,
+        })
+    }
+}
+
+// parses `[ASYNC | SYNC]`, returning whether the flush is asynchronous
+fn parse_flush(value: RespArray, name: &'static str) -> Result<bool, CommandError> {
+    validate_variadic_command(&value, &[name], 0)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let lazy = match args.next() {
+        Some(arg) => match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+            "ASYNC" => true,
+            "SYNC" => false,
+            _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+        },
+        None => false,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+    Ok(lazy)
+}
+
+impl TryFrom<RespArray> for Keys {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["keys"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Keys {
+            pattern: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Scan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["scan"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut scan = Scan {
+            cursor: extract_cursor(args.next())?,
+            pattern: None,
+            count: 10,
+            key_type: None,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            if option == "TYPE" {
+                scan.key_type = Some(extract_key_type(args.next())?);
+            } else if !parse_scan_option(&option, &mut args, &mut scan.pattern, &mut scan.count)? {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+        }
+        Ok(scan)
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["type"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Type {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Rename {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key) = parse_rename(value, "rename")?;
+        Ok(Rename { key, new_key })
+    }
+}
+
+impl TryFrom<RespArray> for RenameNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, new_key) = parse_rename(value, "renamenx")?;
+        Ok(RenameNx { key, new_key })
+    }
+}
+
+impl TryFrom<RespArray> for RandomKey {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["randomkey"], 0)?;
+        Ok(RandomKey)
+    }
+}
+
+impl TryFrom<RespArray> for Copy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["copy"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut copy = Copy {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            db: None,
+            replace: false,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            match option.as_str() {
+                "REPLACE" => copy.replace = true,
+                "DB" => copy.db = Some(extract_db_index(args.next())?),
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        Ok(copy)
+    }
+}
+
+impl TryFrom<RespArray> for Move {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["move"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Move {
+            key: extract_string(args.next())?,
+            db: extract_db_index(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Wait {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["wait"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let numreplicas = extract_integer(args.next())?.max(0) as usize;
+        let timeout = extract_integer(args.next())?;
+        if timeout < 0 {
+            return Err(CommandError::InvalidArgument("timeout is negative".into()));
+        }
+        Ok(Wait {
+            numreplicas,
+            timeout: (timeout > 0).then(|| Duration::from_millis(timeout as u64)),
+        })
+    }
+}
+
+// parses `key newkey`
+fn parse_rename(value: RespArray, name: &'static str) -> Result<(String, String), CommandError> {
+    validate_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    Ok((extract_string(args.next())?, extract_string(args.next())?))
+}
+
+fn extract_key_type(frame: Option<RespFrame>) -> Result<KeyType, CommandError> {
+    let name = extract_string(frame)?.to_ascii_lowercase();
+    [
+        KeyType::String,
+        KeyType::Hash,
+        KeyType::List,
+        KeyType::Set,
+        KeyType::ZSet,
+    ]
+    .into_iter()
+    .find(|key_type| key_type.name() == name)
+    .ok_or_else(|| CommandError::InvalidArgument("unknown type name".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+    use std::collections::HashSet;
+
+    use crate::{BackendError, BulkString, ListEnd, RespDecode, ZRangeBy};
+
+    use super::*;
+
+    #[test]
+    fn test_del_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\ndel\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Del = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+
+        buf.extend_from_slice(b"*1\r\n$6\r\nunlink\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Unlink, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_del_unlink_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        backend.sadd("set".to_string(), vec![b"a".to_vec()])?;
+        let elements = (0..100).map(RespFrame::Integer).collect();
+        backend.push("list".to_string(), ListEnd::Right, elements)?;
+        backend.hset(
+            "hash".to_string(),
+            "f".to_string(),
+            BulkString::new("v").into(),
+        );
+
+        // a key given twice is only deleted once
+        let cmd = Del {
+            keys: ["string", "set", "missing", "string"]
+                .map(String::from)
+                .to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.get("string"), None);
+        assert_eq!(backend.scard("set")?, 0);
+
+        let cmd = Unlink {
+            keys: ["list", "hash", "missing"].map(String::from).to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.llen("list")?, 0);
+        assert_eq!(backend.hget("hash", "f"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exists_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        backend.zadd("zset".to_string(), vec![(1.0, b"a".to_vec())], None, None)?;
+        backend.set("expired".to_string(), RespFrame::Integer(1));
+        backend.db.expire.insert("expired".to_string(), 1);
+
+        // duplicates count every time
+        let cmd = Exists {
+            keys: ["string", "zset", "string", "expired", "missing"]
+                .map(String::from)
+                .to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_keys_command() -> Result<()> {
+        let backend = Backend::new();
+        for key in ["hello", "hallo", "hxllo", "hllo", "expired"] {
+            backend.set(key.to_string(), RespFrame::Integer(1));
+        }
+        backend.sadd("heeello".to_string(), vec![b"a".to_vec()])?;
+        backend.db.expire.insert("expired".to_string(), 1);
+
+        let keys = |pattern: &str| -> Result<Vec<String>> {
+            let cmd = Keys {
+                pattern: pattern.to_string(),
+            };
+            let RespFrame::Array(frames) = cmd.execute(&backend) else {
+                panic!("KEYS replies with an array");
+            };
+            let mut keys = frames
+                .iter()
+                .map(|frame| match frame {
+                    RespFrame::BulkString(key) => Ok(String::from_utf8(key.to_vec())?),
+                    _ => panic!("KEYS replies with bulk strings"),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            keys.sort();
+            Ok(keys)
+        };
+        assert_eq!(keys("h?llo")?, vec!["hallo", "hello", "hxllo"]);
+        assert_eq!(
+            keys("h*llo")?,
+            vec!["hallo", "heeello", "hello", "hllo", "hxllo"]
+        );
+        assert_eq!(keys("h[^e]llo")?, vec!["hallo", "hxllo"]);
+        assert_eq!(keys("h[a-b]llo")?, vec!["hallo"]);
+        assert_eq!(keys("exp*")?, Vec::<String>::new());
+        assert_eq!(keys("h\\*llo")?, Vec::<String>::new());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*8\r\n$4\r\nscan\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$2\r\nk*\r\n$5\r\ncount\r\n$3\r\n100\r\n$4\r\nTYPE\r\n$4\r\nZSET\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Scan = frame.try_into()?;
+        assert_eq!(cmd.cursor, 0);
+        assert_eq!(cmd.pattern, Some("k*".to_string()));
+        assert_eq!(cmd.count, 100);
+        assert_eq!(cmd.key_type, Some(KeyType::ZSet));
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nscan\r\n$1\r\n0\r\n$4\r\nTYPE\r\n$4\r\nblob\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Scan, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_command() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..50 {
+            backend.set(format!("string:{}", i), RespFrame::Integer(i));
+            backend.sadd(format!("set:{}", i), vec![b"a".to_vec()])?;
+        }
+
+        let mut seen = vec![];
+        let mut cursor = 0;
+        loop {
+            let cmd = Scan {
+                cursor,
+                pattern: Some("*:1*".to_string()),
+                count: 7,
+                key_type: Some(KeyType::Set),
+            };
+            let RespFrame::Array(reply) = cmd.execute(&backend) else {
+                panic!("SCAN replies with an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(keys)) = (&reply[0], &reply[1])
+            else {
+                panic!("SCAN replies with a cursor and a page of keys");
+            };
+            seen.extend(keys.iter().cloned());
+            cursor = String::from_utf8(next.to_vec())?.parse()?;
+            if cursor == 0 {
+                break;
+            }
+        }
+        // set:1 and set:10 to set:19
+        assert_eq!(seen.len(), 11);
+        assert!(seen.contains(&BulkString::new("set:1").into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_type_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        backend.push(
+            "list".to_string(),
+            ListEnd::Left,
+            vec![RespFrame::Integer(1)],
+        )?;
+        backend.zadd("zset".to_string(), vec![(1.0, b"a".to_vec())], None, None)?;
+
+        for (key, name) in [
+            ("string", "string"),
+            ("list", "list"),
+            ("zset", "zset"),
+            ("missing", "none"),
+        ] {
+            let cmd = Type {
+                key: key.to_string(),
+            };
+            assert_eq!(cmd.execute(&backend), RespFrame::SimpleString(name.into()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rename_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), RespFrame::Integer(1));
+        backend.expire("a", u64::MAX, None);
+        backend.sadd("b".to_string(), vec![b"x".to_vec()])?;
+
+        // the value keeps its TTL and replaces whatever was at the new key
+        let cmd = Rename {
+            key: "a".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(backend.get("a"), None);
+        assert_eq!(backend.get("b"), Some(RespFrame::Integer(1)));
+        assert_eq!(backend.pexpiretime("b"), u64::MAX as i64);
+
+        let cmd = Rename {
+            key: "missing".to_string(),
+            new_key: "b".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::NoSuchKey.into());
+
+        backend.set("c".to_string(), RespFrame::Integer(2));
+        let cmd = RenameNx {
+            key: "b".to_string(),
+            new_key: "c".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = RenameNx {
+            key: "b".to_string(),
+            new_key: "d".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.get("d"), Some(RespFrame::Integer(1)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomkey_command() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(RandomKey.execute(&backend), RespFrame::Null(RespNull));
+
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        backend.sadd("set".to_string(), vec![b"a".to_vec()])?;
+        for i in 0..10 {
+            let key = format!("expired:{}", i);
+            backend.set(key.clone(), RespFrame::Integer(i));
+            backend.db.expire.insert(key, 1);
+        }
+
+        let mut seen = HashSet::new();
+        for _ in 0..100 {
+            match RandomKey.execute(&backend) {
+                RespFrame::BulkString(key) => seen.insert(String::from_utf8(key.to_vec())?),
+                frame => panic!("unexpected RANDOMKEY reply {:?}", frame),
+            };
+        }
+        // expired keys are never returned
+        assert_eq!(
+            seen,
+            HashSet::from(["string".to_string(), "set".to_string()])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\nDB\r\n$1\r\n0\r\n$7\r\nreplace\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Copy = frame.try_into()?;
+        assert_eq!(cmd.source, "a");
+        assert_eq!(cmd.destination, "b");
+        assert_eq!(cmd.db, Some(0));
+        assert!(cmd.replace);
+
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\ncopy\r\n$1\r\na\r\n$1\r\nb\r\n$2\r\nDB\r\n$2\r\n-1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Copy, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd("set".to_string(), vec![b"a".to_vec()])?;
+        backend.expire("set", u64::MAX, None);
+        backend.set("string".to_string(), RespFrame::Integer(1));
+
+        let cmd = Copy {
+            source: "set".to_string(),
+            destination: "copy".to_string(),
+            db: None,
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.pexpiretime("copy"), u64::MAX as i64);
+
+        // the copy is independent from the source
+        backend.sadd("copy".to_string(), vec![b"b".to_vec()])?;
+        assert_eq!(backend.scard("set")?, 1);
+        assert_eq!(backend.scard("copy")?, 2);
+
+        let cmd = Copy {
+            source: "set".to_string(),
+            destination: "string".to_string(),
+            db: None,
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = Copy {
+            source: "set".to_string(),
+            destination: "string".to_string(),
+            db: None,
+            replace: true,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.type_of("string"), Some(KeyType::Set));
+
+        let cmd = Copy {
+            source: "set".to_string(),
+            destination: "set".to_string(),
+            db: None,
+            replace: true,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::SameObject.into());
+
+        // the same key in another database is another object
+        let cmd = Copy {
+            source: "set".to_string(),
+            destination: "set".to_string(),
+            db: Some(1),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.select(1)?.scard("set")?, 1);
+        let cmd = Copy {
+            source: "set".to_string(),
+            destination: "set".to_string(),
+            db: Some(16),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::DbOutOfRange.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_touch_counts_keyspace_hits() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), RespFrame::Integer(1));
+        backend.push("b".to_string(), ListEnd::Left, vec![RespFrame::Integer(1)])?;
+
+        let cmd = Touch {
+            keys: ["a", "b", "missing"].map(String::from).to_vec(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        // reads of any type count, writes do not
+        backend.get("missing");
+        backend.llen("b")?;
+        backend.set("c".to_string(), RespFrame::Integer(1));
+
+        let stats = backend.keyspace_stats();
+        assert_eq!(stats.keyspace_hits, 3);
+        assert_eq!(stats.keyspace_misses, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nobject\r\n$8\r\nencoding\r\n$3\r\nkey\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Object = frame.try_into()?;
+        assert_eq!(cmd.subcommand, ObjectSubcommand::Encoding);
+        assert_eq!(cmd.key, "key");
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nobject\r\n$4\r\nsize\r\n$3\r\nkey\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Object, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("int".to_string(), BulkString::new("12").into());
+        backend.set("raw".to_string(), BulkString::new("x".repeat(100)).into());
+        backend.sadd("intset".to_string(), vec![b"1".to_vec(), b"2".to_vec()])?;
+        let elements = (0..200).map(RespFrame::Integer).collect();
+        backend.push("quicklist".to_string(), ListEnd::Right, elements)?;
+
+        let object = |subcommand, key: &str| {
+            Object {
+                subcommand,
+                key: key.to_string(),
+            }
+            .execute(&backend)
+        };
+        for (key, encoding) in [
+            ("int", "int"),
+            ("raw", "raw"),
+            ("intset", "intset"),
+            ("quicklist", "quicklist"),
+        ] {
+            assert_eq!(
+                object(ObjectSubcommand::Encoding, key),
+                BulkString::new(encoding).into()
+            );
+        }
+        assert_eq!(
+            object(ObjectSubcommand::Encoding, "missing"),
+            RespFrame::Null(RespNull)
+        );
+        assert_eq!(
+            object(ObjectSubcommand::RefCount, "int"),
+            RespFrame::Integer(i32::MAX as i64)
+        );
+        assert_eq!(
+            object(ObjectSubcommand::RefCount, "raw"),
+            RespFrame::Integer(1)
+        );
+        assert_eq!(
+            object(ObjectSubcommand::IdleTime, "raw"),
+            RespFrame::Integer(0)
+        );
+        // new keys start with a counter that is not the coldest
+        assert!(matches!(
+            object(ObjectSubcommand::Freq, "intset"),
+            RespFrame::Integer(n) if n >= 5
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*7\r\n$7\r\nrestore\r\n$1\r\nk\r\n$3\r\n100\r\n$3\r\nabc\r\n$6\r\nABSTTL\r\n$4\r\nFREQ\r\n$2\r\n10\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Restore = frame.try_into()?;
+        assert_eq!(cmd.ttl, 100);
+        assert_eq!(cmd.payload, b"abc");
+        assert!(cmd.absttl);
+        assert_eq!(cmd.freq, Some(10));
+
+        // IDLETIME and FREQ are mutually exclusive
+        buf.extend_from_slice(b"*8\r\n$7\r\nrestore\r\n$1\r\nk\r\n$1\r\n0\r\n$3\r\nabc\r\n$4\r\nFREQ\r\n$1\r\n1\r\n$8\r\nIDLETIME\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Restore, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_restore_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.zadd(
+            "zset".to_string(),
+            vec![(1.5, b"a".to_vec()), (-2.0, b"b".to_vec())],
+            None,
+            None,
+        )?;
+        backend.hset(
+            "hash".to_string(),
+            "f".to_string(),
+            BulkString::new("v").into(),
+        );
+
+        let dump = |key: &str| match (Dump {
+            key: key.to_string(),
+        })
+        .execute(&backend)
+        {
+            RespFrame::BulkString(payload) => payload.to_vec(),
+            frame => panic!("unexpected DUMP reply {:?}", frame),
+        };
+        let restore = |key: &str, payload: Vec<u8>, ttl: u64, replace: bool| {
+            Restore {
+                key: key.to_string(),
+                ttl,
+                payload,
+                replace,
+                absttl: false,
+                idle_time: Some(100),
+                freq: None,
+            }
+            .execute(&backend)
+        };
+
+        let payload = dump("zset");
+        assert_eq!(
+            restore("copy", payload.clone(), 10_000, false),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            backend.zrange("copy", &ZRangeBy::Rank(0, -1), false, None)?,
+            vec![(b"b".to_vec(), -2.0), (b"a".to_vec(), 1.5)]
+        );
+        assert!(backend.pttl("copy") > 9000);
+
+        assert_eq!(
+            restore("copy", payload.clone(), 0, false),
+            BackendError::BusyKey.into()
+        );
+        assert_eq!(restore("copy", dump("hash"), 0, true), RESP_OK.clone());
+        assert_eq!(backend.hget("copy", "f"), Some(BulkString::new("v").into()));
+        assert_eq!(backend.pttl("copy"), -1);
+
+        let mut corrupted = payload;
+        corrupted[0] ^= 1;
+        assert_eq!(
+            restore("other", corrupted, 0, false),
+            BackendError::BadPayload.into()
+        );
+        assert_eq!(
+            Dump {
+                key: "missing".to_string()
+            }
+            .execute(&backend),
+            RespFrame::Null(RespNull)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dbsize_command() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(DbSize.execute(&backend), RespFrame::Integer(0));
+
+        backend.set("a".to_string(), RespFrame::Integer(1));
+        backend.set("b".to_string(), RespFrame::Integer(1));
+        backend.expire("b", u64::MAX, None);
+        backend.sadd("c".to_string(), vec![b"x".to_vec()])?;
+        backend.set("expired".to_string(), RespFrame::Integer(1));
+        backend.db.expire.insert("expired".to_string(), 1);
+        assert_eq!(DbSize.execute(&backend), RespFrame::Integer(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_flush_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$7\r\nflushdb\r\n$5\r\nasync\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: FlushDb = frame.try_into()?;
+        assert!(cmd.lazy);
+
+        buf.extend_from_slice(b"*1\r\n$8\r\nflushall\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: FlushAll = frame.try_into()?;
+        assert!(!cmd.lazy);
+
+        buf.extend_from_slice(b"*2\r\n$8\r\nflushall\r\n$4\r\nlazy\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<FlushAll, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flush_commands() -> Result<()> {
+        let backend = Backend::new();
+        for lazy in [false, true] {
+            backend.set("string".to_string(), RespFrame::Integer(1));
+            backend.expire("string", u64::MAX, None);
+            let elements = (0..100).map(RespFrame::Integer).collect();
+            backend.push("list".to_string(), ListEnd::Right, elements)?;
+            backend.zadd("zset".to_string(), vec![(1.0, b"a".to_vec())], None, None)?;
+
+            assert_eq!(FlushDb { lazy }.execute(&backend), RESP_OK.clone());
+            assert_eq!(backend.dbsize(), 0);
+            assert_eq!(backend.pttl("string"), -2);
+            assert_eq!(backend.llen("list")?, 0);
+        }
+
+        // FLUSHDB leaves the other databases alone, FLUSHALL empties them all
+        let other = backend.select(1)?;
+        backend.set("a".to_string(), RespFrame::Integer(1));
+        other.set("b".to_string(), RespFrame::Integer(1));
+        assert_eq!(FlushDb { lazy: false }.execute(&other), RESP_OK.clone());
+        assert_eq!((backend.dbsize(), other.dbsize()), (1, 0));
+        other.set("b".to_string(), RespFrame::Integer(1));
+        assert_eq!(FlushAll { lazy: false }.execute(&backend), RESP_OK.clone());
+        assert_eq!((backend.dbsize(), other.dbsize()), (0, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nmove\r\n$1\r\na\r\n$1\r\n2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Move = frame.try_into()?;
+        assert_eq!(cmd.key, "a");
+        assert_eq!(cmd.db, 2);
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nmove\r\n$1\r\na\r\n$3\r\ntwo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Move::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_command() -> Result<()> {
+        let backend = Backend::new();
+        let other = backend.select(1)?;
+        backend.push(
+            "list".to_string(),
+            ListEnd::Left,
+            vec![RespFrame::Integer(1)],
+        )?;
+        backend.expire("list", u64::MAX, None);
+        backend.set("taken".to_string(), RespFrame::Integer(1));
+        other.set("taken".to_string(), RespFrame::Integer(2));
+
+        let execute = |key: &str, db| {
+            Move {
+                key: key.to_string(),
+                db,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(execute("list", 1), RespFrame::Integer(1));
+        assert_eq!(backend.type_of("list"), None);
+        assert_eq!(other.llen("list")?, 1);
+        assert_eq!(other.pexpiretime("list"), u64::MAX as i64);
+
+        assert_eq!(execute("taken", 1), RespFrame::Integer(0));
+        assert_eq!(backend.get("taken"), Some(RespFrame::Integer(1)));
+        assert_eq!(execute("missing", 1), RespFrame::Integer(0));
+        assert_eq!(execute("taken", 0), BackendError::SameObject.into());
+        assert_eq!(execute("taken", 16), BackendError::DbOutOfRange.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nwait\r\n$1\r\n1\r\n$3\r\n100\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Wait = frame.try_into()?;
+        assert_eq!(cmd.numreplicas, 1);
+        assert_eq!(cmd.timeout, Some(Duration::from_millis(100)));
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nwait\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Wait::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_command() {
+        let backend = Backend::new();
+        let cmd = Wait {
+            numreplicas: 0,
+            timeout: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        // there are no replicas to acknowledge the writes
+        let cmd = Wait {
+            numreplicas: 1,
+            timeout: Some(Duration::from_millis(10)),
+        };
+        assert_eq!(cmd.try_execute(&backend), None);
+        assert_eq!(cmd.timed_out(), RespFrame::Integer(0));
+    }
+}
@@ -1,8 +1,15 @@
 use super::{
-    extract_args, validate_command, CommandError, CommandExecutor, HGet, HGetAll, HSet, RESP_OK,
+    extract_args, extract_cursor, extract_float, extract_integer, extract_string,
+    parse_scan_option, scan_matches, scan_reply, validate_command, validate_variadic_command,
+    CommandError, CommandExecutor, Expiry, HDel, HExists, HExpire, HExpireAt, HGet, HGetAll,
+    HIncrBy, HIncrByFloat, HKeys, HLen, HMGet, HPExpire, HPExpireAt, HPTtl, HPersist, HRandField,
+    HScan, HSet, HSetNx, HStrLen, HTtl, HVals,
 };
 
-use crate::{Backend, RespArray, RespFrame, RespMap, RespNull};
+use crate::{
+    format_float, now_ms, Backend, BulkString, ExpireCondition, RespArray, RespFrame, RespMap,
+    RespNull,
+};
 
 impl CommandExecutor for HGet {
     fn execute(self, backend: &Backend) -> RespFrame {
@@ -15,8 +22,10 @@ impl CommandExecutor for HGet {
 
 impl CommandExecutor for HSet {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.hset(self.key, self.field, self.value.clone());
-        RESP_OK.clone()
+        match backend.hmset(self.key, self.pairs) {
+            Ok(created) => RespFrame::Integer(created as i64),
+            Err(e) => e.into(),
+        }
     }
 }
 
@@ -29,6 +38,247 @@ impl CommandExecutor for HGetAll {
     }
 }
 
+impl CommandExecutor for HDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hdel(&self.key, &self.fields) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HExists {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hexists(&self.key, &self.field) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HStrLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hstrlen(&self.key, &self.field) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HKeys {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hkeys(&self.key) {
+            Ok(fields) => {
+                let fields = fields
+                    .into_iter()
+                    .map(|field| BulkString::new(field).into())
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(fields).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HVals {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hvals(&self.key) {
+            Ok(values) => RespArray::new(values).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HMGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hmget(&self.key, &self.fields) {
+            Ok(values) => {
+                let values = values
+                    .into_iter()
+                    .map(|value| value.unwrap_or(RespFrame::Null(RespNull)))
+                    .collect::<Vec<_>>();
+                RespArray::new(values).into()
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincr_by(self.key, self.field, self.increment) {
+            Ok(value) => RespFrame::Integer(value),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HIncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hincr_by_float(self.key, self.field, self.increment) {
+            Ok(value) => BulkString::new(format_float(value)).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hsetnx(self.key, self.field, self.value) {
+            Ok(written) => RespFrame::Integer(written as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for HRandField {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let pairs = match backend.hrandfield(&self.key, self.count.unwrap_or(1)) {
+            Ok(pairs) => pairs,
+            Err(e) => return e.into(),
+        };
+        if self.count.is_none() {
+            return match pairs.into_iter().next() {
+                Some((field, _)) => BulkString::new(field).into(),
+                None => RespFrame::Null(RespNull),
+            };
+        }
+
+        let frames = pairs
+            .into_iter()
+            .map(|(field, value)| {
+                let field = BulkString::new(field).into();
+                if self.with_values {
+                    RespArray::new([field, value]).into()
+                } else {
+                    field
+                }
+            })
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(frames).into()
+    }
+}
+
+impl CommandExecutor for HScan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, pairs) = match backend.hscan(&self.key, self.cursor, self.count) {
+            Ok(page) => page,
+            Err(e) => return e.into(),
+        };
+        let mut frames = Vec::with_capacity(pairs.len() * 2);
+        for (field, value) in pairs {
+            if !scan_matches(&self.pattern, field.as_bytes()) {
+                continue;
+            }
+            frames.push(BulkString::new(field).into());
+            if !self.no_values {
+                frames.push(value);
+            }
+        }
+        scan_reply(cursor, frames)
+    }
+}
+
+impl CommandExecutor for HExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expiry = Expiry::Ex(self.seconds);
+        hexpire(backend, &self.key, expiry, self.condition, &self.fields)
+    }
+}
+
+impl CommandExecutor for HPExpire {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expiry = Expiry::Px(self.milliseconds);
+        hexpire(backend, &self.key, expiry, self.condition, &self.fields)
+    }
+}
+
+impl CommandExecutor for HExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expiry = Expiry::ExAt(self.unix_time_seconds);
+        hexpire(backend, &self.key, expiry, self.condition, &self.fields)
+    }
+}
+
+impl CommandExecutor for HPExpireAt {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expiry = Expiry::PxAt(self.unix_time_milliseconds);
+        hexpire(backend, &self.key, expiry, self.condition, &self.fields)
+    }
+}
+
+impl CommandExecutor for HTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        httl(backend, &self.key, &self.fields, 1000)
+    }
+}
+
+impl CommandExecutor for HPTtl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        httl(backend, &self.key, &self.fields, 1)
+    }
+}
+
+impl CommandExecutor for HPersist {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.hpersist(&self.key, &self.fields) {
+            Ok(replies) => integers(replies),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn hexpire(
+    backend: &Backend,
+    key: &str,
+    expiry: Expiry,
+    condition: Option<ExpireCondition>,
+    fields: &[String],
+) -> RespFrame {
+    // every expiry of the HEXPIRE family carries a time
+    let expire_at = expiry.deadline().unwrap_or_default();
+    match backend.hexpire(key, fields, expire_at, condition) {
+        Ok(replies) => integers(replies),
+        Err(e) => e.into(),
+    }
+}
+
+// remaining TTL of each field in `unit` milliseconds, rounded up like redis does
+fn httl(backend: &Backend, key: &str, fields: &[String], unit: u64) -> RespFrame {
+    let now = now_ms();
+    match backend.hexpiretime(key, fields) {
+        Ok(deadlines) => integers(
+            deadlines
+                .into_iter()
+                .map(|at| match at {
+                    -2 | -1 => at,
+                    at => ((at as u64).saturating_sub(now).div_ceil(unit)) as i64,
+                })
+                .collect(),
+        ),
+        Err(e) => e.into(),
+    }
+}
+
+fn integers(values: Vec<i64>) -> RespFrame {
+    RespArray::new(
+        values
+            .into_iter()
+            .map(RespFrame::Integer)
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
 impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
 
@@ -50,12 +300,176 @@ impl TryFrom<RespArray> for HSet {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hset"], 3)?;
+        validate_variadic_command(&value, &["hset"], 3)?;
+        if !value.len().is_multiple_of(2) {
+            return Err(CommandError::InvalidArgument(
+                "hset command requires field value pairs".into(),
+            ));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let mut pairs = Vec::with_capacity(args.len() / 2);
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            pairs.push((extract_string(Some(field))?, value));
+        }
+        Ok(HSet { key, pairs })
+    }
+}
+
+impl TryFrom<RespArray> for HGetAll {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hgetall"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(HGetAll {
+                key: String::from_utf8(key.0)?,
+            }),
+            _ => Err(CommandError::InvalidCommand("Invalid key".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for HDel {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["hdel"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let fields = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HDel { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HExists {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hexists"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HExists {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HLen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hlen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HStrLen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hstrlen"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HStrLen {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HKeys {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hkeys"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HKeys {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HVals {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hvals"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HVals {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HMGet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["hmget"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let fields = args
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HMGet { key, fields })
+    }
+}
+
+impl TryFrom<RespArray> for HIncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrby"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HIncrBy {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            increment: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HIncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hincrbyfloat"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(HIncrByFloat {
+            key: extract_string(args.next())?,
+            field: extract_string(args.next())?,
+            increment: extract_float(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["hsetnx"], 3)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next(), args.next()) {
             (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(field)), Some(value)) => {
-                Ok(HSet {
+                Ok(HSetNx {
                     key: String::from_utf8(key.0)?,
                     field: String::from_utf8(field.0)?,
                     value,
@@ -68,20 +482,233 @@ impl TryFrom<RespArray> for HSet {
     }
 }
 
-impl TryFrom<RespArray> for HGetAll {
+impl TryFrom<RespArray> for HRandField {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["hgetall"], 1)?;
+        validate_variadic_command(&value, &["hrandfield"], 1)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
-        match args.next() {
-            Some(RespFrame::BulkString(key)) => Ok(HGetAll {
-                key: String::from_utf8(key.0)?,
-            }),
-            _ => Err(CommandError::InvalidCommand("Invalid key".into())),
+        let key = extract_string(args.next())?;
+        let count = args
+            .next()
+            .map(|arg| extract_integer(Some(arg)))
+            .transpose()?;
+        let with_values = match args.next() {
+            Some(arg) if extract_string(Some(arg.clone()))?.eq_ignore_ascii_case("withvalues") => {
+                true
+            }
+            Some(_) => return Err(CommandError::InvalidArgument("syntax error".into())),
+            None => false,
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        Ok(HRandField {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["hscan"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut hscan = HScan {
+            key: extract_string(args.next())?,
+            cursor: extract_cursor(args.next())?,
+            pattern: None,
+            count: 10,
+            no_values: false,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            if option == "NOVALUES" {
+                hscan.no_values = true;
+            } else if !parse_scan_option(&option, &mut args, &mut hscan.pattern, &mut hscan.count)?
+            {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+        }
+        Ok(hscan)
+    }
+}
+
+impl TryFrom<RespArray> for HExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, seconds, condition, fields) = parse_hexpire(value, "hexpire", 1000)?;
+        Ok(HExpire {
+            key,
+            seconds,
+            condition,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HPExpire {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, milliseconds, condition, fields) = parse_hexpire(value, "hpexpire", 1)?;
+        Ok(HPExpire {
+            key,
+            milliseconds,
+            condition,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, unix_time_seconds, condition, fields) = parse_hexpire(value, "hexpireat", 1000)?;
+        Ok(HExpireAt {
+            key,
+            unix_time_seconds,
+            condition,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HPExpireAt {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, unix_time_milliseconds, condition, fields) =
+            parse_hexpire(value, "hpexpireat", 1)?;
+        Ok(HPExpireAt {
+            key,
+            unix_time_milliseconds,
+            condition,
+            fields,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["httl"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        Ok(HTtl {
+            key,
+            fields: extract_fields(args)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HPTtl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["hpttl"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        Ok(HPTtl {
+            key,
+            fields: extract_fields(args)?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HPersist {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["hpersist"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        Ok(HPersist {
+            key,
+            fields: extract_fields(args)?,
+        })
+    }
+}
+
+// same upper bound as redis' hash field expiration times
+const MAX_FIELD_EXPIRE_MS: u64 = (1 << 48) - 1;
+
+type FieldExpireArgs = (String, u64, Option<ExpireCondition>, Vec<String>);
+
+// parses `key time [NX | XX | GT | LT] FIELDS numfields field [field ...]`, `unit` being
+// the number of milliseconds in one unit of time
+fn parse_hexpire(
+    value: RespArray,
+    name: &'static str,
+    unit: u64,
+) -> Result<FieldExpireArgs, CommandError> {
+    validate_variadic_command(&value, &[name], 5)?;
+
+    let mut args = extract_args(value, 1)?.into_iter().peekable();
+    let key = extract_string(args.next())?;
+    let time = match extract_integer(args.next())? {
+        n if n < 0 => {
+            return Err(CommandError::InvalidArgument(
+                "invalid expire time, must be >= 0".into(),
+            ))
         }
+        n if n as u64 > MAX_FIELD_EXPIRE_MS / unit => {
+            return Err(CommandError::InvalidArgument(format!(
+                "invalid expire time in '{}' command",
+                name
+            )))
+        }
+        n => n as u64,
+    };
+    let condition = match args.peek() {
+        Some(RespFrame::BulkString(arg)) => match arg.to_ascii_uppercase().as_slice() {
+            b"NX" => Some(ExpireCondition::IfNoTtl),
+            b"XX" => Some(ExpireCondition::IfHasTtl),
+            b"GT" => Some(ExpireCondition::IfGreater),
+            b"LT" => Some(ExpireCondition::IfLess),
+            _ => None,
+        },
+        _ => None,
+    };
+    if condition.is_some() {
+        args.next();
     }
+    Ok((key, time, condition, extract_fields(args)?))
+}
+
+// parses `FIELDS numfields field [field ...]`
+fn extract_fields(mut args: impl Iterator<Item = RespFrame>) -> Result<Vec<String>, CommandError> {
+    if !extract_string(args.next())?.eq_ignore_ascii_case("FIELDS") {
+        return Err(CommandError::InvalidArgument(
+            "Mandatory argument FIELDS is missing or not at the right position".into(),
+        ));
+    }
+    let n_fields = extract_integer(args.next())?;
+    if n_fields <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "Parameter `numFields` should be greater than 0".into(),
+        ));
+    }
+    let fields = args
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if fields.len() as i64 != n_fields {
+        return Err(CommandError::InvalidArgument(
+            "The `numfields` parameter must match the number of arguments".into(),
+        ));
+    }
+    Ok(fields)
 }
 
 #[cfg(test)]
@@ -89,7 +716,7 @@ mod tests {
     use anyhow::Result;
     use bytes::BytesMut;
 
-    use crate::RespDecode;
+    use crate::{BackendError, RespDecode};
 
     use super::*;
 
@@ -111,8 +738,17 @@ mod tests {
         let frame = RespArray::decode(&mut buf)?;
         let cmd: HSet = frame.try_into()?;
         assert_eq!(cmd.key, "key");
-        assert_eq!(cmd.field, "field");
-        assert_eq!(cmd.value, RespFrame::BulkString(b"value".into()));
+        assert_eq!(
+            cmd.pairs,
+            vec![("field".to_string(), RespFrame::BulkString(b"value".into()))]
+        );
+
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhset\r\n$3\r\nkey\r\n$5\r\nfield\r\n$5\r\nvalue\r\n$3\r\nfoo\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<HSet, _> = frame.try_into();
+        assert!(result.is_err());
         Ok(())
     }
 
@@ -121,18 +757,20 @@ mod tests {
         let backend = Backend::new();
         let cmd = HSet {
             key: "map".to_string(),
-            field: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
+            pairs: vec![("hello".to_string(), RespFrame::BulkString(b"world".into()))],
         };
         let resp = cmd.execute(&backend);
-        assert_eq!(resp, RESP_OK.clone());
+        assert_eq!(resp, RespFrame::Integer(1));
 
         let cmd = HSet {
             key: "map".to_string(),
-            field: "foo".to_string(),
-            value: RespFrame::BulkString(b"bar".into()),
+            pairs: vec![
+                ("hello".to_string(), RespFrame::BulkString(b"world".into())),
+                ("foo".to_string(), RespFrame::BulkString(b"bar".into())),
+            ],
         };
-        cmd.execute(&backend);
+        let resp = cmd.execute(&backend);
+        assert_eq!(resp, RespFrame::Integer(1));
 
         let cmd = HGet {
             key: "map".to_string(),
@@ -152,4 +790,485 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_hdel_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nhdel\r\n$3\r\nkey\r\n$3\r\nfoo\r\n$3\r\nbar\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: HDel = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.fields, vec!["foo".to_string(), "bar".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdel_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+
+        let cmd = HDel {
+            key: "map".to_string(),
+            fields: vec!["hello".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(backend.db.hmap.contains_key("map"));
+
+        let cmd = HDel {
+            key: "map".to_string(),
+            fields: vec!["foo".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(!backend.db.hmap.contains_key("map"));
+
+        let cmd = HDel {
+            key: "map".to_string(),
+            fields: vec!["foo".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexists_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.set("string".to_string(), b"value".into());
+
+        let hexists = |key: &str, field: &str| {
+            HExists {
+                key: key.to_string(),
+                field: field.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hexists("map", "hello"), RespFrame::Integer(1));
+        assert_eq!(hexists("map", "foo"), RespFrame::Integer(0));
+        assert_eq!(hexists("missing", "hello"), RespFrame::Integer(0));
+        assert_eq!(hexists("string", "hello"), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hstrlen_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.hset("map".to_string(), "n".to_string(), RespFrame::Integer(-123));
+        backend.set("string".to_string(), b"value".into());
+
+        let hstrlen = |key: &str, field: &str| {
+            HStrLen {
+                key: key.to_string(),
+                field: field.to_string(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(hstrlen("map", "hello"), RespFrame::Integer(5));
+        assert_eq!(hstrlen("map", "n"), RespFrame::Integer(4));
+        assert_eq!(hstrlen("map", "foo"), RespFrame::Integer(0));
+        assert_eq!(hstrlen("missing", "hello"), RespFrame::Integer(0));
+        assert_eq!(hstrlen("string", "hello"), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hlen_hkeys_hvals_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+
+        let cmd = HLen {
+            key: "map".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = HKeys {
+            key: "map".to_string(),
+        };
+        let RespFrame::Array(fields) = cmd.execute(&backend) else {
+            panic!("HKEYS should reply with an array");
+        };
+        let mut fields = fields.0;
+        fields.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(fields, vec![b"foo".into(), b"hello".into()]);
+
+        let cmd = HVals {
+            key: "map".to_string(),
+        };
+        let RespFrame::Array(values) = cmd.execute(&backend) else {
+            panic!("HVALS should reply with an array");
+        };
+        let mut values = values.0;
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(values, vec![b"bar".into(), b"world".into()]);
+
+        let cmd = HLen {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = HKeys {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new([]).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmget_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+
+        let cmd = HMGet {
+            key: "map".to_string(),
+            fields: vec![
+                "foo".to_string(),
+                "missing".to_string(),
+                "hello".to_string(),
+            ],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([b"bar".into(), RespFrame::Null(RespNull), b"world".into()]).into()
+        );
+
+        let cmd = HMGet {
+            key: "missing".to_string(),
+            fields: vec!["foo".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([RespFrame::Null(RespNull)]).into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nhincrby\r\n$3\r\nkey\r\n$5\r\nfield\r\n$2\r\n-5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: HIncrBy = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.field, "field");
+        assert_eq!(cmd.increment, -5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hincrby_hincrbyfloat_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = HIncrBy {
+            key: "map".to_string(),
+            field: "counter".to_string(),
+            increment: 5,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+        let cmd = HIncrBy {
+            key: "map".to_string(),
+            field: "counter".to_string(),
+            increment: -7,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-2));
+
+        let cmd = HIncrByFloat {
+            key: "map".to_string(),
+            field: "counter".to_string(),
+            increment: 2.5,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::BulkString(b"0.5".into()));
+        let cmd = HIncrBy {
+            key: "map".to_string(),
+            field: "counter".to_string(),
+            increment: 1,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::HashNotInteger.into());
+
+        backend.hset("map".to_string(), "name".to_string(), b"redis".into());
+        let cmd = HIncrByFloat {
+            key: "map".to_string(),
+            field: "name".to_string(),
+            increment: 1.0,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::HashNotFloat.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hsetnx_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = HSetNx {
+            key: "map".to_string(),
+            field: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = HSetNx {
+            key: "map".to_string(),
+            field: "hello".to_string(),
+            value: RespFrame::BulkString(b"redis".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            backend.hget("map", "hello"),
+            Some(RespFrame::BulkString(b"world".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$10\r\nhrandfield\r\n$3\r\nkey\r\n$2\r\n-5\r\n$10\r\nWITHVALUES\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: HRandField = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.count, Some(-5));
+        assert!(cmd.with_values);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: None,
+            with_values: false,
+        };
+        let field = cmd.execute(&backend);
+        assert!(field == b"hello".into() || field == b"foo".into());
+
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(5),
+            with_values: false,
+        };
+        let RespFrame::Array(fields) = cmd.execute(&backend) else {
+            panic!("HRANDFIELD with count should reply with an array");
+        };
+        assert_eq!(fields.len(), 2);
+        assert_ne!(fields[0], fields[1]);
+
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(i64::MAX),
+            with_values: false,
+        };
+        let RespFrame::Array(fields) = cmd.execute(&backend) else {
+            panic!("HRANDFIELD with count should reply with an array");
+        };
+        assert_eq!(fields.len(), 2);
+
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(-5),
+            with_values: true,
+        };
+        let RespFrame::Array(pairs) = cmd.execute(&backend) else {
+            panic!("HRANDFIELD with count should reply with an array");
+        };
+        assert_eq!(pairs.len(), 5);
+        for pair in pairs.iter() {
+            let hello = RespArray::new([b"hello".into(), b"world".into()]).into();
+            let foo = RespArray::new([b"foo".into(), b"bar".into()]).into();
+            assert!(*pair == hello || *pair == foo);
+        }
+
+        let cmd = HRandField {
+            key: "missing".to_string(),
+            count: None,
+            with_values: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*8\r\n$5\r\nhscan\r\n$3\r\nkey\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$2\r\nf*\r\n$5\r\nCOUNT\r\n$3\r\n100\r\n$8\r\nNOVALUES\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: HScan = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.cursor, 0);
+        assert_eq!(cmd.pattern, Some("f*".to_string()));
+        assert_eq!(cmd.count, 100);
+        assert!(cmd.no_values);
+
+        buf.extend_from_slice(b"*3\r\n$5\r\nhscan\r\n$3\r\nkey\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<HScan, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hscan_command() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..30 {
+            backend.hset("map".to_string(), format!("field:{}", i), b"value".into());
+        }
+        backend.hset("map".to_string(), "other".to_string(), b"value".into());
+
+        let mut fields = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let cmd = HScan {
+                key: "map".to_string(),
+                cursor,
+                pattern: Some("field:*".to_string()),
+                count: 7,
+                no_values: true,
+            };
+            let RespFrame::Array(reply) = cmd.execute(&backend) else {
+                panic!("HSCAN should reply with an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(page)) = (&reply[0], &reply[1])
+            else {
+                panic!("HSCAN should reply with a cursor and a page");
+            };
+            fields.extend(page.iter().cloned());
+            cursor = String::from_utf8_lossy(next).parse()?;
+            if cursor == 0 {
+                break;
+            }
+        }
+        assert_eq!(fields.len(), 30);
+        assert!(!fields.contains(&b"other".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*8\r\n$7\r\nhexpire\r\n$3\r\nkey\r\n$3\r\n100\r\n$2\r\nGT\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: HExpire = frame.try_into()?;
+        assert_eq!(cmd.key, "key");
+        assert_eq!(cmd.seconds, 100);
+        assert_eq!(cmd.condition, Some(ExpireCondition::IfGreater));
+        assert_eq!(cmd.fields, vec!["a".to_string(), "b".to_string()]);
+
+        // numfields does not match the number of fields
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nhttl\r\n$3\r\nkey\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: HTtl = frame.try_into()?;
+        assert_eq!(cmd.fields.len(), 2);
+
+        // numfields does not match the number of fields
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nhttl\r\n$3\r\nkey\r\n$6\r\nFIELDS\r\n$1\r\n2\r\n$1\r\na\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<HTtl, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*6\r\n$8\r\nhpexpire\r\n$3\r\nkey\r\n$2\r\n-1\r\n$6\r\nFIELDS\r\n$1\r\n1\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<HPExpire, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hexpire_httl_hpersist_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("map".to_string(), "a".to_string(), b"1".into());
+        backend.hset("map".to_string(), "b".to_string(), b"2".into());
+        backend.set("string".to_string(), b"value".into());
+        let fields = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect();
+        let integers = |values: &[i64]| -> RespFrame {
+            RespArray::new(
+                values
+                    .iter()
+                    .map(|v| RespFrame::Integer(*v))
+                    .collect::<Vec<_>>(),
+            )
+            .into()
+        };
+
+        let cmd = HExpire {
+            key: "map".to_string(),
+            seconds: 100,
+            condition: None,
+            fields: fields(&["a", "missing"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[1, -2]));
+
+        // GT never applies to a field without TTL, LT always does
+        let cmd = HExpire {
+            key: "map".to_string(),
+            seconds: 200,
+            condition: Some(ExpireCondition::IfGreater),
+            fields: fields(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[1, 0]));
+        let cmd = HPExpire {
+            key: "map".to_string(),
+            milliseconds: 300_000,
+            condition: Some(ExpireCondition::IfLess),
+            fields: fields(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[0, 1]));
+
+        let cmd = HTtl {
+            key: "map".to_string(),
+            fields: fields(&["a", "b", "missing"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[200, 300, -2]));
+
+        let cmd = HPersist {
+            key: "map".to_string(),
+            fields: fields(&["a", "a", "missing"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[1, -1, -2]));
+
+        // overwriting a field clears its TTL
+        backend.hset("map".to_string(), "b".to_string(), b"3".into());
+        let cmd = HPTtl {
+            key: "map".to_string(),
+            fields: fields(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[-1, -1]));
+
+        // a deadline in the past deletes the field right away
+        let cmd = HPExpireAt {
+            key: "map".to_string(),
+            unix_time_milliseconds: 1,
+            condition: None,
+            fields: fields(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend), integers(&[2]));
+        assert_eq!(backend.hget("map", "a"), None);
+
+        // elapsed field TTLs are applied lazily, removing the key with its last field
+        backend
+            .db
+            .hexpire
+            .entry("map".to_string())
+            .or_default()
+            .insert("b".to_string(), 1);
+        assert_eq!(backend.hlen("map"), Ok(0));
+        assert!(!backend.db.hmap.contains_key("map"));
+        assert!(!backend.db.hexpire.contains_key("map"));
+
+        let cmd = HExpireAt {
+            key: "string".to_string(),
+            unix_time_seconds: 1,
+            condition: None,
+            fields: fields(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
 }
@@ -0,0 +1,1234 @@
+use super::{
+    extract_args, extract_integer, extract_string, extract_timeout, parse_blocking_pop,
+    validate_command, validate_variadic_command, BLMPop, BLMove, BLPop, BRPop, BlockingCommand,
+    CommandError, CommandExecutor, LIndex, LInsert, LLen, LMPop, LMove, LPop, LPos, LPush, LRange,
+    LRem, LSet, LTrim, RPop, RPopLPush, RPush, RESP_OK,
+};
+
+use crate::{Backend, BackendError, BulkString, ListEnd, RespArray, RespFrame, RespNull};
+use std::time::Duration;
+
+impl CommandExecutor for LPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, ListEnd::Left, self.elements)
+    }
+}
+
+impl CommandExecutor for RPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        push(backend, self.key, ListEnd::Right, self.elements)
+    }
+}
+
+impl CommandExecutor for LPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, &self.key, ListEnd::Left, self.count)
+    }
+}
+
+impl CommandExecutor for RPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop(backend, &self.key, ListEnd::Right, self.count)
+    }
+}
+
+impl CommandExecutor for LRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrange(&self.key, self.start, self.stop) {
+            Ok(elements) => RespArray::new(elements).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.llen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LIndex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lindex(&self.key, self.index) {
+            Ok(element) => element.unwrap_or(RespFrame::Null(RespNull)),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LInsert {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.linsert(&self.key, self.before, &self.pivot, self.element) {
+            Ok(len) => RespFrame::Integer(len),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lset(&self.key, self.index, self.element) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.lrem(&self.key, self.count, &self.element) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LTrim {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.ltrim(&self.key, self.start, self.stop) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for BLPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for BRPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl BlockingCommand for BLPop {
+    fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        pop_one(backend, &self.keys, ListEnd::Left)
+    }
+}
+
+impl BlockingCommand for BRPop {
+    fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        pop_one(backend, &self.keys, ListEnd::Right)
+    }
+}
+
+impl CommandExecutor for LMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        lmove(backend, &self.source, &self.destination, self.from, self.to)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for RPopLPush {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        lmove(
+            backend,
+            &self.source,
+            &self.destination,
+            ListEnd::Right,
+            ListEnd::Left,
+        )
+        .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for BLMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl BlockingCommand for BLMove {
+    fn keys(&self) -> &[String] {
+        std::slice::from_ref(&self.source)
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        lmove(backend, &self.source, &self.destination, self.from, self.to)
+    }
+}
+
+impl CommandExecutor for LPos {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let count = self.count.unwrap_or(1);
+        match backend.lpos(&self.key, &self.element, self.rank, count, self.maxlen) {
+            // with COUNT the reply is an array, even when nothing matched
+            Ok(indexes) => match self.count {
+                Some(_) => RespArray::new(
+                    indexes
+                        .into_iter()
+                        .map(|i| RespFrame::Integer(i as i64))
+                        .collect::<Vec<_>>(),
+                )
+                .into(),
+                None => indexes
+                    .first()
+                    .map_or(RespFrame::Null(RespNull), |&i| RespFrame::Integer(i as i64)),
+            },
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        pop_many(backend, &self.keys, self.end, self.count).unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for BLMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl BlockingCommand for BLMPop {
+    fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        pop_many(backend, &self.keys, self.end, self.count)
+    }
+}
+
+// replies with the moved element, None if the source list does not exist
+fn lmove(
+    backend: &Backend,
+    source: &str,
+    destination: &str,
+    from: ListEnd,
+    to: ListEnd,
+) -> Option<RespFrame> {
+    match backend.lmove(source, destination.to_string(), from, to) {
+        Ok(element) => element,
+        Err(e) => Some(e.into()),
+    }
+}
+
+fn push(backend: &Backend, key: String, end: ListEnd, elements: Vec<RespFrame>) -> RespFrame {
+    match backend.push(key, end, elements) {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+// without a count the reply is the element itself, with one it is an array of elements
+fn pop(backend: &Backend, key: &str, end: ListEnd, count: Option<usize>) -> RespFrame {
+    match backend.pop(key, end, count.unwrap_or(1)) {
+        Ok(Some(elements)) => match count {
+            Some(_) => RespArray::new(elements).into(),
+            None => elements
+                .into_iter()
+                .next()
+                .unwrap_or(RespFrame::Null(RespNull)),
+        },
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+// pops up to `count` elements from the first non-empty list, along with its key
+fn pop_first<'a>(
+    backend: &Backend,
+    keys: &'a [String],
+    end: ListEnd,
+    count: usize,
+) -> Result<Option<(&'a str, Vec<RespFrame>)>, BackendError> {
+    for key in keys {
+        match backend.pop(key, end, count)? {
+            Some(elements) if !elements.is_empty() => return Ok(Some((key, elements))),
+            _ => continue,
+        }
+    }
+    Ok(None)
+}
+
+// replies with the key and the element popped from it, None if all lists are empty
+fn pop_one(backend: &Backend, keys: &[String], end: ListEnd) -> Option<RespFrame> {
+    match pop_first(backend, keys, end, 1) {
+        Ok(Some((key, mut elements))) => {
+            let key = BulkString::new(key).into();
+            Some(RespArray::new([key, elements.remove(0)]).into())
+        }
+        Ok(None) => None,
+        Err(e) => Some(e.into()),
+    }
+}
+
+// replies with the key and an array of the elements popped from it, None if all lists
+// are empty
+fn pop_many(backend: &Backend, keys: &[String], end: ListEnd, count: usize) -> Option<RespFrame> {
+    match pop_first(backend, keys, end, count) {
+        Ok(Some((key, elements))) => {
+            let key = BulkString::new(key).into();
+            Some(RespArray::new([key, RespArray::new(elements).into()]).into())
+        }
+        Ok(None) => None,
+        Err(e) => Some(e.into()),
+    }
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, elements) = parse_push(value, "lpush")?;
+        Ok(LPush { key, elements })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, elements) = parse_push(value, "rpush")?;
+        Ok(RPush { key, elements })
+    }
+}
+
+impl TryFrom<RespArray> for LPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop(value, "lpop")?;
+        Ok(LPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for RPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_pop(value, "rpop")?;
+        Ok(RPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for LRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LRange {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LLen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["llen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LIndex {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lindex"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LIndex {
+            key: extract_string(args.next())?,
+            index: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LInsert {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["linsert"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let before = match extract_string(args.next())?.to_ascii_uppercase().as_str() {
+            "BEFORE" => true,
+            "AFTER" => false,
+            _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+        };
+        match (args.next(), args.next()) {
+            (Some(pivot), Some(element)) => Ok(LInsert {
+                key,
+                before,
+                pivot,
+                element,
+            }),
+            _ => Err(CommandError::InvalidArgument("syntax error".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lset"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let index = extract_integer(args.next())?;
+        match args.next() {
+            Some(element) => Ok(LSet {
+                key,
+                index,
+                element,
+            }),
+            None => Err(CommandError::InvalidArgument("syntax error".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lrem"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = extract_integer(args.next())?;
+        match args.next() {
+            Some(element) => Ok(LRem {
+                key,
+                count,
+                element,
+            }),
+            None => Err(CommandError::InvalidArgument("syntax error".into())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for LTrim {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["ltrim"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LTrim {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop(value, "blpop")?;
+        Ok(BLPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop(value, "brpop")?;
+        Ok(BRPop { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for LMove {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lmove"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(LMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            from: extract_list_end(args.next())?,
+            to: extract_list_end(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for RPopLPush {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["rpoplpush"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(RPopLPush {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for BLMove {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["blmove"], 5)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(BLMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            from: extract_list_end(args.next())?,
+            to: extract_list_end(args.next())?,
+            timeout: extract_timeout(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LPos {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["lpos"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let element = args
+            .next()
+            .ok_or_else(|| CommandError::InvalidArgument("syntax error".into()))?;
+        let mut lpos = LPos {
+            key,
+            element,
+            rank: 1,
+            count: None,
+            maxlen: 0,
+        };
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "RANK" => match extract_integer(args.next())? {
+                    0 => {
+                        return Err(CommandError::InvalidArgument(
+                            "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".into(),
+                        ))
+                    }
+                    i64::MIN => {
+                        return Err(CommandError::InvalidArgument(
+                            "value is out of range".into(),
+                        ))
+                    }
+                    rank => lpos.rank = rank,
+                },
+                "COUNT" => match extract_integer(args.next())? {
+                    n if n < 0 => {
+                        return Err(CommandError::InvalidArgument(
+                            "COUNT can't be negative".into(),
+                        ))
+                    }
+                    n => lpos.count = Some(n as usize),
+                },
+                "MAXLEN" => match extract_integer(args.next())? {
+                    n if n < 0 => {
+                        return Err(CommandError::InvalidArgument(
+                            "MAXLEN can't be negative".into(),
+                        ))
+                    }
+                    n => lpos.maxlen = n as usize,
+                },
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        Ok(lpos)
+    }
+}
+
+impl TryFrom<RespArray> for LMPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["lmpop"], 3)?;
+
+        let args = extract_args(value, 1)?.into_iter();
+        let (keys, end, count) = parse_mpop(args)?;
+        Ok(LMPop { keys, end, count })
+    }
+}
+
+impl TryFrom<RespArray> for BLMPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["blmpop"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let timeout = extract_timeout(args.next())?;
+        let (keys, end, count) = parse_mpop(args)?;
+        Ok(BLMPop {
+            keys,
+            end,
+            count,
+            timeout,
+        })
+    }
+}
+
+// parses `numkeys key [key ...] LEFT | RIGHT [COUNT count]`
+fn parse_mpop(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<(Vec<String>, ListEnd, usize), CommandError> {
+    let n_keys = extract_integer(args.next())?;
+    if n_keys <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "numkeys should be greater than 0".into(),
+        ));
+    }
+    let keys = (0..n_keys)
+        .map(|_| extract_string(args.next()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let end = extract_list_end(args.next())?;
+    let count = match args.next() {
+        Some(arg) => {
+            if !extract_string(Some(arg))?.eq_ignore_ascii_case("COUNT") {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+            match extract_integer(args.next())? {
+                n if n > 0 => n as usize,
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "count should be greater than 0".into(),
+                    ))
+                }
+            }
+        }
+        None => 1,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+    Ok((keys, end, count))
+}
+
+fn extract_list_end(frame: Option<RespFrame>) -> Result<ListEnd, CommandError> {
+    match extract_string(frame)?.to_ascii_uppercase().as_str() {
+        "LEFT" => Ok(ListEnd::Left),
+        "RIGHT" => Ok(ListEnd::Right),
+        _ => Err(CommandError::InvalidArgument("syntax error".into())),
+    }
+}
+
+// parses `key element [element ...]`
+fn parse_push(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<RespFrame>), CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    Ok((key, args.collect()))
+}
+
+// parses `key [count]`
+fn parse_pop(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Option<usize>), CommandError> {
+    validate_variadic_command(&value, &[name], 1)?;
+    if value.len() > 3 {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let count = match args.next() {
+        Some(count) => Some(extract_count(Some(count))?),
+        None => None,
+    };
+    Ok((key, count))
+}
+
+// counts of list commands may not be negative
+fn extract_count(frame: Option<RespFrame>) -> Result<usize, CommandError> {
+    match extract_integer(frame)? {
+        n if n >= 0 => Ok(n as usize),
+        _ => Err(CommandError::InvalidArgument(
+            "value is out of range, must be positive".into(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{BackendError, RespDecode};
+
+    use super::*;
+
+    #[test]
+    fn test_push_pop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nlpush\r\n$4\r\nlist\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: LPush = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert_eq!(cmd.elements, vec![b"a".into(), b"b".into()]);
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nrpop\r\n$4\r\nlist\r\n$1\r\n2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: RPop = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert_eq!(cmd.count, Some(2));
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nlpop\r\n$4\r\nlist\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<LPop, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_push_pop_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), b"value".into());
+
+        let cmd = RPush {
+            key: "list".to_string(),
+            elements: vec![b"c".into(), b"d".into()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        // pushed one after the other, so the last element ends up first
+        let cmd = LPush {
+            key: "list".to_string(),
+            elements: vec![b"b".into(), b"a".into()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+
+        let cmd = LPop {
+            key: "list".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), b"a".into());
+        let cmd = RPop {
+            key: "list".to_string(),
+            count: Some(2),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([b"d".into(), b"c".into()]).into()
+        );
+
+        // popping the last element deletes the key
+        let cmd = LPop {
+            key: "list".to_string(),
+            count: Some(10),
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new([b"b".into()]).into());
+        assert!(!backend.db.list.contains_key("list"));
+        let cmd = LPop {
+            key: "list".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cmd = LPush {
+            key: "string".to_string(),
+            elements: vec![b"a".into()],
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lrange_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nlrange\r\n$4\r\nlist\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: LRange = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert_eq!(cmd.start, 0);
+        assert_eq!(cmd.stop, -1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lrange_llen_lindex_commands() -> Result<()> {
+        let backend = Backend::new();
+        let elements = ["a", "b", "c", "d"]
+            .map(|e| BulkString::new(e).into())
+            .to_vec();
+        backend.push("list".to_string(), ListEnd::Right, elements)?;
+        backend.set("string".to_string(), b"value".into());
+
+        let lrange = |start: i64, stop: i64| {
+            LRange {
+                key: "list".to_string(),
+                start,
+                stop,
+            }
+            .execute(&backend)
+        };
+        let array = |elements: &[&str]| -> RespFrame {
+            RespArray::new(
+                elements
+                    .iter()
+                    .map(|e| BulkString::new(*e).into())
+                    .collect::<Vec<_>>(),
+            )
+            .into()
+        };
+        assert_eq!(lrange(0, -1), array(&["a", "b", "c", "d"]));
+        assert_eq!(lrange(1, 2), array(&["b", "c"]));
+        assert_eq!(lrange(-3, -2), array(&["b", "c"]));
+        // out of range indexes are clamped
+        assert_eq!(lrange(-100, 100), array(&["a", "b", "c", "d"]));
+        assert_eq!(lrange(2, 1), array(&[]));
+        assert_eq!(lrange(5, 10), array(&[]));
+        assert_eq!(lrange(-100, -50), array(&[]));
+
+        let cmd = LLen {
+            key: "list".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+        let cmd = LLen {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let lindex = |key: &str, index: i64| {
+            LIndex {
+                key: key.to_string(),
+                index,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(lindex("list", 0), b"a".into());
+        assert_eq!(lindex("list", -1), b"d".into());
+        assert_eq!(lindex("list", 4), RespFrame::Null(RespNull));
+        assert_eq!(lindex("list", -5), RespFrame::Null(RespNull));
+        assert_eq!(lindex("string", 0), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_linsert_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$7\r\nlinsert\r\n$4\r\nlist\r\n$5\r\nafter\r\n$1\r\nb\r\n$1\r\nc\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: LInsert = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert!(!cmd.before);
+        assert_eq!(cmd.pivot, b"b".into());
+        assert_eq!(cmd.element, b"c".into());
+
+        buf.extend_from_slice(
+            b"*5\r\n$7\r\nlinsert\r\n$4\r\nlist\r\n$6\r\nbehind\r\n$1\r\nb\r\n$1\r\nc\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<LInsert, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_linsert_lset_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            vec![b"a".into(), b"c".into()],
+        )?;
+
+        let linsert = |key: &str, before: bool, pivot: &[u8], element: &[u8]| {
+            LInsert {
+                key: key.to_string(),
+                before,
+                pivot: BulkString::new(pivot).into(),
+                element: BulkString::new(element).into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(linsert("list", true, b"c", b"b"), RespFrame::Integer(3));
+        assert_eq!(linsert("list", false, b"c", b"d"), RespFrame::Integer(4));
+        assert_eq!(linsert("list", false, b"x", b"y"), RespFrame::Integer(-1));
+        assert_eq!(linsert("missing", false, b"a", b"b"), RespFrame::Integer(0));
+        assert_eq!(
+            backend.lrange("list", 0, -1)?,
+            vec![b"a".into(), b"b".into(), b"c".into(), b"d".into()]
+        );
+
+        let lset = |key: &str, index: i64| {
+            LSet {
+                key: key.to_string(),
+                index,
+                element: b"z".into(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(lset("list", -1), RESP_OK.clone());
+        assert_eq!(backend.lindex("list", 3)?, Some(b"z".into()));
+        assert_eq!(lset("list", 4), BackendError::IndexOutOfRange.into());
+        assert_eq!(lset("missing", 0), BackendError::NoSuchKey.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lrem_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nlrem\r\n$4\r\nlist\r\n$2\r\n-2\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: LRem = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert_eq!(cmd.count, -2);
+        assert_eq!(cmd.element, b"a".into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lrem_ltrim_commands() -> Result<()> {
+        let backend = Backend::new();
+        let reset = || -> Result<()> {
+            backend.db.list.remove("list");
+            let elements = ["a", "b", "a", "c", "a"].map(|e| BulkString::new(e).into());
+            backend.push("list".to_string(), ListEnd::Right, elements.to_vec())?;
+            Ok(())
+        };
+        let lrem = |count: i64| {
+            LRem {
+                key: "list".to_string(),
+                count,
+                element: b"a".into(),
+            }
+            .execute(&backend)
+        };
+        let elements = |elements: &[&str]| -> Vec<RespFrame> {
+            elements
+                .iter()
+                .map(|e| BulkString::new(*e).into())
+                .collect()
+        };
+
+        reset()?;
+        assert_eq!(lrem(2), RespFrame::Integer(2));
+        assert_eq!(backend.lrange("list", 0, -1)?, elements(&["b", "c", "a"]));
+        reset()?;
+        assert_eq!(lrem(-2), RespFrame::Integer(2));
+        assert_eq!(backend.lrange("list", 0, -1)?, elements(&["a", "b", "c"]));
+        reset()?;
+        assert_eq!(lrem(0), RespFrame::Integer(3));
+        assert_eq!(backend.lrange("list", 0, -1)?, elements(&["b", "c"]));
+
+        let ltrim = |start: i64, stop: i64| {
+            LTrim {
+                key: "list".to_string(),
+                start,
+                stop,
+            }
+            .execute(&backend)
+        };
+        reset()?;
+        assert_eq!(ltrim(1, -2), RESP_OK.clone());
+        assert_eq!(backend.lrange("list", 0, -1)?, elements(&["b", "a", "c"]));
+        // an empty range deletes the key
+        assert_eq!(ltrim(5, 10), RESP_OK.clone());
+        assert!(!backend.db.list.contains_key("list"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_blpop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nblpop\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\n1.5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BLPop = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cmd.timeout, Some(Duration::from_millis(1500)));
+
+        buf.extend_from_slice(b"*3\r\n$5\r\nbrpop\r\n$1\r\na\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BRPop = frame.try_into()?;
+        assert_eq!(cmd.timeout, None);
+
+        buf.extend_from_slice(b"*3\r\n$5\r\nblpop\r\n$1\r\na\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<BLPop, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*3\r\n$5\r\nblpop\r\n$1\r\na\r\n$4\r\n1e20\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<BLPop, _> = frame.try_into();
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            CommandError::InvalidArgument("timeout is out of range".into()).to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_blpop_brpop_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            "b".to_string(),
+            ListEnd::Right,
+            vec![b"1".into(), b"2".into()],
+        )?;
+
+        let cmd = BLPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: None,
+        };
+        assert_eq!(
+            cmd.try_execute(&backend),
+            Some(RespArray::new([b"b".into(), b"1".into()]).into())
+        );
+        let cmd = BRPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: None,
+        };
+        assert_eq!(
+            cmd.try_execute(&backend),
+            Some(RespArray::new([b"b".into(), b"2".into()]).into())
+        );
+        // nothing left to pop, so it would block, or reply Null when executed directly
+        assert_eq!(cmd.try_execute(&backend), None);
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*6\r\n$6\r\nblmove\r\n$3\r\nsrc\r\n$3\r\ndst\r\n$5\r\nRIGHT\r\n$4\r\nleft\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BLMove = frame.try_into()?;
+        assert_eq!(cmd.source, "src");
+        assert_eq!(cmd.destination, "dst");
+        assert_eq!(cmd.from, ListEnd::Right);
+        assert_eq!(cmd.to, ListEnd::Left);
+        assert_eq!(cmd.timeout, Some(Duration::from_secs(1)));
+
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nlmove\r\n$3\r\nsrc\r\n$3\r\ndst\r\n$2\r\nup\r\n$4\r\nleft\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<LMove, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmove_rpoplpush_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            "src".to_string(),
+            ListEnd::Right,
+            vec![b"a".into(), b"b".into()],
+        )?;
+        backend.set("string".to_string(), b"value".into());
+
+        let cmd = LMove {
+            source: "src".to_string(),
+            destination: "dst".to_string(),
+            from: ListEnd::Left,
+            to: ListEnd::Right,
+        };
+        assert_eq!(cmd.execute(&backend), b"a".into());
+        let cmd = RPopLPush {
+            source: "src".to_string(),
+            destination: "dst".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), b"b".into());
+        assert!(!backend.db.list.contains_key("src"));
+        assert_eq!(
+            backend.lrange("dst", 0, -1)?,
+            vec![b"b".into(), b"a".into()]
+        );
+
+        // rotating a list onto itself
+        let cmd = RPopLPush {
+            source: "dst".to_string(),
+            destination: "dst".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), b"a".into());
+        assert_eq!(
+            backend.lrange("dst", 0, -1)?,
+            vec![b"a".into(), b"b".into()]
+        );
+
+        // a destination of the wrong type leaves the source untouched
+        let cmd = RPopLPush {
+            source: "dst".to_string(),
+            destination: "string".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        assert_eq!(backend.llen("dst")?, 2);
+
+        let cmd = BLMove {
+            source: "missing".to_string(),
+            destination: "dst".to_string(),
+            from: ListEnd::Left,
+            to: ListEnd::Left,
+            timeout: None,
+        };
+        assert_eq!(cmd.try_execute(&backend), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*9\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$2\r\n-2\r\n$5\r\nCOUNT\r\n$1\r\n0\r\n$6\r\nMAXLEN\r\n$2\r\n10\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: LPos = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert_eq!(cmd.element, b"a".into());
+        assert_eq!(cmd.rank, -2);
+        assert_eq!(cmd.count, Some(0));
+        assert_eq!(cmd.maxlen, 10);
+
+        buf.extend_from_slice(
+            b"*5\r\n$4\r\nlpos\r\n$4\r\nlist\r\n$1\r\na\r\n$4\r\nRANK\r\n$1\r\n0\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<LPos, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_command() -> Result<()> {
+        let backend = Backend::new();
+        let elements = ["a", "b", "c", "1", "2", "3", "c", "c"].map(|e| BulkString::new(e).into());
+        backend.push("list".to_string(), ListEnd::Right, elements.to_vec())?;
+
+        let lpos = |rank: i64, count: Option<usize>, maxlen: usize| {
+            LPos {
+                key: "list".to_string(),
+                element: b"c".into(),
+                rank,
+                count,
+                maxlen,
+            }
+            .execute(&backend)
+        };
+        let indexes = |indexes: &[i64]| -> RespFrame {
+            RespArray::new(
+                indexes
+                    .iter()
+                    .map(|&i| RespFrame::Integer(i))
+                    .collect::<Vec<_>>(),
+            )
+            .into()
+        };
+        assert_eq!(lpos(1, None, 0), RespFrame::Integer(2));
+        assert_eq!(lpos(2, None, 0), RespFrame::Integer(6));
+        assert_eq!(lpos(-1, None, 0), RespFrame::Integer(7));
+        assert_eq!(lpos(4, None, 0), RespFrame::Null(RespNull));
+        assert_eq!(lpos(1, Some(2), 0), indexes(&[2, 6]));
+        assert_eq!(lpos(1, Some(0), 0), indexes(&[2, 6, 7]));
+        assert_eq!(lpos(-2, Some(0), 0), indexes(&[6, 2]));
+        assert_eq!(lpos(1, Some(0), 7), indexes(&[2, 6]));
+        assert_eq!(lpos(1, Some(0), 2), indexes(&[]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmpop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*8\r\n$6\r\nblmpop\r\n$1\r\n0\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nRIGHT\r\n$5\r\nCOUNT\r\n$1\r\n3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BLMPop = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cmd.end, ListEnd::Right);
+        assert_eq!(cmd.count, 3);
+        assert_eq!(cmd.timeout, None);
+
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nlmpop\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$4\r\nLEFT\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: LMPop = frame.try_into()?;
+        assert_eq!(cmd.count, 1);
+
+        // numkeys does not match the keys
+        buf.extend_from_slice(b"*4\r\n$5\r\nlmpop\r\n$1\r\n2\r\n$1\r\na\r\n$4\r\nLEFT\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<LMPop, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lmpop_command() -> Result<()> {
+        let backend = Backend::new();
+        let elements = ["1", "2", "3"].map(|e| BulkString::new(e).into());
+        backend.push("b".to_string(), ListEnd::Right, elements.to_vec())?;
+
+        let cmd = LMPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            end: ListEnd::Left,
+            count: 2,
+        };
+        let expected = RespArray::new([
+            b"b".into(),
+            RespArray::new([b"1".into(), b"2".into()]).into(),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = BLMPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            end: ListEnd::Right,
+            count: 5,
+            timeout: None,
+        };
+        let expected = RespArray::new([b"b".into(), RespArray::new([b"3".into()]).into()]);
+        assert_eq!(cmd.try_execute(&backend), Some(expected.into()));
+        assert_eq!(cmd.try_execute(&backend), None);
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
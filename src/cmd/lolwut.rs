@@ -0,0 +1,226 @@
+use super::server::REDIS_VERSION;
+use super::{
+    extract_args, extract_integer, validate_variadic_command, CommandError, CommandExecutor, Lolwut,
+};
+
+use crate::{Backend, BulkString, RespArray, RespFrame};
+use rand::Rng;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, SQRT_2};
+
+// console columns and squares per row and per column drawn when not given
+const DEFAULT_SCHOTTER: [i64; 3] = [66, 8, 12];
+
+impl CommandExecutor for Lolwut {
+    fn execute(self, _: &Backend) -> RespFrame {
+        let mut output = String::new();
+        if self.version.is_none_or(|version| version == 5) {
+            let [cols, per_row, per_col] = DEFAULT_SCHOTTER;
+            let arg = |i: usize, default: i64, max: i64| {
+                self.args.get(i).copied().unwrap_or(default).clamp(1, max) as usize
+            };
+            let canvas = draw_schotter(
+                arg(0, cols, 1000),
+                arg(1, per_row, 200),
+                arg(2, per_col, 200),
+            );
+            output.push_str(&canvas.render());
+            output.push_str("\nGeorg Nees - schotter, plotter on paper, 1968. ");
+        }
+        output.push_str(&format!("Redis ver. {}\n", REDIS_VERSION));
+        BulkString::new(output).into()
+    }
+}
+
+impl TryFrom<RespArray> for Lolwut {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["lolwut"], 0)?;
+
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let mut version = None;
+        if let Some(RespFrame::BulkString(arg)) = args.peek() {
+            if arg.eq_ignore_ascii_case(b"version") {
+                args.next();
+                version = Some(extract_integer(args.next())?);
+            }
+        }
+        let args = args
+            .map(|arg| extract_integer(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(Lolwut { version, args })
+    }
+}
+
+// pixels that are either on or off, printed with braille characters of 2x4 pixels each
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    fn set(&mut self, x: i64, y: i64) {
+        if (0..self.width as i64).contains(&x) && (0..self.height as i64).contains(&y) {
+            self.pixels[y as usize * self.width + x as usize] = true;
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+
+    // Bresenham's line from (x1, y1) to (x2, y2)
+    fn draw_line(&mut self, (mut x1, mut y1): (i64, i64), (x2, y2): (i64, i64)) {
+        let (dx, dy) = ((x2 - x1).abs(), (y2 - y1).abs());
+        let (sx, sy) = ((x2 - x1).signum(), (y2 - y1).signum());
+        let mut err = dx - dy;
+        loop {
+            self.set(x1, y1);
+            if x1 == x2 && y1 == y2 {
+                break;
+            }
+            let e2 = err * 2;
+            if e2 > -dy {
+                err -= dy;
+                x1 += sx;
+            }
+            if e2 < dx {
+                err += dx;
+                y1 += sy;
+            }
+        }
+    }
+
+    // square with sides of `size` centered on (x, y), rotated by `angle` radians
+    fn draw_square(&mut self, (x, y): (f64, f64), size: f64, angle: f64) {
+        let size = (size / SQRT_2).round();
+        let corners = (0..4)
+            .map(|k| {
+                let angle = angle + FRAC_PI_4 + FRAC_PI_2 * k as f64;
+                (
+                    (angle.sin() * size + x) as i64,
+                    (angle.cos() * size + y) as i64,
+                )
+            })
+            .collect::<Vec<_>>();
+        for k in 0..4 {
+            self.draw_line(corners[k], corners[(k + 1) % 4]);
+        }
+    }
+
+    fn render(&self) -> String {
+        // bit of each of the 2x4 dots of a braille character, by column then row
+        const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+        let mut lines = vec![];
+        for y in (0..self.height).step_by(4) {
+            let line = (0..self.width)
+                .step_by(2)
+                .map(|x| {
+                    let mut bits = 0;
+                    for (dx, column) in DOTS.iter().enumerate() {
+                        for (dy, bit) in column.iter().enumerate() {
+                            if self.get(x + dx, y + dy) {
+                                bits |= bit;
+                            }
+                        }
+                    }
+                    char::from_u32(0x2800 + bits).unwrap_or(' ')
+                })
+                .collect::<String>();
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+}
+
+// Georg Nees' Schotter: a grid of squares, more and more out of place further down
+fn draw_schotter(cols: usize, per_row: usize, per_col: usize) -> Canvas {
+    let width = cols * 2;
+    let padding = if width > 4 { 2.0 } else { 0.0 };
+    let side = (width as f64 - padding * 2.0) / per_row as f64;
+    let height = (side * per_col as f64 + padding * 2.0) as usize;
+    let mut canvas = Canvas::new(width, height);
+    let mut rng = rand::thread_rng();
+    for y in 0..per_col {
+        for x in 0..per_row {
+            let mut cx = x as f64 * side + side / 2.0 + padding;
+            let mut cy = y as f64 * side + side / 2.0 + padding;
+            let mut angle = 0.0;
+            if y > 1 {
+                let mut disorder = || {
+                    let r = rng.gen::<f64>() / per_col as f64 * y as f64;
+                    if rng.gen() {
+                        r
+                    } else {
+                        -r
+                    }
+                };
+                angle = disorder();
+                cx += disorder() * side / 3.0;
+                cy += disorder() * side / 3.0;
+            }
+            canvas.draw_square((cx, cy), side, angle);
+        }
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::RespDecode;
+
+    use super::*;
+
+    #[test]
+    fn test_lolwut_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nlolwut\r\n$7\r\nVERSION\r\n$1\r\n5\r\n$2\r\n40\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Lolwut = frame.try_into()?;
+        assert_eq!(cmd.version, Some(5));
+        assert_eq!(cmd.args, vec![40]);
+
+        buf.extend_from_slice(b"*2\r\n$6\r\nlolwut\r\n$3\r\nbig\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Lolwut::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lolwut() {
+        let backend = Backend::new();
+        let cmd = Lolwut {
+            version: None,
+            args: vec![10, 2, 3],
+        };
+        let RespFrame::BulkString(output) = cmd.execute(&backend) else {
+            panic!("LOLWUT replies with a bulk string");
+        };
+        let output = String::from_utf8(output.to_vec()).unwrap();
+        let (art, caption) = output.trim_end().rsplit_once('\n').unwrap();
+        assert!(art.lines().all(|line| line.chars().count() == 10));
+        assert!(caption.starts_with("Georg Nees"));
+        assert!(output.ends_with(&format!("Redis ver. {}\n", REDIS_VERSION)));
+
+        let cmd = Lolwut {
+            version: Some(6),
+            args: vec![],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            BulkString::new(format!("Redis ver. {}\n", REDIS_VERSION)).into()
+        );
+    }
+}
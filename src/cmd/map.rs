@@ -1,7 +1,13 @@
-use crate::{Backend, RespArray, RespNull};
+use crate::{
+    format_float, now_ms, Backend, BackendError, BulkString, RespArray, RespMap, RespNull,
+    SetCondition,
+};
 
 use super::{
-    extract_args, validate_command, CommandError, CommandExecutor, Get, RespFrame, Set, RESP_OK,
+    extract_args, extract_float, extract_integer, extract_string, validate_command,
+    validate_variadic_command, Append, CommandError, CommandExecutor, Decr, DecrBy, Expiry, Get,
+    GetDel, GetEx, GetRange, GetSet, Incr, IncrBy, IncrByFloat, Lcs, MGet, MSet, MSetNx, PSetEx,
+    RespFrame, Set, SetEx, SetNx, SetRange, StrLen, RESP_OK,
 };
 
 impl CommandExecutor for Get {
@@ -15,11 +21,312 @@ impl CommandExecutor for Get {
 
 impl CommandExecutor for Set {
     fn execute(self, backend: &Backend) -> RespFrame {
-        backend.set(self.key, self.value);
+        let keep_ttl = self.expiry == Some(Expiry::KeepTtl);
+        let expire_at = self.expiry.and_then(Expiry::deadline);
+        let (written, old) =
+            backend.set_with(self.key, self.value, self.condition, expire_at, keep_ttl);
+        if self.get {
+            old.unwrap_or(RespFrame::Null(RespNull))
+        } else if written {
+            RESP_OK.clone()
+        } else {
+            RespFrame::Null(RespNull)
+        }
+    }
+}
+
+impl CommandExecutor for SetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.set_nx(self.key, self.value) as i64)
+    }
+}
+
+impl CommandExecutor for SetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire_at = Expiry::Ex(self.seconds).deadline();
+        backend.set_with(self.key, self.value, None, expire_at, false);
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for PSetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let expire_at = Expiry::Px(self.milliseconds).deadline();
+        backend.set_with(self.key, self.value, None, expire_at, false);
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for GetSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getset(self.key, self.value) {
+            Ok(old) => old.unwrap_or(RespFrame::Null(RespNull)),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetDel {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getdel(&self.key) {
+            Ok(old) => old.unwrap_or(RespFrame::Null(RespNull)),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for StrLen {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.strlen(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for GetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.getrange(&self.key, self.start, self.end) {
+            Ok(value) => BulkString::new(value).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SetRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.setrange(self.key, self.offset, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Lcs {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (a, b) = match (
+            backend.get_string(&self.key1),
+            backend.get_string(&self.key2),
+        ) {
+            (Ok(a), Ok(b)) => (a.unwrap_or_default(), b.unwrap_or_default()),
+            (Err(e), _) | (_, Err(e)) => return e.into(),
+        };
+        let (common, matches) = lcs(&a, &b);
+        if self.len {
+            return RespFrame::Integer(common.len() as i64);
+        }
+        if !self.idx {
+            return BulkString::new(common).into();
+        }
+
+        let matches = matches
+            .into_iter()
+            .filter(|m| m.len() >= self.min_match_len)
+            .map(|m| {
+                let range = |(start, end): (usize, usize)| {
+                    RespArray::new([
+                        RespFrame::Integer(start as i64),
+                        RespFrame::Integer(end as i64),
+                    ])
+                    .into()
+                };
+                let mut frames = vec![range(m.a), range(m.b)];
+                if self.with_match_len {
+                    frames.push(RespFrame::Integer(m.len() as i64));
+                }
+                RespArray::new(frames).into()
+            })
+            .collect::<Vec<RespFrame>>();
+        let mut map = RespMap::new();
+        map.insert("matches".to_string(), RespArray::new(matches).into());
+        map.insert("len".to_string(), RespFrame::Integer(common.len() as i64));
+        map.into()
+    }
+}
+
+// inclusive ranges of a common substring in both strings
+#[derive(Debug, PartialEq)]
+struct LcsMatch {
+    a: (usize, usize),
+    b: (usize, usize),
+}
+
+impl LcsMatch {
+    fn len(&self) -> usize {
+        self.a.1 - self.a.0 + 1
+    }
+}
+
+// computes the longest common subsequence of `a` and `b` along with the contiguous
+// ranges it is made of, from the last one to the first one like redis does
+fn lcs(a: &[u8], b: &[u8]) -> (Vec<u8>, Vec<LcsMatch>) {
+    let (alen, blen) = (a.len(), b.len());
+    // dp[i * (blen + 1) + j] is the LCS length of a[..i] and b[..j]
+    let mut dp = vec![0u32; (alen + 1) * (blen + 1)];
+    let at = |i: usize, j: usize| i * (blen + 1) + j;
+    for i in 1..=alen {
+        for j in 1..=blen {
+            dp[at(i, j)] = if a[i - 1] == b[j - 1] {
+                dp[at(i - 1, j - 1)] + 1
+            } else {
+                dp[at(i - 1, j)].max(dp[at(i, j - 1)])
+            };
+        }
+    }
+
+    let mut idx = dp[at(alen, blen)] as usize;
+    let mut common = vec![0u8; idx];
+    let mut matches = Vec::new();
+    // `alen` as a range start means there is no range being tracked
+    let (mut arange_start, mut arange_end, mut brange_start, mut brange_end) = (alen, 0, 0, 0);
+    let (mut i, mut j) = (alen, blen);
+    while i > 0 && j > 0 {
+        let mut emit_range = false;
+        if a[i - 1] == b[j - 1] {
+            common[idx - 1] = a[i - 1];
+            if arange_start == alen {
+                arange_start = i - 1;
+                arange_end = i - 1;
+                brange_start = j - 1;
+                brange_end = j - 1;
+            } else if arange_start == i && brange_start == j {
+                // the range is contiguous, extend it backward
+                arange_start -= 1;
+                brange_start -= 1;
+            } else {
+                emit_range = true;
+            }
+            // matched the first byte of one of the strings, the loop is about to exit
+            if arange_start == 0 || brange_start == 0 {
+                emit_range = true;
+            }
+            idx -= 1;
+            i -= 1;
+            j -= 1;
+        } else {
+            if dp[at(i - 1, j)] > dp[at(i, j - 1)] {
+                i -= 1;
+            } else {
+                j -= 1;
+            }
+            if arange_start != alen {
+                emit_range = true;
+            }
+        }
+
+        if emit_range {
+            matches.push(LcsMatch {
+                a: (arange_start, arange_end),
+                b: (brange_start, brange_end),
+            });
+            arange_start = alen;
+        }
+    }
+    (common, matches)
+}
+
+impl CommandExecutor for Append {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.append(self.key, &self.value) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for Incr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, self.key, 1)
+    }
+}
+
+impl CommandExecutor for Decr {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, self.key, -1)
+    }
+}
+
+impl CommandExecutor for IncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        incr_by(backend, self.key, self.increment)
+    }
+}
+
+impl CommandExecutor for DecrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.decrement.checked_neg() {
+            Some(delta) => incr_by(backend, self.key, delta),
+            None => BackendError::Overflow.into(),
+        }
+    }
+}
+
+impl CommandExecutor for IncrByFloat {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.incr_by_float(self.key, self.increment) {
+            Ok(value) => BulkString::new(format_float(value)).into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn incr_by(backend: &Backend, key: String, delta: i64) -> RespFrame {
+    match backend.incr_by(key, delta) {
+        Ok(value) => RespFrame::Integer(value),
+        Err(e) => e.into(),
+    }
+}
+
+impl CommandExecutor for MSet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        backend.mset(self.pairs);
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for MSetNx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.msetnx(self.pairs) as i64)
+    }
+}
+
+impl CommandExecutor for MGet {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let values = self
+            .keys
+            .iter()
+            .map(|key| backend.get(key).unwrap_or(RespFrame::Null(RespNull)))
+            .collect::<Vec<_>>();
+        RespArray::new(values).into()
+    }
+}
+
+impl CommandExecutor for GetEx {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let result = match self.expiry {
+            Some(expiry) => backend.getex(&self.key, expiry.deadline()),
+            None => Ok(backend.get(&self.key)),
+        };
+        match result {
+            Ok(value) => value.unwrap_or(RespFrame::Null(RespNull)),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl Expiry {
+    /// Absolute expiration time in unix milliseconds, None for KEEPTTL and PERSIST.
+    pub(super) fn deadline(self) -> Option<u64> {
+        match self {
+            Expiry::Ex(secs) => Some(now_ms().saturating_add(secs.saturating_mul(1000))),
+            Expiry::Px(ms) => Some(now_ms().saturating_add(ms)),
+            Expiry::ExAt(secs) => Some(secs.saturating_mul(1000)),
+            Expiry::PxAt(ms) => Some(ms),
+            Expiry::KeepTtl | Expiry::Persist => None,
+        }
+    }
+}
+
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
 
@@ -42,67 +349,1081 @@ impl TryFrom<RespArray> for Set {
     type Error = CommandError;
 
     fn try_from(value: RespArray) -> Result<Self, Self::Error> {
-        validate_command(&value, &["set"], 2)?;
+        validate_variadic_command(&value, &["set"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let (key, value) = match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => (String::from_utf8(key.0)?, value),
+            _ => {
+                return Err(CommandError::InvalidCommand(
+                    "Invalid key or value for SET command".into(),
+                ))
+            }
+        };
+
+        let mut expiry = None;
+        let mut condition = None;
+        let mut get = false;
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            match option.as_str() {
+                "NX" if condition.is_none() => condition = Some(SetCondition::IfNotExists),
+                "XX" if condition.is_none() => condition = Some(SetCondition::IfExists),
+                "GET" if !get => get = true,
+                "KEEPTTL" if expiry.is_none() => expiry = Some(Expiry::KeepTtl),
+                "EX" | "PX" | "EXAT" | "PXAT" if expiry.is_none() => {
+                    expiry = Some(parse_expiry(&option, &mut args, "set")?);
+                }
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+
+        Ok(Set {
+            key,
+            value,
+            expiry,
+            condition,
+            get,
+        })
+    }
+}
+
+// parses the argument of an EX/PX/EXAT/PXAT option
+fn parse_expiry(
+    option: &str,
+    args: &mut impl Iterator<Item = RespFrame>,
+    name: &str,
+) -> Result<Expiry, CommandError> {
+    let n = extract_expire_time(args.next(), name)?;
+    match option {
+        "EX" => Ok(Expiry::Ex(n)),
+        "PX" => Ok(Expiry::Px(n)),
+        "EXAT" => Ok(Expiry::ExAt(n)),
+        "PXAT" => Ok(Expiry::PxAt(n)),
+        _ => Err(CommandError::InvalidArgument("syntax error".into())),
+    }
+}
+
+// expire times must be positive integers
+fn extract_expire_time(frame: Option<RespFrame>, name: &str) -> Result<u64, CommandError> {
+    match extract_integer(frame)? {
+        n if n > 0 => Ok(n as u64),
+        _ => Err(CommandError::InvalidArgument(format!(
+            "invalid expire time in '{}' command",
+            name
+        ))),
+    }
+}
+
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["getex"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let expiry = match args.next() {
+            Some(arg) => {
+                let option = extract_string(Some(arg))?.to_ascii_uppercase();
+                match option.as_str() {
+                    "PERSIST" => Expiry::Persist,
+                    _ => parse_expiry(&option, &mut args, "getex")?,
+                }
+            }
+            None => return Ok(GetEx { key, expiry: None }),
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        Ok(GetEx {
+            key,
+            expiry: Some(expiry),
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setnx"], 2)?;
 
         let mut args = extract_args(value, 1)?.into_iter();
         match (args.next(), args.next()) {
-            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(Set {
+            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(SetNx {
                 key: String::from_utf8(key.0)?,
                 value,
             }),
             _ => Err(CommandError::InvalidCommand(
-                "Invalid key or value for SET command".into(),
+                "Invalid key or value for SETNX command".into(),
             )),
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
+impl TryFrom<RespArray> for SetEx {
+    type Error = CommandError;
 
-    use crate::RespDecode;
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setex"], 3)?;
 
-    use super::*;
-    use anyhow::Result;
-    use bytes::BytesMut;
-    #[test]
-    fn test_get_from_resp_array() -> Result<()> {
-        let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n");
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let seconds = extract_expire_time(args.next(), "setex")?;
+        match args.next() {
+            Some(value) => Ok(SetEx {
+                key,
+                seconds,
+                value,
+            }),
+            None => Err(CommandError::InvalidCommand(
+                "Invalid value for SETEX command".into(),
+            )),
+        }
+    }
+}
 
-        let frame = RespArray::decode(&mut buf)?;
-        let result: Get = frame.try_into()?;
-        assert_eq!(result.key, "hello");
-        Ok(())
+impl TryFrom<RespArray> for PSetEx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["psetex"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let milliseconds = extract_expire_time(args.next(), "psetex")?;
+        match args.next() {
+            Some(value) => Ok(PSetEx {
+                key,
+                milliseconds,
+                value,
+            }),
+            None => Err(CommandError::InvalidCommand(
+                "Invalid value for PSETEX command".into(),
+            )),
+        }
     }
+}
 
-    #[test]
-    fn test_set_from_resp_array() -> Result<()> {
-        let mut buf = BytesMut::new();
-        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+impl TryFrom<RespArray> for GetSet {
+    type Error = CommandError;
 
-        let frame = RespArray::decode(&mut buf)?;
-        let result: Set = frame.try_into()?;
-        assert_eq!(result.key, "hello");
-        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
-        Ok(())
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getset"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(value)) => Ok(GetSet {
+                key: String::from_utf8(key.0)?,
+                value,
+            }),
+            _ => Err(CommandError::InvalidCommand(
+                "Invalid key or value for GETSET command".into(),
+            )),
+        }
     }
+}
 
-    #[test]
-    fn test_set_get_command() -> Result<()> {
-        let backend = Backend::new();
-        let set = Set {
-            key: "hello".to_string(),
-            value: RespFrame::BulkString(b"world".into()),
-        };
-        let result = set.execute(&backend);
-        assert_eq!(result, RESP_OK.clone());
+impl TryFrom<RespArray> for GetDel {
+    type Error = CommandError;
 
-        let get = Get {
-            key: "hello".to_string(),
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getdel"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetDel {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for StrLen {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["strlen"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(StrLen {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for GetRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["getrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(GetRange {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            end: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SetRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["setrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let offset = usize::try_from(extract_integer(args.next())?)
+            .map_err(|_| CommandError::InvalidArgument("offset is out of range".into()))?;
+        match args.next() {
+            Some(RespFrame::BulkString(value)) => Ok(SetRange { key, offset, value }),
+            _ => Err(CommandError::InvalidCommand(
+                "Invalid value for SETRANGE command".into(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Lcs {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["lcs"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut lcs = Lcs {
+            key1: extract_string(args.next())?,
+            key2: extract_string(args.next())?,
+            len: false,
+            idx: false,
+            min_match_len: 0,
+            with_match_len: false,
         };
-        let result = get.execute(&backend);
-        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "LEN" => lcs.len = true,
+                "IDX" => lcs.idx = true,
+                "WITHMATCHLEN" => lcs.with_match_len = true,
+                // negative values are the same as no minimum
+                "MINMATCHLEN" => lcs.min_match_len = extract_integer(args.next())?.max(0) as usize,
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        if lcs.len && lcs.idx {
+            return Err(CommandError::InvalidArgument(
+                "If you want both the length and indexes, please just use IDX.".into(),
+            ));
+        }
+        Ok(lcs)
+    }
+}
+
+impl TryFrom<RespArray> for Append {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["append"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(key)), Some(RespFrame::BulkString(value))) => Ok(Append {
+                key: String::from_utf8(key.0)?,
+                value,
+            }),
+            _ => Err(CommandError::InvalidCommand(
+                "Invalid key or value for APPEND command".into(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Incr {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Incr {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Decr {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decr"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Decr {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrby"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(IncrBy {
+            key: extract_string(args.next())?,
+            increment: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for DecrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["decrby"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(DecrBy {
+            key: extract_string(args.next())?,
+            decrement: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["incrbyfloat"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(IncrByFloat {
+            key: extract_string(args.next())?,
+            increment: extract_float(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for MSet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MSet {
+            pairs: extract_pairs(value, "mset")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for MSetNx {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(MSetNx {
+            pairs: extract_pairs(value, "msetnx")?,
+        })
+    }
+}
+
+fn extract_pairs(
+    value: RespArray,
+    name: &'static str,
+) -> Result<Vec<(String, RespFrame)>, CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+    if value.len().is_multiple_of(2) {
+        return Err(CommandError::InvalidArgument(format!(
+            "{} command requires key value pairs",
+            name
+        )));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let mut pairs = Vec::with_capacity(args.len() / 2);
+    while let (Some(key), Some(value)) = (args.next(), args.next()) {
+        pairs.push((extract_string(Some(key))?, value));
+    }
+    Ok(pairs)
+}
+
+impl TryFrom<RespArray> for MGet {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["mget"], 1)?;
+
+        let keys = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(MGet { keys })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{BackendError, ListEnd, RespDecode};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+    #[test]
+    fn test_get_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Get = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Set = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+        assert_eq!(result.expiry, None);
+        assert_eq!(result.condition, None);
+        assert!(!result.get);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_options_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*6\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nex\r\n$2\r\n10\r\n$2\r\nNX\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Set = frame.try_into()?;
+        assert_eq!(result.expiry, Some(Expiry::Ex(10)));
+        assert_eq!(result.condition, Some(SetCondition::IfNotExists));
+
+        buf.extend_from_slice(
+            b"*6\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nNX\r\n$2\r\nXX\r\n$3\r\nGET\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Set, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(
+            b"*5\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nPX\r\n$1\r\n0\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Set, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_get_command() -> Result<()> {
+        let backend = Backend::new();
+        let set = Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+            expiry: None,
+            condition: None,
+            get: false,
+        };
+        let result = set.execute(&backend);
+        assert_eq!(result, RESP_OK.clone());
+
+        let get = Get {
+            key: "hello".to_string(),
+        };
+        let result = get.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_with_options_command() -> Result<()> {
+        let backend = Backend::new();
+        let set = Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+            expiry: None,
+            condition: Some(SetCondition::IfExists),
+            get: false,
+        };
+        assert_eq!(set.execute(&backend), RespFrame::Null(RespNull));
+        assert_eq!(backend.get("hello"), None);
+
+        let set = Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+            expiry: Some(Expiry::Ex(100)),
+            condition: Some(SetCondition::IfNotExists),
+            get: false,
+        };
+        assert_eq!(set.execute(&backend), RESP_OK.clone());
+        assert!(backend.db.expire.contains_key("hello"));
+
+        let set = Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"redis".into()),
+            expiry: Some(Expiry::KeepTtl),
+            condition: None,
+            get: true,
+        };
+        let result = set.execute(&backend);
+        assert_eq!(result, RespFrame::BulkString(b"world".into()));
+        assert!(backend.db.expire.contains_key("hello"));
+
+        let set = Set {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+            expiry: Some(Expiry::PxAt(1)),
+            condition: None,
+            get: false,
+        };
+        set.execute(&backend);
+        assert_eq!(backend.get("hello"), None);
+        assert!(!backend.db.expire.contains_key("hello"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nappend\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Append = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.value, b"world".into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_command() -> Result<()> {
+        let backend = Backend::new();
+        let append = Append {
+            key: "hello".to_string(),
+            value: b"hello".into(),
+        };
+        assert_eq!(append.execute(&backend), RespFrame::Integer(5));
+
+        let append = Append {
+            key: "hello".to_string(),
+            value: b" world".into(),
+        };
+        assert_eq!(append.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(
+            backend.get("hello"),
+            Some(RespFrame::BulkString(b"hello world".into()))
+        );
+
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+        let append = Append {
+            key: "map".to_string(),
+            value: b"baz".into(),
+        };
+        assert_eq!(append.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrby_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nincrby\r\n$7\r\ncounter\r\n$2\r\n-5\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: IncrBy = frame.try_into()?;
+        assert_eq!(result.key, "counter");
+        assert_eq!(result.increment, -5);
+
+        buf.extend_from_slice(b"*3\r\n$6\r\ndecrby\r\n$7\r\ncounter\r\n$3\r\nabc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<DecrBy, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incr_decr_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = Incr {
+            key: "counter".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = IncrBy {
+            key: "counter".to_string(),
+            increment: 10,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+
+        let cmd = DecrBy {
+            key: "counter".to_string(),
+            decrement: 20,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-9));
+
+        let cmd = Decr {
+            key: "counter".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(-10));
+        assert_eq!(
+            backend.get("counter"),
+            Some(RespFrame::BulkString(b"-10".into()))
+        );
+
+        backend.set("hello".to_string(), RespFrame::BulkString(b"world".into()));
+        let cmd = Incr {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::NotInteger.into());
+
+        backend.set(
+            "max".to_string(),
+            RespFrame::BulkString(b"9223372036854775807".into()),
+        );
+        let cmd = Incr {
+            key: "max".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::Overflow.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("mykey".to_string(), RespFrame::BulkString(b"10.50".into()));
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: 0.1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::BulkString(b"10.6".into()));
+
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: -5.0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::BulkString(b"5.6".into()));
+
+        backend.set("mykey".to_string(), RespFrame::BulkString(b"5.0e3".into()));
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: 200.0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::BulkString(b"5200".into()));
+
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: f64::MAX,
+        };
+        cmd.execute(&backend);
+        let cmd = IncrByFloat {
+            key: "mykey".to_string(),
+            increment: f64::MAX,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::NotFinite.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*5\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n$1\r\n2\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+        let result: MSet = frame.try_into()?;
+        assert_eq!(
+            result.pairs,
+            vec![
+                ("a".to_string(), RespFrame::BulkString(b"1".into())),
+                ("b".to_string(), RespFrame::BulkString(b"2".into())),
+            ]
+        );
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nmset\r\n$1\r\na\r\n$1\r\n1\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<MSet, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mset_mget_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = MSet {
+            pairs: vec![
+                ("a".to_string(), RespFrame::BulkString(b"1".into())),
+                ("b".to_string(), RespFrame::BulkString(b"2".into())),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+
+        let cmd = MGet {
+            keys: vec!["a".to_string(), "missing".to_string(), "b".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespArray::new([
+                RespFrame::BulkString(b"1".into()),
+                RespFrame::Null(RespNull),
+                RespFrame::BulkString(b"2".into()),
+            ])
+            .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_msetnx_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = MSetNx {
+            pairs: vec![
+                ("a".to_string(), RespFrame::BulkString(b"1".into())),
+                ("b".to_string(), RespFrame::BulkString(b"2".into())),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = MSetNx {
+            pairs: vec![
+                ("b".to_string(), RespFrame::BulkString(b"3".into())),
+                ("c".to_string(), RespFrame::BulkString(b"4".into())),
+            ],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.get("b"), Some(RespFrame::BulkString(b"2".into())));
+        assert_eq!(backend.get("c"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_setnx_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = SetNx {
+            key: "lock".to_string(),
+            value: RespFrame::BulkString(b"owner1".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = SetNx {
+            key: "lock".to_string(),
+            value: RespFrame::BulkString(b"owner2".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            backend.get("lock"),
+            Some(RespFrame::BulkString(b"owner1".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_getset_getdel_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = GetSet {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cmd = GetSet {
+            key: "hello".to_string(),
+            value: RespFrame::BulkString(b"redis".into()),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"world".into())
+        );
+
+        let cmd = GetDel {
+            key: "hello".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"redis".into())
+        );
+        assert_eq!(backend.get("hello"), None);
+
+        let cmd = GetDel {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        // a collection at the key is left alone
+        backend.push("list".to_string(), ListEnd::Right, vec![b"a".into()])?;
+        let cmd = GetSet {
+            key: "list".to_string(),
+            value: RespFrame::BulkString(b"redis".into()),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        assert_eq!(backend.get("list"), None);
+        assert_eq!(backend.llen("list")?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$2\r\npx\r\n$3\r\n100\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: GetEx = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.expiry, Some(Expiry::Px(100)));
+
+        buf.extend_from_slice(b"*3\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$7\r\nPERSIST\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: GetEx = frame.try_into()?;
+        assert_eq!(result.expiry, Some(Expiry::Persist));
+
+        buf.extend_from_slice(b"*3\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$7\r\nKEEPTTL\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<GetEx, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_getex_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), RespFrame::BulkString(b"world".into()));
+
+        let cmd = GetEx {
+            key: "hello".to_string(),
+            expiry: Some(Expiry::Ex(100)),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"world".into())
+        );
+        assert!(backend.db.expire.contains_key("hello"));
+
+        let cmd = GetEx {
+            key: "hello".to_string(),
+            expiry: Some(Expiry::Persist),
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            RespFrame::BulkString(b"world".into())
+        );
+        assert!(!backend.db.expire.contains_key("hello"));
+
+        let cmd = GetEx {
+            key: "missing".to_string(),
+            expiry: Some(Expiry::Ex(100)),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        assert!(!backend.db.expire.contains_key("missing"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_strlen_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), RespFrame::BulkString(b"world".into()));
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+
+        let cmd = StrLen {
+            key: "hello".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(5));
+
+        let cmd = StrLen {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        let cmd = StrLen {
+            key: "map".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_getrange_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(
+            "mykey".to_string(),
+            RespFrame::BulkString(b"This is a string".into()),
+        );
+        let getrange = |start, end| {
+            GetRange {
+                key: "mykey".to_string(),
+                start,
+                end,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(getrange(0, 3), RespFrame::BulkString(b"This".into()));
+        assert_eq!(getrange(-3, -1), RespFrame::BulkString(b"ing".into()));
+        assert_eq!(
+            getrange(0, -1),
+            RespFrame::BulkString(b"This is a string".into())
+        );
+        assert_eq!(getrange(10, 100), RespFrame::BulkString(b"string".into()));
+        assert_eq!(getrange(5, 3), RespFrame::BulkString(b"".into()));
+        assert_eq!(getrange(-100, -50), RespFrame::BulkString(b"".into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_setrange_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(
+            "key1".to_string(),
+            RespFrame::BulkString(b"Hello World".into()),
+        );
+        let cmd = SetRange {
+            key: "key1".to_string(),
+            offset: 6,
+            value: b"Redis".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(
+            backend.get("key1"),
+            Some(RespFrame::BulkString(b"Hello Redis".into()))
+        );
+
+        let cmd = SetRange {
+            key: "key2".to_string(),
+            offset: 6,
+            value: b"Redis".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(11));
+        assert_eq!(
+            backend.get("key2"),
+            Some(RespFrame::BulkString(b"\0\0\0\0\0\0Redis".into()))
+        );
+
+        let cmd = SetRange {
+            key: "key3".to_string(),
+            offset: 6,
+            value: b"".into(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert_eq!(backend.get("key3"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_setex_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nsetex\r\n$5\r\nhello\r\n$2\r\n10\r\n$5\r\nworld\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: SetEx = frame.try_into()?;
+        assert_eq!(result.key, "hello");
+        assert_eq!(result.seconds, 10);
+        assert_eq!(result.value, RespFrame::BulkString(b"world".into()));
+
+        buf.extend_from_slice(b"*4\r\n$6\r\npsetex\r\n$5\r\nhello\r\n$2\r\n-1\r\n$5\r\nworld\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<PSetEx, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_setex_psetex_commands() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = SetEx {
+            key: "hello".to_string(),
+            seconds: 10,
+            value: RespFrame::BulkString(b"world".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert!(backend.db.expire.contains_key("hello"));
+
+        let cmd = PSetEx {
+            key: "hello".to_string(),
+            milliseconds: 10000,
+            value: RespFrame::BulkString(b"redis".into()),
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(
+            backend.get("hello"),
+            Some(RespFrame::BulkString(b"redis".into()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*6\r\n$3\r\nlcs\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$3\r\nidx\r\n$11\r\nminmatchlen\r\n$1\r\n4\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Lcs = frame.try_into()?;
+        assert_eq!(result.key1, "key1");
+        assert_eq!(result.key2, "key2");
+        assert!(result.idx);
+        assert_eq!(result.min_match_len, 4);
+
+        buf.extend_from_slice(
+            b"*5\r\n$3\r\nlcs\r\n$4\r\nkey1\r\n$4\r\nkey2\r\n$3\r\nidx\r\n$3\r\nlen\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Lcs, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_lcs_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(
+            "key1".to_string(),
+            RespFrame::BulkString(b"ohmytext".into()),
+        );
+        backend.set(
+            "key2".to_string(),
+            RespFrame::BulkString(b"mynewtext".into()),
+        );
+        let lcs = |len, idx, min_match_len, with_match_len| {
+            Lcs {
+                key1: "key1".to_string(),
+                key2: "key2".to_string(),
+                len,
+                idx,
+                min_match_len,
+                with_match_len,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(
+            lcs(false, false, 0, false),
+            RespFrame::BulkString(b"mytext".into())
+        );
+        assert_eq!(lcs(true, false, 0, false), RespFrame::Integer(6));
+
+        let range =
+            |start, end| RespArray::new([RespFrame::Integer(start), RespFrame::Integer(end)]);
+        let mut expected = RespMap::new();
+        expected.insert(
+            "matches".to_string(),
+            RespArray::new([
+                RespArray::new([range(4, 7).into(), range(5, 8).into()]).into(),
+                RespArray::new([range(2, 3).into(), range(0, 1).into()]).into(),
+            ])
+            .into(),
+        );
+        expected.insert("len".to_string(), RespFrame::Integer(6));
+        assert_eq!(lcs(false, true, 0, false), expected.into());
+
+        let mut expected = RespMap::new();
+        expected.insert(
+            "matches".to_string(),
+            RespArray::new([RespArray::new([
+                range(4, 7).into(),
+                range(5, 8).into(),
+                RespFrame::Integer(4),
+            ])
+            .into()])
+            .into(),
+        );
+        expected.insert("len".to_string(), RespFrame::Integer(6));
+        assert_eq!(lcs(false, true, 4, true), expected.into());
         Ok(())
     }
 }
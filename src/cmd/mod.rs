@@ -1,76 +1,1467 @@
+mod connection;
+mod expire;
+mod generic;
 mod hmap;
+mod list;
+mod lolwut;
 mod map;
+mod pubsub;
+mod server;
+mod set;
+mod sort;
+mod table;
+mod transaction;
+mod zset;
 
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
+use std::ops::Bound;
+use std::time::Duration;
 use thiserror::Error;
 
-use crate::{Backend, RespArray, RespError, RespFrame};
+use crate::{
+    glob_match, Aggregate, Backend, BulkString, ClientFilter, Connection, ExpireCondition, KeyType,
+    LatencyEvent, ListEnd, PauseMode, ReplyMode, RespArray, RespError, RespFrame, RespNull,
+    ScoreCondition, SetCondition, SortOptions, TrackingOptions, ZRangeBy, ZSetEnd,
+};
+
+pub use table::{lookup_command, CommandSpec, KeySpec, COMMAND_TABLE};
 
 // once_cell is also an option
 lazy_static! {
     pub static ref RESP_OK: RespFrame = RespFrame::SimpleString("OK".into());
 }
 
-#[derive(Error, Debug)]
-pub enum CommandError {
-    #[error("Invalid command: {0}")]
-    InvalidCommand(String),
-    #[error("Invalid argument: {0}")]
-    InvalidArgument(String),
+#[derive(Error, Debug)]
+pub enum CommandError {
+    #[error("Invalid command: {0}")]
+    InvalidCommand(String),
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("{0}")]
+    RespError(#[from] RespError),
+    #[error("UTF-8 error: {0}")]
+    Utf8Error(#[from] std::string::FromUtf8Error),
+}
+
+#[enum_dispatch(CommandExecutor)]
+#[derive(Debug)]
+pub enum Command {
+    Get(Get),
+    Set(Set),
+    SetNx(SetNx),
+    SetEx(SetEx),
+    PSetEx(PSetEx),
+    GetSet(GetSet),
+    GetDel(GetDel),
+    GetEx(GetEx),
+    StrLen(StrLen),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    Lcs(Lcs),
+    Append(Append),
+    Incr(Incr),
+    Decr(Decr),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    MSet(MSet),
+    MSetNx(MSetNx),
+    MGet(MGet),
+    HGet(HGet),
+    HSet(HSet),
+    HGetAll(HGetAll),
+    HDel(HDel),
+    HExists(HExists),
+    HLen(HLen),
+    HStrLen(HStrLen),
+    HKeys(HKeys),
+    HVals(HVals),
+    HMGet(HMGet),
+    HIncrBy(HIncrBy),
+    HIncrByFloat(HIncrByFloat),
+    HSetNx(HSetNx),
+    HRandField(HRandField),
+    HScan(HScan),
+    HExpire(HExpire),
+    HPExpire(HPExpire),
+    HExpireAt(HExpireAt),
+    HPExpireAt(HPExpireAt),
+    HTtl(HTtl),
+    HPTtl(HPTtl),
+    HPersist(HPersist),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    LRange(LRange),
+    LLen(LLen),
+    LIndex(LIndex),
+    LInsert(LInsert),
+    LSet(LSet),
+    LRem(LRem),
+    LTrim(LTrim),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    LMove(LMove),
+    RPopLPush(RPopLPush),
+    BLMove(BLMove),
+    LPos(LPos),
+    LMPop(LMPop),
+    BLMPop(BLMPop),
+    SAdd(SAdd),
+    SRem(SRem),
+    SMembers(SMembers),
+    SIsMember(SIsMember),
+    SCard(SCard),
+    SInter(SInter),
+    SUnion(SUnion),
+    SDiff(SDiff),
+    SInterStore(SInterStore),
+    SUnionStore(SUnionStore),
+    SDiffStore(SDiffStore),
+    SPop(SPop),
+    SRandMember(SRandMember),
+    SMove(SMove),
+    SMIsMember(SMIsMember),
+    SScan(SScan),
+    SInterCard(SInterCard),
+    ZAdd(ZAdd),
+    ZScore(ZScore),
+    ZCard(ZCard),
+    ZRange(ZRange),
+    ZRangeByScore(ZRangeByScore),
+    ZRangeByLex(ZRangeByLex),
+    ZRevRange(ZRevRange),
+    ZIncrBy(ZIncrBy),
+    ZRank(ZRank),
+    ZRevRank(ZRevRank),
+    ZRem(ZRem),
+    ZRemRangeByScore(ZRemRangeByScore),
+    ZRemRangeByRank(ZRemRangeByRank),
+    ZPopMin(ZPopMin),
+    ZPopMax(ZPopMax),
+    BZPopMin(BZPopMin),
+    BZPopMax(BZPopMax),
+    ZUnion(ZUnion),
+    ZInter(ZInter),
+    ZDiff(ZDiff),
+    ZUnionStore(ZUnionStore),
+    ZInterStore(ZInterStore),
+    ZDiffStore(ZDiffStore),
+    ZScan(ZScan),
+    ZRandMember(ZRandMember),
+    ZMPop(ZMPop),
+    BZMPop(BZMPop),
+    Sort(Sort),
+    Del(Del),
+    Unlink(Unlink),
+    Exists(Exists),
+    Expire(Expire),
+    PExpire(PExpire),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    Persist(Persist),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    Info(Info),
+    Keys(Keys),
+    Scan(Scan),
+    Type(Type),
+    Rename(Rename),
+    RenameNx(RenameNx),
+    RandomKey(RandomKey),
+    Copy(Copy),
+    Touch(Touch),
+    Object(Object),
+    Dump(Dump),
+    Restore(Restore),
+    DbSize(DbSize),
+    FlushDb(FlushDb),
+    FlushAll(FlushAll),
+    Config(Config),
+    Commands(Commands),
+    Client(Client),
+    Debug(Debug),
+    Memory(Memory),
+    Latency(Latency),
+    Shutdown(Shutdown),
+    Save(Save),
+    BgSave(BgSave),
+    LastSave(LastSave),
+    Monitor(Monitor),
+    Time(Time),
+    Lolwut(Lolwut),
+    Wait(Wait),
+    Reset(Reset),
+    Select(Select),
+    Move(Move),
+    SwapDb(SwapDb),
+    Auth(Auth),
+    Hello(Hello),
+    Quit(Quit),
+    Acl(Acl),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    Publish(Publish),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Pubsub(Pubsub),
+    SSubscribe(SSubscribe),
+    SUnsubscribe(SUnsubscribe),
+    SPublish(SPublish),
+    Ping(Ping),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
+    // unrecognized command
+    Unrecognized(Unrecognized),
+}
+
+#[enum_dispatch]
+pub trait CommandExecutor {
+    fn execute(self, backend: &Backend) -> RespFrame;
+}
+
+/// A command that waits for one of its keys to be written to when it cannot be served
+/// right away. Executing it directly never blocks and replies with a Null instead.
+pub trait BlockingCommand: Send + Sync {
+    fn keys(&self) -> &[String];
+
+    /// How long to wait for, None to wait forever.
+    fn timeout(&self) -> Option<Duration>;
+
+    /// Serves the command if possible, None if it has to wait.
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame>;
+
+    /// Reply once the timeout elapsed without the command being served.
+    fn timed_out(&self) -> RespFrame {
+        RespFrame::Null(RespNull)
+    }
+}
+
+/// A command about the connection it is sent on rather than only the keyspace.
+/// Executing it directly, without a connection, replies with an error unless the
+/// command doesn't need one.
+pub trait ConnectionCommand: Send + Sync {
+    fn execute_on(&self, connection: &mut Connection, backend: &Backend) -> RespFrame;
+}
+
+#[derive(Debug)]
+pub struct Get {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct Set {
+    pub key: String,
+    pub value: RespFrame,
+    pub expiry: Option<Expiry>,
+    pub condition: Option<SetCondition>,
+    // GET: reply with the previous value instead of OK
+    pub get: bool,
+}
+
+#[derive(Debug)]
+pub struct SetNx {
+    pub key: String,
+    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct SetEx {
+    pub key: String,
+    pub seconds: u64,
+    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct PSetEx {
+    pub key: String,
+    pub milliseconds: u64,
+    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct GetSet {
+    pub key: String,
+    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct GetDel {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct GetEx {
+    pub key: String,
+    pub expiry: Option<Expiry>,
+}
+
+#[derive(Debug)]
+pub struct StrLen {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct GetRange {
+    pub key: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+#[derive(Debug)]
+pub struct SetRange {
+    pub key: String,
+    pub offset: usize,
+    pub value: BulkString,
+}
+
+#[derive(Debug)]
+pub struct Lcs {
+    pub key1: String,
+    pub key2: String,
+    // LEN: reply with the length of the match only
+    pub len: bool,
+    // IDX: reply with the ranges of the matches
+    pub idx: bool,
+    // MINMATCHLEN: ignore matches shorter than this
+    pub min_match_len: usize,
+    // WITHMATCHLEN: include the length of each match
+    pub with_match_len: bool,
+}
+
+#[derive(Debug)]
+pub struct Append {
+    pub key: String,
+    pub value: BulkString,
+}
+
+#[derive(Debug)]
+pub struct Incr {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct Decr {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct IncrBy {
+    pub key: String,
+    pub increment: i64,
+}
+
+#[derive(Debug)]
+pub struct DecrBy {
+    pub key: String,
+    pub decrement: i64,
+}
+
+#[derive(Debug)]
+pub struct IncrByFloat {
+    pub key: String,
+    pub increment: f64,
+}
+
+#[derive(Debug)]
+pub struct MSet {
+    pub pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct MSetNx {
+    pub pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct MGet {
+    pub keys: Vec<String>,
+}
+
+/// TTL option of a write command, as given by the client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    // EX seconds
+    Ex(u64),
+    // PX milliseconds
+    Px(u64),
+    // EXAT unix-time-seconds
+    ExAt(u64),
+    // PXAT unix-time-milliseconds
+    PxAt(u64),
+    // KEEPTTL: retain the TTL already associated with the key
+    KeepTtl,
+    // PERSIST: remove the TTL associated with the key
+    Persist,
+}
+
+#[derive(Debug)]
+pub struct HGet {
+    pub key: String,
+    pub field: String,
+}
+
+#[derive(Debug)]
+pub struct HSet {
+    pub key: String,
+    pub pairs: Vec<(String, RespFrame)>,
+}
+
+#[derive(Debug)]
+pub struct HGetAll {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct HDel {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HExists {
+    pub key: String,
+    pub field: String,
+}
+
+#[derive(Debug)]
+pub struct HStrLen {
+    pub key: String,
+    pub field: String,
+}
+
+#[derive(Debug)]
+pub struct HLen {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct HKeys {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct HVals {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct HMGet {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HIncrBy {
+    pub key: String,
+    pub field: String,
+    pub increment: i64,
+}
+
+#[derive(Debug)]
+pub struct HIncrByFloat {
+    pub key: String,
+    pub field: String,
+    pub increment: f64,
+}
+
+#[derive(Debug)]
+pub struct HSetNx {
+    pub key: String,
+    pub field: String,
+    pub value: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct HRandField {
+    pub key: String,
+    // None replies with a single field instead of an array
+    pub count: Option<i64>,
+    pub with_values: bool,
+}
+
+#[derive(Debug)]
+pub struct HScan {
+    pub key: String,
+    pub cursor: u64,
+    // MATCH: only return fields matching this glob-style pattern
+    pub pattern: Option<String>,
+    // COUNT: how much work to do per call, not an exact number of fields
+    pub count: usize,
+    // NOVALUES: only return the fields
+    pub no_values: bool,
+}
+
+#[derive(Debug)]
+pub struct HExpire {
+    pub key: String,
+    pub seconds: u64,
+    pub condition: Option<ExpireCondition>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPExpire {
+    pub key: String,
+    pub milliseconds: u64,
+    pub condition: Option<ExpireCondition>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HExpireAt {
+    pub key: String,
+    pub unix_time_seconds: u64,
+    pub condition: Option<ExpireCondition>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPExpireAt {
+    pub key: String,
+    pub unix_time_milliseconds: u64,
+    pub condition: Option<ExpireCondition>,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HTtl {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPTtl {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct HPersist {
+    pub key: String,
+    pub fields: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct LPush {
+    pub key: String,
+    pub elements: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct RPush {
+    pub key: String,
+    pub elements: Vec<RespFrame>,
+}
+
+#[derive(Debug)]
+pub struct LPop {
+    pub key: String,
+    // without a count a single element is popped and returned on its own
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RPop {
+    pub key: String,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct LRange {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+}
+
+#[derive(Debug)]
+pub struct LLen {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct LIndex {
+    pub key: String,
+    pub index: i64,
+}
+
+#[derive(Debug)]
+pub struct LInsert {
+    pub key: String,
+    // BEFORE or AFTER the pivot
+    pub before: bool,
+    pub pivot: RespFrame,
+    pub element: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LSet {
+    pub key: String,
+    pub index: i64,
+    pub element: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LRem {
+    pub key: String,
+    pub count: i64,
+    pub element: RespFrame,
+}
+
+#[derive(Debug)]
+pub struct LTrim {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+}
+
+#[derive(Debug)]
+pub struct BLPop {
+    pub keys: Vec<String>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BRPop {
+    pub keys: Vec<String>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct LMove {
+    pub source: String,
+    pub destination: String,
+    pub from: ListEnd,
+    pub to: ListEnd,
+}
+
+#[derive(Debug)]
+pub struct RPopLPush {
+    pub source: String,
+    pub destination: String,
+}
+
+#[derive(Debug)]
+pub struct BLMove {
+    pub source: String,
+    pub destination: String,
+    pub from: ListEnd,
+    pub to: ListEnd,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct LPos {
+    pub key: String,
+    pub element: RespFrame,
+    // RANK: which match to start from, negative to search from the tail
+    pub rank: i64,
+    // COUNT: how many matches to return, 0 for all of them
+    pub count: Option<usize>,
+    // MAXLEN: how many elements to compare at most, 0 for no limit
+    pub maxlen: usize,
+}
+
+#[derive(Debug)]
+pub struct LMPop {
+    pub keys: Vec<String>,
+    pub end: ListEnd,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct BLMPop {
+    pub keys: Vec<String>,
+    pub end: ListEnd,
+    pub count: usize,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct SAdd {
+    pub key: String,
+    pub members: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct SRem {
+    pub key: String,
+    pub members: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct SMembers {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct SIsMember {
+    pub key: String,
+    pub member: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SCard {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct SInter {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnion {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiff {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SInterStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnionStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SDiffStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SPop {
+    pub key: String,
+    // without a count a single member is popped and returned on its own
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct SRandMember {
+    pub key: String,
+    // negative counts may return the same member several times
+    pub count: Option<i64>,
+}
+
+#[derive(Debug)]
+pub struct SMove {
+    pub source: String,
+    pub destination: String,
+    pub member: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SMIsMember {
+    pub key: String,
+    pub members: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct SScan {
+    pub key: String,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct SInterCard {
+    pub keys: Vec<String>,
+    pub limit: usize,
+}
+
+#[derive(Debug)]
+pub struct ZAdd {
+    pub key: String,
+    pub members: Vec<(f64, Vec<u8>)>,
+    pub condition: Option<SetCondition>,
+    pub comparison: Option<ScoreCondition>,
+    // CH: count the members whose score changed along with the added ones
+    pub changed: bool,
+    // INCR: behave like ZINCRBY and reply with the new score
+    pub incr: bool,
+}
+
+#[derive(Debug)]
+pub struct ZScore {
+    pub key: String,
+    pub member: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct ZCard {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct ZRange {
+    pub key: String,
+    pub range: ZRangeBy,
+    pub rev: bool,
+    // LIMIT offset count
+    pub limit: Option<(i64, i64)>,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByScore {
+    pub key: String,
+    pub min: Bound<f64>,
+    pub max: Bound<f64>,
+    pub limit: Option<(i64, i64)>,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRangeByLex {
+    pub key: String,
+    pub min: Bound<Vec<u8>>,
+    pub max: Bound<Vec<u8>>,
+    pub limit: Option<(i64, i64)>,
+}
+
+#[derive(Debug)]
+pub struct ZRevRange {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZIncrBy {
+    pub key: String,
+    pub increment: f64,
+    pub member: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct ZRank {
+    pub key: String,
+    pub member: Vec<u8>,
+    // WITHSCORE: reply with the rank and the score
+    pub with_score: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRevRank {
+    pub key: String,
+    pub member: Vec<u8>,
+    pub with_score: bool,
+}
+
+#[derive(Debug)]
+pub struct ZRem {
+    pub key: String,
+    pub members: Vec<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByScore {
+    pub key: String,
+    pub min: Bound<f64>,
+    pub max: Bound<f64>,
+}
+
+#[derive(Debug)]
+pub struct ZRemRangeByRank {
+    pub key: String,
+    pub start: i64,
+    pub stop: i64,
+}
+
+#[derive(Debug)]
+pub struct ZPopMin {
+    pub key: String,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct ZPopMax {
+    pub key: String,
+    pub count: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct BZPopMin {
+    pub keys: Vec<String>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct BZPopMax {
+    pub keys: Vec<String>,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct ZUnion {
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZInter {
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZDiff {
+    pub keys: Vec<String>,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZUnionStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct ZInterStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+    pub weights: Vec<f64>,
+    pub aggregate: Aggregate,
+}
+
+#[derive(Debug)]
+pub struct ZDiffStore {
+    pub destination: String,
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ZScan {
+    pub key: String,
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct ZRandMember {
+    pub key: String,
+    pub count: Option<i64>,
+    pub with_scores: bool,
+}
+
+#[derive(Debug)]
+pub struct ZMPop {
+    pub keys: Vec<String>,
+    pub end: ZSetEnd,
+    pub count: usize,
+}
+
+#[derive(Debug)]
+pub struct BZMPop {
+    pub keys: Vec<String>,
+    pub end: ZSetEnd,
+    pub count: usize,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct Sort {
+    pub key: String,
+    pub options: SortOptions,
+    // STORE: store the result as a list instead of replying with it
+    pub store: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Del {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unlink {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Exists {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Expire {
+    pub key: String,
+    pub seconds: i64,
+    pub condition: Option<ExpireCondition>,
+}
+
+#[derive(Debug)]
+pub struct PExpire {
+    pub key: String,
+    pub milliseconds: i64,
+    pub condition: Option<ExpireCondition>,
+}
+
+#[derive(Debug)]
+pub struct Ttl {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct PTtl {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct ExpireAt {
+    pub key: String,
+    pub unix_time_seconds: i64,
+    pub condition: Option<ExpireCondition>,
+}
 
-    #[error("{0}")]
-    RespError(#[from] RespError),
-    #[error("UTF-8 error: {0}")]
-    Utf8Error(#[from] std::string::FromUtf8Error),
+#[derive(Debug)]
+pub struct PExpireAt {
+    pub key: String,
+    pub unix_time_milliseconds: i64,
+    pub condition: Option<ExpireCondition>,
 }
 
-#[enum_dispatch(CommandExecutor)]
 #[derive(Debug)]
-pub enum Command {
-    Get(Get),
-    Set(Set),
-    HGet(HGet),
-    HSet(HSet),
-    HGetAll(HGetAll),
-    // unrecognized command
-    Unrecognized(Unrecognized),
+pub struct Persist {
+    pub key: String,
 }
 
-#[enum_dispatch]
-pub trait CommandExecutor {
-    fn execute(self, backend: &Backend) -> RespFrame;
+#[derive(Debug)]
+pub struct ExpireTime {
+    pub key: String,
 }
 
 #[derive(Debug)]
-pub struct Get {
+pub struct PExpireTime {
     pub key: String,
 }
 
 #[derive(Debug)]
-pub struct Set {
+pub struct Touch {
+    pub keys: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Object {
+    pub subcommand: ObjectSubcommand,
     pub key: String,
-    pub value: RespFrame,
+}
+
+/// Metadata of a key queried by OBJECT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectSubcommand {
+    Encoding,
+    RefCount,
+    IdleTime,
+    Freq,
 }
 
 #[derive(Debug)]
-pub struct HGet {
+pub struct Dump {
     pub key: String,
-    pub field: String,
 }
 
 #[derive(Debug)]
-pub struct HSet {
+pub struct Restore {
     pub key: String,
-    pub field: String,
-    pub value: RespFrame,
+    // milliseconds, 0 for no TTL
+    pub ttl: u64,
+    pub payload: Vec<u8>,
+    // REPLACE: overwrite the key if it exists
+    pub replace: bool,
+    // ABSTTL: the TTL is a unix time in milliseconds
+    pub absttl: bool,
+    // IDLETIME seconds
+    pub idle_time: Option<u64>,
+    // FREQ frequency
+    pub freq: Option<u8>,
 }
 
 #[derive(Debug)]
-pub struct HGetAll {
+pub struct DbSize;
+
+#[derive(Debug)]
+pub struct FlushDb {
+    // ASYNC: free the values in the background
+    pub lazy: bool,
+}
+
+#[derive(Debug)]
+pub struct FlushAll {
+    // ASYNC: free the values in the background
+    pub lazy: bool,
+}
+
+#[derive(Debug)]
+pub struct Keys {
+    pub pattern: String,
+}
+
+#[derive(Debug)]
+pub struct Scan {
+    pub cursor: u64,
+    pub pattern: Option<String>,
+    pub count: usize,
+    // TYPE: only return keys holding this type of value
+    pub key_type: Option<KeyType>,
+}
+
+#[derive(Debug)]
+pub struct Type {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct Rename {
+    pub key: String,
+    pub new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RenameNx {
+    pub key: String,
+    pub new_key: String,
+}
+
+#[derive(Debug)]
+pub struct RandomKey;
+
+#[derive(Debug)]
+pub struct Copy {
+    pub source: String,
+    pub destination: String,
+    // DB: database to copy to, the current one when not given
+    pub db: Option<usize>,
+    // REPLACE: overwrite the destination if it exists
+    pub replace: bool,
+}
+
+#[derive(Debug)]
+pub struct Info {
+    // lowercased section names, all the default ones when empty
+    pub sections: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub subcommand: ConfigSubcommand,
+}
+
+/// What CONFIG does with the runtime configuration.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConfigSubcommand {
+    // glob-style patterns of parameter names
+    Get(Vec<String>),
+    // parameter names and their new values
+    Set(Vec<(String, String)>),
+    // persist the configuration to the file it was loaded from
+    Rewrite,
+    // zero the statistics reported by INFO
+    ResetStat,
+}
+
+#[derive(Debug)]
+pub struct Commands {
+    pub subcommand: CommandsSubcommand,
+}
+
+/// What COMMAND reports about the command table.
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandsSubcommand {
+    // details of every command
+    All,
+    Count,
+    // details of the named commands, or of every command when empty
+    Info(Vec<String>),
+    // documentation of the named commands, or of every command when empty
+    Docs(Vec<String>),
+    // keys of a full command line
+    GetKeys(Vec<String>),
+}
+
+#[derive(Debug)]
+pub struct Client {
+    pub subcommand: ClientSubcommand,
+}
+
+/// What CLIENT does with the connected clients.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClientSubcommand {
+    // clients with the given ids, or every client when empty
+    List(Vec<u64>),
+    // the client sending the command
+    Info,
+    Id,
+    // names the client sending the command, an empty name clears it
+    SetName(String),
+    GetName,
+    // closes the matching clients, the one sending the command too unless `skip_me`;
+    // `legacy` is the form with only an address, replying OK instead of a count
+    Kill {
+        filter: ClientFilter,
+        skip_me: bool,
+        legacy: bool,
+    },
+    // holds back the commands selected by `mode` of every client for `timeout`
+    Pause {
+        timeout: Duration,
+        mode: PauseMode,
+    },
+    Unpause,
+    // whether the client sending the command gets replies
+    Reply(ReplyMode),
+    // turns tracking of the keys the client caches on with the options, or off
+    Tracking(Option<TrackingOptions>),
+    // id of the client invalidations are redirected to
+    GetRedir,
+    TrackingInfo,
+}
+
+#[derive(Debug)]
+pub struct Debug {
+    pub subcommand: DebugSubcommand,
+}
+
+/// Testing aids of DEBUG.
+#[derive(Debug, PartialEq)]
+pub enum DebugSubcommand {
+    // holds the client back for the duration
+    Sleep(Duration),
+    // internal details of the value at a key
+    Object(String),
+    // turns the active expire cycle on or off
+    SetActiveExpire(bool),
+    Jmap,
+}
+
+#[derive(Debug)]
+pub struct Memory {
+    pub subcommand: MemorySubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MemorySubcommand {
+    // estimated bytes used by a key, from up to `samples` elements, all when 0
+    Usage { key: String, samples: usize },
+    // breakdown of the memory used by the server
+    Stats,
+    // report of likely memory issues
+    Doctor,
+}
+
+#[derive(Debug)]
+pub struct Latency {
+    pub subcommand: LatencySubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LatencySubcommand {
+    // latest and worst spike of every event
+    Latest,
+    // recent spikes of an event
+    History(String),
+    // forgets the spikes of the events, or of all of them
+    Reset(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Shutdown {
+    // SAVE or NOSAVE, saving only when save points are configured if neither is given
+    pub save: Option<bool>,
+    // NOW: don't wait for lagging replicas, there are none
+    pub now: bool,
+    // FORCE: stop even if the snapshot can't be saved
+    pub force: bool,
+    // ABORT: cancel a shutdown waiting for replicas
+    pub abort: bool,
+}
+
+#[derive(Debug)]
+pub struct Save;
+
+#[derive(Debug)]
+pub struct BgSave {
+    // SCHEDULE: run after the background save in progress instead of failing
+    pub schedule: bool,
+}
+
+#[derive(Debug)]
+pub struct LastSave;
+
+#[derive(Debug)]
+pub struct Monitor;
+
+#[derive(Debug)]
+pub struct Time;
+
+#[derive(Debug)]
+pub struct Reset;
+
+#[derive(Debug)]
+pub struct Select {
+    pub index: usize,
+}
+
+#[derive(Debug)]
+pub struct Auth {
+    // the default user when not given
+    pub username: Option<String>,
+    pub password: String,
+}
+
+#[derive(Debug)]
+pub struct Hello {
+    // RESP version to switch to, the current one when not given
+    pub protover: Option<i64>,
+    // username and password of the AUTH clause
+    pub auth: Option<(String, String)>,
+    pub setname: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Quit;
+
+#[derive(Debug)]
+pub struct Acl {
+    pub subcommand: AclSubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AclSubcommand {
+    // the most recent entries of the ACL log
+    Log(usize),
+    LogReset,
+}
+
+#[derive(Debug)]
+pub struct Move {
     pub key: String,
+    pub db: usize,
+}
+
+#[derive(Debug)]
+pub struct SwapDb {
+    pub first: usize,
+    pub second: usize,
+}
+
+#[derive(Debug)]
+pub struct Lolwut {
+    // art to draw, the latest one when not given
+    pub version: Option<i64>,
+    // parameters of the art, like the number of columns
+    pub args: Vec<i64>,
+}
+
+#[derive(Debug)]
+pub struct Wait {
+    // replicas that have to acknowledge the writes made so far
+    pub numreplicas: usize,
+    pub timeout: Option<Duration>,
+}
+
+#[derive(Debug)]
+pub struct Subscribe {
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Unsubscribe {
+    // every channel subscribed to when empty
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PSubscribe {
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    // every pattern subscribed to when empty
+    pub patterns: Vec<String>,
 }
 
+#[derive(Debug)]
+pub struct Publish {
+    pub channel: String,
+    pub message: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct SSubscribe {
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SUnsubscribe {
+    // every shard channel subscribed to when empty
+    pub channels: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct SPublish {
+    pub channel: String,
+    pub message: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct Pubsub {
+    pub subcommand: PubsubSubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PubsubSubcommand {
+    // channels with subscribers, only those matching the pattern if given
+    Channels(Option<String>),
+    // subscribers of each channel
+    NumSub(Vec<String>),
+    // patterns subscribed to
+    NumPat,
+    // shard channels with subscribers, only those matching the pattern if given
+    ShardChannels(Option<String>),
+    // subscribers of each shard channel
+    ShardNumSub(Vec<String>),
+}
+
+#[derive(Debug)]
+pub struct Ping {
+    // echoed back instead of PONG when given
+    pub message: Option<Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct Multi;
+
+#[derive(Debug)]
+pub struct Exec;
+
+#[derive(Debug)]
+pub struct Discard;
+
 #[derive(Debug)]
 pub struct Unrecognized;
 
@@ -80,6 +1471,82 @@ impl CommandExecutor for Unrecognized {
     }
 }
 
+impl Command {
+    /// The command as a blocking one, if it may wait for its keys.
+    pub fn as_blocking(&self) -> Option<&dyn BlockingCommand> {
+        match self {
+            Command::BLPop(cmd) => Some(cmd),
+            Command::BRPop(cmd) => Some(cmd),
+            Command::BLMove(cmd) => Some(cmd),
+            Command::BLMPop(cmd) => Some(cmd),
+            Command::BZPopMin(cmd) => Some(cmd),
+            Command::BZPopMax(cmd) => Some(cmd),
+            Command::BZMPop(cmd) => Some(cmd),
+            Command::Wait(cmd) => Some(cmd),
+            _ => None,
+        }
+    }
+
+    /// Masks the passwords among `args`, the arguments the command was parsed from, so
+    /// that they are not shown to monitors.
+    pub fn redact(&self, args: &mut [Vec<u8>]) {
+        match self {
+            Command::Auth(_) => args
+                .iter_mut()
+                .skip(1)
+                .for_each(|arg| *arg = b"(redacted)".to_vec()),
+            Command::Hello(_) => {
+                // the username and password follow the AUTH clause
+                let mut args = args.iter_mut().skip(1);
+                while let Some(arg) = args.next() {
+                    if arg.eq_ignore_ascii_case(b"auth") {
+                        args.by_ref()
+                            .take(2)
+                            .for_each(|arg| *arg = b"(redacted)".to_vec());
+                    } else if arg.eq_ignore_ascii_case(b"setname") {
+                        args.next();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// How long the command sleeps for, if it is DEBUG SLEEP.
+    pub fn sleep(&self) -> Option<Duration> {
+        match self {
+            Command::Debug(Debug {
+                subcommand: DebugSubcommand::Sleep(duration),
+            }) => Some(*duration),
+            _ => None,
+        }
+    }
+
+    /// The command as one about its connection, if it needs the connection to execute.
+    pub fn as_connection(&self) -> Option<&dyn ConnectionCommand> {
+        match self {
+            Command::Client(cmd) => Some(cmd),
+            Command::Monitor(cmd) => Some(cmd),
+            Command::Reset(cmd) => Some(cmd),
+            Command::Select(cmd) => Some(cmd),
+            Command::Auth(cmd) => Some(cmd),
+            Command::Hello(cmd) => Some(cmd),
+            Command::Quit(cmd) => Some(cmd),
+            Command::Subscribe(cmd) => Some(cmd),
+            Command::Unsubscribe(cmd) => Some(cmd),
+            Command::PSubscribe(cmd) => Some(cmd),
+            Command::PUnsubscribe(cmd) => Some(cmd),
+            Command::SSubscribe(cmd) => Some(cmd),
+            Command::SUnsubscribe(cmd) => Some(cmd),
+            Command::Ping(cmd) => Some(cmd),
+            Command::Multi(cmd) => Some(cmd),
+            Command::Exec(cmd) => Some(cmd),
+            Command::Discard(cmd) => Some(cmd),
+            _ => None,
+        }
+    }
+}
+
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
 
@@ -101,9 +1568,173 @@ impl TryFrom<RespArray> for Command {
             Some(RespFrame::BulkString(ref cmd)) => match cmd.as_ref() {
                 b"get" => Ok(Get::try_from(value)?.into()),
                 b"set" => Ok(Set::try_from(value)?.into()),
+                b"setnx" => Ok(SetNx::try_from(value)?.into()),
+                b"setex" => Ok(SetEx::try_from(value)?.into()),
+                b"psetex" => Ok(PSetEx::try_from(value)?.into()),
+                b"getset" => Ok(GetSet::try_from(value)?.into()),
+                b"getdel" => Ok(GetDel::try_from(value)?.into()),
+                b"getex" => Ok(GetEx::try_from(value)?.into()),
+                b"strlen" => Ok(StrLen::try_from(value)?.into()),
+                b"getrange" => Ok(GetRange::try_from(value)?.into()),
+                b"setrange" => Ok(SetRange::try_from(value)?.into()),
+                b"lcs" => Ok(Lcs::try_from(value)?.into()),
+                b"append" => Ok(Append::try_from(value)?.into()),
+                b"incr" => Ok(Incr::try_from(value)?.into()),
+                b"decr" => Ok(Decr::try_from(value)?.into()),
+                b"incrby" => Ok(IncrBy::try_from(value)?.into()),
+                b"decrby" => Ok(DecrBy::try_from(value)?.into()),
+                b"incrbyfloat" => Ok(IncrByFloat::try_from(value)?.into()),
+                b"mset" => Ok(MSet::try_from(value)?.into()),
+                b"msetnx" => Ok(MSetNx::try_from(value)?.into()),
+                b"mget" => Ok(MGet::try_from(value)?.into()),
                 b"hget" => Ok(HGet::try_from(value)?.into()),
                 b"hset" => Ok(HSet::try_from(value)?.into()),
                 b"hgetall" => Ok(HGetAll::try_from(value)?.into()),
+                b"hdel" => Ok(HDel::try_from(value)?.into()),
+                b"hexists" => Ok(HExists::try_from(value)?.into()),
+                b"hlen" => Ok(HLen::try_from(value)?.into()),
+                b"hstrlen" => Ok(HStrLen::try_from(value)?.into()),
+                b"hkeys" => Ok(HKeys::try_from(value)?.into()),
+                b"hvals" => Ok(HVals::try_from(value)?.into()),
+                b"hmget" => Ok(HMGet::try_from(value)?.into()),
+                b"hincrby" => Ok(HIncrBy::try_from(value)?.into()),
+                b"hincrbyfloat" => Ok(HIncrByFloat::try_from(value)?.into()),
+                b"hsetnx" => Ok(HSetNx::try_from(value)?.into()),
+                b"hrandfield" => Ok(HRandField::try_from(value)?.into()),
+                b"hscan" => Ok(HScan::try_from(value)?.into()),
+                b"hexpire" => Ok(HExpire::try_from(value)?.into()),
+                b"hpexpire" => Ok(HPExpire::try_from(value)?.into()),
+                b"hexpireat" => Ok(HExpireAt::try_from(value)?.into()),
+                b"hpexpireat" => Ok(HPExpireAt::try_from(value)?.into()),
+                b"httl" => Ok(HTtl::try_from(value)?.into()),
+                b"hpttl" => Ok(HPTtl::try_from(value)?.into()),
+                b"hpersist" => Ok(HPersist::try_from(value)?.into()),
+                b"lpush" => Ok(LPush::try_from(value)?.into()),
+                b"rpush" => Ok(RPush::try_from(value)?.into()),
+                b"lpop" => Ok(LPop::try_from(value)?.into()),
+                b"rpop" => Ok(RPop::try_from(value)?.into()),
+                b"lrange" => Ok(LRange::try_from(value)?.into()),
+                b"llen" => Ok(LLen::try_from(value)?.into()),
+                b"lindex" => Ok(LIndex::try_from(value)?.into()),
+                b"linsert" => Ok(LInsert::try_from(value)?.into()),
+                b"lset" => Ok(LSet::try_from(value)?.into()),
+                b"lrem" => Ok(LRem::try_from(value)?.into()),
+                b"ltrim" => Ok(LTrim::try_from(value)?.into()),
+                b"blpop" => Ok(BLPop::try_from(value)?.into()),
+                b"brpop" => Ok(BRPop::try_from(value)?.into()),
+                b"lmove" => Ok(LMove::try_from(value)?.into()),
+                b"rpoplpush" => Ok(RPopLPush::try_from(value)?.into()),
+                b"blmove" => Ok(BLMove::try_from(value)?.into()),
+                b"lpos" => Ok(LPos::try_from(value)?.into()),
+                b"lmpop" => Ok(LMPop::try_from(value)?.into()),
+                b"blmpop" => Ok(BLMPop::try_from(value)?.into()),
+                b"sadd" => Ok(SAdd::try_from(value)?.into()),
+                b"srem" => Ok(SRem::try_from(value)?.into()),
+                b"smembers" => Ok(SMembers::try_from(value)?.into()),
+                b"sismember" => Ok(SIsMember::try_from(value)?.into()),
+                b"scard" => Ok(SCard::try_from(value)?.into()),
+                b"sinter" => Ok(SInter::try_from(value)?.into()),
+                b"sunion" => Ok(SUnion::try_from(value)?.into()),
+                b"sdiff" => Ok(SDiff::try_from(value)?.into()),
+                b"sinterstore" => Ok(SInterStore::try_from(value)?.into()),
+                b"sunionstore" => Ok(SUnionStore::try_from(value)?.into()),
+                b"sdiffstore" => Ok(SDiffStore::try_from(value)?.into()),
+                b"spop" => Ok(SPop::try_from(value)?.into()),
+                b"srandmember" => Ok(SRandMember::try_from(value)?.into()),
+                b"smove" => Ok(SMove::try_from(value)?.into()),
+                b"smismember" => Ok(SMIsMember::try_from(value)?.into()),
+                b"sscan" => Ok(SScan::try_from(value)?.into()),
+                b"sintercard" => Ok(SInterCard::try_from(value)?.into()),
+                b"zadd" => Ok(ZAdd::try_from(value)?.into()),
+                b"zscore" => Ok(ZScore::try_from(value)?.into()),
+                b"zcard" => Ok(ZCard::try_from(value)?.into()),
+                b"zrange" => Ok(ZRange::try_from(value)?.into()),
+                b"zrangebyscore" => Ok(ZRangeByScore::try_from(value)?.into()),
+                b"zrangebylex" => Ok(ZRangeByLex::try_from(value)?.into()),
+                b"zrevrange" => Ok(ZRevRange::try_from(value)?.into()),
+                b"zincrby" => Ok(ZIncrBy::try_from(value)?.into()),
+                b"zrank" => Ok(ZRank::try_from(value)?.into()),
+                b"zrevrank" => Ok(ZRevRank::try_from(value)?.into()),
+                b"zrem" => Ok(ZRem::try_from(value)?.into()),
+                b"zremrangebyscore" => Ok(ZRemRangeByScore::try_from(value)?.into()),
+                b"zremrangebyrank" => Ok(ZRemRangeByRank::try_from(value)?.into()),
+                b"zpopmin" => Ok(ZPopMin::try_from(value)?.into()),
+                b"zpopmax" => Ok(ZPopMax::try_from(value)?.into()),
+                b"bzpopmin" => Ok(BZPopMin::try_from(value)?.into()),
+                b"bzpopmax" => Ok(BZPopMax::try_from(value)?.into()),
+                b"zunion" => Ok(ZUnion::try_from(value)?.into()),
+                b"zinter" => Ok(ZInter::try_from(value)?.into()),
+                b"zdiff" => Ok(ZDiff::try_from(value)?.into()),
+                b"zunionstore" => Ok(ZUnionStore::try_from(value)?.into()),
+                b"zinterstore" => Ok(ZInterStore::try_from(value)?.into()),
+                b"zdiffstore" => Ok(ZDiffStore::try_from(value)?.into()),
+                b"zscan" => Ok(ZScan::try_from(value)?.into()),
+                b"zrandmember" => Ok(ZRandMember::try_from(value)?.into()),
+                b"zmpop" => Ok(ZMPop::try_from(value)?.into()),
+                b"bzmpop" => Ok(BZMPop::try_from(value)?.into()),
+                b"sort" => Ok(Sort::try_from(value)?.into()),
+                b"del" => Ok(Del::try_from(value)?.into()),
+                b"unlink" => Ok(Unlink::try_from(value)?.into()),
+                b"exists" => Ok(Exists::try_from(value)?.into()),
+                b"expire" => Ok(Expire::try_from(value)?.into()),
+                b"pexpire" => Ok(PExpire::try_from(value)?.into()),
+                b"ttl" => Ok(Ttl::try_from(value)?.into()),
+                b"pttl" => Ok(PTtl::try_from(value)?.into()),
+                b"expireat" => Ok(ExpireAt::try_from(value)?.into()),
+                b"pexpireat" => Ok(PExpireAt::try_from(value)?.into()),
+                b"persist" => Ok(Persist::try_from(value)?.into()),
+                b"expiretime" => Ok(ExpireTime::try_from(value)?.into()),
+                b"pexpiretime" => Ok(PExpireTime::try_from(value)?.into()),
+                b"info" => Ok(Info::try_from(value)?.into()),
+                b"keys" => Ok(Keys::try_from(value)?.into()),
+                b"scan" => Ok(Scan::try_from(value)?.into()),
+                b"type" => Ok(Type::try_from(value)?.into()),
+                b"rename" => Ok(Rename::try_from(value)?.into()),
+                b"renamenx" => Ok(RenameNx::try_from(value)?.into()),
+                b"randomkey" => Ok(RandomKey::try_from(value)?.into()),
+                b"copy" => Ok(Copy::try_from(value)?.into()),
+                b"touch" => Ok(Touch::try_from(value)?.into()),
+                b"object" => Ok(Object::try_from(value)?.into()),
+                b"dump" => Ok(Dump::try_from(value)?.into()),
+                b"restore" => Ok(Restore::try_from(value)?.into()),
+                b"dbsize" => Ok(DbSize::try_from(value)?.into()),
+                b"flushdb" => Ok(FlushDb::try_from(value)?.into()),
+                b"flushall" => Ok(FlushAll::try_from(value)?.into()),
+                b"config" => Ok(Config::try_from(value)?.into()),
+                b"command" => Ok(Commands::try_from(value)?.into()),
+                b"client" => Ok(Client::try_from(value)?.into()),
+                b"debug" => Ok(Debug::try_from(value)?.into()),
+                b"memory" => Ok(Memory::try_from(value)?.into()),
+                b"latency" => Ok(Latency::try_from(value)?.into()),
+                b"shutdown" => Ok(Shutdown::try_from(value)?.into()),
+                b"save" => Ok(Save::try_from(value)?.into()),
+                b"bgsave" => Ok(BgSave::try_from(value)?.into()),
+                b"lastsave" => Ok(LastSave::try_from(value)?.into()),
+                b"monitor" => Ok(Monitor::try_from(value)?.into()),
+                b"time" => Ok(Time::try_from(value)?.into()),
+                b"lolwut" => Ok(Lolwut::try_from(value)?.into()),
+                b"wait" => Ok(Wait::try_from(value)?.into()),
+                b"reset" => Ok(Reset::try_from(value)?.into()),
+                b"select" => Ok(Select::try_from(value)?.into()),
+                b"move" => Ok(Move::try_from(value)?.into()),
+                b"swapdb" => Ok(SwapDb::try_from(value)?.into()),
+                b"auth" => Ok(Auth::try_from(value)?.into()),
+                b"hello" => Ok(Hello::try_from(value)?.into()),
+                b"quit" => Ok(Quit::try_from(value)?.into()),
+                b"acl" => Ok(Acl::try_from(value)?.into()),
+                b"subscribe" => Ok(Subscribe::try_from(value)?.into()),
+                b"unsubscribe" => Ok(Unsubscribe::try_from(value)?.into()),
+                b"publish" => Ok(Publish::try_from(value)?.into()),
+                b"psubscribe" => Ok(PSubscribe::try_from(value)?.into()),
+                b"punsubscribe" => Ok(PUnsubscribe::try_from(value)?.into()),
+                b"pubsub" => Ok(Pubsub::try_from(value)?.into()),
+                b"ssubscribe" => Ok(SSubscribe::try_from(value)?.into()),
+                b"sunsubscribe" => Ok(SUnsubscribe::try_from(value)?.into()),
+                b"spublish" => Ok(SPublish::try_from(value)?.into()),
+                b"ping" => Ok(Ping::try_from(value)?.into()),
+                b"multi" => Ok(Multi::try_from(value)?.into()),
+                b"exec" => Ok(Exec::try_from(value)?.into()),
+                b"discard" => Ok(Discard::try_from(value)?.into()),
                 _ => Ok(Unrecognized.into()),
             },
             _ => Err(CommandError::InvalidCommand(
@@ -113,6 +1744,65 @@ impl TryFrom<RespArray> for Command {
     }
 }
 
+/// Lowercase name of the command in `frame`, if it looks like one.
+pub fn command_name(frame: &RespFrame) -> Option<String> {
+    match frame {
+        RespFrame::Array(array) => match array.first() {
+            Some(RespFrame::BulkString(name)) => {
+                Some(String::from_utf8_lossy(name).to_ascii_lowercase())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Arguments of the command in `frame`, including its name.
+pub fn command_args(frame: &RespFrame) -> Vec<Vec<u8>> {
+    match frame {
+        RespFrame::Array(array) => array
+            .iter()
+            .filter_map(|arg| match arg {
+                RespFrame::BulkString(arg) => Some(arg.to_vec()),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Accounts for command `name` replying `frame` after running for `elapsed`, in the
+/// command stats and the latency monitor.
+pub fn record_execution(backend: &Backend, name: &str, frame: &RespFrame, elapsed: Duration) {
+    let failed = matches!(frame, RespFrame::Error(_));
+    backend.stats.record_command(name, elapsed, failed);
+    let event = if lookup_command(name).is_some_and(|spec| spec.flags.contains(&"fast")) {
+        LatencyEvent::FastCommand
+    } else {
+        LatencyEvent::Command
+    };
+    backend.record_latency(event, elapsed);
+}
+
+/// Remembers the keys read by the command in `args`, for a client tracking them to be
+/// told once they change. Only read-only commands count.
+pub fn track_reads(connection: &Connection, args: &[Vec<u8>]) {
+    let spec = args
+        .first()
+        .and_then(|name| lookup_command(&String::from_utf8_lossy(name)))
+        .filter(|spec| spec.flags.contains(&"readonly"));
+    let Some(spec) = spec else {
+        return;
+    };
+    let args = args
+        .iter()
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect::<Vec<_>>();
+    if let Some(positions) = spec.key_positions(&args) {
+        connection.track_reads(positions.into_iter().map(|i| args[i].clone()));
+    }
+}
+
 fn validate_command(
     value: &RespArray,
     names: &[&'static str],
@@ -126,6 +1816,27 @@ fn validate_command(
         )));
     }
 
+    validate_names(value, names)
+}
+
+// for commands taking optional or repeated arguments
+fn validate_variadic_command(
+    value: &RespArray,
+    names: &[&'static str],
+    min_args: usize,
+) -> Result<(), CommandError> {
+    if value.len() < min_args + names.len() {
+        return Err(CommandError::InvalidArgument(format!(
+            "{} command must have at least {} arguments",
+            names.join(" "),
+            min_args
+        )));
+    }
+
+    validate_names(value, names)
+}
+
+fn validate_names(value: &RespArray, names: &[&'static str]) -> Result<(), CommandError> {
     for (i, name) in names.iter().enumerate() {
         match value[i] {
             RespFrame::BulkString(ref cmd) => {
@@ -155,6 +1866,128 @@ fn extract_args(value: RespArray, start: usize) -> Result<Vec<RespFrame>, Comman
     Ok(value.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
 
+fn extract_string(frame: Option<RespFrame>) -> Result<String, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(s)) => Ok(String::from_utf8(s.0)?),
+        _ => Err(CommandError::InvalidArgument("syntax error".into())),
+    }
+}
+
+// binary-safe arguments such as set members
+fn extract_bytes(frame: Option<RespFrame>) -> Result<Vec<u8>, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(s)) => Ok(s.0),
+        _ => Err(CommandError::InvalidArgument("syntax error".into())),
+    }
+}
+
+fn extract_integer(frame: Option<RespFrame>) -> Result<i64, CommandError> {
+    extract_string(frame)?.parse().map_err(|_| {
+        CommandError::InvalidArgument("value is not an integer or out of range".into())
+    })
+}
+
+// database indexes, checked against the number of databases when the command runs
+fn extract_db_index(frame: Option<RespFrame>) -> Result<usize, CommandError> {
+    usize::try_from(extract_integer(frame)?)
+        .map_err(|_| CommandError::InvalidArgument("DB index is out of range".into()))
+}
+
+fn extract_cursor(frame: Option<RespFrame>) -> Result<u64, CommandError> {
+    extract_string(frame)?
+        .parse()
+        .map_err(|_| CommandError::InvalidArgument("invalid cursor".into()))
+}
+
+// blocking timeouts are given in seconds, 0 meaning forever
+fn extract_timeout(frame: Option<RespFrame>) -> Result<Option<Duration>, CommandError> {
+    let timeout = extract_string(frame)?
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| {
+            CommandError::InvalidArgument("timeout is not a float or out of range".into())
+        })?;
+    if timeout < 0.0 {
+        return Err(CommandError::InvalidArgument("timeout is negative".into()));
+    }
+    if timeout == 0.0 {
+        return Ok(None);
+    }
+    Duration::try_from_secs_f64(timeout)
+        .map(Some)
+        .map_err(|_| CommandError::InvalidArgument("timeout is out of range".into()))
+}
+
+// parses `key [key ...] timeout`
+fn parse_blocking_pop(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(Vec<String>, Option<Duration>), CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?;
+    let timeout = extract_timeout(args.pop())?;
+    let keys = args
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<_, _>>()?;
+    Ok((keys, timeout))
+}
+
+// parses `key [key ...]`
+fn parse_keys(value: RespArray, name: &'static str) -> Result<Vec<String>, CommandError> {
+    validate_variadic_command(&value, &[name], 1)?;
+
+    extract_args(value, 1)?
+        .into_iter()
+        .map(|arg| extract_string(Some(arg)))
+        .collect()
+}
+
+// handles the MATCH and COUNT options of the SCAN family, returning false for any other
+// option
+fn parse_scan_option(
+    option: &str,
+    args: &mut impl Iterator<Item = RespFrame>,
+    pattern: &mut Option<String>,
+    count: &mut usize,
+) -> Result<bool, CommandError> {
+    match option {
+        "MATCH" => *pattern = Some(extract_string(args.next())?),
+        "COUNT" => match extract_integer(args.next())? {
+            n if n >= 1 => *count = n as usize,
+            _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+        },
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+// MATCH filters a page after it was fetched, so pages may come back empty
+fn scan_matches(pattern: &Option<String>, name: &[u8]) -> bool {
+    pattern
+        .as_ref()
+        .is_none_or(|pattern| glob_match(pattern.as_bytes(), name, false))
+}
+
+// the SCAN family replies with the next cursor and the elements of the page
+fn scan_reply(cursor: u64, elements: Vec<RespFrame>) -> RespFrame {
+    RespArray::new([
+        BulkString::new(cursor.to_string()).into(),
+        RespArray::new(elements).into(),
+    ])
+    .into()
+}
+
+fn extract_float(frame: Option<RespFrame>) -> Result<f64, CommandError> {
+    extract_string(frame)?
+        .parse()
+        .ok()
+        .filter(|v: &f64| v.is_finite())
+        .ok_or_else(|| CommandError::InvalidArgument("value is not a valid float".into()))
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -0,0 +1,775 @@
+use super::{
+    extract_args, extract_bytes, extract_string, validate_command, validate_variadic_command,
+    CommandError, CommandExecutor, ConnectionCommand, PSubscribe, PUnsubscribe, Publish, Pubsub,
+    PubsubSubcommand, SPublish, SSubscribe, SUnsubscribe, Subscribe, Unsubscribe,
+};
+
+use crate::{
+    Backend, BulkString, Connection, RespArray, RespFrame, RespNull, RespPush, SimpleError,
+};
+
+impl CommandExecutor for Subscribe {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR SUBSCRIBE needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Subscribe {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        subscribe_each(
+            connection,
+            "subscribe",
+            &self.channels,
+            Connection::subscribe,
+        )
+    }
+}
+
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["subscribe"], 1)?;
+
+        let channels = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(Subscribe { channels })
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR UNSUBSCRIBE needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Unsubscribe {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        let channels = if self.channels.is_empty() {
+            connection.channels()
+        } else {
+            self.channels.clone()
+        };
+        subscribe_each(
+            connection,
+            "unsubscribe",
+            &channels,
+            Connection::unsubscribe,
+        )
+    }
+}
+
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["unsubscribe"], 0)?;
+
+        let channels = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(Unsubscribe { channels })
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR PSUBSCRIBE needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for PSubscribe {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        subscribe_each(
+            connection,
+            "psubscribe",
+            &self.patterns,
+            Connection::psubscribe,
+        )
+    }
+}
+
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["psubscribe"], 1)?;
+
+        let patterns = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(PSubscribe { patterns })
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR PUNSUBSCRIBE needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for PUnsubscribe {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        let patterns = if self.patterns.is_empty() {
+            connection.patterns()
+        } else {
+            self.patterns.clone()
+        };
+        subscribe_each(
+            connection,
+            "punsubscribe",
+            &patterns,
+            Connection::punsubscribe,
+        )
+    }
+}
+
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["punsubscribe"], 0)?;
+
+        let patterns = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(PUnsubscribe { patterns })
+    }
+}
+
+impl CommandExecutor for Publish {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.publish(&self.channel, &self.message) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["publish"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(Publish {
+            channel: extract_string(args.next())?,
+            message: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl CommandExecutor for SSubscribe {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR SSUBSCRIBE needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for SSubscribe {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        subscribe_each(
+            connection,
+            "ssubscribe",
+            &self.channels,
+            Connection::ssubscribe,
+        )
+    }
+}
+
+impl TryFrom<RespArray> for SSubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["ssubscribe"], 1)?;
+
+        let channels = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(SSubscribe { channels })
+    }
+}
+
+impl CommandExecutor for SUnsubscribe {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR SUNSUBSCRIBE needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for SUnsubscribe {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        let channels = if self.channels.is_empty() {
+            connection.shard_channels()
+        } else {
+            self.channels.clone()
+        };
+        // shard channels are counted apart from channels and patterns
+        if channels.is_empty() {
+            return confirmation("sunsubscribe", None, 0);
+        }
+        subscribe_each(
+            connection,
+            "sunsubscribe",
+            &channels,
+            Connection::sunsubscribe,
+        )
+    }
+}
+
+impl TryFrom<RespArray> for SUnsubscribe {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["sunsubscribe"], 0)?;
+
+        let channels = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(SUnsubscribe { channels })
+    }
+}
+
+impl CommandExecutor for SPublish {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.spublish(&self.channel, &self.message) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for SPublish {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["spublish"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SPublish {
+            channel: extract_string(args.next())?,
+            message: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl CommandExecutor for Pubsub {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            PubsubSubcommand::Channels(pattern) => {
+                list_channels(backend.pubsub_channels(pattern.as_deref()))
+            }
+            PubsubSubcommand::NumSub(channels) => {
+                count_subscribers(channels, |channel| backend.pubsub_numsub(channel))
+            }
+            PubsubSubcommand::ShardChannels(pattern) => {
+                list_channels(backend.pubsub_shard_channels(pattern.as_deref()))
+            }
+            PubsubSubcommand::ShardNumSub(channels) => {
+                count_subscribers(channels, |channel| backend.pubsub_shard_numsub(channel))
+            }
+            PubsubSubcommand::NumPat => RespFrame::Integer(backend.pubsub_numpat() as i64),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Pubsub {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["pubsub"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        let arity_error = || {
+            CommandError::InvalidArgument(format!(
+                "wrong number of arguments for 'pubsub|{}' command",
+                name.to_ascii_lowercase()
+            ))
+        };
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "CHANNELS" if args.len() <= 1 => PubsubSubcommand::Channels(
+                args.next()
+                    .map(|arg| extract_string(Some(arg)))
+                    .transpose()?,
+            ),
+            "NUMSUB" => PubsubSubcommand::NumSub(
+                args.map(|arg| extract_string(Some(arg)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "NUMPAT" if args.len() == 0 => PubsubSubcommand::NumPat,
+            "SHARDCHANNELS" if args.len() <= 1 => PubsubSubcommand::ShardChannels(
+                args.next()
+                    .map(|arg| extract_string(Some(arg)))
+                    .transpose()?,
+            ),
+            "SHARDNUMSUB" => PubsubSubcommand::ShardNumSub(
+                args.map(|arg| extract_string(Some(arg)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "CHANNELS" | "NUMPAT" | "SHARDCHANNELS" => return Err(arity_error()),
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try PUBSUB HELP.",
+                    name
+                )))
+            }
+        };
+        Ok(Pubsub { subcommand })
+    }
+}
+
+// the reply to PUBSUB (SHARD)CHANNELS
+fn list_channels(channels: Vec<String>) -> RespFrame {
+    let channels = channels
+        .into_iter()
+        .map(|channel| BulkString::new(channel).into())
+        .collect::<Vec<_>>();
+    RespArray::new(channels).into()
+}
+
+// the reply to PUBSUB (SHARD)NUMSUB, each channel followed by its number of subscribers
+fn count_subscribers(channels: Vec<String>, count: impl Fn(&str) -> usize) -> RespFrame {
+    let counts = channels
+        .into_iter()
+        .flat_map(|channel| {
+            let subscribers = count(&channel) as i64;
+            [
+                BulkString::new(channel).into(),
+                RespFrame::Integer(subscribers),
+            ]
+        })
+        .collect::<Vec<_>>();
+    RespArray::new(counts).into()
+}
+
+// subscribes or unsubscribes the client with `op` for each of `names`, replying about each.
+// Like redis, a client unsubscribing from everything when subscribed to nothing is still
+// told so
+fn subscribe_each(
+    connection: &mut Connection,
+    kind: &str,
+    names: &[String],
+    op: fn(&mut Connection, &str) -> usize,
+) -> RespFrame {
+    if names.is_empty() {
+        return confirmation(kind, None, connection.subscription_count());
+    }
+    let mut replies = names
+        .iter()
+        .map(|name| {
+            let count = op(connection, name);
+            confirmation(kind, Some(name), count)
+        })
+        .collect::<Vec<_>>();
+    // every reply but the last goes ahead of the reply to the command
+    let last = replies.pop();
+    for reply in replies {
+        connection.queue_reply(reply);
+    }
+    // `names` isn't empty
+    last.unwrap_or_else(|| confirmation(kind, None, 0))
+}
+
+// the reply to (un)subscribing from `channel`, with the number of subscriptions left. A push
+// like the messages, so that RESP3 clients tell them apart from replies to other commands
+fn confirmation(kind: &str, channel: Option<&str>, count: usize) -> RespFrame {
+    let channel = match channel {
+        Some(channel) => BulkString::new(channel).into(),
+        None => RespNull.into(),
+    };
+    RespPush::new(vec![
+        BulkString::new(kind).into(),
+        channel,
+        RespFrame::Integer(count as i64),
+    ])
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{ListEnd, RespDecode};
+
+    use super::*;
+
+    #[test]
+    fn test_subscribe_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n$6\r\nsports\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Subscribe = frame.try_into()?;
+        assert_eq!(cmd.channels, vec!["news", "sports"]);
+
+        buf.extend_from_slice(b"*1\r\n$9\r\nsubscribe\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Subscribe::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*1\r\n$11\r\nunsubscribe\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Unsubscribe = frame.try_into()?;
+        assert!(cmd.channels.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_publish_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Publish = frame.try_into()?;
+        assert_eq!(cmd.channel, "news");
+        assert_eq!(cmd.message, b"hello");
+
+        buf.extend_from_slice(b"*2\r\n$7\r\npublish\r\n$4\r\nnews\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Publish::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_publish() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut subscriber = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let subscribe = Subscribe {
+            channels: vec!["news".to_string(), "sports".to_string()],
+        };
+        assert_eq!(
+            subscribe.execute_on(&mut subscriber, &backend),
+            confirmation("subscribe", Some("sports"), 2)
+        );
+        assert_eq!(
+            subscriber.take_queued_replies(),
+            vec![confirmation("subscribe", Some("news"), 1)]
+        );
+
+        let publish = |channel: &str| Publish {
+            channel: channel.to_string(),
+            message: b"hello".to_vec(),
+        };
+        assert_eq!(publish("news").execute(&backend), RespFrame::Integer(1));
+        assert_eq!(publish("weather").execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            subscriber.next_push().await,
+            RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new("news").into(),
+                BulkString::new("hello").into(),
+            ])
+            .into()
+        );
+
+        let unsubscribe = Unsubscribe { channels: vec![] };
+        assert_eq!(
+            unsubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("unsubscribe", Some("sports"), 0)
+        );
+        assert_eq!(
+            subscriber.take_queued_replies(),
+            vec![confirmation("unsubscribe", Some("news"), 1)]
+        );
+        assert_eq!(publish("news").execute(&backend), RespFrame::Integer(0));
+        assert_eq!(
+            unsubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("unsubscribe", None, 0)
+        );
+
+        // subscriptions end with the connection
+        subscriber.subscribe("news");
+        drop(subscriber);
+        assert_eq!(publish("news").execute(&backend), RespFrame::Integer(0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_psubscribe_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$10\r\npsubscribe\r\n$6\r\nnews.*\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: PSubscribe = frame.try_into()?;
+        assert_eq!(cmd.patterns, vec!["news.*"]);
+
+        buf.extend_from_slice(b"*1\r\n$10\r\npsubscribe\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(PSubscribe::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psubscribe_and_publish() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut subscriber = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        subscriber.subscribe("news.tech");
+        let psubscribe = PSubscribe {
+            patterns: vec!["news.*".to_string()],
+        };
+        assert_eq!(
+            psubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("psubscribe", Some("news.*"), 2)
+        );
+
+        // a client subscribed to both the channel and a pattern matching it gets both
+        assert_eq!(backend.publish("news.tech", b"hello"), 2);
+        assert_eq!(
+            subscriber.next_push().await,
+            RespPush::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new("news.tech").into(),
+                BulkString::new("hello").into(),
+            ])
+            .into()
+        );
+        assert_eq!(
+            subscriber.next_push().await,
+            RespPush::new(vec![
+                BulkString::new("pmessage").into(),
+                BulkString::new("news.*").into(),
+                BulkString::new("news.tech").into(),
+                BulkString::new("hello").into(),
+            ])
+            .into()
+        );
+        assert_eq!(backend.publish("sports", b"hello"), 0);
+
+        let punsubscribe = PUnsubscribe { patterns: vec![] };
+        assert_eq!(
+            punsubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("punsubscribe", Some("news.*"), 1)
+        );
+        assert_eq!(
+            punsubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("punsubscribe", None, 1)
+        );
+        assert_eq!(backend.publish("news.sports", b"hello"), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubsub_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\npubsub\r\n$8\r\nchannels\r\n$6\r\nnews.*\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Pubsub = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            PubsubSubcommand::Channels(Some("news.*".to_string()))
+        );
+
+        buf.extend_from_slice(b"*2\r\n$6\r\npubsub\r\n$6\r\nNUMSUB\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Pubsub = frame.try_into()?;
+        assert_eq!(cmd.subcommand, PubsubSubcommand::NumSub(vec![]));
+
+        buf.extend_from_slice(b"*3\r\n$6\r\npubsub\r\n$6\r\nnumpat\r\n$1\r\nx\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Pubsub::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pubsub() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut first = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        let mut second = Connection::new(&backend, "127.0.0.1:50001".parse()?, laddr);
+        first.subscribe("news.tech");
+        first.subscribe("sports");
+        second.subscribe("news.tech");
+        first.psubscribe("news.*");
+        second.psubscribe("news.*");
+        second.psubscribe("weather.*");
+
+        let pubsub = |subcommand| Pubsub { subcommand }.execute(&backend);
+        assert_eq!(
+            pubsub(PubsubSubcommand::Channels(None)),
+            RespArray::new(vec![
+                BulkString::new("news.tech").into(),
+                BulkString::new("sports").into(),
+            ])
+            .into()
+        );
+        assert_eq!(
+            pubsub(PubsubSubcommand::Channels(Some("news.*".to_string()))),
+            RespArray::new(vec![BulkString::new("news.tech").into()]).into()
+        );
+        assert_eq!(
+            pubsub(PubsubSubcommand::NumSub(vec![
+                "news.tech".to_string(),
+                "weather".to_string(),
+            ])),
+            RespArray::new(vec![
+                BulkString::new("news.tech").into(),
+                RespFrame::Integer(2),
+                BulkString::new("weather").into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+        assert_eq!(pubsub(PubsubSubcommand::NumPat), RespFrame::Integer(2));
+
+        drop(second);
+        assert_eq!(pubsub(PubsubSubcommand::NumPat), RespFrame::Integer(1));
+        assert_eq!(backend.pubsub_numsub("news.tech"), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_keyspace_notifications() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut subscriber = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        subscriber.psubscribe("__key*__:*");
+        let pmessage = |channel: &str, message: &str| -> RespFrame {
+            RespPush::new(vec![
+                BulkString::new("pmessage").into(),
+                BulkString::new("__key*__:*").into(),
+                BulkString::new(channel).into(),
+                BulkString::new(message).into(),
+            ])
+            .into()
+        };
+
+        // nothing is published until notify-keyspace-events enables some classes
+        backend.set("ignored".to_string(), BulkString::new("value").into());
+        backend.config_set(&[("notify-keyspace-events".to_string(), "KEg$".to_string())])?;
+        backend.set("greeting".to_string(), BulkString::new("hello").into());
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyspace@0__:greeting", "set")
+        );
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyevent@0__:set", "greeting")
+        );
+
+        // list events are not enabled, the generic del of the emptied list is
+        let db = backend.select(3)?;
+        db.push(
+            "list".to_string(),
+            ListEnd::Left,
+            vec![BulkString::new("a").into()],
+        )?;
+        db.pop("list", ListEnd::Left, 1)?;
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyspace@3__:list", "del")
+        );
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyevent@3__:del", "list")
+        );
+        // events follow a database moved by SWAPDB
+        backend.swap_dbs(0, 3)?;
+        db.push(
+            "list".to_string(),
+            ListEnd::Left,
+            vec![BulkString::new("a").into()],
+        )?;
+        db.pop("list", ListEnd::Left, 1)?;
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyspace@0__:list", "del")
+        );
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyevent@0__:del", "list")
+        );
+        backend.swap_dbs(0, 3)?;
+
+        // a key removed once its TTL elapsed is expired rather than deleted
+        backend.config_set(&[("notify-keyspace-events".to_string(), "Ex".to_string())])?;
+        backend.set_with(
+            "ignored".to_string(),
+            BulkString::new("value").into(),
+            None,
+            Some(1),
+            false,
+        );
+        assert_eq!(backend.get("ignored"), None);
+        assert_eq!(
+            subscriber.next_push().await,
+            pmessage("__keyevent@0__:expired", "ignored")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_ssubscribe_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$10\r\nssubscribe\r\n$5\r\norder\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SSubscribe = frame.try_into()?;
+        assert_eq!(cmd.channels, vec!["order"]);
+
+        buf.extend_from_slice(b"*2\r\n$8\r\nspublish\r\n$5\r\norder\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(SPublish::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sharded_pubsub() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut subscriber = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        subscriber.subscribe("news");
+        let ssubscribe = SSubscribe {
+            channels: vec!["order".to_string(), "news".to_string()],
+        };
+        // shard channels are counted apart from channels
+        assert_eq!(
+            ssubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("ssubscribe", Some("news"), 2)
+        );
+        assert_eq!(
+            subscriber.take_queued_replies(),
+            vec![confirmation("ssubscribe", Some("order"), 1)]
+        );
+
+        let spublish = |channel: &str| SPublish {
+            channel: channel.to_string(),
+            message: b"hello".to_vec(),
+        };
+        assert_eq!(spublish("order").execute(&backend), RespFrame::Integer(1));
+        assert_eq!(
+            subscriber.next_push().await,
+            RespPush::new(vec![
+                BulkString::new("smessage").into(),
+                BulkString::new("order").into(),
+                BulkString::new("hello").into(),
+            ])
+            .into()
+        );
+        // the channel and the shard channel of the same name are unrelated
+        assert_eq!(backend.publish("order", b"hello"), 0);
+        assert_eq!(
+            Pubsub {
+                subcommand: PubsubSubcommand::ShardChannels(None),
+            }
+            .execute(&backend),
+            RespArray::new(vec![
+                BulkString::new("news").into(),
+                BulkString::new("order").into(),
+            ])
+            .into()
+        );
+
+        let sunsubscribe = SUnsubscribe { channels: vec![] };
+        assert_eq!(
+            sunsubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("sunsubscribe", Some("order"), 0)
+        );
+        subscriber.take_queued_replies();
+        assert_eq!(
+            sunsubscribe.execute_on(&mut subscriber, &backend),
+            confirmation("sunsubscribe", None, 0)
+        );
+        assert_eq!(backend.pubsub_shard_numsub("order"), 0);
+        assert_eq!(backend.pubsub_numsub("news"), 1);
+        Ok(())
+    }
+}
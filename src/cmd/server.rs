@@ -0,0 +1,1850 @@
+use super::{
+    extract_args, extract_db_index, extract_integer, extract_string, lookup_command,
+    validate_command, validate_variadic_command, Acl, AclSubcommand, BgSave, CommandError,
+    CommandExecutor, CommandSpec, Commands, CommandsSubcommand, Config, ConfigSubcommand, Debug,
+    DebugSubcommand, Info, LastSave, Latency, LatencySubcommand, Memory, MemorySubcommand, Save,
+    Shutdown, SwapDb, Time, COMMAND_TABLE, RESP_OK,
+};
+
+use crate::{
+    now_ms, now_us, resident_memory, Backend, BackendError, BgSaveStatus, BulkString, LatencyEvent,
+    RespArray, RespFrame, RespMap, RespNull, SimpleError, SimpleString, DEFAULT_MEMORY_SAMPLES,
+};
+use std::fmt::Write;
+use std::time::Duration;
+use tracing::warn;
+
+// version of redis whose INFO fields clients can expect
+pub(super) const REDIS_VERSION: &str = "7.4.0";
+
+// sections reported when no section, "default" or "all" is asked for
+const DEFAULT_SECTIONS: &[&str] = &[
+    "server",
+    "clients",
+    "memory",
+    "persistence",
+    "stats",
+    "replication",
+    "keyspace",
+];
+
+impl CommandExecutor for Info {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let everything = self
+            .sections
+            .iter()
+            .any(|s| matches!(s.as_str(), "all" | "everything"));
+        let default =
+            everything || self.sections.is_empty() || self.sections.iter().any(|s| s == "default");
+        let wanted = |section: &str| {
+            (default && DEFAULT_SECTIONS.contains(&section))
+                || (everything && section == "commandstats")
+                || self.sections.iter().any(|s| s == section)
+        };
+
+        let sections = [
+            ("server", server_section as fn(&Backend) -> String),
+            ("clients", clients_section),
+            ("memory", memory_section),
+            ("persistence", persistence_section),
+            ("stats", stats_section),
+            ("replication", replication_section),
+            ("commandstats", commandstats_section),
+            ("keyspace", keyspace_section),
+        ];
+        let info = sections
+            .iter()
+            .filter(|(name, _)| wanted(name))
+            .map(|(_, render)| render(backend))
+            .filter(|section| !section.is_empty())
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        BulkString::new(info).into()
+    }
+}
+
+// writing to a String never fails, so the results of write! below are ignored
+
+fn server_section(backend: &Backend) -> String {
+    let stats = backend.server_stats();
+    let (hz, _) = backend.active_expire_config();
+    let uptime = stats.uptime.as_secs();
+    let mut info = String::from("# Server\r\n");
+    let _ = write!(
+        info,
+        "redis_version:{}\r\n\
+         redis_mode:standalone\r\n\
+         process_id:{}\r\n\
+         run_id:{}\r\n\
+         uptime_in_seconds:{}\r\n\
+         uptime_in_days:{}\r\n\
+         hz:{}\r\n",
+        REDIS_VERSION,
+        std::process::id(),
+        stats.run_id,
+        uptime,
+        uptime / 86400,
+        hz,
+    );
+    info
+}
+
+fn clients_section(backend: &Backend) -> String {
+    let stats = backend.server_stats();
+    let mut info = String::from("# Clients\r\n");
+    let _ = write!(
+        info,
+        "connected_clients:{}\r\n\
+         blocked_clients:{}\r\n",
+        stats.connected_clients, stats.blocked_clients,
+    );
+    info
+}
+
+fn memory_section(backend: &Backend) -> String {
+    let mut info = String::from("# Memory\r\n");
+    let stats = backend.memory_stats();
+    let _ = write!(
+        info,
+        "used_memory:{}\r\n\
+         used_memory_peak:{}\r\n\
+         used_memory_startup:{}\r\n\
+         used_memory_dataset:{}\r\n",
+        stats.total_allocated,
+        stats.peak_allocated,
+        stats.startup_allocated,
+        stats.dataset(),
+    );
+    if let Some(rss) = resident_memory() {
+        let _ = write!(info, "used_memory_rss:{}\r\n", rss);
+    }
+    let _ = write!(
+        info,
+        "maxmemory:{}\r\n\
+         maxmemory_policy:{}\r\n",
+        backend.maxmemory(),
+        backend.maxmemory_policy(),
+    );
+    info
+}
+
+fn persistence_section(backend: &Backend) -> String {
+    let persistence = backend.persistence_stats();
+    format!(
+        "# Persistence\r\n\
+         loading:0\r\n\
+         rdb_changes_since_last_save:{}\r\n\
+         rdb_bgsave_in_progress:{}\r\n\
+         rdb_last_save_time:{}\r\n\
+         rdb_last_bgsave_status:{}\r\n\
+         aof_enabled:0\r\n",
+        persistence.changes_since_last_save,
+        persistence.bgsave_in_progress as u8,
+        persistence.last_save,
+        if persistence.last_bgsave_ok {
+            "ok"
+        } else {
+            "err"
+        },
+    )
+}
+
+fn stats_section(backend: &Backend) -> String {
+    let server = backend.server_stats();
+    let expire = backend.expire_stats();
+    let keyspace = backend.keyspace_stats();
+    let mut info = String::from("# Stats\r\n");
+    let _ = write!(
+        info,
+        "total_connections_received:{}\r\n\
+         total_commands_processed:{}\r\n\
+         expired_keys:{}\r\n\
+         expired_stale_perc:{:.2}\r\n\
+         expired_time_cap_reached_count:{}\r\n\
+         expire_cycle_cpu_milliseconds:{}\r\n\
+         keyspace_hits:{}\r\n\
+         keyspace_misses:{}\r\n\
+         pubsub_channels:{}\r\n\
+         pubsub_patterns:{}\r\n",
+        server.total_connections_received,
+        server.total_commands_processed,
+        expire.expired_keys,
+        expire.expired_stale_perc * 100.0,
+        expire.expired_time_cap_reached_count,
+        expire.expire_cycle_cpu_milliseconds,
+        keyspace.keyspace_hits,
+        keyspace.keyspace_misses,
+        backend.pubsub_channels(None).len(),
+        backend.pubsub_numpat(),
+    );
+    info
+}
+
+fn replication_section(backend: &Backend) -> String {
+    let stats = backend.server_stats();
+    let mut info = String::from("# Replication\r\n");
+    let _ = write!(
+        info,
+        "role:master\r\n\
+         connected_slaves:{}\r\n\
+         master_replid:{}\r\n\
+         master_repl_offset:{}\r\n",
+        backend.connected_replicas(),
+        stats.run_id,
+        backend.repl_offset(),
+    );
+    info
+}
+
+fn commandstats_section(backend: &Backend) -> String {
+    let mut info = String::from("# Commandstats\r\n");
+    for (name, stats) in backend.command_stats() {
+        let per_call = if stats.calls == 0 {
+            0.0
+        } else {
+            stats.usec as f64 / stats.calls as f64
+        };
+        let _ = write!(
+            info,
+            "cmdstat_{}:calls={},usec={},usec_per_call={:.2},rejected_calls={},failed_calls={}\r\n",
+            name, stats.calls, stats.usec, per_call, stats.rejected_calls, stats.failed_calls,
+        );
+    }
+    info
+}
+
+fn keyspace_section(backend: &Backend) -> String {
+    let mut info = String::from("# Keyspace\r\n");
+    for index in 0..backend.databases() {
+        let Ok(db) = backend.select(index) else {
+            continue;
+        };
+        let (keys, expires) = db.keyspace_size();
+        if keys > 0 {
+            let _ = write!(
+                info,
+                "db{}:keys={},expires={},avg_ttl=0\r\n",
+                index, keys, expires
+            );
+        }
+    }
+    info
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["info"], 0)?;
+
+        let sections = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| Ok(extract_string(Some(arg))?.to_ascii_lowercase()))
+            .collect::<Result<_, CommandError>>()?;
+        Ok(Info { sections })
+    }
+}
+
+impl CommandExecutor for Config {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            ConfigSubcommand::Get(patterns) => {
+                let reply = backend
+                    .config_get(&patterns)
+                    .into_iter()
+                    .flat_map(|(name, value)| {
+                        [BulkString::new(name).into(), BulkString::new(value).into()]
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(reply).into()
+            }
+            ConfigSubcommand::Set(pairs) => match backend.config_set(&pairs) {
+                Ok(()) => RESP_OK.clone(),
+                Err(e) => e.into(),
+            },
+            ConfigSubcommand::Rewrite => match backend.config_rewrite() {
+                Ok(()) => RESP_OK.clone(),
+                Err(e) => e.into(),
+            },
+            ConfigSubcommand::ResetStat => {
+                backend.reset_stats();
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Config {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["config"], 1)?;
+
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (name, args) = args.split_first().expect("validated at least one argument");
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "GET" if !args.is_empty() => ConfigSubcommand::Get(args.to_vec()),
+            "SET" if !args.is_empty() && args.len() % 2 == 0 => ConfigSubcommand::Set(
+                args.chunks(2)
+                    .map(|pair| (pair[0].clone(), pair[1].clone()))
+                    .collect(),
+            ),
+            "REWRITE" if args.is_empty() => ConfigSubcommand::Rewrite,
+            "RESETSTAT" if args.is_empty() => ConfigSubcommand::ResetStat,
+            "GET" | "SET" | "REWRITE" | "RESETSTAT" => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "wrong number of arguments for 'config|{}' command",
+                    name.to_ascii_lowercase()
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try CONFIG HELP.",
+                    name
+                )))
+            }
+        };
+        Ok(Config { subcommand })
+    }
+}
+
+impl CommandExecutor for Commands {
+    fn execute(self, _: &Backend) -> RespFrame {
+        match self.subcommand {
+            CommandsSubcommand::Count => RespFrame::Integer(COMMAND_TABLE.len() as i64),
+            CommandsSubcommand::Info(names) if !names.is_empty() => {
+                let reply = names
+                    .iter()
+                    .map(|name| match lookup_command(name) {
+                        Some(spec) => command_info(spec),
+                        None => RespFrame::Null(RespNull),
+                    })
+                    .collect::<Vec<_>>();
+                RespArray::new(reply).into()
+            }
+            CommandsSubcommand::All | CommandsSubcommand::Info(_) => {
+                RespArray::new(COMMAND_TABLE.iter().map(command_info).collect::<Vec<_>>()).into()
+            }
+            CommandsSubcommand::GetKeys(args) => match command_keys(&args) {
+                Ok(keys) => RespArray::new(
+                    keys.into_iter()
+                        .map(|key| BulkString::new(key).into())
+                        .collect::<Vec<RespFrame>>(),
+                )
+                .into(),
+                Err(e) => SimpleError::new(format!("ERR {}", e)).into(),
+            },
+            CommandsSubcommand::Docs(names) => {
+                let specs: Vec<&CommandSpec> = if names.is_empty() {
+                    COMMAND_TABLE.iter().collect()
+                } else {
+                    // unknown commands are left out
+                    names
+                        .iter()
+                        .filter_map(|name| lookup_command(name))
+                        .collect()
+                };
+                let mut docs = RespMap::new();
+                for spec in specs {
+                    let mut doc = RespMap::new();
+                    doc.insert("summary".to_string(), BulkString::new(spec.summary).into());
+                    doc.insert("since".to_string(), BulkString::new(spec.since).into());
+                    doc.insert("group".to_string(), BulkString::new(spec.group).into());
+                    docs.insert(spec.name.to_string(), RespFrame::Map(doc));
+                }
+                RespFrame::Map(docs)
+            }
+        }
+    }
+}
+
+// keys that `args` would touch if executed, according to the key specs of the command
+fn command_keys(args: &[String]) -> Result<Vec<String>, &'static str> {
+    let spec = lookup_command(&args[0]).ok_or("Invalid command specified")?;
+    if !spec.accepts(args.len()) {
+        return Err("Invalid number of arguments specified for command");
+    }
+    if spec.keys.is_empty() {
+        return Err("The command has no key arguments");
+    }
+    let positions = spec
+        .key_positions(args)
+        .ok_or("Invalid arguments specified for command")?;
+    Ok(positions.into_iter().map(|i| args[i].clone()).collect())
+}
+
+// name, arity, flags, first key, last key, key step, ACL categories, tips, key specs and
+// subcommands, like redis replies to COMMAND INFO
+fn command_info(spec: &CommandSpec) -> RespFrame {
+    let status = |values: Vec<&str>| -> RespFrame {
+        RespArray::new(
+            values
+                .into_iter()
+                .map(|value| SimpleString::new(value).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    };
+    let (first, last, step) = spec.key_range();
+    RespArray::new(vec![
+        BulkString::new(spec.name).into(),
+        RespFrame::Integer(spec.arity),
+        status(spec.all_flags()),
+        RespFrame::Integer(first),
+        RespFrame::Integer(last),
+        RespFrame::Integer(step),
+        status(spec.acl_categories()),
+        RespArray::new([]).into(),
+        RespArray::new([]).into(),
+        RespArray::new([]).into(),
+    ])
+    .into()
+}
+
+impl TryFrom<RespArray> for Commands {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["command"], 0)?;
+
+        let args = extract_args(value, 1)?
+            .into_iter()
+            .map(|arg| extract_string(Some(arg)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let Some((name, args)) = args.split_first() else {
+            return Ok(Commands {
+                subcommand: CommandsSubcommand::All,
+            });
+        };
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "COUNT" if args.is_empty() => CommandsSubcommand::Count,
+            "INFO" => CommandsSubcommand::Info(args.to_vec()),
+            "DOCS" => CommandsSubcommand::Docs(args.to_vec()),
+            "GETKEYS" if !args.is_empty() => CommandsSubcommand::GetKeys(args.to_vec()),
+            "COUNT" | "GETKEYS" => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "wrong number of arguments for 'command|{}' command",
+                    name.to_ascii_lowercase()
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try COMMAND HELP.",
+                    name
+                )))
+            }
+        };
+        Ok(Commands { subcommand })
+    }
+}
+
+impl CommandExecutor for Debug {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            // only reached from EXEC, where like in redis the sleep holds every other
+            // client back. Sent on its own, the handler sleeps without parking a thread
+            DebugSubcommand::Sleep(duration) => {
+                std::thread::sleep(duration);
+                RESP_OK.clone()
+            }
+            DebugSubcommand::Object(key) => match backend.debug_object(&key) {
+                Some(details) => SimpleString::new(details).into(),
+                None => BackendError::NoSuchKey.into(),
+            },
+            DebugSubcommand::SetActiveExpire(enabled) => {
+                backend.set_active_expire(enabled);
+                RESP_OK.clone()
+            }
+            // CountingAllocator only keeps totals, there is no heap map to dump. Kept so
+            // that scripts calling it work
+            DebugSubcommand::Jmap => RESP_OK.clone(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Debug {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["debug"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "SLEEP" => {
+                let seconds = extract_string(args.next())?
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|s| s.is_finite() && *s >= 0.0)
+                    .ok_or_else(|| {
+                        CommandError::InvalidArgument("value is not a valid float".into())
+                    })?;
+                let duration = Duration::try_from_secs_f64(seconds)
+                    .map_err(|_| CommandError::InvalidArgument("value is out of range".into()))?;
+                DebugSubcommand::Sleep(duration)
+            }
+            "OBJECT" => DebugSubcommand::Object(extract_string(args.next())?),
+            "SET-ACTIVE-EXPIRE" => match extract_string(args.next())?.as_str() {
+                "0" => DebugSubcommand::SetActiveExpire(false),
+                "1" => DebugSubcommand::SetActiveExpire(true),
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            },
+            "JMAP" => DebugSubcommand::Jmap,
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try DEBUG HELP.",
+                    name
+                )))
+            }
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument(format!(
+                "wrong number of arguments for 'debug|{}' command",
+                name.to_ascii_lowercase()
+            )));
+        }
+        Ok(Debug { subcommand })
+    }
+}
+
+impl CommandExecutor for Memory {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            MemorySubcommand::Usage { key, samples } => match backend.memory_usage(&key, samples) {
+                Some(bytes) => RespFrame::Integer(bytes as i64),
+                None => RespFrame::Null(RespNull),
+            },
+            MemorySubcommand::Stats => memory_stats(backend),
+            MemorySubcommand::Doctor => BulkString::new(backend.memory_doctor()).into(),
+        }
+    }
+}
+
+fn memory_stats(backend: &Backend) -> RespFrame {
+    let stats = backend.memory_stats();
+    let dataset = stats.dataset();
+    let integer = |bytes: usize| RespFrame::Integer(bytes as i64);
+    let percentage = |part: usize, total: usize| match total {
+        0 => RespFrame::Double(0.0),
+        total => RespFrame::Double(part as f64 * 100.0 / total as f64),
+    };
+
+    let mut reply = RespMap::new();
+    reply.insert("peak.allocated".to_string(), integer(stats.peak_allocated));
+    reply.insert(
+        "total.allocated".to_string(),
+        integer(stats.total_allocated),
+    );
+    reply.insert(
+        "startup.allocated".to_string(),
+        integer(stats.startup_allocated),
+    );
+    reply.insert("clients.normal".to_string(), integer(stats.clients));
+    reply.insert(
+        "overhead.keyspace".to_string(),
+        integer(stats.keyspace_overhead),
+    );
+    reply.insert("overhead.total".to_string(), integer(stats.overhead()));
+    reply.insert("keys.count".to_string(), integer(stats.keys));
+    reply.insert(
+        "keys.bytes-per-key".to_string(),
+        integer(dataset.checked_div(stats.keys).unwrap_or(0)),
+    );
+    reply.insert("dataset.bytes".to_string(), integer(dataset));
+    for (key_type, bytes) in &stats.dataset_by_type {
+        reply.insert(format!("dataset.{}", key_type.name()), integer(*bytes));
+    }
+    reply.insert(
+        "dataset.percentage".to_string(),
+        percentage(dataset, stats.total_allocated - stats.startup_allocated),
+    );
+    reply.insert(
+        "peak.percentage".to_string(),
+        percentage(stats.total_allocated, stats.peak_allocated),
+    );
+    reply.insert(
+        "allocator.accounted".to_string(),
+        RespFrame::Boolean(stats.accounted),
+    );
+    reply.into()
+}
+
+impl TryFrom<RespArray> for Memory {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["memory"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "USAGE" => {
+                let key = extract_string(args.next())?;
+                let mut samples = DEFAULT_MEMORY_SAMPLES;
+                while let Some(option) = args.next() {
+                    let option = extract_string(Some(option))?;
+                    if !option.eq_ignore_ascii_case("samples") {
+                        return Err(CommandError::InvalidArgument("syntax error".into()));
+                    }
+                    samples = extract_integer(args.next())?.try_into().map_err(|_| {
+                        CommandError::InvalidArgument(
+                            "value is not an integer or out of range".into(),
+                        )
+                    })?;
+                }
+                MemorySubcommand::Usage { key, samples }
+            }
+            "STATS" => MemorySubcommand::Stats,
+            "DOCTOR" => MemorySubcommand::Doctor,
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try MEMORY HELP.",
+                    name
+                )))
+            }
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument(format!(
+                "wrong number of arguments for 'memory|{}' command",
+                name.to_ascii_lowercase()
+            )));
+        }
+        Ok(Memory { subcommand })
+    }
+}
+
+impl CommandExecutor for Latency {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            LatencySubcommand::Latest => {
+                let reports = backend.latency_latest().into_iter().map(|report| {
+                    RespArray::new(vec![
+                        BulkString::new(report.event.name()).into(),
+                        RespFrame::Integer(report.latest.time as i64),
+                        RespFrame::Integer(report.latest.latency as i64),
+                        RespFrame::Integer(report.max as i64),
+                    ])
+                    .into()
+                });
+                RespArray::new(reports.collect::<Vec<_>>()).into()
+            }
+            LatencySubcommand::History(name) => {
+                // like redis, an event that is unknown has no history rather than failing
+                let samples = LatencyEvent::from_name(&name)
+                    .map(|event| backend.latency_history(event))
+                    .unwrap_or_default();
+                let samples = samples.into_iter().map(|sample| {
+                    RespArray::new(vec![
+                        RespFrame::Integer(sample.time as i64),
+                        RespFrame::Integer(sample.latency as i64),
+                    ])
+                    .into()
+                });
+                RespArray::new(samples.collect::<Vec<_>>()).into()
+            }
+            LatencySubcommand::Reset(names) => {
+                let events = names
+                    .iter()
+                    .filter_map(|name| LatencyEvent::from_name(name))
+                    .collect::<Vec<_>>();
+                if !names.is_empty() && events.is_empty() {
+                    return RespFrame::Integer(0);
+                }
+                RespFrame::Integer(backend.latency_reset(&events) as i64)
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Latency {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["latency"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        let arity_error = || {
+            CommandError::InvalidArgument(format!(
+                "wrong number of arguments for 'latency|{}' command",
+                name.to_ascii_lowercase()
+            ))
+        };
+        let subcommand = match name.to_ascii_uppercase().as_str() {
+            "LATEST" if args.len() == 0 => LatencySubcommand::Latest,
+            "HISTORY" if args.len() == 1 => {
+                LatencySubcommand::History(extract_string(args.next())?)
+            }
+            "RESET" => LatencySubcommand::Reset(
+                args.map(|arg| extract_string(Some(arg)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            "LATEST" | "HISTORY" => return Err(arity_error()),
+            _ => {
+                return Err(CommandError::InvalidArgument(format!(
+                    "unknown subcommand '{}'. Try LATENCY HELP.",
+                    name
+                )))
+            }
+        };
+        Ok(Latency { subcommand })
+    }
+}
+
+impl CommandExecutor for Acl {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            AclSubcommand::Log(count) => {
+                let now = now_ms();
+                let entries = backend.acl_log(count).into_iter().map(|entry| {
+                    let age = now.saturating_sub(entry.created_at) as f64 / 1000.0;
+                    let mut map = RespMap::new();
+                    map.insert("count".to_string(), (entry.count as i64).into());
+                    map.insert(
+                        "reason".to_string(),
+                        BulkString::new(entry.reason.name()).into(),
+                    );
+                    // there are no MULTI blocks or scripts yet
+                    map.insert("context".to_string(), BulkString::new("toplevel").into());
+                    map.insert("object".to_string(), BulkString::new(entry.object).into());
+                    map.insert(
+                        "username".to_string(),
+                        BulkString::new(entry.username).into(),
+                    );
+                    map.insert("age-seconds".to_string(), age.into());
+                    map.insert(
+                        "client-info".to_string(),
+                        BulkString::new(entry.client_info).into(),
+                    );
+                    map.insert("entry-id".to_string(), (entry.entry_id as i64).into());
+                    map.insert(
+                        "timestamp-created".to_string(),
+                        (entry.created_at as i64).into(),
+                    );
+                    map.insert(
+                        "timestamp-last-updated".to_string(),
+                        (entry.updated_at as i64).into(),
+                    );
+                    map.into()
+                });
+                RespArray::new(entries.collect::<Vec<_>>()).into()
+            }
+            AclSubcommand::LogReset => {
+                backend.acl_log_reset();
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Acl {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["acl"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let name = extract_string(args.next())?;
+        if !name.eq_ignore_ascii_case("log") {
+            return Err(CommandError::InvalidArgument(format!(
+                "unknown subcommand '{}'. Try ACL HELP.",
+                name
+            )));
+        }
+        let subcommand = match args.next() {
+            // like redis, 10 entries unless asked for more
+            None => AclSubcommand::Log(10),
+            Some(RespFrame::BulkString(arg)) if arg.eq_ignore_ascii_case(b"reset") => {
+                AclSubcommand::LogReset
+            }
+            arg => {
+                let count = extract_integer(arg)?;
+                if count < 0 {
+                    return Err(CommandError::InvalidArgument(
+                        "value is out of range, must be positive".into(),
+                    ));
+                }
+                AclSubcommand::Log(count as usize)
+            }
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        Ok(Acl { subcommand })
+    }
+}
+
+impl CommandExecutor for Shutdown {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        // shutting down is immediate, so there is never one to abort
+        if self.abort {
+            return SimpleError::new("ERR No shutdown in progress.").into();
+        }
+        match backend.shutdown(self.save, self.force) {
+            // the client is disconnected rather than sent this
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => {
+                warn!("Error trying to save the DB, can't exit: {}", e);
+                BackendError::ShutdownFailed.into()
+            }
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Shutdown {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["shutdown"], 0)?;
+
+        let mut cmd = Shutdown {
+            save: None,
+            now: false,
+            force: false,
+            abort: false,
+        };
+        let syntax_error = || CommandError::InvalidArgument("syntax error".into());
+        for arg in extract_args(value, 1)? {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "SAVE" if cmd.save.is_none() => cmd.save = Some(true),
+                "NOSAVE" if cmd.save.is_none() => cmd.save = Some(false),
+                "NOW" => cmd.now = true,
+                "FORCE" => cmd.force = true,
+                "ABORT" => cmd.abort = true,
+                _ => return Err(syntax_error()),
+            }
+        }
+        if cmd.abort && (cmd.save.is_some() || cmd.now || cmd.force) {
+            return Err(syntax_error());
+        }
+        Ok(cmd)
+    }
+}
+
+impl CommandExecutor for Save {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.save_snapshot() {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for BgSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.bgsave(self.schedule) {
+            Ok(BgSaveStatus::Started) => SimpleString::new("Background saving started").into(),
+            Ok(BgSaveStatus::Scheduled) => SimpleString::new("Background saving scheduled").into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for LastSave {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persistence_stats().last_save as i64)
+    }
+}
+
+impl TryFrom<RespArray> for Save {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["save"], 0)?;
+        Ok(Save)
+    }
+}
+
+impl TryFrom<RespArray> for BgSave {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["bgsave"], 0)?;
+        let mut args = extract_args(value, 1)?.into_iter();
+        let syntax_error = || CommandError::InvalidArgument("syntax error".into());
+        let schedule = match args.next() {
+            None => false,
+            Some(arg) => {
+                if !extract_string(Some(arg))?.eq_ignore_ascii_case("schedule") {
+                    return Err(syntax_error());
+                }
+                true
+            }
+        };
+        if args.next().is_some() {
+            return Err(syntax_error());
+        }
+        Ok(BgSave { schedule })
+    }
+}
+
+impl TryFrom<RespArray> for LastSave {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["lastsave"], 0)?;
+        Ok(LastSave)
+    }
+}
+
+impl CommandExecutor for SwapDb {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.swap_dbs(self.first, self.second) {
+            Ok(()) => RESP_OK.clone(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SwapDb {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["swapdb"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SwapDb {
+            first: extract_db_index(args.next())?,
+            second: extract_db_index(args.next())?,
+        })
+    }
+}
+
+impl CommandExecutor for Time {
+    fn execute(self, _: &Backend) -> RespFrame {
+        let now = now_us();
+        RespArray::new(vec![
+            BulkString::new((now / 1_000_000).to_string()).into(),
+            BulkString::new((now % 1_000_000).to_string()).into(),
+        ])
+        .into()
+    }
+}
+
+impl TryFrom<RespArray> for Time {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["time"], 0)?;
+        Ok(Time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{cmd::Command, AclDenial, Connection, RespDecode};
+
+    use super::*;
+
+    #[test]
+    fn test_info_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\ninfo\r\n$5\r\nSTATS\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Info = frame.try_into()?;
+        assert_eq!(cmd.sections, vec!["stats".to_string()]);
+
+        buf.extend_from_slice(b"*1\r\n$4\r\ninfo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Info = frame.try_into()?;
+        assert!(cmd.sections.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_reports_active_expire() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..100 {
+            let key = format!("key:{}", i);
+            backend.set(key.clone(), RespFrame::Integer(i));
+            // half of the keys are already expired
+            let at = if i % 2 == 0 { 1 } else { u64::MAX };
+            backend.db.expire.insert(key, at);
+        }
+
+        // the cycle keeps sampling while many of the sampled keys are expired
+        let expired = backend.active_expire_cycle();
+        assert!(expired > 0);
+        assert_eq!(backend.expire_stats().expired_keys, expired as u64);
+
+        let cmd = Info {
+            sections: vec!["stats".to_string()],
+        };
+        let RespFrame::BulkString(info) = cmd.execute(&backend) else {
+            panic!("INFO replies with a bulk string");
+        };
+        let info = String::from_utf8(info.to_vec())?;
+        assert!(info.starts_with("# Stats\r\n"));
+        assert!(info.contains(&format!("expired_keys:{}\r\n", expired)));
+
+        backend
+            .select(2)?
+            .set("other".to_string(), RespFrame::Integer(1));
+        let cmd = Info {
+            sections: vec!["keyspace".to_string()],
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            BulkString::new(
+                "# Keyspace\r\ndb0:keys=50,expires=50,avg_ttl=0\r\n\
+                 db2:keys=1,expires=0,avg_ttl=0\r\n"
+            )
+            .into()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_info_sections() -> Result<()> {
+        let backend = Backend::new();
+        backend
+            .stats
+            .record_command("get", std::time::Duration::from_micros(30), false);
+        backend
+            .stats
+            .record_command("get", std::time::Duration::from_micros(10), true);
+        backend.stats.record_rejected_command("set");
+
+        let render = |sections: &[&str]| -> Result<String> {
+            let cmd = Info {
+                sections: sections.iter().map(|s| s.to_string()).collect(),
+            };
+            let RespFrame::BulkString(info) = cmd.execute(&backend) else {
+                panic!("INFO replies with a bulk string");
+            };
+            Ok(String::from_utf8(info.to_vec())?)
+        };
+
+        let info = render(&[])?;
+        for header in [
+            "# Server",
+            "# Clients",
+            "# Memory",
+            "# Stats",
+            "# Replication",
+        ] {
+            assert!(info.contains(header), "missing {}", header);
+        }
+        assert!(info.contains("total_commands_processed:2\r\n"));
+        // commandstats and an empty keyspace are left out by default
+        assert!(!info.contains("# Commandstats"));
+        assert!(info.contains("# Keyspace\r\n"));
+
+        let info = render(&["commandstats"])?;
+        assert_eq!(
+            info,
+            "# Commandstats\r\n\
+             cmdstat_get:calls=2,usec=40,usec_per_call=20.00,rejected_calls=0,failed_calls=1\r\n\
+             cmdstat_set:calls=0,usec=0,usec_per_call=0.00,rejected_calls=1,failed_calls=0\r\n"
+        );
+        assert!(render(&["everything"])?.contains("# Commandstats"));
+        assert_eq!(render(&["nosuchsection"])?, "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$6\r\nconfig\r\n$3\r\nget\r\n$2\r\nhz\r\n$1\r\n*\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Config = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            ConfigSubcommand::Get(vec!["hz".to_string(), "*".to_string()])
+        );
+
+        buf.extend_from_slice(b"*4\r\n$6\r\nconfig\r\n$3\r\nSET\r\n$2\r\nhz\r\n$2\r\n20\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Config = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            ConfigSubcommand::Set(vec![("hz".to_string(), "20".to_string())])
+        );
+
+        buf.extend_from_slice(b"*3\r\n$6\r\nconfig\r\n$3\r\nset\r\n$2\r\nhz\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Config::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_get_and_set() {
+        let backend = Backend::new();
+        let get = |pattern: &str| {
+            Config {
+                subcommand: ConfigSubcommand::Get(vec![pattern.to_string()]),
+            }
+            .execute(&backend)
+        };
+        let set = |pairs: &[(&str, &str)]| {
+            Config {
+                subcommand: ConfigSubcommand::Set(
+                    pairs
+                        .iter()
+                        .map(|(name, value)| (name.to_string(), value.to_string()))
+                        .collect(),
+                ),
+            }
+            .execute(&backend)
+        };
+        let pair = |name: &str, value: &str| -> Vec<RespFrame> {
+            vec![BulkString::new(name).into(), BulkString::new(value).into()]
+        };
+
+        assert_eq!(
+            get("MAXMEMORY*"),
+            RespArray::new(
+                [
+                    pair("maxmemory", "0"),
+                    pair("maxmemory-policy", "noeviction")
+                ]
+                .concat()
+            )
+            .into()
+        );
+
+        assert_eq!(
+            set(&[("maxmemory", "1mb"), ("timeout", "30")]),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            get("maxmemory"),
+            RespArray::new(pair("maxmemory", "1048576")).into()
+        );
+        assert_eq!(backend.maxmemory(), 1048576);
+        assert_eq!(
+            backend.client_timeout(),
+            Some(std::time::Duration::from_secs(30))
+        );
+
+        // out of range frequencies are clamped and applied to the active expire cycle
+        assert_eq!(set(&[("hz", "1000")]), RESP_OK.clone());
+        assert_eq!(get("hz"), RespArray::new(pair("hz", "500")).into());
+        assert_eq!(backend.active_expire_config().0, 500);
+
+        // event classes are listed in canonical order, folded into A when all are set
+        assert_eq!(
+            set(&[("notify-keyspace-events", "Kg$lshzxetdE")]),
+            RESP_OK.clone()
+        );
+        assert_eq!(
+            get("notify-keyspace-events"),
+            RespArray::new(pair("notify-keyspace-events", "AKE")).into()
+        );
+        assert_eq!(
+            set(&[("notify-keyspace-events", "Kq")]),
+            SimpleError::new(
+                "ERR CONFIG SET failed (possibly related to argument 'notify-keyspace-events') \
+                 - Invalid event class character. Use 'Ag$lshzxeKEtmd'."
+            )
+            .into()
+        );
+        // no event is published for new keys, so their class can't be enabled
+        assert_eq!(
+            set(&[("notify-keyspace-events", "KEn")]),
+            SimpleError::new(
+                "ERR CONFIG SET failed (possibly related to argument 'notify-keyspace-events') \
+                 - Invalid event class character. Use 'Ag$lshzxeKEtmd'."
+            )
+            .into()
+        );
+
+        // nothing is set when any of the parameters is invalid
+        assert_eq!(
+            set(&[("timeout", "0"), ("loglevel", "loud")]),
+            SimpleError::new(
+                "ERR CONFIG SET failed (possibly related to argument 'loglevel') - argument(s) \
+                 must be one of the following: debug, verbose, notice, warning, nothing"
+            )
+            .into()
+        );
+        assert_eq!(get("timeout"), RespArray::new(pair("timeout", "30")).into());
+        assert_eq!(
+            set(&[("port", "6380")]),
+            SimpleError::new(
+                "ERR CONFIG SET failed (possibly related to argument 'port') - can't set \
+                 immutable config"
+            )
+            .into()
+        );
+        assert_eq!(
+            set(&[("nosuchparameter", "1")]),
+            SimpleError::new(
+                "ERR Unknown option or number of arguments for CONFIG SET - 'nosuchparameter'"
+            )
+            .into()
+        );
+        assert_eq!(get("nosuch*"), RespArray::new([]).into());
+    }
+
+    #[test]
+    fn test_config_rewrite_and_resetstat() -> Result<()> {
+        let backend = Backend::new();
+        let execute = |subcommand| Config { subcommand }.execute(&backend);
+        assert_eq!(
+            execute(ConfigSubcommand::Rewrite),
+            SimpleError::new("ERR The server is running without a config file").into()
+        );
+
+        let path = std::env::temp_dir().join(format!("simple-redis-{}.conf", std::process::id()));
+        std::fs::write(
+            &path,
+            "# comment\nport 6380\nhz 20\nsave \"\"\ndatabases 4\n",
+        )?;
+        backend.load_config(&path)?;
+        assert_eq!(backend.port(), 6380);
+        assert_eq!(backend.databases(), 4);
+
+        let pairs = vec![
+            ("hz".to_string(), "30".to_string()),
+            ("timeout".to_string(), "60".to_string()),
+        ];
+        assert_eq!(execute(ConfigSubcommand::Set(pairs)), RESP_OK.clone());
+        assert_eq!(execute(ConfigSubcommand::Rewrite), RESP_OK.clone());
+        let content = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(
+            content,
+            "# comment\nport 6380\nhz 30\nsave \"\"\ndatabases 4\ntimeout 60\n"
+        );
+
+        backend
+            .stats
+            .record_command("get", std::time::Duration::from_micros(5), false);
+        assert_eq!(execute(ConfigSubcommand::ResetStat), RESP_OK.clone());
+        assert_eq!(backend.server_stats().total_commands_processed, 0);
+        assert!(backend.command_stats().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_commands_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$7\r\ncommand\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Commands = frame.try_into()?;
+        assert_eq!(cmd.subcommand, CommandsSubcommand::All);
+
+        buf.extend_from_slice(b"*3\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nGET\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Commands = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            CommandsSubcommand::Info(vec!["GET".to_string()])
+        );
+
+        buf.extend_from_slice(b"*3\r\n$7\r\ncommand\r\n$5\r\ncount\r\n$3\r\nget\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Commands::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_commands_describe_the_command_table() -> Result<()> {
+        let backend = Backend::new();
+        let execute = |subcommand| Commands { subcommand }.execute(&backend);
+        assert_eq!(
+            execute(CommandsSubcommand::Count),
+            RespFrame::Integer(COMMAND_TABLE.len() as i64)
+        );
+
+        let status = |values: &[&str]| -> RespFrame {
+            RespArray::new(
+                values
+                    .iter()
+                    .map(|v| SimpleString::new(v.to_string()).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+        let reply = execute(CommandsSubcommand::Info(vec![
+            "ZUNIONSTORE".to_string(),
+            "nosuchcommand".to_string(),
+        ]));
+        assert_eq!(
+            reply,
+            RespArray::new(vec![
+                RespArray::new(vec![
+                    BulkString::new("zunionstore").into(),
+                    RespFrame::Integer(-4),
+                    status(&["write", "denyoom", "movablekeys"]),
+                    RespFrame::Integer(1),
+                    RespFrame::Integer(1),
+                    RespFrame::Integer(1),
+                    status(&["@write", "@sortedset", "@slow"]),
+                    RespArray::new([]).into(),
+                    RespArray::new([]).into(),
+                    RespArray::new([]).into(),
+                ])
+                .into(),
+                RespFrame::Null(RespNull),
+            ])
+            .into()
+        );
+
+        let RespFrame::Map(docs) = execute(CommandsSubcommand::Docs(vec!["get".to_string()]))
+        else {
+            panic!("COMMAND DOCS replies with a map");
+        };
+        let RespFrame::Map(doc) = &docs["get"] else {
+            panic!("the docs of a command are a map");
+        };
+        assert_eq!(doc["group"], BulkString::new("string").into());
+
+        // every command in the table is dispatched, possibly failing on missing arguments
+        for spec in COMMAND_TABLE {
+            let frame = RespArray::new(vec![BulkString::new(spec.name).into()]);
+            let cmd = crate::cmd::Command::try_from(frame);
+            assert!(
+                !matches!(cmd, Ok(crate::cmd::Command::Unrecognized(_))),
+                "{} is not dispatched",
+                spec.name
+            );
+        }
+        assert!(COMMAND_TABLE.windows(2).all(|w| w[0].name < w[1].name));
+        Ok(())
+    }
+
+    #[test]
+    fn test_commands_getkeys() {
+        let backend = Backend::new();
+        let getkeys = |line: &str| {
+            let args = line.split(' ').map(String::from).collect();
+            Commands {
+                subcommand: CommandsSubcommand::GetKeys(args),
+            }
+            .execute(&backend)
+        };
+        let keys = |keys: &[&str]| -> RespFrame {
+            RespArray::new(
+                keys.iter()
+                    .map(|key| BulkString::new(key.to_string()).into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        assert_eq!(getkeys("MSET a 1 b 2"), keys(&["a", "b"]));
+        assert_eq!(getkeys("blpop l1 l2 0"), keys(&["l1", "l2"]));
+        assert_eq!(
+            getkeys("zunionstore dst 2 z1 z2 WEIGHTS 1 2"),
+            keys(&["dst", "z1", "z2"])
+        );
+        assert_eq!(getkeys("sort src by w_* store dst"), keys(&["src", "dst"]));
+        assert_eq!(getkeys("object encoding k"), keys(&["k"]));
+        assert_eq!(
+            getkeys("zunion 3 z1 z2"),
+            SimpleError::new("ERR Invalid arguments specified for command").into()
+        );
+        assert_eq!(
+            getkeys("get"),
+            SimpleError::new("ERR Invalid number of arguments specified for command").into()
+        );
+        assert_eq!(
+            getkeys("dbsize"),
+            SimpleError::new("ERR The command has no key arguments").into()
+        );
+        assert_eq!(
+            getkeys("nosuchcommand k"),
+            SimpleError::new("ERR Invalid command specified").into()
+        );
+    }
+
+    #[test]
+    fn test_debug_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n$3\r\n0.5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Debug = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            DebugSubcommand::Sleep(Duration::from_millis(500))
+        );
+
+        buf.extend_from_slice(b"*3\r\n$5\r\ndebug\r\n$17\r\nset-active-expire\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Debug = frame.try_into()?;
+        assert_eq!(cmd.subcommand, DebugSubcommand::SetActiveExpire(false));
+
+        buf.extend_from_slice(b"*3\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Debug::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*3\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n$4\r\n1e20\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Debug::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_debug_sleep() {
+        let backend = Backend::new();
+        let sleep = Command::Debug(Debug {
+            subcommand: DebugSubcommand::Sleep(Duration::from_millis(50)),
+        });
+        // sent on its own, the connection handler sleeps for it instead of blocking on keys
+        assert!(sleep.as_blocking().is_none());
+        assert_eq!(sleep.sleep(), Some(Duration::from_millis(50)));
+
+        // run from EXEC, it sleeps right away
+        let start = std::time::Instant::now();
+        assert_eq!(sleep.execute(&backend), RESP_OK.clone());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        let expire = Command::Debug(Debug {
+            subcommand: DebugSubcommand::SetActiveExpire(true),
+        });
+        assert_eq!(expire.sleep(), None);
+    }
+
+    #[test]
+    fn test_debug_object() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("12").into());
+        let cmd = Debug {
+            subcommand: DebugSubcommand::Object("key".to_string()),
+        };
+        let RespFrame::SimpleString(details) = cmd.execute(&backend) else {
+            panic!("DEBUG OBJECT replies with a simple string");
+        };
+        assert!(details.starts_with("Value at:0x"));
+        assert!(details.contains(" refcount:2147483647 encoding:int serializedlength:"));
+        assert!(details.ends_with(" lru_seconds_idle:0"));
+
+        let cmd = Debug {
+            subcommand: DebugSubcommand::Object("missing".to_string()),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::NoSuchKey.into());
+    }
+
+    #[test]
+    fn test_memory_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Memory = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            MemorySubcommand::Usage {
+                key: "key".to_string(),
+                samples: DEFAULT_MEMORY_SAMPLES
+            }
+        );
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n$7\r\nSAMPLES\r\n$1\r\n0\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Memory = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            MemorySubcommand::Usage {
+                key: "key".to_string(),
+                samples: 0
+            }
+        );
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$3\r\nkey\r\n$7\r\nsamples\r\n$2\r\n-1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Memory::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let backend = Backend::new();
+        let usage = |key: &str, samples| {
+            let cmd = Memory {
+                subcommand: MemorySubcommand::Usage {
+                    key: key.to_string(),
+                    samples,
+                },
+            };
+            match cmd.execute(&backend) {
+                RespFrame::Integer(bytes) => Some(bytes),
+                RespFrame::Null(_) => None,
+                frame => panic!("unexpected reply {:?}", frame),
+            }
+        };
+        assert_eq!(usage("missing", 0), None);
+
+        backend.set("short".to_string(), BulkString::new("a").into());
+        backend.set("long".to_string(), BulkString::new("a".repeat(1000)).into());
+        let short = usage("short", 0).unwrap();
+        assert!(usage("long", 0).unwrap() >= short + 990);
+
+        for i in 0..100 {
+            backend.hset(
+                "hash".to_string(),
+                format!("field{}", i),
+                BulkString::new("a".repeat(100)).into(),
+            );
+        }
+        let hash = usage("hash", 0).unwrap();
+        assert!(hash > 100 * 100);
+        // every field has the same size, so sampling doesn't change the estimate much
+        let sampled = usage("hash", DEFAULT_MEMORY_SAMPLES).unwrap();
+        assert!((sampled - hash).abs() < hash / 10);
+    }
+
+    #[test]
+    fn test_memory_stats_and_doctor() {
+        let backend = Backend::new();
+        backend.set("key".to_string(), BulkString::new("a".repeat(100)).into());
+        backend.hset(
+            "hash".to_string(),
+            "field".to_string(),
+            BulkString::new("value").into(),
+        );
+        let cmd = Memory {
+            subcommand: MemorySubcommand::Stats,
+        };
+        let RespFrame::Map(stats) = cmd.execute(&backend) else {
+            panic!("MEMORY STATS replies with a map");
+        };
+        assert_eq!(stats.get("keys.count"), Some(&RespFrame::Integer(2)));
+        let Some(RespFrame::Integer(strings)) = stats.get("dataset.string") else {
+            panic!("MEMORY STATS breaks the dataset down by type");
+        };
+        assert!(*strings > 100);
+        let Some(RespFrame::Integer(total)) = stats.get("total.allocated") else {
+            panic!("MEMORY STATS reports the total allocated");
+        };
+        assert!(total > strings);
+
+        let cmd = Memory {
+            subcommand: MemorySubcommand::Doctor,
+        };
+        let RespFrame::BulkString(report) = cmd.execute(&backend) else {
+            panic!("MEMORY DOCTOR replies with a bulk string");
+        };
+        assert!(String::from_utf8_lossy(&report.0).contains("very little memory"));
+    }
+
+    #[test]
+    fn test_latency_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$7\r\nlatency\r\n$7\r\nhistory\r\n$7\r\ncommand\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Latency = frame.try_into()?;
+        assert_eq!(
+            cmd.subcommand,
+            LatencySubcommand::History("command".to_string())
+        );
+
+        buf.extend_from_slice(b"*2\r\n$7\r\nlatency\r\n$5\r\nRESET\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Latency = frame.try_into()?;
+        assert_eq!(cmd.subcommand, LatencySubcommand::Reset(vec![]));
+
+        buf.extend_from_slice(b"*3\r\n$7\r\nlatency\r\n$6\r\nlatest\r\n$7\r\ncommand\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Latency::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_latency_tracks_spikes() {
+        let backend = Backend::new();
+        backend.record_latency(LatencyEvent::Command, Duration::from_millis(500));
+        let latest = |backend: &Backend| {
+            Latency {
+                subcommand: LatencySubcommand::Latest,
+            }
+            .execute(backend)
+        };
+        // nothing is tracked until a threshold is set
+        assert_eq!(latest(&backend), RespArray::new(vec![]).into());
+
+        backend
+            .config_set(&[("latency-monitor-threshold".to_string(), "100".to_string())])
+            .unwrap();
+        backend.record_latency(LatencyEvent::Command, Duration::from_millis(50));
+        backend.record_latency(LatencyEvent::Command, Duration::from_millis(300));
+        backend.record_latency(LatencyEvent::Command, Duration::from_millis(200));
+        let RespFrame::Array(reports) = latest(&backend) else {
+            panic!("LATENCY LATEST replies with an array");
+        };
+        let RespFrame::Array(report) = &reports[0] else {
+            panic!("every event is reported as an array");
+        };
+        assert_eq!(report[0], BulkString::new("command").into());
+        // spikes within the same second are merged, keeping the worst
+        assert_eq!(report[2], RespFrame::Integer(300));
+        assert_eq!(report[3], RespFrame::Integer(300));
+
+        let cmd = Latency {
+            subcommand: LatencySubcommand::History("command".to_string()),
+        };
+        let RespFrame::Array(samples) = cmd.execute(&backend) else {
+            panic!("LATENCY HISTORY replies with an array");
+        };
+        assert_eq!(samples.len(), 1);
+
+        let cmd = Latency {
+            subcommand: LatencySubcommand::Reset(vec![
+                "command".to_string(),
+                "expire-cycle".to_string(),
+            ]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(latest(&backend), RespArray::new(vec![]).into());
+    }
+
+    #[test]
+    fn test_shutdown_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$8\r\nshutdown\r\n$6\r\nnosave\r\n$3\r\nNOW\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Shutdown = frame.try_into()?;
+        assert_eq!(
+            cmd,
+            Shutdown {
+                save: Some(false),
+                now: true,
+                force: false,
+                abort: false,
+            }
+        );
+
+        buf.extend_from_slice(b"*3\r\n$8\r\nshutdown\r\n$4\r\nsave\r\n$6\r\nnosave\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Shutdown::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*3\r\n$8\r\nshutdown\r\n$5\r\nabort\r\n$5\r\nforce\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Shutdown::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let backend = Backend::new();
+        let shutdown = |save, abort| Shutdown {
+            save,
+            now: false,
+            force: false,
+            abort,
+        };
+        assert_eq!(
+            shutdown(None, true).execute(&backend),
+            SimpleError::new("ERR No shutdown in progress.").into()
+        );
+        assert!(!backend.is_shutting_down());
+
+        // the snapshot can't be written there, which cancels the shutdown
+        backend
+            .config_set(&[("dir".to_string(), "/proc".to_string())])
+            .unwrap();
+        assert_eq!(
+            shutdown(Some(true), false).execute(&backend),
+            BackendError::ShutdownFailed.into()
+        );
+        assert!(!backend.is_shutting_down());
+
+        assert_eq!(
+            shutdown(Some(false), false).execute(&backend),
+            RESP_OK.clone()
+        );
+        assert!(backend.is_shutting_down());
+    }
+
+    #[test]
+    fn test_bgsave_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$6\r\nbgsave\r\n$8\r\nSCHEDULE\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BgSave = frame.try_into()?;
+        assert!(cmd.schedule);
+
+        buf.extend_from_slice(b"*2\r\n$6\r\nbgsave\r\n$3\r\nnow\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(BgSave::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*2\r\n$4\r\nsave\r\n$3\r\nnow\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Save::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_save_and_bgsave() {
+        let dir = std::env::temp_dir().join(format!("simple-redis-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let backend = Backend::new();
+        backend
+            .config_set(&[("dir".to_string(), dir.to_string_lossy().to_string())])
+            .unwrap();
+        backend.set("key".to_string(), BulkString::new("value").into());
+
+        assert_eq!(Save.execute(&backend), RESP_OK.clone());
+        assert!(backend.snapshot_path().exists());
+        let RespFrame::Integer(last_save) = LastSave.execute(&backend) else {
+            panic!("LASTSAVE replies with an integer");
+        };
+        assert!(last_save > 0);
+
+        std::fs::remove_file(backend.snapshot_path()).unwrap();
+        assert_eq!(
+            BgSave { schedule: false }.execute(&backend),
+            SimpleString::new("Background saving started").into()
+        );
+        // a second one is refused unless scheduled
+        let second = BgSave { schedule: false }.execute(&backend);
+        if backend.persistence_stats().bgsave_in_progress {
+            assert_eq!(second, BackendError::BgSaveInProgress.into());
+            assert_eq!(
+                Save.execute(&backend),
+                BackendError::BgSaveInProgress.into()
+            );
+        }
+        while backend.persistence_stats().bgsave_in_progress {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(backend.persistence_stats().last_bgsave_ok);
+        assert!(backend.snapshot_path().exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_time() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*2\r\n$4\r\ntime\r\n$3\r\nnow\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Time::try_from(frame).is_err());
+
+        let before = crate::now_ms();
+        let RespFrame::Array(time) = Time.execute(&Backend::new()) else {
+            panic!("TIME replies with an array");
+        };
+        let part = |i: usize| match &time[i] {
+            RespFrame::BulkString(s) => String::from_utf8_lossy(s).parse::<u64>().unwrap(),
+            frame => panic!("unexpected part {:?}", frame),
+        };
+        let (secs, micros) = (part(0), part(1));
+        assert!(micros < 1_000_000);
+        assert!(secs * 1000 + micros / 1000 >= before);
+        Ok(())
+    }
+
+    #[test]
+    fn test_swapdb() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$6\r\nswapdb\r\n$1\r\n0\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SwapDb = frame.try_into()?;
+        assert_eq!((cmd.first, cmd.second), (0, 1));
+
+        buf.extend_from_slice(b"*2\r\n$6\r\nswapdb\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(SwapDb::try_from(frame).is_err());
+
+        let backend = Backend::new();
+        backend.set("a".to_string(), RespFrame::Integer(0));
+        backend
+            .select(1)?
+            .set("b".to_string(), RespFrame::Integer(1));
+        let cmd = SwapDb {
+            first: 0,
+            second: 1,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        let (first, second) = (backend.select(0)?, backend.select(1)?);
+        assert_eq!(first.get("b"), Some(RespFrame::Integer(1)));
+        assert_eq!(second.get("a"), Some(RespFrame::Integer(0)));
+        assert_eq!(first.get("a"), None);
+
+        let cmd = SwapDb {
+            first: 0,
+            second: 16,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::DbOutOfRange.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_acl_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$3\r\nacl\r\n$3\r\nlog\r\n$1\r\n5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Acl = frame.try_into()?;
+        assert_eq!(cmd.subcommand, AclSubcommand::Log(5));
+
+        buf.extend_from_slice(b"*3\r\n$3\r\nacl\r\n$3\r\nLOG\r\n$5\r\nreset\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Acl = frame.try_into()?;
+        assert_eq!(cmd.subcommand, AclSubcommand::LogReset);
+
+        buf.extend_from_slice(b"*3\r\n$3\r\nacl\r\n$3\r\nlog\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Acl::try_from(frame).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_acl_log() -> Result<()> {
+        let backend = Backend::new();
+        backend.config_set(&[("requirepass".to_string(), "secret".to_string())])?;
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        assert!(connection.authenticate(Some("admin"), "guess").is_err());
+        assert!(connection.authenticate(Some("admin"), "other").is_err());
+        backend.record_acl_denial(AclDenial::Command, "get", None, connection.info());
+
+        let log = |count| {
+            Acl {
+                subcommand: AclSubcommand::Log(count),
+            }
+            .execute(&backend)
+        };
+        let RespFrame::Array(entries) = log(10) else {
+            panic!("ACL LOG replies with an array");
+        };
+        assert_eq!(entries.len(), 2);
+        let RespFrame::Map(newest) = &entries[0] else {
+            panic!("every entry is a map");
+        };
+        assert_eq!(
+            newest.get("reason"),
+            Some(&BulkString::new("command").into())
+        );
+        assert_eq!(
+            newest.get("username"),
+            Some(&BulkString::new("default").into())
+        );
+        let RespFrame::Map(oldest) = &entries[1] else {
+            panic!("every entry is a map");
+        };
+        // failures close to each other are grouped
+        assert_eq!(oldest.get("count"), Some(&RespFrame::Integer(2)));
+        assert_eq!(oldest.get("object"), Some(&BulkString::new("AUTH").into()));
+        assert_eq!(
+            oldest.get("username"),
+            Some(&BulkString::new("admin").into())
+        );
+
+        let RespFrame::Array(entries) = log(1) else {
+            panic!("ACL LOG replies with an array");
+        };
+        assert_eq!(entries.len(), 1);
+
+        backend.config_set(&[("acllog-max-len".to_string(), "1".to_string())])?;
+        backend.record_acl_denial(AclDenial::Command, "set", None, None);
+        assert_eq!(backend.acl_log(10).len(), 1);
+
+        let cmd = Acl {
+            subcommand: AclSubcommand::LogReset,
+        };
+        assert_eq!(cmd.execute(&backend), RESP_OK.clone());
+        assert_eq!(log(10), RespArray::new(vec![]).into());
+        Ok(())
+    }
+}
@@ -0,0 +1,868 @@
+use super::{
+    extract_args, extract_bytes, extract_cursor, extract_integer, extract_string, parse_keys,
+    parse_scan_option, scan_matches, scan_reply, validate_command, validate_variadic_command,
+    CommandError, CommandExecutor, SAdd, SCard, SDiff, SDiffStore, SInter, SInterCard, SInterStore,
+    SIsMember, SMIsMember, SMembers, SMove, SPop, SRandMember, SRem, SScan, SUnion, SUnionStore,
+};
+
+use crate::{Backend, BulkString, RespArray, RespFrame, RespNull, RespSet, SetOp};
+
+impl CommandExecutor for SAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sadd(self.key, self.members) {
+            Ok(added) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.srem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMembers {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smembers(&self.key) {
+            Ok(members) => members_set(members),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SIsMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sismember(&self.key, &self.member) {
+            Ok(exists) => RespFrame::Integer(exists as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMIsMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smismember(&self.key, &self.members) {
+            Ok(exists) => RespArray::new(
+                exists
+                    .into_iter()
+                    .map(|exists| RespFrame::Integer(exists as i64))
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SMove {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.smove(&self.source, self.destination, self.member) {
+            Ok(moved) => RespFrame::Integer(moved as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.scard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        scombine(backend, SetOp::Inter, &self.keys)
+    }
+}
+
+impl CommandExecutor for SUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        scombine(backend, SetOp::Union, &self.keys)
+    }
+}
+
+impl CommandExecutor for SDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        scombine(backend, SetOp::Diff, &self.keys)
+    }
+}
+
+impl CommandExecutor for SInterStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        scombine_store(backend, SetOp::Inter, self.destination, &self.keys)
+    }
+}
+
+impl CommandExecutor for SUnionStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        scombine_store(backend, SetOp::Union, self.destination, &self.keys)
+    }
+}
+
+impl CommandExecutor for SDiffStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        scombine_store(backend, SetOp::Diff, self.destination, &self.keys)
+    }
+}
+
+impl CommandExecutor for SPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.spop(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => random_members(members, self.count.is_some()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SRandMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.srandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => random_members(members, self.count.is_some()),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for SInterCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sintercard(&self.keys, self.limit) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+// without a count the reply is the member itself, with one it is an array of members
+fn random_members(members: Vec<Vec<u8>>, with_count: bool) -> RespFrame {
+    let mut members = members
+        .into_iter()
+        .map(|member| BulkString::new(member).into());
+    if with_count {
+        return RespArray::new(members.collect::<Vec<_>>()).into();
+    }
+    members.next().unwrap_or(RespFrame::Null(RespNull))
+}
+
+impl CommandExecutor for SScan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.sscan(&self.key, self.cursor, self.count) {
+            Ok((cursor, members)) => {
+                let members = members
+                    .into_iter()
+                    .filter(|member| scan_matches(&self.pattern, member))
+                    .map(|member| BulkString::new(member).into())
+                    .collect();
+                scan_reply(cursor, members)
+            }
+            Err(e) => e.into(),
+        }
+    }
+}
+
+fn scombine(backend: &Backend, op: SetOp, keys: &[String]) -> RespFrame {
+    match backend.scombine(op, keys) {
+        Ok(members) => members_set(members),
+        Err(e) => e.into(),
+    }
+}
+
+fn scombine_store(backend: &Backend, op: SetOp, destination: String, keys: &[String]) -> RespFrame {
+    match backend.scombine_store(op, destination, keys) {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+fn members_set(members: Vec<Vec<u8>>) -> RespFrame {
+    RespSet::new(
+        members
+            .into_iter()
+            .map(|member| BulkString::new(member).into())
+            .collect::<Vec<_>>(),
+    )
+    .into()
+}
+
+impl TryFrom<RespArray> for SAdd {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_members(value, "sadd")?;
+        Ok(SAdd { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_members(value, "srem")?;
+        Ok(SRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SMembers {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["smembers"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SMembers {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SIsMember {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["sismember"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SIsMember {
+            key: extract_string(args.next())?,
+            member: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SMIsMember {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, members) = parse_members(value, "smismember")?;
+        Ok(SMIsMember { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for SMove {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["smove"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SMove {
+            source: extract_string(args.next())?,
+            destination: extract_string(args.next())?,
+            member: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SCard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["scard"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(SCard {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SInter {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SInter {
+            keys: parse_keys(value, "sinter")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SUnion {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SUnion {
+            keys: parse_keys(value, "sunion")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SDiff {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        Ok(SDiff {
+            keys: parse_keys(value, "sdiff")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for SInterStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_store(value, "sinterstore")?;
+        Ok(SInterStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SUnionStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_store(value, "sunionstore")?;
+        Ok(SUnionStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SDiffStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (destination, keys) = parse_store(value, "sdiffstore")?;
+        Ok(SDiffStore { destination, keys })
+    }
+}
+
+impl TryFrom<RespArray> for SPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["spop"], 1)?;
+        if value.len() > 3 {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = match args.next() {
+            Some(count) => match extract_integer(Some(count))? {
+                n if n >= 0 => Some(n as usize),
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "value is out of range, must be positive".into(),
+                    ))
+                }
+            },
+            None => None,
+        };
+        Ok(SPop { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SRandMember {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["srandmember"], 1)?;
+        if value.len() > 3 {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = match args.next() {
+            Some(count) => Some(extract_integer(Some(count))?),
+            None => None,
+        };
+        Ok(SRandMember { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for SInterCard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["sintercard"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let n_keys = extract_integer(args.next())?;
+        if n_keys <= 0 {
+            return Err(CommandError::InvalidArgument(
+                "numkeys should be greater than 0".into(),
+            ));
+        }
+        let keys = (0..n_keys)
+            .map(|_| extract_string(args.next()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let limit = match args.next() {
+            Some(arg) => {
+                if !extract_string(Some(arg))?.eq_ignore_ascii_case("LIMIT") {
+                    return Err(CommandError::InvalidArgument("syntax error".into()));
+                }
+                match extract_integer(args.next())? {
+                    n if n >= 0 => n as usize,
+                    _ => {
+                        return Err(CommandError::InvalidArgument(
+                            "LIMIT can't be negative".into(),
+                        ))
+                    }
+                }
+            }
+            None => 0,
+        };
+        if args.next().is_some() {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        Ok(SInterCard { keys, limit })
+    }
+}
+
+impl TryFrom<RespArray> for SScan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["sscan"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut sscan = SScan {
+            key: extract_string(args.next())?,
+            cursor: extract_cursor(args.next())?,
+            pattern: None,
+            count: 10,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            if !parse_scan_option(&option, &mut args, &mut sscan.pattern, &mut sscan.count)? {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+        }
+        Ok(sscan)
+    }
+}
+
+// parses `destination key [key ...]`
+fn parse_store(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<String>), CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let destination = extract_string(args.next())?;
+    let keys = args
+        .map(|arg| extract_string(Some(arg)))
+        .collect::<Result<_, _>>()?;
+    Ok((destination, keys))
+}
+
+// parses `key member [member ...]`
+fn parse_members(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<Vec<u8>>), CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let members = args
+        .map(|arg| extract_bytes(Some(arg)))
+        .collect::<Result<_, _>>()?;
+    Ok((key, members))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{BackendError, RespDecode};
+
+    use super::*;
+
+    #[test]
+    fn test_sadd_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$4\r\nsadd\r\n$3\r\nset\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SAdd = frame.try_into()?;
+        assert_eq!(cmd.key, "set");
+        assert_eq!(cmd.members, vec![b"a".to_vec(), b"b".to_vec()]);
+
+        buf.extend_from_slice(b"*2\r\n$4\r\nsrem\r\n$3\r\nset\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<SRem, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), b"value".into());
+
+        let cmd = SAdd {
+            key: "set".to_string(),
+            members: vec![b"a".to_vec(), b"b".to_vec(), b"a".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        let cmd = SAdd {
+            key: "set".to_string(),
+            members: vec![b"b".to_vec(), b"c".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = SCard {
+            key: "set".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let sismember = |member: &[u8]| {
+            SIsMember {
+                key: "set".to_string(),
+                member: member.to_vec(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(sismember(b"a"), RespFrame::Integer(1));
+        assert_eq!(sismember(b"d"), RespFrame::Integer(0));
+
+        let cmd = SRem {
+            key: "set".to_string(),
+            members: vec![b"a".to_vec(), b"d".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = SMembers {
+            key: "set".to_string(),
+        };
+        let RespFrame::Set(members) = cmd.execute(&backend) else {
+            panic!("SMEMBERS should reply with a set");
+        };
+        let mut members = members.0;
+        members.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(members, vec![b"b".into(), b"c".into()]);
+
+        // removing the last members deletes the key
+        let cmd = SRem {
+            key: "set".to_string(),
+            members: vec![b"b".to_vec(), b"c".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert!(!backend.db.set.contains_key("set"));
+
+        let cmd = SAdd {
+            key: "string".to_string(),
+            members: vec![b"a".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sinterstore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$11\r\nsinterstore\r\n$3\r\ndst\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SInterStore = frame.try_into()?;
+        assert_eq!(cmd.destination, "dst");
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_algebra_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = |members: &[&str]| members.iter().map(|m| m.as_bytes().to_vec()).collect();
+        backend.sadd("a".to_string(), members(&["1", "2", "3"]))?;
+        backend.sadd("b".to_string(), members(&["2", "3", "4"]))?;
+        backend.set("string".to_string(), b"value".into());
+        let keys = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        let sorted = |frame: RespFrame| {
+            let RespFrame::Set(members) = frame else {
+                panic!("expected a set reply, got {:?}", frame);
+            };
+            let mut members = members.0;
+            members.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            members
+        };
+        let expected = |members: &[&str]| -> Vec<RespFrame> {
+            members.iter().map(|m| BulkString::new(*m).into()).collect()
+        };
+
+        let cmd = SInter {
+            keys: keys(&["a", "b"]),
+        };
+        assert_eq!(sorted(cmd.execute(&backend)), expected(&["2", "3"]));
+        let cmd = SUnion {
+            keys: keys(&["a", "b", "missing"]),
+        };
+        assert_eq!(
+            sorted(cmd.execute(&backend)),
+            expected(&["1", "2", "3", "4"])
+        );
+        let cmd = SDiff {
+            keys: keys(&["a", "b"]),
+        };
+        assert_eq!(sorted(cmd.execute(&backend)), expected(&["1"]));
+        let cmd = SInter {
+            keys: keys(&["a", "missing"]),
+        };
+        assert_eq!(sorted(cmd.execute(&backend)), expected(&[]));
+        let cmd = SInter {
+            keys: keys(&["a", "string"]),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+
+        // the destination is overwritten whatever its type
+        let cmd = SUnionStore {
+            destination: "string".to_string(),
+            keys: keys(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+        assert_eq!(backend.get("string"), None);
+        assert_eq!(backend.scard("string")?, 4);
+
+        // the destination may be one of the sources
+        let cmd = SDiffStore {
+            destination: "a".to_string(),
+            keys: keys(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.smembers("a")?, vec![b"1".to_vec()]);
+
+        // an empty result deletes the destination
+        let cmd = SInterStore {
+            destination: "a".to_string(),
+            keys: keys(&["a", "b"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(!backend.db.set.contains_key("a"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_spop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$4\r\nspop\r\n$3\r\nset\r\n$1\r\n2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SPop = frame.try_into()?;
+        assert_eq!(cmd.key, "set");
+        assert_eq!(cmd.count, Some(2));
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nspop\r\n$3\r\nset\r\n$2\r\n-2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<SPop, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*3\r\n$11\r\nsrandmember\r\n$3\r\nset\r\n$2\r\n-2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SRandMember = frame.try_into()?;
+        assert_eq!(cmd.count, Some(-2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_spop_srandmember_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()];
+        backend.sadd("set".to_string(), members)?;
+
+        let srandmember = |count: Option<i64>| {
+            SRandMember {
+                key: "set".to_string(),
+                count,
+            }
+            .execute(&backend)
+        };
+        let RespFrame::Array(distinct) = srandmember(Some(5)) else {
+            panic!("SRANDMEMBER with a count should reply with an array");
+        };
+        let mut distinct = distinct.0;
+        distinct.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(distinct, vec![b"a".into(), b"b".into(), b"c".into()]);
+        let RespFrame::Array(all) = srandmember(Some(i64::MAX)) else {
+            panic!("SRANDMEMBER with a count should reply with an array");
+        };
+        assert_eq!(all.len(), 3);
+        let RespFrame::Array(repeated) = srandmember(Some(-5)) else {
+            panic!("SRANDMEMBER with a count should reply with an array");
+        };
+        assert_eq!(repeated.len(), 5);
+        assert!(matches!(srandmember(None), RespFrame::BulkString(_)));
+        assert_eq!(backend.scard("set")?, 3);
+
+        let spop = |count: Option<usize>| {
+            SPop {
+                key: "set".to_string(),
+                count,
+            }
+            .execute(&backend)
+        };
+        assert!(matches!(spop(None), RespFrame::BulkString(_)));
+        let RespFrame::Array(popped) = spop(Some(i64::MAX as usize)) else {
+            panic!("SPOP with a count should reply with an array");
+        };
+        assert_eq!(popped.len(), 2);
+        // popping the last members deletes the key
+        assert!(!backend.db.set.contains_key("set"));
+        assert_eq!(spop(None), RespFrame::Null(RespNull));
+        assert_eq!(spop(Some(1)), RespArray::new(vec![]).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_smove_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$5\r\nsmove\r\n$3\r\nsrc\r\n$3\r\ndst\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SMove = frame.try_into()?;
+        assert_eq!(cmd.source, "src");
+        assert_eq!(cmd.destination, "dst");
+        assert_eq!(cmd.member, b"a".to_vec());
+        Ok(())
+    }
+
+    #[test]
+    fn test_smove_smismember_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd("src".to_string(), vec![b"a".to_vec(), b"b".to_vec()])?;
+        backend.set("string".to_string(), b"value".into());
+
+        let smove = |source: &str, destination: &str, member: &[u8]| {
+            SMove {
+                source: source.to_string(),
+                destination: destination.to_string(),
+                member: member.to_vec(),
+            }
+            .execute(&backend)
+        };
+        assert_eq!(smove("src", "dst", b"a"), RespFrame::Integer(1));
+        assert_eq!(smove("src", "dst", b"c"), RespFrame::Integer(0));
+        assert_eq!(smove("src", "src", b"b"), RespFrame::Integer(1));
+        assert_eq!(smove("src", "string", b"b"), BackendError::WrongType.into());
+        assert_eq!(smove("src", "dst", b"b"), RespFrame::Integer(1));
+        // moving the last member deletes the source
+        assert!(!backend.db.set.contains_key("src"));
+
+        let cmd = SMIsMember {
+            key: "dst".to_string(),
+            members: vec![b"a".to_vec(), b"c".to_vec(), b"b".to_vec()],
+        };
+        let expected = [1, 0, 1].map(RespFrame::Integer);
+        assert_eq!(cmd.execute(&backend), RespArray::new(expected).into());
+        let cmd = SMIsMember {
+            key: "missing".to_string(),
+            members: vec![b"a".to_vec()],
+        };
+        let expected = [RespFrame::Integer(0)];
+        assert_eq!(cmd.execute(&backend), RespArray::new(expected).into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sscan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*7\r\n$5\r\nsscan\r\n$3\r\nset\r\n$2\r\n42\r\n$5\r\nmatch\r\n$2\r\na*\r\n$5\r\ncount\r\n$1\r\n5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SScan = frame.try_into()?;
+        assert_eq!(cmd.key, "set");
+        assert_eq!(cmd.cursor, 42);
+        assert_eq!(cmd.pattern, Some("a*".to_string()));
+        assert_eq!(cmd.count, 5);
+
+        buf.extend_from_slice(b"*4\r\n$5\r\nsscan\r\n$3\r\nset\r\n$1\r\n0\r\n$8\r\nNOVALUES\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<SScan, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sscan_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = (0..50)
+            .map(|i| format!("member:{}", i).into_bytes())
+            .collect();
+        backend.sadd("set".to_string(), members)?;
+        backend.sadd("set".to_string(), vec![b"other".to_vec()])?;
+
+        let mut members = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let cmd = SScan {
+                key: "set".to_string(),
+                cursor,
+                pattern: Some("member:*".to_string()),
+                count: 8,
+            };
+            let RespFrame::Array(reply) = cmd.execute(&backend) else {
+                panic!("SSCAN should reply with an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(page)) = (&reply[0], &reply[1])
+            else {
+                panic!("SSCAN should reply with a cursor and a page");
+            };
+            members.extend(page.iter().cloned());
+            cursor = String::from_utf8_lossy(next).parse()?;
+            if cursor == 0 {
+                break;
+            }
+        }
+        members.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        members.dedup();
+        assert_eq!(members.len(), 50);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sintercard_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*6\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$5\r\nLIMIT\r\n$1\r\n3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: SInterCard = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cmd.limit, 3);
+
+        buf.extend_from_slice(
+            b"*5\r\n$10\r\nsintercard\r\n$1\r\n1\r\n$1\r\na\r\n$5\r\nLIMIT\r\n$2\r\n-1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<SInterCard, _> = frame.try_into();
+        assert!(result.is_err());
+
+        // numkeys does not match the keys
+        buf.extend_from_slice(b"*3\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<SInterCard, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sintercard_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = |members: &[&str]| members.iter().map(|m| m.as_bytes().to_vec()).collect();
+        backend.sadd("a".to_string(), members(&["1", "2", "3", "4"]))?;
+        backend.sadd("b".to_string(), members(&["2", "3", "4", "5"]))?;
+        let keys = vec!["a".to_string(), "b".to_string()];
+
+        let cmd = SInterCard {
+            keys: keys.clone(),
+            limit: 0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(3));
+
+        let cmd = SInterCard { keys, limit: 2 };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = SInterCard {
+            keys: vec!["a".to_string(), "missing".to_string()],
+            limit: 0,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        let cmd = SInterCard {
+            keys: vec!["a".to_string(), "string".to_string()],
+            limit: 0,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+}
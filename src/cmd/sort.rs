@@ -0,0 +1,189 @@
+use super::{
+    extract_args, extract_integer, extract_string, validate_variadic_command, CommandError,
+    CommandExecutor, Sort,
+};
+
+use crate::{Backend, BulkString, RespArray, RespFrame, RespNull, SortOptions};
+
+impl CommandExecutor for Sort {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if let Some(destination) = self.store {
+            return match backend.sort_store(&self.key, &self.options, destination) {
+                Ok(len) => RespFrame::Integer(len as i64),
+                Err(e) => e.into(),
+            };
+        }
+        match backend.sort(&self.key, &self.options) {
+            Ok(elements) => RespArray::new(
+                elements
+                    .into_iter()
+                    .map(|element| match element {
+                        Some(element) => BulkString::new(element).into(),
+                        None => RespFrame::Null(RespNull),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Sort {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["sort"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut sort = Sort {
+            key: extract_string(args.next())?,
+            options: SortOptions::default(),
+            store: None,
+        };
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "BY" => sort.options.by = Some(extract_string(args.next())?),
+                "LIMIT" => {
+                    let offset = extract_integer(args.next())?;
+                    let count = extract_integer(args.next())?;
+                    sort.options.limit = Some((offset, count));
+                }
+                "GET" => sort.options.get.push(extract_string(args.next())?),
+                "ASC" => sort.options.desc = false,
+                "DESC" => sort.options.desc = true,
+                "ALPHA" => sort.options.alpha = true,
+                "STORE" => sort.store = Some(extract_string(args.next())?),
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        Ok(sort)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{BackendError, ListEnd, RespDecode};
+
+    use super::*;
+
+    #[test]
+    fn test_sort_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*13\r\n$4\r\nsort\r\n$4\r\nlist\r\n$2\r\nby\r\n$8\r\nweight_*\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$1\r\n5\r\n$3\r\nGET\r\n$1\r\n#\r\n$4\r\nDESC\r\n$5\r\nALPHA\r\n$5\r\nSTORE\r\n$3\r\nout\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: Sort = frame.try_into()?;
+        assert_eq!(cmd.key, "list");
+        assert_eq!(
+            cmd.options,
+            SortOptions {
+                by: Some("weight_*".to_string()),
+                limit: Some((0, 5)),
+                get: vec!["#".to_string()],
+                desc: true,
+                alpha: true,
+            }
+        );
+        assert_eq!(cmd.store, Some("out".to_string()));
+
+        buf.extend_from_slice(b"*3\r\n$4\r\nsort\r\n$4\r\nlist\r\n$5\r\nLIMIT\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<Sort, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_command() -> Result<()> {
+        let backend = Backend::new();
+        let elements = ["3", "1", "2", "10"].map(|e| BulkString::new(e).into());
+        backend.push("list".to_string(), ListEnd::Right, elements.to_vec())?;
+        let frames = |elements: &[Option<&str>]| -> RespFrame {
+            let elements = elements
+                .iter()
+                .map(|element| match element {
+                    Some(element) => BulkString::new(*element).into(),
+                    None => RespFrame::Null(RespNull),
+                })
+                .collect::<Vec<RespFrame>>();
+            RespArray::new(elements).into()
+        };
+        let sort = |options: SortOptions| Sort {
+            key: "list".to_string(),
+            options,
+            store: None,
+        };
+
+        let cmd = sort(SortOptions::default());
+        assert_eq!(
+            cmd.execute(&backend),
+            frames(&[Some("1"), Some("2"), Some("3"), Some("10")])
+        );
+
+        let cmd = sort(SortOptions {
+            alpha: true,
+            desc: true,
+            limit: Some((1, 2)),
+            ..Default::default()
+        });
+        assert_eq!(cmd.execute(&backend), frames(&[Some("2"), Some("10")]));
+
+        // weights and values looked up in strings and hashes
+        backend.set("w_1".to_string(), BulkString::new("30").into());
+        backend.set("w_2".to_string(), BulkString::new("20").into());
+        backend.set("w_3".to_string(), BulkString::new("10").into());
+        backend.hset(
+            "h_3".to_string(),
+            "name".to_string(),
+            BulkString::new("three").into(),
+        );
+        let cmd = sort(SortOptions {
+            by: Some("w_*".to_string()),
+            get: vec!["#".to_string(), "h_*->name".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            cmd.execute(&backend),
+            frames(&[
+                Some("10"),
+                None,
+                Some("3"),
+                Some("three"),
+                Some("2"),
+                None,
+                Some("1"),
+                None,
+            ])
+        );
+
+        // a BY pattern without `*` keeps the original order
+        let cmd = sort(SortOptions {
+            by: Some("nosort".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(
+            cmd.execute(&backend),
+            frames(&[Some("3"), Some("1"), Some("2"), Some("10")])
+        );
+
+        let cmd = Sort {
+            key: "list".to_string(),
+            options: SortOptions::default(),
+            store: Some("out".to_string()),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+        assert_eq!(backend.llen("out")?, 4);
+
+        backend.push(
+            "list".to_string(),
+            ListEnd::Right,
+            vec![BulkString::new("x").into()],
+        )?;
+        let cmd = sort(SortOptions::default());
+        assert_eq!(cmd.execute(&backend), BackendError::SortNotFloat.into());
+        Ok(())
+    }
+}
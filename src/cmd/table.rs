@@ -0,0 +1,415 @@
+/// Where the keys of a command are among its arguments, counting the command name as
+/// argument 0, like the key specs of redis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySpec {
+    // every `step` arguments from `first` to `last`, negative values counting from the end
+    Range { first: i64, last: i64, step: i64 },
+    // the argument at `numkeys` is the number of keys right after it
+    KeyNum { numkeys: usize },
+    // the argument following `keyword`, if present
+    Keyword { keyword: &'static str },
+}
+
+/// Static description of a command, as reported by COMMAND.
+#[derive(Debug)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    // exact number of arguments including the name, or the minimum when negative
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    pub keys: &'static [KeySpec],
+    pub group: &'static str,
+    // version of redis that introduced the command
+    pub since: &'static str,
+    pub summary: &'static str,
+}
+
+impl CommandSpec {
+    /// Flags including the ones implied by the key specs.
+    pub fn all_flags(&self) -> Vec<&'static str> {
+        let mut flags = self.flags.to_vec();
+        if self.has_movable_keys() {
+            flags.push("movablekeys");
+        }
+        flags
+    }
+
+    // keys whose position depends on the other arguments
+    fn has_movable_keys(&self) -> bool {
+        self.keys
+            .iter()
+            .any(|spec| !matches!(spec, KeySpec::Range { .. }))
+    }
+
+    /// Legacy `(first, last, step)` key positions, from the first range of keys.
+    pub fn key_range(&self) -> (i64, i64, i64) {
+        self.keys
+            .iter()
+            .find_map(|spec| match *spec {
+                KeySpec::Range { first, last, step } => Some((first, last, step)),
+                _ => None,
+            })
+            .unwrap_or((0, 0, 0))
+    }
+
+    /// Whether `argc` arguments, including the name, fit the arity of the command.
+    pub fn accepts(&self, argc: usize) -> bool {
+        let argc = argc as i64;
+        if self.arity < 0 {
+            argc >= -self.arity
+        } else {
+            argc == self.arity
+        }
+    }
+
+    /// Positions of the keys in `args`, the full command line including the name, or
+    /// `None` if they can't be found where the key specs say they are.
+    pub fn key_positions(&self, args: &[String]) -> Option<Vec<usize>> {
+        let argc = args.len() as i64;
+        let mut positions = Vec::new();
+        for spec in self.keys {
+            // a keyword is only looked for after the keys found so far
+            let start = positions.last().map_or(1, |last| last + 1);
+            match *spec {
+                KeySpec::Range { first, last, step } => {
+                    let last = if last < 0 { argc + last } else { last };
+                    if first >= argc || last >= argc {
+                        return None;
+                    }
+                    positions.extend((first..=last).step_by(step as usize).map(|i| i as usize));
+                }
+                KeySpec::KeyNum { numkeys } => {
+                    let count = args.get(numkeys)?.parse::<usize>().ok()?;
+                    let first = numkeys + 1;
+                    if count == 0 || first + count > args.len() {
+                        return None;
+                    }
+                    positions.extend(first..first + count);
+                }
+                KeySpec::Keyword { keyword } => {
+                    let found = args
+                        .iter()
+                        .enumerate()
+                        .skip(start)
+                        .find(|(_, arg)| arg.eq_ignore_ascii_case(keyword));
+                    if let Some((i, _)) = found {
+                        if i + 1 >= args.len() {
+                            return None;
+                        }
+                        positions.push(i + 1);
+                    }
+                }
+            }
+        }
+        Some(positions)
+    }
+
+    /// ACL categories of the command, derived from its group and flags.
+    pub fn acl_categories(&self) -> Vec<&'static str> {
+        let mut categories = Vec::new();
+        let flag = |name| self.flags.contains(&name);
+        if flag("write") {
+            categories.push("@write");
+        }
+        if flag("readonly") {
+            categories.push("@read");
+        }
+        match self.group {
+            "string" => categories.push("@string"),
+            "hash" => categories.push("@hash"),
+            "list" => categories.push("@list"),
+            "set" => categories.push("@set"),
+            "sorted-set" => categories.push("@sortedset"),
+            "generic" => categories.push("@keyspace"),
+            "pubsub" => categories.push("@pubsub"),
+            "transactions" => categories.push("@transaction"),
+            _ => {}
+        }
+        categories.push(if flag("fast") { "@fast" } else { "@slow" });
+        if flag("blocking") {
+            categories.push("@blocking");
+        }
+        if flag("admin") {
+            categories.extend(["@admin", "@dangerous"]);
+        }
+        categories
+    }
+}
+
+const R: &[&str] = &["readonly"];
+const RF: &[&str] = &["readonly", "fast"];
+const W: &[&str] = &["write"];
+const WF: &[&str] = &["write", "fast"];
+const WD: &[&str] = &["write", "denyoom"];
+const WDF: &[&str] = &["write", "denyoom", "fast"];
+const WB: &[&str] = &["write", "blocking"];
+const WFB: &[&str] = &["write", "fast", "blocking"];
+const WDB: &[&str] = &["write", "denyoom", "blocking"];
+const ADMIN: &[&str] = &["admin", "noscript", "loading", "stale"];
+const STALE: &[&str] = &["loading", "stale"];
+const STALE_FAST: &[&str] = &["loading", "stale", "fast"];
+const NOSCRIPT: &[&str] = &["noscript"];
+const PUBSUB: &[&str] = &["pubsub", "noscript", "loading", "stale"];
+const PUBSUB_FAST: &[&str] = &["pubsub", "loading", "stale", "fast"];
+const PUBSUB_STALE: &[&str] = &["pubsub", "loading", "stale"];
+const TRANSACTION: &[&str] = &["noscript", "loading", "stale"];
+const TRANSACTION_FAST: &[&str] = &["noscript", "loading", "stale", "fast"];
+// allowed before the client authenticates
+const NO_AUTH: &[&str] = &["noscript", "loading", "stale", "fast", "no_auth"];
+
+const NO_KEYS: &[KeySpec] = &[];
+const FIRST: &[KeySpec] = &[KeySpec::Range {
+    first: 1,
+    last: 1,
+    step: 1,
+}];
+const SECOND: &[KeySpec] = &[KeySpec::Range {
+    first: 2,
+    last: 2,
+    step: 1,
+}];
+const FIRST_TWO: &[KeySpec] = &[KeySpec::Range {
+    first: 1,
+    last: 2,
+    step: 1,
+}];
+const ALL: &[KeySpec] = &[KeySpec::Range {
+    first: 1,
+    last: -1,
+    step: 1,
+}];
+// keys followed by a timeout
+const ALL_BUT_LAST: &[KeySpec] = &[KeySpec::Range {
+    first: 1,
+    last: -2,
+    step: 1,
+}];
+// keys alternating with values
+const PAIRS: &[KeySpec] = &[KeySpec::Range {
+    first: 1,
+    last: -1,
+    step: 2,
+}];
+const NUMKEYS: &[KeySpec] = &[KeySpec::KeyNum { numkeys: 1 }];
+// numkeys after a timeout
+const TIMEOUT_NUMKEYS: &[KeySpec] = &[KeySpec::KeyNum { numkeys: 2 }];
+// numkeys after a destination
+const STORE_NUMKEYS: &[KeySpec] = &[
+    KeySpec::Range {
+        first: 1,
+        last: 1,
+        step: 1,
+    },
+    KeySpec::KeyNum { numkeys: 2 },
+];
+const SORT_KEYS: &[KeySpec] = &[
+    KeySpec::Range {
+        first: 1,
+        last: 1,
+        step: 1,
+    },
+    KeySpec::Keyword { keyword: "STORE" },
+];
+
+const fn spec(
+    name: &'static str,
+    arity: i64,
+    flags: &'static [&'static str],
+    keys: &'static [KeySpec],
+    group: &'static str,
+    since: &'static str,
+    summary: &'static str,
+) -> CommandSpec {
+    CommandSpec {
+        name,
+        arity,
+        flags,
+        keys,
+        group,
+        since,
+        summary,
+    }
+}
+
+/// Every implemented command, sorted by name.
+#[rustfmt::skip]
+pub const COMMAND_TABLE: &[CommandSpec] = &[
+    spec("acl", -2, ADMIN, NO_KEYS, "server", "6.0.0", "A container for Access List Control commands."),
+    spec("append", 3, WDF, FIRST, "string", "2.0.0", "Appends a string to the value of a key. Creates the key if it doesn't exist."),
+    spec("auth", -2, NO_AUTH, NO_KEYS, "connection", "1.0.0", "Authenticates the connection."),
+    spec("bgsave", -1, ADMIN, NO_KEYS, "server", "1.0.0", "Asynchronously saves the database(s) to disk."),
+    spec("blmove", 6, WDB, FIRST_TWO, "list", "6.2.0", "Pops an element from a list, pushes it to another list and returns it. Blocks until an element is available otherwise."),
+    spec("blmpop", -5, WB, TIMEOUT_NUMKEYS, "list", "7.0.0", "Pops the first element from one of multiple lists. Blocks until an element is available otherwise."),
+    spec("blpop", -3, WB, ALL_BUT_LAST, "list", "2.0.0", "Removes and returns the first element in a list. Blocks until an element is available otherwise."),
+    spec("brpop", -3, WB, ALL_BUT_LAST, "list", "2.0.0", "Removes and returns the last element in a list. Blocks until an element is available otherwise."),
+    spec("bzmpop", -5, WB, TIMEOUT_NUMKEYS, "sorted-set", "7.0.0", "Removes and returns a member by score from one or more sorted sets. Blocks until a member is available otherwise."),
+    spec("bzpopmax", -3, WFB, ALL_BUT_LAST, "sorted-set", "5.0.0", "Removes and returns the member with the highest score from one or more sorted sets. Blocks until a member is available otherwise."),
+    spec("bzpopmin", -3, WFB, ALL_BUT_LAST, "sorted-set", "5.0.0", "Removes and returns the member with the lowest score from one or more sorted sets. Blocks until a member is available otherwise."),
+    spec("client", -2, ADMIN, NO_KEYS, "connection", "2.4.0", "Lists, inspects and manages the connected clients."),
+    spec("command", -1, STALE, NO_KEYS, "server", "2.8.13", "Returns detailed information about all commands."),
+    spec("config", -2, ADMIN, NO_KEYS, "server", "2.0.0", "Gets, sets, persists or resets the runtime configuration."),
+    spec("copy", -3, WD, FIRST_TWO, "generic", "6.2.0", "Copies the value of a key to a new key."),
+    spec("dbsize", 1, RF, NO_KEYS, "server", "1.0.0", "Returns the number of keys in the database."),
+    spec("debug", -2, ADMIN, NO_KEYS, "server", "1.0.0", "A container for debugging commands."),
+    spec("decr", 2, WDF, FIRST, "string", "1.0.0", "Decrements the integer value of a key by one. Uses 0 as initial value if the key doesn't exist."),
+    spec("decrby", 3, WDF, FIRST, "string", "1.0.0", "Decrements a number from the integer value of a key. Uses 0 as initial value if the key doesn't exist."),
+    spec("del", -2, W, ALL, "generic", "1.0.0", "Deletes one or more keys."),
+    spec("discard", 1, TRANSACTION_FAST, NO_KEYS, "transactions", "2.0.0", "Discards a transaction."),
+    spec("dump", 2, R, FIRST, "generic", "2.6.0", "Returns a serialized representation of the value stored at a key."),
+    spec("exec", 1, TRANSACTION, NO_KEYS, "transactions", "1.2.0", "Executes all commands in a transaction."),
+    spec("exists", -2, RF, ALL, "generic", "1.0.0", "Determines whether one or more keys exist."),
+    spec("expire", -3, WF, FIRST, "generic", "1.0.0", "Sets the expiration time of a key in seconds."),
+    spec("expireat", -3, WF, FIRST, "generic", "1.2.0", "Sets the expiration time of a key to a Unix timestamp."),
+    spec("expiretime", 2, RF, FIRST, "generic", "7.0.0", "Returns the expiration time of a key as a Unix timestamp."),
+    spec("flushall", -1, W, NO_KEYS, "server", "1.0.0", "Removes all keys from all databases."),
+    spec("flushdb", -1, W, NO_KEYS, "server", "1.0.0", "Removes all keys from the current database."),
+    spec("get", 2, RF, FIRST, "string", "1.0.0", "Returns the string value of a key."),
+    spec("getdel", 2, WF, FIRST, "string", "6.2.0", "Returns the string value of a key after deleting the key."),
+    spec("getex", -2, WF, FIRST, "string", "6.2.0", "Returns the string value of a key after setting its expiration time."),
+    spec("getrange", 4, R, FIRST, "string", "2.4.0", "Returns a substring of the string stored at a key."),
+    spec("getset", 3, WDF, FIRST, "string", "1.0.0", "Returns the previous string value of a key after setting it to a new value."),
+    spec("hdel", -3, WF, FIRST, "hash", "2.0.0", "Deletes one or more fields and their values from a hash. Deletes the hash if no fields remain."),
+    spec("hello", -1, NO_AUTH, NO_KEYS, "connection", "6.0.0", "Handshakes with the Redis server."),
+    spec("hexists", 3, RF, FIRST, "hash", "2.0.0", "Determines whether a field exists in a hash."),
+    spec("hexpire", -6, WF, FIRST, "hash", "7.4.0", "Set expiry for hash field using relative time to expire (seconds)"),
+    spec("hexpireat", -6, WF, FIRST, "hash", "7.4.0", "Set expiry for hash field using an absolute Unix timestamp (seconds)"),
+    spec("hget", 3, RF, FIRST, "hash", "2.0.0", "Returns the value of a field in a hash."),
+    spec("hgetall", 2, R, FIRST, "hash", "2.0.0", "Returns all fields and values in a hash."),
+    spec("hincrby", 4, WDF, FIRST, "hash", "2.0.0", "Increments the integer value of a field in a hash by a number. Uses 0 as initial value if the field doesn't exist."),
+    spec("hincrbyfloat", 4, WDF, FIRST, "hash", "2.6.0", "Increments the floating point value of a field by a number. Uses 0 as initial value if the field doesn't exist."),
+    spec("hkeys", 2, R, FIRST, "hash", "2.0.0", "Returns all fields in a hash."),
+    spec("hlen", 2, RF, FIRST, "hash", "2.0.0", "Returns the number of fields in a hash."),
+    spec("hmget", -3, RF, FIRST, "hash", "2.0.0", "Returns the values of all fields in a hash."),
+    spec("hpersist", -5, WF, FIRST, "hash", "7.4.0", "Removes the expiration time for each specified field"),
+    spec("hpexpire", -6, WF, FIRST, "hash", "7.4.0", "Set expiry for hash field using relative time to expire (milliseconds)"),
+    spec("hpexpireat", -6, WF, FIRST, "hash", "7.4.0", "Set expiry for hash field using an absolute Unix timestamp (milliseconds)"),
+    spec("hpttl", -5, RF, FIRST, "hash", "7.4.0", "Returns the TTL in milliseconds of a hash field."),
+    spec("hrandfield", -2, R, FIRST, "hash", "6.2.0", "Returns one or more random fields from a hash."),
+    spec("hscan", -3, R, FIRST, "hash", "2.8.0", "Iterates over fields and values of a hash."),
+    spec("hset", -4, WDF, FIRST, "hash", "2.0.0", "Creates or modifies the value of a field in a hash."),
+    spec("hsetnx", 4, WDF, FIRST, "hash", "2.0.0", "Sets the value of a field in a hash only when the field doesn't exist."),
+    spec("hstrlen", 3, RF, FIRST, "hash", "3.2.0", "Returns the length of the value of a field."),
+    spec("httl", -5, RF, FIRST, "hash", "7.4.0", "Returns the TTL in seconds of a hash field."),
+    spec("hvals", 2, R, FIRST, "hash", "2.0.0", "Returns all values in a hash."),
+    spec("incr", 2, WDF, FIRST, "string", "1.0.0", "Increments the integer value of a key by one. Uses 0 as initial value if the key doesn't exist."),
+    spec("incrby", 3, WDF, FIRST, "string", "1.0.0", "Increments the integer value of a key by a number. Uses 0 as initial value if the key doesn't exist."),
+    spec("incrbyfloat", 3, WDF, FIRST, "string", "2.6.0", "Increment the floating point value of a key by a number. Uses 0 as initial value if the key doesn't exist."),
+    spec("info", -1, STALE, NO_KEYS, "server", "1.0.0", "Returns information and statistics about the server."),
+    spec("keys", 2, R, NO_KEYS, "generic", "1.0.0", "Returns all key names that match a pattern."),
+    spec("lastsave", 1, STALE_FAST, NO_KEYS, "server", "1.0.0", "Returns the Unix timestamp of the last successful save to disk."),
+    spec("latency", -2, ADMIN, NO_KEYS, "server", "2.8.13", "A container for latency diagnostics commands."),
+    spec("lcs", -3, R, FIRST_TWO, "string", "7.0.0", "Finds the longest common substring."),
+    spec("lindex", 3, R, FIRST, "list", "1.0.0", "Returns an element from a list by its index."),
+    spec("linsert", 5, WD, FIRST, "list", "2.2.0", "Inserts an element before or after another element in a list."),
+    spec("llen", 2, RF, FIRST, "list", "1.0.0", "Returns the length of a list."),
+    spec("lmove", 5, WD, FIRST_TWO, "list", "6.2.0", "Returns an element after popping it from one list and pushing it to another. Deletes the list if the last element was moved."),
+    spec("lmpop", -4, W, NUMKEYS, "list", "7.0.0", "Returns multiple elements from a list after removing them. Deletes the list if the last element was popped."),
+    spec("lolwut", -1, RF, NO_KEYS, "server", "5.0.0", "Displays computer art and the Redis version"),
+    spec("lpop", -2, WF, FIRST, "list", "1.0.0", "Returns the first elements in a list after removing it. Deletes the list if the last element was popped."),
+    spec("lpos", -3, R, FIRST, "list", "6.0.6", "Returns the index of matching elements in a list."),
+    spec("lpush", -3, WDF, FIRST, "list", "1.0.0", "Prepends one or more elements to a list. Creates the key if it doesn't exist."),
+    spec("lrange", 4, R, FIRST, "list", "1.0.0", "Returns a range of elements from a list."),
+    spec("lrem", 4, W, FIRST, "list", "1.0.0", "Removes elements from a list. Deletes the list if the last element was removed."),
+    spec("lset", 4, WD, FIRST, "list", "1.0.0", "Sets the value of an element in a list by its index."),
+    spec("ltrim", 4, W, FIRST, "list", "1.0.0", "Removes elements from both ends a list. Deletes the list if all elements were trimmed."),
+    spec("memory", -2, R, SECOND, "server", "4.0.0", "A container for memory diagnostics commands."),
+    spec("mget", -2, RF, ALL, "string", "1.0.0", "Atomically returns the string values of one or more keys."),
+    spec("monitor", 1, ADMIN, NO_KEYS, "server", "1.0.0", "Listens for all requests received by the server in real-time."),
+    spec("move", 3, WF, FIRST, "generic", "1.0.0", "Moves a key to another database."),
+    spec("mset", -3, WD, PAIRS, "string", "1.0.1", "Atomically creates or modifies the string values of one or more keys."),
+    spec("msetnx", -3, WD, PAIRS, "string", "1.0.1", "Atomically modifies the string values of one or more keys only when all keys don't exist."),
+    spec("multi", 1, TRANSACTION_FAST, NO_KEYS, "transactions", "1.2.0", "Starts a transaction."),
+    spec("object", -2, R, SECOND, "generic", "2.2.3", "Returns the internal encoding, reference count, idle time or access frequency of a key."),
+    spec("persist", 2, WF, FIRST, "generic", "2.2.0", "Removes the expiration time of a key."),
+    spec("pexpire", -3, WF, FIRST, "generic", "2.6.0", "Sets the expiration time of a key in milliseconds."),
+    spec("pexpireat", -3, WF, FIRST, "generic", "2.6.0", "Sets the expiration time of a key to a Unix milliseconds timestamp."),
+    spec("pexpiretime", 2, RF, FIRST, "generic", "7.0.0", "Returns the expiration time of a key as a Unix milliseconds timestamp."),
+    spec("ping", -1, STALE_FAST, NO_KEYS, "connection", "1.0.0", "Returns the server's liveliness response."),
+    spec("psetex", 4, WD, FIRST, "string", "2.6.0", "Sets both string value and expiration time in milliseconds of a key. The key is created if it doesn't exist."),
+    spec("psubscribe", -2, PUBSUB, NO_KEYS, "pubsub", "2.0.0", "Listens for messages published to channels that match one or more patterns."),
+    spec("pttl", 2, RF, FIRST, "generic", "2.6.0", "Returns the expiration time in milliseconds of a key."),
+    spec("publish", 3, PUBSUB_FAST, NO_KEYS, "pubsub", "2.0.0", "Posts a message to a channel."),
+    spec("pubsub", -2, PUBSUB_STALE, NO_KEYS, "pubsub", "2.8.0", "A container for Pub/Sub commands."),
+    spec("punsubscribe", -1, PUBSUB, NO_KEYS, "pubsub", "2.0.0", "Stops listening to messages published to channels that match one or more patterns."),
+    spec("quit", -1, NO_AUTH, NO_KEYS, "connection", "1.0.0", "Closes the connection."),
+    spec("randomkey", 1, R, NO_KEYS, "generic", "1.0.0", "Returns a random key name from the database."),
+    spec("rename", 3, W, FIRST_TWO, "generic", "1.0.0", "Renames a key and overwrites the destination."),
+    spec("renamenx", 3, WF, FIRST_TWO, "generic", "1.0.0", "Renames a key only when the target key name doesn't exist."),
+    spec("reset", 1, NO_AUTH, NO_KEYS, "connection", "6.2.0", "Resets the connection."),
+    spec("restore", -4, WD, FIRST, "generic", "2.6.0", "Creates a key from the serialized representation of a value."),
+    spec("rpop", -2, WF, FIRST, "list", "1.0.0", "Returns and removes the last elements of a list. Deletes the list if the last element was popped."),
+    spec("rpoplpush", 3, WD, FIRST_TWO, "list", "1.2.0", "Returns the last element of a list after removing and pushing it to another list. Deletes the list if the last element was popped."),
+    spec("rpush", -3, WDF, FIRST, "list", "1.0.0", "Appends one or more elements to a list. Creates the key if it doesn't exist."),
+    spec("sadd", -3, WDF, FIRST, "set", "1.0.0", "Adds one or more members to a set. Creates the key if it doesn't exist."),
+    spec("save", 1, ADMIN, NO_KEYS, "server", "1.0.0", "Synchronously saves the database(s) to disk."),
+    spec("scan", -2, R, NO_KEYS, "generic", "2.8.0", "Iterates over the key names in the database."),
+    spec("scard", 2, RF, FIRST, "set", "1.0.0", "Returns the number of members in a set."),
+    spec("sdiff", -2, R, ALL, "set", "1.0.0", "Returns the difference of multiple sets."),
+    spec("sdiffstore", -3, WD, ALL, "set", "1.0.0", "Stores the difference of multiple sets in a key."),
+    spec("select", 2, STALE_FAST, NO_KEYS, "connection", "1.0.0", "Changes the selected database."),
+    spec("set", -3, WD, FIRST, "string", "1.0.0", "Sets the string value of a key, ignoring its type. The key is created if it doesn't exist."),
+    spec("setex", 4, WD, FIRST, "string", "2.0.0", "Sets the string value and expiration time of a key. Creates the key if it doesn't exist."),
+    spec("setnx", 3, WDF, FIRST, "string", "1.0.0", "Set the string value of a key only when the key doesn't exist."),
+    spec("setrange", 4, WD, FIRST, "string", "2.2.0", "Overwrites a part of a string value with another by an offset. Creates the key if it doesn't exist."),
+    spec("shutdown", -1, ADMIN, NO_KEYS, "server", "1.0.0", "Synchronously saves the database(s) to disk and shuts down the Redis server."),
+    spec("sinter", -2, R, ALL, "set", "1.0.0", "Returns the intersect of multiple sets."),
+    spec("sintercard", -3, R, NUMKEYS, "set", "7.0.0", "Returns the number of members of the intersect of multiple sets."),
+    spec("sinterstore", -3, WD, ALL, "set", "1.0.0", "Stores the intersect of multiple sets in a key."),
+    spec("sismember", 3, RF, FIRST, "set", "1.0.0", "Determines whether a member belongs to a set."),
+    spec("smembers", 2, R, FIRST, "set", "1.0.0", "Returns all members of a set."),
+    spec("smismember", -3, RF, FIRST, "set", "6.2.0", "Determines whether multiple members belong to a set."),
+    spec("smove", 4, WF, FIRST_TWO, "set", "1.0.0", "Moves a member from one set to another."),
+    spec("sort", -2, WD, SORT_KEYS, "generic", "1.0.0", "Sorts the elements in a list, a set, or a sorted set, optionally storing the result."),
+    spec("spop", -2, WF, FIRST, "set", "1.0.0", "Returns one or more random members from a set after removing them. Deletes the set if the last member was popped."),
+    spec("spublish", 3, PUBSUB_FAST, FIRST, "pubsub", "7.0.0", "Posts a message to a shard channel."),
+    spec("srandmember", -2, R, FIRST, "set", "1.0.0", "Get one or multiple random members from a set"),
+    spec("srem", -3, WF, FIRST, "set", "1.0.0", "Removes one or more members from a set. Deletes the set if the last member was removed."),
+    spec("sscan", -3, R, FIRST, "set", "2.8.0", "Iterates over members of a set."),
+    spec("ssubscribe", -2, PUBSUB, ALL, "pubsub", "7.0.0", "Listens for messages published to shard channels."),
+    spec("strlen", 2, RF, FIRST, "string", "2.2.0", "Returns the length of a string value."),
+    spec("subscribe", -2, PUBSUB, NO_KEYS, "pubsub", "2.0.0", "Listens for messages published to channels."),
+    spec("sunion", -2, R, ALL, "set", "1.0.0", "Returns the union of multiple sets."),
+    spec("sunionstore", -3, WD, ALL, "set", "1.0.0", "Stores the union of multiple sets in a key."),
+    spec("sunsubscribe", -1, PUBSUB, ALL, "pubsub", "7.0.0", "Stops listening to messages posted to shard channels."),
+    spec("swapdb", 3, WF, NO_KEYS, "server", "4.0.0", "Swaps two Redis databases."),
+    spec("time", 1, STALE_FAST, NO_KEYS, "server", "2.6.0", "Returns the server time."),
+    spec("touch", -2, RF, ALL, "generic", "3.2.1", "Returns the number of existing keys out of those specified after updating the time they were last accessed."),
+    spec("ttl", 2, RF, FIRST, "generic", "1.0.0", "Returns the expiration time in seconds of a key."),
+    spec("type", 2, RF, FIRST, "generic", "1.0.0", "Determines the type of value stored at a key."),
+    spec("unlink", -2, WF, ALL, "generic", "4.0.0", "Asynchronously deletes one or more keys."),
+    spec("unsubscribe", -1, PUBSUB, NO_KEYS, "pubsub", "2.0.0", "Stops listening to messages posted to channels."),
+    spec("wait", 3, NOSCRIPT, NO_KEYS, "generic", "3.0.0", "Blocks until the asynchronous replication of all preceding write commands sent by the connection is completed."),
+    spec("zadd", -4, WDF, FIRST, "sorted-set", "1.2.0", "Adds one or more members to a sorted set, or updates their scores. Creates the key if it doesn't exist."),
+    spec("zcard", 2, RF, FIRST, "sorted-set", "1.2.0", "Returns the number of members in a sorted set."),
+    spec("zdiff", -3, R, NUMKEYS, "sorted-set", "6.2.0", "Returns the difference between multiple sorted sets."),
+    spec("zdiffstore", -4, WD, STORE_NUMKEYS, "sorted-set", "6.2.0", "Stores the difference of multiple sorted sets in a key."),
+    spec("zincrby", 4, WDF, FIRST, "sorted-set", "1.2.0", "Increments the score of a member in a sorted set."),
+    spec("zinter", -3, R, NUMKEYS, "sorted-set", "6.2.0", "Returns the intersect of multiple sorted sets."),
+    spec("zinterstore", -4, WD, STORE_NUMKEYS, "sorted-set", "2.0.0", "Stores the intersect of multiple sorted sets in a key."),
+    spec("zmpop", -4, W, NUMKEYS, "sorted-set", "7.0.0", "Returns the highest- or lowest-scoring members from one or more sorted sets after removing them. Deletes the sorted set if the last member was popped."),
+    spec("zpopmax", -2, WF, FIRST, "sorted-set", "5.0.0", "Returns the highest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+    spec("zpopmin", -2, WF, FIRST, "sorted-set", "5.0.0", "Returns the lowest-scoring members from a sorted set after removing them. Deletes the sorted set if the last member was popped."),
+    spec("zrandmember", -2, R, FIRST, "sorted-set", "6.2.0", "Returns one or more random members from a sorted set."),
+    spec("zrange", -4, R, FIRST, "sorted-set", "1.2.0", "Returns members in a sorted set within a range of indexes."),
+    spec("zrangebylex", -4, R, FIRST, "sorted-set", "2.8.9", "Returns members in a sorted set within a lexicographical range."),
+    spec("zrangebyscore", -4, R, FIRST, "sorted-set", "1.0.5", "Returns members in a sorted set within a range of scores."),
+    spec("zrank", -3, RF, FIRST, "sorted-set", "2.0.0", "Returns the index of a member in a sorted set ordered by ascending scores."),
+    spec("zrem", -3, WF, FIRST, "sorted-set", "1.2.0", "Removes one or more members from a sorted set. Deletes the sorted set if all members were removed."),
+    spec("zremrangebyrank", 4, W, FIRST, "sorted-set", "2.0.0", "Removes members in a sorted set within a range of indexes. Deletes the sorted set if all members were removed."),
+    spec("zremrangebyscore", 4, W, FIRST, "sorted-set", "1.2.0", "Removes members in a sorted set within a range of scores. Deletes the sorted set if all members were removed."),
+    spec("zrevrange", -4, R, FIRST, "sorted-set", "1.2.0", "Returns members in a sorted set within a range of indexes in reverse order."),
+    spec("zrevrank", -3, RF, FIRST, "sorted-set", "2.0.0", "Returns the index of a member in a sorted set ordered by descending scores."),
+    spec("zscan", -3, R, FIRST, "sorted-set", "2.8.0", "Iterates over members and scores of a sorted set."),
+    spec("zscore", 3, RF, FIRST, "sorted-set", "1.2.0", "Returns the score of a member in a sorted set."),
+    spec("zunion", -3, R, NUMKEYS, "sorted-set", "6.2.0", "Returns the union of multiple sorted sets."),
+    spec("zunionstore", -4, WD, STORE_NUMKEYS, "sorted-set", "2.0.0", "Stores the union of multiple sorted sets in a key."),
+];
+
+/// Looks up a command by name, case-insensitively.
+pub fn lookup_command(name: &str) -> Option<&'static CommandSpec> {
+    let name = name.to_ascii_lowercase();
+    COMMAND_TABLE
+        .binary_search_by(|spec| spec.name.cmp(name.as_str()))
+        .ok()
+        .map(|i| &COMMAND_TABLE[i])
+}
@@ -0,0 +1,227 @@
+use super::{
+    command_args, command_name, record_execution, track_reads, validate_command, Command,
+    CommandError, CommandExecutor, ConnectionCommand, Discard, Exec, Multi, RESP_OK,
+};
+use crate::{Backend, Connection, RespArray, RespFrame, SimpleError};
+use std::time::Instant;
+
+impl CommandExecutor for Multi {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR MULTI needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Multi {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        if !connection.begin_transaction() {
+            return SimpleError::new("ERR MULTI calls can not be nested").into();
+        }
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Multi {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["multi"], 0)?;
+        Ok(Multi)
+    }
+}
+
+impl CommandExecutor for Exec {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR EXEC needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Exec {
+    fn execute_on(&self, connection: &mut Connection, backend: &Backend) -> RespFrame {
+        if !connection.in_transaction() {
+            return SimpleError::new("ERR EXEC without MULTI").into();
+        }
+        let failed = connection.is_transaction_failed();
+        let queued = connection.take_transaction().unwrap_or_default();
+        if failed {
+            return SimpleError::new("EXECABORT Transaction discarded because of previous errors.")
+                .into();
+        }
+        let _guard = backend.transaction_lock();
+        let replies: Vec<_> = queued
+            .into_iter()
+            .map(|frame| execute_queued(frame, connection, backend))
+            .collect();
+        RespArray::new(replies).into()
+    }
+}
+
+impl TryFrom<RespArray> for Exec {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["exec"], 0)?;
+        Ok(Exec)
+    }
+}
+
+impl CommandExecutor for Discard {
+    fn execute(self, _: &Backend) -> RespFrame {
+        SimpleError::new("ERR DISCARD needs a connection").into()
+    }
+}
+
+impl ConnectionCommand for Discard {
+    fn execute_on(&self, connection: &mut Connection, _: &Backend) -> RespFrame {
+        if connection.take_transaction().is_none() {
+            return SimpleError::new("ERR DISCARD without MULTI").into();
+        }
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for Discard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["discard"], 0)?;
+        Ok(Discard)
+    }
+}
+
+// runs a command queued by MULTI, which was checked to parse when it was queued. Blocking
+// commands don't block inside a transaction, replying as if they timed out like in redis
+fn execute_queued(frame: RespFrame, connection: &mut Connection, backend: &Backend) -> RespFrame {
+    let name = command_name(&frame);
+    let tracked = connection.is_tracking_reads().then(|| command_args(&frame));
+    let cmd = match Command::try_from(frame) {
+        Ok(cmd) => cmd,
+        Err(e) => return SimpleError::new(format!("ERR {}", e)).into(),
+    };
+    let recognized = !matches!(cmd, Command::Unrecognized(_));
+    // SELECT inside the transaction applies to the commands after it
+    let backend = backend.select(connection.db()).unwrap_or(backend.clone());
+    let start = Instant::now();
+    let frame = if let Some(cmd) = cmd.as_connection() {
+        cmd.execute_on(connection, &backend)
+    } else {
+        cmd.execute(&backend)
+    };
+    if let (true, Some(name)) = (recognized, &name) {
+        record_execution(&backend, name, &frame, start.elapsed());
+    }
+    if let Some(args) = tracked {
+        track_reads(connection, &args);
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulkString, RespDecode};
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn command(args: &[&str]) -> RespFrame {
+        RespArray::new(
+            args.iter()
+                .map(|arg| BulkString::new(*arg).into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn test_multi_exec_discard_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*1\r\n$5\r\nmulti\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let _: Multi = frame.try_into()?;
+
+        buf.extend_from_slice(b"*2\r\n$4\r\nexec\r\n$3\r\nnow\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Exec::try_from(frame).is_err());
+
+        buf.extend_from_slice(b"*1\r\n$7\r\ndiscard\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let _: Discard = frame.try_into()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_exec_discard() -> Result<()> {
+        let backend = Backend::new();
+        let laddr = "127.0.0.1:6379".parse()?;
+        let mut connection = Connection::new(&backend, "127.0.0.1:50000".parse()?, laddr);
+        assert_eq!(
+            Exec.execute_on(&mut connection, &backend),
+            SimpleError::new("ERR EXEC without MULTI").into()
+        );
+        assert_eq!(
+            Discard.execute_on(&mut connection, &backend),
+            SimpleError::new("ERR DISCARD without MULTI").into()
+        );
+
+        assert_eq!(Multi.execute_on(&mut connection, &backend), RESP_OK.clone());
+        assert_eq!(
+            Multi.execute_on(&mut connection, &backend),
+            SimpleError::new("ERR MULTI calls can not be nested").into()
+        );
+        connection.queue_command(command(&["set", "counter", "1"]));
+        connection.queue_command(command(&["incr", "counter"]));
+        connection.queue_command(command(&["select", "2"]));
+        connection.queue_command(command(&["set", "counter", "10"]));
+        assert_eq!(backend.get("counter"), None);
+        assert_eq!(
+            Exec.execute_on(&mut connection, &backend),
+            RespArray::new(vec![
+                RESP_OK.clone(),
+                RespFrame::Integer(2),
+                RESP_OK.clone(),
+                RESP_OK.clone(),
+            ])
+            .into()
+        );
+        assert!(!connection.in_transaction());
+        assert_eq!(backend.get("counter"), Some(BulkString::new("2").into()));
+        assert_eq!(connection.db(), 2);
+
+        Multi.execute_on(&mut connection, &backend);
+        connection.queue_command(command(&["set", "discarded", "1"]));
+        assert_eq!(
+            Discard.execute_on(&mut connection, &backend),
+            RESP_OK.clone()
+        );
+        assert!(!connection.in_transaction());
+
+        // a command that could not be queued aborts the whole transaction
+        Multi.execute_on(&mut connection, &backend);
+        connection.queue_command(command(&["set", "aborted", "1"]));
+        connection.fail_transaction();
+        assert_eq!(
+            Exec.execute_on(&mut connection, &backend),
+            SimpleError::new("EXECABORT Transaction discarded because of previous errors.").into()
+        );
+        assert!(!connection.in_transaction());
+        assert_eq!(backend.select(2).unwrap().get("aborted"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_expiry_during_exec() {
+        let backend = Backend::new();
+        backend.set("stale".to_string(), RespFrame::Integer(1));
+        backend.db.expire.insert("stale".to_string(), 1);
+
+        let guard = backend.transaction_lock();
+        let cycle = std::thread::spawn({
+            let backend = backend.clone();
+            move || backend.active_expire_cycle()
+        });
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(backend.db.expire.contains_key("stale"));
+        drop(guard);
+        assert_eq!(cycle.join().unwrap(), 1);
+        assert!(!backend.db.expire.contains_key("stale"));
+    }
+}
@@ -0,0 +1,2078 @@
+use super::{
+    extract_args, extract_bytes, extract_cursor, extract_integer, extract_string, extract_timeout,
+    parse_blocking_pop, parse_scan_option, scan_matches, scan_reply, validate_command,
+    validate_variadic_command, BZMPop, BZPopMax, BZPopMin, BlockingCommand, CommandError,
+    CommandExecutor, ZAdd, ZCard, ZDiff, ZDiffStore, ZIncrBy, ZInter, ZInterStore, ZMPop, ZPopMax,
+    ZPopMin, ZRandMember, ZRange, ZRangeByLex, ZRangeByScore, ZRank, ZRem, ZRemRangeByRank,
+    ZRemRangeByScore, ZRevRange, ZRevRank, ZScan, ZScore, ZUnion, ZUnionStore,
+};
+
+use crate::{
+    format_score, Aggregate, Backend, BackendError, BulkString, RespArray, RespFrame, RespNull,
+    ScoreCondition, ScoredMember, SetCondition, SetOp, ZRangeBy, ZSetEnd,
+};
+use std::ops::Bound;
+use std::time::Duration;
+
+impl CommandExecutor for ZAdd {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        if self.incr {
+            // the parser makes sure INCR comes with a single pair
+            let Some((delta, member)) = self.members.into_iter().next() else {
+                return RespFrame::Null(RespNull);
+            };
+            return match backend.zincr_by(self.key, member, delta, self.condition, self.comparison)
+            {
+                Ok(Some(score)) => score_frame(score),
+                Ok(None) => RespFrame::Null(RespNull),
+                Err(e) => e.into(),
+            };
+        }
+        match backend.zadd(self.key, self.members, self.condition, self.comparison) {
+            Ok((added, updated)) if self.changed => RespFrame::Integer((added + updated) as i64),
+            Ok((added, _)) => RespFrame::Integer(added as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZIncrBy {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zincr_by(self.key, self.member, self.increment, None, None) {
+            Ok(Some(score)) => score_frame(score),
+            // without NX/XX/GT/LT the update always happens
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zscore(&self.key, &self.member) {
+            Ok(Some(score)) => score_frame(score),
+            Ok(None) => RespFrame::Null(RespNull),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zrank(backend, &self.key, &self.member, false, self.with_score)
+    }
+}
+
+impl CommandExecutor for ZRevRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zrank(backend, &self.key, &self.member, true, self.with_score)
+    }
+}
+
+fn zrank(backend: &Backend, key: &str, member: &[u8], rev: bool, with_score: bool) -> RespFrame {
+    match backend.zrank(key, member, rev) {
+        Ok(Some((rank, score))) if with_score => {
+            RespArray::new([RespFrame::Integer(rank as i64), score_frame(score)]).into()
+        }
+        Ok(Some((rank, _))) => RespFrame::Integer(rank as i64),
+        Ok(None) => RespFrame::Null(RespNull),
+        Err(e) => e.into(),
+    }
+}
+
+impl CommandExecutor for ZRem {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zrem(&self.key, &self.members) {
+            Ok(removed) => RespFrame::Integer(removed as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZRemRangeByScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zremrange(backend, &self.key, &ZRangeBy::Score(self.min, self.max))
+    }
+}
+
+impl CommandExecutor for ZRemRangeByRank {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zremrange(backend, &self.key, &ZRangeBy::Rank(self.start, self.stop))
+    }
+}
+
+fn zremrange(backend: &Backend, key: &str, range: &ZRangeBy) -> RespFrame {
+    match backend.zremrange(key, range) {
+        Ok(removed) => RespFrame::Integer(removed as i64),
+        Err(e) => e.into(),
+    }
+}
+
+impl CommandExecutor for ZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop(backend, &self.key, ZSetEnd::Min, self.count)
+    }
+}
+
+impl CommandExecutor for ZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop(backend, &self.key, ZSetEnd::Max, self.count)
+    }
+}
+
+impl CommandExecutor for BZPopMin {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for BZPopMax {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl BlockingCommand for BZPopMin {
+    fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        zpop_one(backend, &self.keys, ZSetEnd::Min)
+    }
+}
+
+impl BlockingCommand for BZPopMax {
+    fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        zpop_one(backend, &self.keys, ZSetEnd::Max)
+    }
+}
+
+// without a count the reply is the member and its score, with one it is an array of
+// [member, score] pairs
+fn zpop(backend: &Backend, key: &str, end: ZSetEnd, count: Option<usize>) -> RespFrame {
+    match backend.zpop(key, end, count.unwrap_or(1)) {
+        Ok(members) if count.is_some() => members_frame(members, true),
+        Ok(members) => {
+            let frames = members
+                .into_iter()
+                .flat_map(|(member, score)| [BulkString::new(member).into(), score_frame(score)])
+                .collect::<Vec<_>>();
+            RespArray::new(frames).into()
+        }
+        Err(e) => e.into(),
+    }
+}
+
+impl CommandExecutor for ZMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zpop_many(backend, &self.keys, self.end, self.count).unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl CommandExecutor for BZMPop {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        self.try_execute(backend)
+            .unwrap_or(RespFrame::Null(RespNull))
+    }
+}
+
+impl BlockingCommand for BZMPop {
+    fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    fn try_execute(&self, backend: &Backend) -> Option<RespFrame> {
+        zpop_many(backend, &self.keys, self.end, self.count)
+    }
+}
+
+// pops up to `count` members from the first non-empty sorted set, along with its key
+fn zpop_first<'a>(
+    backend: &Backend,
+    keys: &'a [String],
+    end: ZSetEnd,
+    count: usize,
+) -> Result<Option<(&'a str, Vec<ScoredMember>)>, BackendError> {
+    for key in keys {
+        let members = backend.zpop(key, end, count)?;
+        if !members.is_empty() {
+            return Ok(Some((key, members)));
+        }
+    }
+    Ok(None)
+}
+
+// replies with the key, member and score popped from the first non-empty sorted set,
+// None if they are all empty
+fn zpop_one(backend: &Backend, keys: &[String], end: ZSetEnd) -> Option<RespFrame> {
+    match zpop_first(backend, keys, end, 1) {
+        Ok(Some((key, mut members))) => {
+            let (member, score) = members.remove(0);
+            let key = BulkString::new(key).into();
+            let member = BulkString::new(member).into();
+            Some(RespArray::new([key, member, score_frame(score)]).into())
+        }
+        Ok(None) => None,
+        Err(e) => Some(e.into()),
+    }
+}
+
+// replies with the key and the [member, score] pairs popped from it, None if all sorted
+// sets are empty
+fn zpop_many(backend: &Backend, keys: &[String], end: ZSetEnd, count: usize) -> Option<RespFrame> {
+    match zpop_first(backend, keys, end, count) {
+        Ok(Some((key, members))) => {
+            let key = BulkString::new(key).into();
+            Some(RespArray::new([key, members_frame(members, true)]).into())
+        }
+        Ok(None) => None,
+        Err(e) => Some(e.into()),
+    }
+}
+
+impl CommandExecutor for ZUnion {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let members = backend.zcombine(SetOp::Union, &self.keys, &self.weights, self.aggregate);
+        zcombine(members, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZInter {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let members = backend.zcombine(SetOp::Inter, &self.keys, &self.weights, self.aggregate);
+        zcombine(members, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZDiff {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let members = backend.zcombine(SetOp::Diff, &self.keys, &[], Aggregate::Sum);
+        zcombine(members, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZUnionStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let len = backend.zcombine_store(
+            SetOp::Union,
+            self.destination,
+            &self.keys,
+            &self.weights,
+            self.aggregate,
+        );
+        zcombine_store(len)
+    }
+}
+
+impl CommandExecutor for ZInterStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let len = backend.zcombine_store(
+            SetOp::Inter,
+            self.destination,
+            &self.keys,
+            &self.weights,
+            self.aggregate,
+        );
+        zcombine_store(len)
+    }
+}
+
+impl CommandExecutor for ZDiffStore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let len = backend.zcombine_store(
+            SetOp::Diff,
+            self.destination,
+            &self.keys,
+            &[],
+            Aggregate::Sum,
+        );
+        zcombine_store(len)
+    }
+}
+
+fn zcombine(members: Result<Vec<ScoredMember>, BackendError>, with_scores: bool) -> RespFrame {
+    match members {
+        Ok(members) => members_frame(members, with_scores),
+        Err(e) => e.into(),
+    }
+}
+
+fn zcombine_store(len: Result<usize, BackendError>) -> RespFrame {
+    match len {
+        Ok(len) => RespFrame::Integer(len as i64),
+        Err(e) => e.into(),
+    }
+}
+
+impl CommandExecutor for ZScan {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let (cursor, members) = match backend.zscan(&self.key, self.cursor, self.count) {
+            Ok(page) => page,
+            Err(e) => return e.into(),
+        };
+        let mut frames = Vec::with_capacity(members.len() * 2);
+        for (member, score) in members {
+            if !scan_matches(&self.pattern, &member) {
+                continue;
+            }
+            frames.push(BulkString::new(member).into());
+            frames.push(score_frame(score));
+        }
+        scan_reply(cursor, frames)
+    }
+}
+
+impl CommandExecutor for ZRandMember {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let members = match backend.zrandmember(&self.key, self.count.unwrap_or(1)) {
+            Ok(members) => members,
+            Err(e) => return e.into(),
+        };
+        // without a count the reply is the member itself
+        if self.count.is_none() {
+            return match members.into_iter().next() {
+                Some((member, _)) => BulkString::new(member).into(),
+                None => RespFrame::Null(RespNull),
+            };
+        }
+        members_frame(members, self.with_scores)
+    }
+}
+
+impl CommandExecutor for ZCard {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        match backend.zcard(&self.key) {
+            Ok(len) => RespFrame::Integer(len as i64),
+            Err(e) => e.into(),
+        }
+    }
+}
+
+impl CommandExecutor for ZRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        zrange(
+            backend,
+            &self.key,
+            &self.range,
+            self.rev,
+            self.limit,
+            self.with_scores,
+        )
+    }
+}
+
+impl CommandExecutor for ZRangeByScore {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let range = ZRangeBy::Score(self.min, self.max);
+        zrange(
+            backend,
+            &self.key,
+            &range,
+            false,
+            self.limit,
+            self.with_scores,
+        )
+    }
+}
+
+impl CommandExecutor for ZRangeByLex {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let range = ZRangeBy::Lex(self.min, self.max);
+        zrange(backend, &self.key, &range, false, self.limit, false)
+    }
+}
+
+impl CommandExecutor for ZRevRange {
+    fn execute(self, backend: &Backend) -> RespFrame {
+        let range = ZRangeBy::Rank(self.start, self.stop);
+        zrange(backend, &self.key, &range, true, None, self.with_scores)
+    }
+}
+
+fn zrange(
+    backend: &Backend,
+    key: &str,
+    range: &ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+) -> RespFrame {
+    match backend.zrange(key, range, rev, limit) {
+        Ok(members) => members_frame(members, with_scores),
+        Err(e) => e.into(),
+    }
+}
+
+// members alone, or [member, score] pairs WITHSCORES
+fn members_frame(members: Vec<ScoredMember>, with_scores: bool) -> RespFrame {
+    let frames = members
+        .into_iter()
+        .map(|(member, score)| {
+            let member = BulkString::new(member).into();
+            if with_scores {
+                RespArray::new([member, score_frame(score)]).into()
+            } else {
+                member
+            }
+        })
+        .collect::<Vec<RespFrame>>();
+    RespArray::new(frames).into()
+}
+
+// scores are replied as bulk strings, like the other float replies
+fn score_frame(score: f64) -> RespFrame {
+    BulkString::new(format_score(score)).into()
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zadd"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter().peekable();
+        let key = extract_string(args.next())?;
+        let mut zadd = ZAdd {
+            key,
+            members: vec![],
+            condition: None,
+            comparison: None,
+            changed: false,
+            incr: false,
+        };
+        let (mut nx, mut xx, mut gt, mut lt) = (false, false, false, false);
+        // the flags come before the first score
+        while let Some(RespFrame::BulkString(arg)) = args.peek() {
+            match arg.to_ascii_uppercase().as_slice() {
+                b"NX" => nx = true,
+                b"XX" => xx = true,
+                b"GT" => gt = true,
+                b"LT" => lt = true,
+                b"CH" => zadd.changed = true,
+                b"INCR" => zadd.incr = true,
+                _ => break,
+            }
+            args.next();
+        }
+        if nx && xx {
+            return Err(CommandError::InvalidArgument(
+                "XX and NX options at the same time are not compatible".into(),
+            ));
+        }
+        if (nx && (gt || lt)) || (gt && lt) {
+            return Err(CommandError::InvalidArgument(
+                "GT, LT, and/or NX options at the same time are not compatible".into(),
+            ));
+        }
+        zadd.condition = match (nx, xx) {
+            (true, _) => Some(SetCondition::IfNotExists),
+            (_, true) => Some(SetCondition::IfExists),
+            _ => None,
+        };
+        zadd.comparison = match (gt, lt) {
+            (true, _) => Some(ScoreCondition::IfGreater),
+            (_, true) => Some(ScoreCondition::IfLess),
+            _ => None,
+        };
+
+        let args = args.collect::<Vec<_>>();
+        if args.is_empty() || args.len() % 2 != 0 {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        if zadd.incr && args.len() > 2 {
+            return Err(CommandError::InvalidArgument(
+                "INCR option supports a single increment-element pair".into(),
+            ));
+        }
+        let mut args = args.into_iter();
+        while let Some(score) = args.next() {
+            zadd.members
+                .push((extract_score(Some(score))?, extract_bytes(args.next())?));
+        }
+        Ok(zadd)
+    }
+}
+
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zincrby"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZIncrBy {
+            key: extract_string(args.next())?,
+            increment: extract_score(args.next())?,
+            member: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZScore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zscore"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZScore {
+            key: extract_string(args.next())?,
+            member: extract_bytes(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRank {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank(value, "zrank")?;
+        Ok(ZRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRank {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, member, with_score) = parse_rank(value, "zrevrank")?;
+        Ok(ZRevRank {
+            key,
+            member,
+            with_score,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRem {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zrem"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let members = args
+            .map(|arg| extract_bytes(Some(arg)))
+            .collect::<Result<_, _>>()?;
+        Ok(ZRem { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for ZRemRangeByScore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zremrangebyscore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZRemRangeByScore {
+            key: extract_string(args.next())?,
+            min: extract_score_bound(args.next())?,
+            max: extract_score_bound(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRemRangeByRank {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zremrangebyrank"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZRemRangeByRank {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMin {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_zpop(value, "zpopmin")?;
+        Ok(ZPopMin { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for ZPopMax {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (key, count) = parse_zpop(value, "zpopmax")?;
+        Ok(ZPopMax { key, count })
+    }
+}
+
+impl TryFrom<RespArray> for BZPopMin {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop(value, "bzpopmin")?;
+        Ok(BZPopMin { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BZPopMax {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = parse_blocking_pop(value, "bzpopmax")?;
+        Ok(BZPopMax { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnion {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zunion"], 2)?;
+
+        let args = extract_args(value, 1)?.into_iter();
+        let combination = parse_combination(args, "zunion", false, true)?;
+        Ok(ZUnion {
+            keys: combination.keys,
+            weights: combination.weights,
+            aggregate: combination.aggregate,
+            with_scores: combination.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInter {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zinter"], 2)?;
+
+        let args = extract_args(value, 1)?.into_iter();
+        let combination = parse_combination(args, "zinter", false, true)?;
+        Ok(ZInter {
+            keys: combination.keys,
+            weights: combination.weights,
+            aggregate: combination.aggregate,
+            with_scores: combination.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiff {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zdiff"], 2)?;
+
+        let args = extract_args(value, 1)?.into_iter();
+        let combination = parse_combination(args, "zdiff", true, true)?;
+        Ok(ZDiff {
+            keys: combination.keys,
+            with_scores: combination.with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZUnionStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zunionstore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let combination = parse_combination(args, "zunionstore", false, false)?;
+        Ok(ZUnionStore {
+            destination,
+            keys: combination.keys,
+            weights: combination.weights,
+            aggregate: combination.aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZInterStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zinterstore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let combination = parse_combination(args, "zinterstore", false, false)?;
+        Ok(ZInterStore {
+            destination,
+            keys: combination.keys,
+            weights: combination.weights,
+            aggregate: combination.aggregate,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZDiffStore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zdiffstore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let destination = extract_string(args.next())?;
+        let combination = parse_combination(args, "zdiffstore", true, false)?;
+        Ok(ZDiffStore {
+            destination,
+            keys: combination.keys,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZScan {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zscan"], 2)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut zscan = ZScan {
+            key: extract_string(args.next())?,
+            cursor: extract_cursor(args.next())?,
+            pattern: None,
+            count: 10,
+        };
+        while let Some(arg) = args.next() {
+            let option = extract_string(Some(arg))?.to_ascii_uppercase();
+            if !parse_scan_option(&option, &mut args, &mut zscan.pattern, &mut zscan.count)? {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+        }
+        Ok(zscan)
+    }
+}
+
+impl TryFrom<RespArray> for ZRandMember {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zrandmember"], 1)?;
+        if value.len() > 4 {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let count = args
+            .next()
+            .map(|arg| extract_integer(Some(arg)))
+            .transpose()?;
+        let with_scores = match args.next() {
+            Some(arg) => {
+                if !extract_string(Some(arg))?.eq_ignore_ascii_case("WITHSCORES") {
+                    return Err(CommandError::InvalidArgument("syntax error".into()));
+                }
+                true
+            }
+            None => false,
+        };
+        Ok(ZRandMember {
+            key,
+            count,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZMPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zmpop"], 3)?;
+
+        let args = extract_args(value, 1)?.into_iter();
+        let (keys, end, count) = parse_zmpop(args)?;
+        Ok(ZMPop { keys, end, count })
+    }
+}
+
+impl TryFrom<RespArray> for BZMPop {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["bzmpop"], 4)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let timeout = extract_timeout(args.next())?;
+        let (keys, end, count) = parse_zmpop(args)?;
+        Ok(BZMPop {
+            keys,
+            end,
+            count,
+            timeout,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZCard {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&value, &["zcard"], 1)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        Ok(ZCard {
+            key: extract_string(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zrange"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let (start, stop) = (args.next(), args.next());
+        let (mut by_score, mut by_lex, mut rev, mut with_scores) = (false, false, false, false);
+        let mut limit = None;
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "BYSCORE" => by_score = true,
+                "BYLEX" => by_lex = true,
+                "REV" => rev = true,
+                "LIMIT" => limit = Some(parse_limit(&mut args)?),
+                "WITHSCORES" => with_scores = true,
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        if by_score && by_lex {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+        if limit.is_some() && !by_score && !by_lex {
+            return Err(CommandError::InvalidArgument(
+                "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                    .into(),
+            ));
+        }
+        if with_scores && by_lex {
+            return Err(CommandError::InvalidArgument(
+                "syntax error, WITHSCORES not supported in combination with BYLEX".into(),
+            ));
+        }
+
+        // REV takes score and lex ranges from max to min
+        let (min, max) = match rev && (by_score || by_lex) {
+            true => (stop, start),
+            false => (start, stop),
+        };
+        let range = if by_score {
+            ZRangeBy::Score(extract_score_bound(min)?, extract_score_bound(max)?)
+        } else if by_lex {
+            lex_range(min, max)?
+        } else {
+            ZRangeBy::Rank(extract_integer(min)?, extract_integer(max)?)
+        };
+        Ok(ZRange {
+            key,
+            range,
+            rev,
+            limit,
+            with_scores,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByScore {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zrangebyscore"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let mut zrange = ZRangeByScore {
+            key: extract_string(args.next())?,
+            min: extract_score_bound(args.next())?,
+            max: extract_score_bound(args.next())?,
+            limit: None,
+            with_scores: false,
+        };
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "LIMIT" => zrange.limit = Some(parse_limit(&mut args)?),
+                "WITHSCORES" => zrange.with_scores = true,
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        Ok(zrange)
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByLex {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zrangebylex"], 3)?;
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let key = extract_string(args.next())?;
+        let ZRangeBy::Lex(min, max) = lex_range(args.next(), args.next())? else {
+            unreachable!("lex_range only builds lex ranges");
+        };
+        let mut limit = None;
+        while let Some(arg) = args.next() {
+            match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+                "LIMIT" => limit = Some(parse_limit(&mut args)?),
+                _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+            }
+        }
+        Ok(ZRangeByLex {
+            key,
+            min,
+            max,
+            limit,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZRevRange {
+    type Error = CommandError;
+
+    fn try_from(value: RespArray) -> Result<Self, Self::Error> {
+        validate_variadic_command(&value, &["zrevrange"], 3)?;
+        if value.len() > 5 {
+            return Err(CommandError::InvalidArgument("syntax error".into()));
+        }
+
+        let mut args = extract_args(value, 1)?.into_iter();
+        let zrevrange = ZRevRange {
+            key: extract_string(args.next())?,
+            start: extract_integer(args.next())?,
+            stop: extract_integer(args.next())?,
+            with_scores: match args.next() {
+                Some(arg) => {
+                    if !extract_string(Some(arg))?.eq_ignore_ascii_case("WITHSCORES") {
+                        return Err(CommandError::InvalidArgument("syntax error".into()));
+                    }
+                    true
+                }
+                None => false,
+            },
+        };
+        Ok(zrevrange)
+    }
+}
+
+// parses `key member [WITHSCORE]`
+fn parse_rank(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<u8>, bool), CommandError> {
+    validate_variadic_command(&value, &[name], 2)?;
+    if value.len() > 4 {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let member = extract_bytes(args.next())?;
+    let with_score = match args.next() {
+        Some(arg) => {
+            if !extract_string(Some(arg))?.eq_ignore_ascii_case("WITHSCORE") {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+            true
+        }
+        None => false,
+    };
+    Ok((key, member, with_score))
+}
+
+// parses `key [count]`
+fn parse_zpop(
+    value: RespArray,
+    name: &'static str,
+) -> Result<(String, Option<usize>), CommandError> {
+    validate_variadic_command(&value, &[name], 1)?;
+    if value.len() > 3 {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+
+    let mut args = extract_args(value, 1)?.into_iter();
+    let key = extract_string(args.next())?;
+    let count = match args.next() {
+        Some(count) => match extract_integer(Some(count))? {
+            n if n >= 0 => Some(n as usize),
+            _ => {
+                return Err(CommandError::InvalidArgument(
+                    "value is out of range, must be positive".into(),
+                ))
+            }
+        },
+        None => None,
+    };
+    Ok((key, count))
+}
+
+// arguments shared by the sorted set combination commands
+struct Combination {
+    keys: Vec<String>,
+    weights: Vec<f64>,
+    aggregate: Aggregate,
+    with_scores: bool,
+}
+
+// parses `numkeys key [key ...]` followed by `[WEIGHTS weight [weight ...]]` and
+// `[AGGREGATE SUM | MIN | MAX]` unless `diff`, and by `[WITHSCORES]` unless it stores
+fn parse_combination(
+    mut args: impl Iterator<Item = RespFrame>,
+    name: &str,
+    diff: bool,
+    with_scores: bool,
+) -> Result<Combination, CommandError> {
+    let n_keys = extract_integer(args.next())?;
+    if n_keys <= 0 {
+        return Err(CommandError::InvalidArgument(format!(
+            "at least 1 input key is needed for '{}' command",
+            name
+        )));
+    }
+    let keys = (0..n_keys)
+        .map(|_| extract_string(args.next()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut combination = Combination {
+        weights: vec![],
+        aggregate: Aggregate::Sum,
+        with_scores: false,
+        keys,
+    };
+    while let Some(arg) = args.next() {
+        match extract_string(Some(arg))?.to_ascii_uppercase().as_str() {
+            "WEIGHTS" if !diff => {
+                combination.weights = (0..combination.keys.len())
+                    .map(|_| {
+                        extract_score(args.next()).map_err(|_| {
+                            CommandError::InvalidArgument("weight value is not a float".into())
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+            }
+            "AGGREGATE" if !diff => {
+                combination.aggregate =
+                    match extract_string(args.next())?.to_ascii_uppercase().as_str() {
+                        "SUM" => Aggregate::Sum,
+                        "MIN" => Aggregate::Min,
+                        "MAX" => Aggregate::Max,
+                        _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+                    };
+            }
+            "WITHSCORES" if with_scores => combination.with_scores = true,
+            _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+        }
+    }
+    Ok(combination)
+}
+
+// parses `numkeys key [key ...] MIN | MAX [COUNT count]`
+fn parse_zmpop(
+    mut args: impl Iterator<Item = RespFrame>,
+) -> Result<(Vec<String>, ZSetEnd, usize), CommandError> {
+    let n_keys = extract_integer(args.next())?;
+    if n_keys <= 0 {
+        return Err(CommandError::InvalidArgument(
+            "numkeys should be greater than 0".into(),
+        ));
+    }
+    let keys = (0..n_keys)
+        .map(|_| extract_string(args.next()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let end = match extract_string(args.next())?.to_ascii_uppercase().as_str() {
+        "MIN" => ZSetEnd::Min,
+        "MAX" => ZSetEnd::Max,
+        _ => return Err(CommandError::InvalidArgument("syntax error".into())),
+    };
+    let count = match args.next() {
+        Some(arg) => {
+            if !extract_string(Some(arg))?.eq_ignore_ascii_case("COUNT") {
+                return Err(CommandError::InvalidArgument("syntax error".into()));
+            }
+            match extract_integer(args.next())? {
+                n if n > 0 => n as usize,
+                _ => {
+                    return Err(CommandError::InvalidArgument(
+                        "count should be greater than 0".into(),
+                    ))
+                }
+            }
+        }
+        None => 1,
+    };
+    if args.next().is_some() {
+        return Err(CommandError::InvalidArgument("syntax error".into()));
+    }
+    Ok((keys, end, count))
+}
+
+// parses `offset count` after LIMIT
+fn parse_limit(args: &mut impl Iterator<Item = RespFrame>) -> Result<(i64, i64), CommandError> {
+    Ok((extract_integer(args.next())?, extract_integer(args.next())?))
+}
+
+// parses a score bound, exclusive when prefixed with `(`
+fn extract_score_bound(frame: Option<RespFrame>) -> Result<Bound<f64>, CommandError> {
+    let bound = extract_string(frame)?;
+    let (score, exclusive) = match bound.strip_prefix('(') {
+        Some(score) => (score, true),
+        None => (bound.as_str(), false),
+    };
+    let score = score
+        .parse()
+        .ok()
+        .filter(|v: &f64| !v.is_nan())
+        .ok_or_else(|| CommandError::InvalidArgument("min or max is not a float".into()))?;
+    Ok(match exclusive {
+        true => Bound::Excluded(score),
+        false => Bound::Included(score),
+    })
+}
+
+// parses a lex range: `-` and `+` for the smallest and largest strings, otherwise a member
+// prefixed with `[` when inclusive or `(` when exclusive
+fn lex_range(min: Option<RespFrame>, max: Option<RespFrame>) -> Result<ZRangeBy, CommandError> {
+    let (min, max) = (extract_bytes(min)?, extract_bytes(max)?);
+    // nothing is above `+` or below `-`
+    if min == b"+" || max == b"-" {
+        return Ok(ZRangeBy::Lex(Bound::Unbounded, Bound::Excluded(vec![])));
+    }
+    Ok(ZRangeBy::Lex(lex_bound(min)?, lex_bound(max)?))
+}
+
+fn lex_bound(bound: Vec<u8>) -> Result<Bound<Vec<u8>>, CommandError> {
+    match bound.split_first() {
+        Some((b'-' | b'+', [])) => Ok(Bound::Unbounded),
+        Some((b'[', member)) => Ok(Bound::Included(member.to_vec())),
+        Some((b'(', member)) => Ok(Bound::Excluded(member.to_vec())),
+        _ => Err(CommandError::InvalidArgument(
+            "min or max not valid string range item".into(),
+        )),
+    }
+}
+
+// unlike other floats, scores may be infinite
+fn extract_score(frame: Option<RespFrame>) -> Result<f64, CommandError> {
+    extract_string(frame)?
+        .parse()
+        .ok()
+        .filter(|v: &f64| !v.is_nan())
+        .ok_or_else(|| CommandError::InvalidArgument("value is not a valid float".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    use crate::{BackendError, RespDecode};
+
+    use super::*;
+
+    #[test]
+    fn test_zadd_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*8\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$2\r\nxx\r\n$2\r\nCH\r\n$1\r\n1\r\n$1\r\na\r\n$4\r\n-inf\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZAdd = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(
+            cmd.members,
+            vec![(1.0, b"a".to_vec()), (f64::NEG_INFINITY, b"b".to_vec())]
+        );
+        assert_eq!(cmd.condition, Some(SetCondition::IfExists));
+        assert_eq!(cmd.comparison, None);
+        assert!(cmd.changed);
+        assert!(!cmd.incr);
+
+        buf.extend_from_slice(
+            b"*6\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$2\r\nNX\r\n$2\r\nGT\r\n$1\r\n1\r\n$1\r\na\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZAdd, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*7\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$4\r\nINCR\r\n$1\r\n1\r\n$1\r\na\r\n$1\r\n2\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZAdd, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*4\r\n$4\r\nzadd\r\n$4\r\nzset\r\n$3\r\nnan\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZAdd, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zset_commands() -> Result<()> {
+        let backend = Backend::new();
+        let zadd = |members: &[(f64, &str)], condition, comparison, changed, incr| ZAdd {
+            key: "zset".to_string(),
+            members: members
+                .iter()
+                .map(|(score, member)| (*score, member.as_bytes().to_vec()))
+                .collect(),
+            condition,
+            comparison,
+            changed,
+            incr,
+        };
+        let zscore = |member: &str| ZScore {
+            key: "zset".to_string(),
+            member: member.as_bytes().to_vec(),
+        };
+
+        let cmd = zadd(&[(1.0, "a"), (2.0, "b")], None, None, false, false);
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        // NX never touches existing members
+        let cmd = zadd(
+            &[(5.0, "a"), (3.0, "c")],
+            Some(SetCondition::IfNotExists),
+            None,
+            false,
+            false,
+        );
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(zscore("a").execute(&backend), BulkString::new("1").into());
+
+        // XX never adds members, CH counts the updates
+        let cmd = zadd(
+            &[(5.0, "a"), (4.0, "d")],
+            Some(SetCondition::IfExists),
+            None,
+            true,
+            false,
+        );
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(zscore("d").execute(&backend), RespFrame::Null(RespNull));
+
+        // GT only lets scores grow but still adds new members
+        let cmd = zadd(
+            &[(0.0, "a"), (3.0, "b"), (1.5, "e")],
+            None,
+            Some(ScoreCondition::IfGreater),
+            true,
+            false,
+        );
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(zscore("a").execute(&backend), BulkString::new("5").into());
+        assert_eq!(zscore("b").execute(&backend), BulkString::new("3").into());
+
+        let cmd = zadd(&[(2.5, "a")], None, None, false, true);
+        assert_eq!(cmd.execute(&backend), BulkString::new("7.5").into());
+        let cmd = zadd(
+            &[(1.0, "a")],
+            None,
+            Some(ScoreCondition::IfLess),
+            false,
+            true,
+        );
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+
+        let cmd = zadd(&[(f64::INFINITY, "a")], None, None, false, false);
+        cmd.execute(&backend);
+        assert_eq!(zscore("a").execute(&backend), BulkString::new("inf").into());
+        let cmd = zadd(&[(f64::NEG_INFINITY, "a")], None, None, false, true);
+        assert_eq!(cmd.execute(&backend), BackendError::ScoreNaN.into());
+
+        let cmd = ZCard {
+            key: "zset".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(4));
+
+        // XX on a missing key creates nothing
+        let cmd = ZAdd {
+            key: "missing".to_string(),
+            ..zadd(
+                &[(1.0, "a")],
+                Some(SetCondition::IfExists),
+                None,
+                false,
+                false,
+            )
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        let cmd = ZCard {
+            key: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(backend.db.zset.get("missing").is_none());
+
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        let cmd = ZCard {
+            key: "string".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*10\r\n$6\r\nzrange\r\n$4\r\nzset\r\n$4\r\n(5.5\r\n$4\r\n-inf\r\n$7\r\nbyscore\r\n$3\r\nREV\r\n$5\r\nLIMIT\r\n$1\r\n1\r\n$1\r\n2\r\n$10\r\nWITHSCORES\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZRange = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(
+            cmd.range,
+            ZRangeBy::Score(Bound::Included(f64::NEG_INFINITY), Bound::Excluded(5.5))
+        );
+        assert!(cmd.rev);
+        assert_eq!(cmd.limit, Some((1, 2)));
+        assert!(cmd.with_scores);
+
+        buf.extend_from_slice(
+            b"*5\r\n$6\r\nzrange\r\n$4\r\nzset\r\n$1\r\n-\r\n$2\r\n(c\r\n$5\r\nBYLEX\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZRange = frame.try_into()?;
+        assert_eq!(
+            cmd.range,
+            ZRangeBy::Lex(Bound::Unbounded, Bound::Excluded(b"c".to_vec()))
+        );
+
+        // LIMIT needs BYSCORE or BYLEX
+        buf.extend_from_slice(b"*7\r\n$6\r\nzrange\r\n$4\r\nzset\r\n$1\r\n0\r\n$2\r\n-1\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZRange, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*4\r\n$11\r\nzrangebylex\r\n$4\r\nzset\r\n$1\r\na\r\n$1\r\n+\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZRangeByLex, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(
+            b"*4\r\n$13\r\nzrangebyscore\r\n$4\r\nzset\r\n$1\r\na\r\n$1\r\n1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZRangeByScore, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = [(1.0, "a"), (2.0, "b"), (2.0, "c"), (3.0, "d")]
+            .map(|(score, member)| (score, member.as_bytes().to_vec()));
+        backend.zadd("zset".to_string(), members.to_vec(), None, None)?;
+        let frames = |members: &[&str]| -> RespFrame {
+            let members = members
+                .iter()
+                .map(|member| BulkString::new(*member).into())
+                .collect::<Vec<RespFrame>>();
+            RespArray::new(members).into()
+        };
+
+        let cmd = ZRange {
+            key: "zset".to_string(),
+            range: ZRangeBy::Rank(1, -1),
+            rev: false,
+            limit: None,
+            with_scores: false,
+        };
+        assert_eq!(cmd.execute(&backend), frames(&["b", "c", "d"]));
+
+        let cmd = ZRange {
+            key: "zset".to_string(),
+            range: ZRangeBy::Score(Bound::Excluded(1.0), Bound::Included(f64::INFINITY)),
+            rev: true,
+            limit: Some((1, 1)),
+            with_scores: true,
+        };
+        let pair = RespArray::new([BulkString::new("c").into(), BulkString::new("2").into()]);
+        assert_eq!(cmd.execute(&backend), RespArray::new([pair.into()]).into());
+
+        let cmd = ZRangeByScore {
+            key: "zset".to_string(),
+            min: Bound::Included(2.0),
+            max: Bound::Included(2.0),
+            limit: None,
+            with_scores: false,
+        };
+        assert_eq!(cmd.execute(&backend), frames(&["b", "c"]));
+
+        let cmd = ZRangeByLex {
+            key: "zset".to_string(),
+            min: Bound::Included(b"b".to_vec()),
+            max: Bound::Unbounded,
+            limit: Some((0, 2)),
+        };
+        assert_eq!(cmd.execute(&backend), frames(&["b", "c"]));
+
+        let cmd = ZRevRange {
+            key: "zset".to_string(),
+            start: 0,
+            stop: 1,
+            with_scores: false,
+        };
+        assert_eq!(cmd.execute(&backend), frames(&["d", "c"]));
+
+        // ranks closer to the end are walked from there, in the order asked for
+        let range = |range, rev| {
+            ZRange {
+                key: "zset".to_string(),
+                range,
+                rev,
+                limit: None,
+                with_scores: false,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(range(ZRangeBy::Rank(2, 3), false), frames(&["c", "d"]));
+        assert_eq!(range(ZRangeBy::Rank(2, -1), true), frames(&["b", "a"]));
+        // scores equal to an excluded bound are left out, crossed bounds select nothing
+        let score = ZRangeBy::Score(Bound::Excluded(2.0), Bound::Excluded(3.0));
+        assert_eq!(range(score, false), frames(&[]));
+        let score = ZRangeBy::Score(Bound::Included(-0.0), Bound::Excluded(2.0));
+        assert_eq!(range(score, true), frames(&["a"]));
+        let score = ZRangeBy::Score(Bound::Included(3.0), Bound::Included(1.0));
+        assert_eq!(range(score, false), frames(&[]));
+
+        // members sharing one score are sought by name
+        let names = ["a", "b", "c", "d", "e"].map(|member| (0.0, member.as_bytes().to_vec()));
+        backend.zadd("names".to_string(), names.to_vec(), None, None)?;
+        let lex = |min, max, rev| {
+            ZRange {
+                key: "names".to_string(),
+                range: ZRangeBy::Lex(min, max),
+                rev,
+                limit: None,
+                with_scores: false,
+            }
+            .execute(&backend)
+        };
+        assert_eq!(
+            lex(
+                Bound::Excluded(b"b".to_vec()),
+                Bound::Included(b"d".to_vec()),
+                false
+            ),
+            frames(&["c", "d"])
+        );
+        assert_eq!(
+            lex(Bound::Unbounded, Bound::Excluded(b"c".to_vec()), true),
+            frames(&["b", "a"])
+        );
+        assert_eq!(
+            lex(
+                Bound::Excluded(b"c".to_vec()),
+                Bound::Excluded(b"c".to_vec()),
+                false
+            ),
+            frames(&[])
+        );
+
+        let cmd = ZRevRange {
+            key: "missing".to_string(),
+            start: 0,
+            stop: -1,
+            with_scores: false,
+        };
+        assert_eq!(cmd.execute(&backend), frames(&[]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincrby_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*4\r\n$7\r\nzincrby\r\n$4\r\nzset\r\n$4\r\n-1.5\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZIncrBy = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(cmd.increment, -1.5);
+        assert_eq!(cmd.member, b"a");
+
+        buf.extend_from_slice(b"*4\r\n$7\r\nzincrby\r\n$4\r\nzset\r\n$3\r\none\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZIncrBy, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zincrby_command() -> Result<()> {
+        let backend = Backend::new();
+        let zincrby = |increment| ZIncrBy {
+            key: "zset".to_string(),
+            increment,
+            member: b"a".to_vec(),
+        };
+
+        assert_eq!(
+            zincrby(0.1).execute(&backend),
+            BulkString::new("0.1").into()
+        );
+        assert_eq!(
+            zincrby(0.2).execute(&backend),
+            BulkString::new("0.30000000000000004").into()
+        );
+        assert_eq!(
+            zincrby(1e20).execute(&backend),
+            BulkString::new("1e+20").into()
+        );
+        assert_eq!(
+            zincrby(f64::INFINITY).execute(&backend),
+            BulkString::new("inf").into()
+        );
+        assert_eq!(
+            zincrby(f64::NEG_INFINITY).execute(&backend),
+            BackendError::ScoreNaN.into()
+        );
+
+        assert_eq!(format_score(-2.5e-5), "-2.5e-05");
+        assert_eq!(format_score(1e16), "10000000000000000");
+        assert_eq!(format_score(0.0001), "0.0001");
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrank_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$8\r\nzrevrank\r\n$4\r\nzset\r\n$1\r\na\r\n$9\r\nwithscore\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZRevRank = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(cmd.member, b"a");
+        assert!(cmd.with_score);
+
+        buf.extend_from_slice(
+            b"*4\r\n$5\r\nzrank\r\n$4\r\nzset\r\n$1\r\na\r\n$10\r\nWITHSCORES\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZRank, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrank_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = [(1.0, "a"), (2.0, "b"), (3.0, "c")]
+            .map(|(score, member)| (score, member.as_bytes().to_vec()));
+        backend.zadd("zset".to_string(), members.to_vec(), None, None)?;
+
+        let cmd = ZRank {
+            key: "zset".to_string(),
+            member: b"b".to_vec(),
+            with_score: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        // counted from the end when closer to it
+        let cmd = ZRank {
+            key: "zset".to_string(),
+            member: b"c".to_vec(),
+            with_score: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+
+        let cmd = ZRevRank {
+            key: "zset".to_string(),
+            member: b"a".to_vec(),
+            with_score: true,
+        };
+        let expected = RespArray::new([RespFrame::Integer(2), BulkString::new("1").into()]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = ZRank {
+            key: "zset".to_string(),
+            member: b"missing".to_vec(),
+            with_score: true,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zremrangebyscore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$16\r\nzremrangebyscore\r\n$4\r\nzset\r\n$2\r\n(1\r\n$4\r\n+inf\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZRemRangeByScore = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(cmd.min, Bound::Excluded(1.0));
+        assert_eq!(cmd.max, Bound::Included(f64::INFINITY));
+
+        buf.extend_from_slice(b"*2\r\n$4\r\nzrem\r\n$4\r\nzset\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZRem, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrem_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = [(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d"), (5.0, "e")]
+            .map(|(score, member)| (score, member.as_bytes().to_vec()));
+        backend.zadd("zset".to_string(), members.to_vec(), None, None)?;
+
+        let cmd = ZRem {
+            key: "zset".to_string(),
+            members: vec![b"a".to_vec(), b"missing".to_vec()],
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = ZRemRangeByScore {
+            key: "zset".to_string(),
+            min: Bound::Excluded(2.0),
+            max: Bound::Included(3.0),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+
+        let cmd = ZRemRangeByRank {
+            key: "zset".to_string(),
+            start: -2,
+            stop: -1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(2));
+        assert_eq!(backend.zcard("zset")?, 1);
+
+        // the key goes away with its last member
+        let cmd = ZRemRangeByRank {
+            key: "zset".to_string(),
+            start: 0,
+            stop: -1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert!(backend.db.zset.get("zset").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zpop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*3\r\n$7\r\nzpopmax\r\n$4\r\nzset\r\n$1\r\n2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZPopMax = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(cmd.count, Some(2));
+
+        buf.extend_from_slice(b"*3\r\n$7\r\nzpopmin\r\n$4\r\nzset\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZPopMin, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*4\r\n$8\r\nbzpopmin\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\n0.5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BZPopMin = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cmd.timeout, Some(Duration::from_millis(500)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zpop_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = [(1.0, "a"), (2.0, "b"), (3.0, "c")]
+            .map(|(score, member)| (score, member.as_bytes().to_vec()));
+        backend.zadd("zset".to_string(), members.to_vec(), None, None)?;
+        let bulk = |s: &str| -> RespFrame { BulkString::new(s).into() };
+
+        let cmd = ZPopMin {
+            key: "zset".to_string(),
+            count: None,
+        };
+        let expected = RespArray::new([bulk("a"), bulk("1")]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = ZPopMax {
+            key: "zset".to_string(),
+            count: Some(5),
+        };
+        let expected = RespArray::new([
+            RespArray::new([bulk("c"), bulk("3")]).into(),
+            RespArray::new([bulk("b"), bulk("2")]).into(),
+        ]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+        assert!(backend.db.zset.get("zset").is_none());
+
+        let cmd = ZPopMin {
+            key: "zset".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespArray::new(vec![]).into());
+
+        backend.zadd("other".to_string(), vec![(4.0, b"d".to_vec())], None, None)?;
+        let cmd = BZPopMax {
+            keys: vec!["zset".to_string(), "other".to_string()],
+            timeout: None,
+        };
+        let expected = RespArray::new([bulk("other"), bulk("d"), bulk("4")]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        // executed directly a blocking pop does not wait
+        let cmd = BZPopMin {
+            keys: vec!["zset".to_string()],
+            timeout: None,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zunionstore_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*10\r\n$11\r\nzunionstore\r\n$3\r\nout\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$7\r\nweights\r\n$1\r\n2\r\n$3\r\n0.5\r\n$9\r\naggregate\r\n$3\r\nmax\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZUnionStore = frame.try_into()?;
+        assert_eq!(cmd.destination, "out");
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cmd.weights, vec![2.0, 0.5]);
+        assert_eq!(cmd.aggregate, Aggregate::Max);
+
+        // a weight for every key
+        buf.extend_from_slice(
+            b"*6\r\n$6\r\nzinter\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$7\r\nWEIGHTS\r\n$1\r\n2\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZInter, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nzdiff\r\n$1\r\n1\r\n$1\r\na\r\n$9\r\nAGGREGATE\r\n$3\r\nSUM\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZDiff, _> = frame.try_into();
+        assert!(result.is_err());
+
+        buf.extend_from_slice(b"*4\r\n$10\r\nzdiffstore\r\n$3\r\nout\r\n$1\r\n0\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZDiffStore, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zset_algebra_commands() -> Result<()> {
+        let backend = Backend::new();
+        let members = |members: &[(f64, &str)]| {
+            members
+                .iter()
+                .map(|(score, member)| (*score, member.as_bytes().to_vec()))
+                .collect()
+        };
+        backend.zadd(
+            "a".to_string(),
+            members(&[(1.0, "x"), (2.0, "y")]),
+            None,
+            None,
+        )?;
+        backend.zadd(
+            "b".to_string(),
+            members(&[(3.0, "y"), (4.0, "z")]),
+            None,
+            None,
+        )?;
+        backend.sadd("s".to_string(), vec![b"y".to_vec()])?;
+        let pairs = |pairs: &[(&str, &str)]| -> RespFrame {
+            let pairs = pairs
+                .iter()
+                .map(|(member, score)| {
+                    RespArray::new([
+                        BulkString::new(*member).into(),
+                        BulkString::new(*score).into(),
+                    ])
+                    .into()
+                })
+                .collect::<Vec<RespFrame>>();
+            RespArray::new(pairs).into()
+        };
+        let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+
+        let cmd = ZUnion {
+            keys: keys(&["a", "b"]),
+            weights: vec![1.0, 2.0],
+            aggregate: Aggregate::Sum,
+            with_scores: true,
+        };
+        assert_eq!(
+            cmd.execute(&backend),
+            pairs(&[("x", "1"), ("y", "8"), ("z", "8")])
+        );
+
+        // plain sets score 1
+        let cmd = ZInter {
+            keys: keys(&["a", "b", "s"]),
+            weights: vec![],
+            aggregate: Aggregate::Min,
+            with_scores: true,
+        };
+        assert_eq!(cmd.execute(&backend), pairs(&[("y", "1")]));
+
+        let cmd = ZDiff {
+            keys: keys(&["a", "b", "missing"]),
+            with_scores: false,
+        };
+        let expected = RespArray::new([BulkString::new("x").into()]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = ZInterStore {
+            destination: "out".to_string(),
+            keys: keys(&["a", "b"]),
+            weights: vec![],
+            aggregate: Aggregate::Max,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(1));
+        assert_eq!(backend.zscore("out", b"y")?, Some(3.0));
+
+        // an empty result deletes the destination
+        let cmd = ZDiffStore {
+            destination: "out".to_string(),
+            keys: keys(&["a", "a"]),
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Integer(0));
+        assert!(backend.db.zset.get("out").is_none());
+
+        backend.set("string".to_string(), RespFrame::Integer(1));
+        let cmd = ZUnionStore {
+            destination: "out".to_string(),
+            keys: keys(&["a", "string"]),
+            weights: vec![],
+            aggregate: Aggregate::Sum,
+        };
+        assert_eq!(cmd.execute(&backend), BackendError::WrongType.into());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zscan_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nzscan\r\n$4\r\nzset\r\n$1\r\n0\r\n$5\r\nCOUNT\r\n$2\r\n20\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZScan = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(cmd.cursor, 0);
+        assert_eq!(cmd.pattern, None);
+        assert_eq!(cmd.count, 20);
+
+        buf.extend_from_slice(
+            b"*5\r\n$5\r\nzscan\r\n$4\r\nzset\r\n$1\r\n0\r\n$5\r\nCOUNT\r\n$1\r\n0\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZScan, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zscan_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = (0..30)
+            .map(|i| (i as f64, format!("member:{}", i).into_bytes()))
+            .collect();
+        backend.zadd("zset".to_string(), members, None, None)?;
+        backend.zadd(
+            "zset".to_string(),
+            vec![(0.0, b"other".to_vec())],
+            None,
+            None,
+        )?;
+
+        let mut pairs = Vec::new();
+        let mut cursor = 0;
+        loop {
+            let cmd = ZScan {
+                key: "zset".to_string(),
+                cursor,
+                pattern: Some("member:*".to_string()),
+                count: 7,
+            };
+            let RespFrame::Array(reply) = cmd.execute(&backend) else {
+                panic!("ZSCAN should reply with an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(page)) = (&reply[0], &reply[1])
+            else {
+                panic!("ZSCAN should reply with a cursor and a page");
+            };
+            pairs.extend(page.chunks(2).map(|pair| pair.to_vec()));
+            cursor = String::from_utf8_lossy(next).parse()?;
+            if cursor == 0 {
+                break;
+            }
+        }
+        pairs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        pairs.dedup();
+        assert_eq!(pairs.len(), 30);
+        let score_of_7 = [
+            BulkString::new("member:7").into(),
+            BulkString::new("7").into(),
+        ];
+        assert!(pairs.contains(&score_of_7.to_vec()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrandmember_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(
+            b"*4\r\n$11\r\nzrandmember\r\n$4\r\nzset\r\n$2\r\n-5\r\n$10\r\nwithscores\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: ZRandMember = frame.try_into()?;
+        assert_eq!(cmd.key, "zset");
+        assert_eq!(cmd.count, Some(-5));
+        assert!(cmd.with_scores);
+
+        buf.extend_from_slice(
+            b"*4\r\n$11\r\nzrandmember\r\n$4\r\nzset\r\n$1\r\n1\r\n$9\r\nWITHSCORE\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZRandMember, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrandmember_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = [(1.0, "a"), (2.0, "b"), (3.0, "c")]
+            .map(|(score, member)| (score, member.as_bytes().to_vec()));
+        backend.zadd("zset".to_string(), members.to_vec(), None, None)?;
+
+        let cmd = ZRandMember {
+            key: "zset".to_string(),
+            count: Some(5),
+            with_scores: true,
+        };
+        let RespFrame::Array(pairs) = cmd.execute(&backend) else {
+            panic!("ZRANDMEMBER with a count should reply with an array");
+        };
+        // a positive count never repeats members
+        assert_eq!(pairs.len(), 3);
+        let mut pairs = pairs.to_vec();
+        pairs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let pair = |member: &str, score: &str| -> RespFrame {
+            RespArray::new([
+                BulkString::new(member).into(),
+                BulkString::new(score).into(),
+            ])
+            .into()
+        };
+        assert_eq!(pairs, vec![pair("a", "1"), pair("b", "2"), pair("c", "3")]);
+
+        let cmd = ZRandMember {
+            key: "zset".to_string(),
+            count: Some(i64::MAX),
+            with_scores: false,
+        };
+        let RespFrame::Array(members) = cmd.execute(&backend) else {
+            panic!("ZRANDMEMBER with a count should reply with an array");
+        };
+        assert_eq!(members.len(), 3);
+
+        let cmd = ZRandMember {
+            key: "zset".to_string(),
+            count: Some(-10),
+            with_scores: false,
+        };
+        let RespFrame::Array(members) = cmd.execute(&backend) else {
+            panic!("ZRANDMEMBER with a count should reply with an array");
+        };
+        assert_eq!(members.len(), 10);
+
+        let cmd = ZRandMember {
+            key: "missing".to_string(),
+            count: None,
+            with_scores: false,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+
+    #[test]
+    fn test_zmpop_from_resp_array() -> Result<()> {
+        let mut buf = BytesMut::new();
+        buf.extend_from_slice(b"*8\r\n$6\r\nbzmpop\r\n$1\r\n1\r\n$1\r\n2\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\nmax\r\n$5\r\nCOUNT\r\n$1\r\n3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd: BZMPop = frame.try_into()?;
+        assert_eq!(cmd.keys, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cmd.end, ZSetEnd::Max);
+        assert_eq!(cmd.count, 3);
+        assert_eq!(cmd.timeout, Some(Duration::from_secs(1)));
+
+        buf.extend_from_slice(b"*4\r\n$5\r\nzmpop\r\n$1\r\n1\r\n$1\r\na\r\n$4\r\nLEFT\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let result: Result<ZMPop, _> = frame.try_into();
+        assert!(result.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_zmpop_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = [(1.0, "a"), (2.0, "b"), (3.0, "c")]
+            .map(|(score, member)| (score, member.as_bytes().to_vec()));
+        backend.zadd("second".to_string(), members.to_vec(), None, None)?;
+        let bulk = |s: &str| -> RespFrame { BulkString::new(s).into() };
+
+        let cmd = ZMPop {
+            keys: vec!["first".to_string(), "second".to_string()],
+            end: ZSetEnd::Min,
+            count: 2,
+        };
+        let pairs = RespArray::new([
+            RespArray::new([bulk("a"), bulk("1")]).into(),
+            RespArray::new([bulk("b"), bulk("2")]).into(),
+        ]);
+        let expected = RespArray::new([bulk("second"), pairs.into()]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = BZMPop {
+            keys: vec!["second".to_string()],
+            end: ZSetEnd::Max,
+            count: 10,
+            timeout: None,
+        };
+        let pairs = RespArray::new([RespArray::new([bulk("c"), bulk("3")]).into()]);
+        let expected = RespArray::new([bulk("second"), pairs.into()]);
+        assert_eq!(cmd.execute(&backend), expected.into());
+
+        let cmd = ZMPop {
+            keys: vec!["second".to_string()],
+            end: ZSetEnd::Min,
+            count: 1,
+        };
+        assert_eq!(cmd.execute(&backend), RespFrame::Null(RespNull));
+        Ok(())
+    }
+}
@@ -1,8 +1,10 @@
 mod backend;
+mod pattern;
 mod resp;
 
 pub mod cmd;
 pub mod network;
 
 pub use backend::*;
+pub use pattern::*;
 pub use resp::*;
@@ -1,20 +1,58 @@
 use anyhow::Result;
-use simple_redis::{network, Backend};
+use simple_redis::{network, Backend, CountingAllocator};
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tracing::{info, warn};
+use tracing_subscriber::{filter::filter_fn, fmt, prelude::*, EnvFilter};
+
+// how long SHUTDOWN waits for the connections to close
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+// counts allocated bytes for MEMORY STATS and INFO
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    tracing_subscriber::fmt::init();
+    let backend = Backend::new();
 
-    let addr = "0.0.0.0:6379";
+    // RUST_LOG takes precedence over the loglevel config, which can change at runtime
+    match EnvFilter::try_from_default_env() {
+        Ok(filter) => fmt().with_env_filter(filter).init(),
+        Err(_) => {
+            let config = backend.clone();
+            let filter = filter_fn(move |metadata| config.log_level() >= *metadata.level());
+            tracing_subscriber::registry()
+                .with(fmt::layer().with_filter(filter))
+                .init();
+        }
+    }
+
+    // like redis-server, the only argument is the path of the config file
+    if let Some(path) = std::env::args().nth(1) {
+        backend.load_config(&path)?;
+    }
+    let loaded = backend.load_snapshot()?;
+    if loaded > 0 {
+        info!(
+            "Loaded {} keys from {}",
+            loaded,
+            backend.snapshot_path().display()
+        );
+    }
+
+    let addr = format!("0.0.0.0:{}", backend.port());
     info!("Simple Redis Server listening on {}", addr);
-    let listener = TcpListener::bind(addr).await?;
+    let listener = TcpListener::bind(&addr).await?;
 
-    let backend = Backend::new();
+    backend.start_active_expire();
+    backend.start_save_schedule();
 
     loop {
-        let (stream, raddr) = listener.accept().await?;
+        let (stream, raddr) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = backend.shutdown_requested() => break,
+        };
         info!("Accepted connection from {}", raddr);
         let cloned_backend = backend.clone();
         tokio::spawn(async move {
@@ -24,4 +62,12 @@ async fn main() -> Result<()> {
             }
         });
     }
+
+    drop(listener);
+    warn!("User requested shutdown...");
+    if !backend.drain_clients(SHUTDOWN_DRAIN_TIMEOUT).await {
+        warn!("Some clients didn't disconnect in time");
+    }
+    info!("Simple Redis Server is now ready to exit, bye bye...");
+    Ok(())
 }
@@ -1,15 +1,38 @@
 use crate::{
-    cmd::{Command, CommandExecutor},
-    Backend, RespDecode, RespEncode, RespError, RespFrame,
+    cmd::{
+        command_args, command_name, lookup_command, record_execution, track_reads, BlockingCommand,
+        Command, CommandExecutor, RESP_OK,
+    },
+    AclDenial, Backend, BackendError, Connection, RespDecode, RespEncode, RespError, RespFrame,
+    SimpleError, SimpleString,
 };
 use anyhow::Result;
 use bytes::BytesMut;
 use futures::SinkExt;
+use std::time::Duration;
 use tokio::net::TcpStream;
+use tokio::time::{timeout_at, Instant};
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
 use tracing::info;
 
+// commands RESP2 clients may send while subscribed, as every other reply would be mistaken
+// for a message
+const SUBSCRIBED_COMMANDS: &[&str] = &[
+    "subscribe",
+    "unsubscribe",
+    "psubscribe",
+    "punsubscribe",
+    "ssubscribe",
+    "sunsubscribe",
+    "ping",
+    "quit",
+    "reset",
+];
+
+// commands run right away inside MULTI instead of being queued
+const TRANSACTION_COMMANDS: &[&str] = &["multi", "exec", "discard", "quit", "reset"];
+
 #[derive(Debug)]
 struct RespFrameCodec;
 
@@ -25,18 +48,53 @@ pub struct RedisResponse {
 }
 
 pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
+    let mut connection = Connection::new(&backend, stream.peer_addr()?, stream.local_addr()?);
+    // CLIENT KILL closes the connection even while the client is blocked
+    let killed = connection.killed();
+    tokio::pin!(killed);
     let mut framed = Framed::new(stream, RespFrameCodec);
     loop {
-        match framed.next().await {
+        // like replicas in redis, monitors are never closed for being idle
+        let idle = backend
+            .client_timeout()
+            .filter(|_| !connection.is_monitor());
+        let next = tokio::select! {
+            next = read_frame(&mut framed, idle) => next,
+            frame = connection.next_push() => {
+                framed.send(encode_for(&connection, frame)).await?;
+                continue;
+            }
+            _ = &mut killed => return Ok(()),
+        };
+        match next {
             Some(Ok(frame)) => {
                 info!("Received frame: {:?}", frame);
                 let request = RedisRequest {
                     frame,
                     backend: backend.clone(),
                 };
-                let response = request_handler(request).await?;
-                info!("Sending response: {:?}", response.frame);
-                framed.send(response.frame).await?;
+                let response = tokio::select! {
+                    response = request_handler(request, &mut connection) => response?,
+                    _ = &mut killed => return Ok(()),
+                };
+                // like redis, SHUTDOWN closes the connection instead of replying
+                if backend.is_shutting_down() {
+                    return Ok(());
+                }
+                // the reply isn't even encoded when the client turned replies off
+                let queued = connection.take_queued_replies();
+                if connection.should_reply() {
+                    for frame in queued {
+                        framed.feed(encode_for(&connection, frame)).await?;
+                    }
+                    let frame = encode_for(&connection, response.frame);
+                    info!("Sending response: {:?}", frame);
+                    framed.send(frame).await?;
+                }
+                // the reply to QUIT has been flushed by now
+                if connection.is_closing() {
+                    return Ok(());
+                }
             }
             Some(Err(e)) => return Err(e),
             None => return Ok(()),
@@ -44,14 +102,178 @@ pub async fn stream_handler(stream: TcpStream, backend: Backend) -> Result<()> {
     }
 }
 
-async fn request_handler(request: RedisRequest) -> Result<RedisResponse> {
+// `frame` downgraded to the protocol version the client negotiated
+fn encode_for(connection: &Connection, frame: RespFrame) -> RespFrame {
+    match connection.protocol() {
+        2 => frame.into_resp2(),
+        _ => frame,
+    }
+}
+
+// the next frame sent by the client, None once it disconnects or has been idle for longer
+// than `idle`, in which case it is closed without a reply like redis does
+async fn read_frame(
+    framed: &mut Framed<TcpStream, RespFrameCodec>,
+    idle: Option<Duration>,
+) -> Option<Result<RespFrame>> {
+    match idle {
+        Some(idle) => tokio::time::timeout(idle, framed.next())
+            .await
+            .unwrap_or(None),
+        None => framed.next().await,
+    }
+}
+
+async fn request_handler(
+    request: RedisRequest,
+    connection: &mut Connection,
+) -> Result<RedisResponse> {
     let (frame, backend) = (request.frame, request.backend);
-    let cmd = Command::try_from(frame)?;
-    info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(&backend);
+    // databases can't go away once clients connect, so the selected one still exists
+    let backend = backend.select(connection.db()).unwrap_or(backend);
+    let name = command_name(&frame);
+    // only copied when some client is listening
+    let monitored = backend.has_monitors().then(|| command_args(&frame));
+    let tracked = connection.is_tracking_reads().then(|| command_args(&frame));
+    // inside MULTI, the command is only parsed to be checked before being queued
+    let queued = connection.in_transaction().then(|| frame.clone());
+    let frame = match Command::try_from(frame) {
+        Ok(cmd) => {
+            info!("Executing command: {:?}", cmd);
+            let recognized = !matches!(cmd, Command::Unrecognized(_));
+            let spec = name.as_deref().and_then(lookup_command);
+            let no_auth = spec.is_some_and(|spec| spec.flags.contains(&"no_auth"));
+            if !connection.is_authenticated() && !no_auth {
+                if let (true, Some(name)) = (recognized, &name) {
+                    backend.stats.record_rejected_command(name);
+                    backend.record_acl_denial(AclDenial::Command, name, None, connection.info());
+                }
+                let frame = BackendError::NoAuth.into();
+                return Ok(RedisResponse { frame });
+            }
+            if let (true, 2, Some(name)) =
+                (connection.is_subscribed(), connection.protocol(), &name)
+            {
+                if !SUBSCRIBED_COMMANDS.contains(&name.as_str()) {
+                    backend.stats.record_rejected_command(name);
+                    let frame = SimpleError::new(format!(
+                        "ERR Can't execute '{}': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed in this context",
+                        name
+                    ))
+                    .into();
+                    return Ok(RedisResponse { frame });
+                }
+            }
+            // like redis with noeviction, commands that may use more memory are refused
+            // past maxmemory
+            let denyoom = spec.is_some_and(|spec| spec.flags.contains(&"denyoom"));
+            if let (true, Some(name)) = (denyoom && backend.out_of_memory(), &name) {
+                backend.stats.record_rejected_command(name);
+                // like redis, EXEC then discards the transaction the command was sent in
+                connection.fail_transaction();
+                let frame = BackendError::OutOfMemory.into();
+                return Ok(RedisResponse { frame });
+            }
+            let write = spec.is_some_and(|spec| spec.flags.contains(&"write"));
+            backend.wait_unpaused(write).await;
+            // like redis, admin commands are not shown to monitors
+            let admin = spec.is_some_and(|spec| spec.flags.contains(&"admin"));
+            if let (Some(mut args), true, false) = (monitored, recognized, admin) {
+                // like redis, passwords are not shown to monitors
+                cmd.redact(&mut args);
+                if let Some(info) = connection.info() {
+                    backend.feed_monitors(&info, &args);
+                }
+            }
+            if let (true, Some(name)) = (recognized, &name) {
+                connection.record_command(name);
+            }
+            let transactional = name
+                .as_deref()
+                .is_some_and(|name| TRANSACTION_COMMANDS.contains(&name));
+            if let (Some(frame), false) = (queued, transactional) {
+                connection.queue_command(frame);
+                let frame = SimpleString::new("QUEUED").into();
+                return Ok(RedisResponse { frame });
+            }
+            let start = Instant::now();
+            let (frame, elapsed) = if let Some(duration) = cmd.sleep() {
+                // DEBUG SLEEP holds back this client only, without parking a thread
+                tokio::time::sleep(duration).await;
+                (RESP_OK.clone(), start.elapsed())
+            } else if let Some(blocking) = cmd.as_blocking() {
+                // like redis, the time spent blocked is not accounted for
+                (
+                    block(blocking, &backend, connection.db()).await,
+                    Duration::ZERO,
+                )
+            } else {
+                // EXEC takes the lock exclusively itself to run the transaction
+                let _guard = (!matches!(cmd, Command::Exec(_))).then(|| backend.command_lock());
+                let frame = if let Some(cmd) = cmd.as_connection() {
+                    cmd.execute_on(connection, &backend)
+                } else {
+                    cmd.execute(&backend)
+                };
+                (frame, start.elapsed())
+            };
+            if let (true, Some(name)) = (recognized, &name) {
+                record_execution(&backend, name, &frame, elapsed);
+            }
+            // the keys read are remembered to tell the client once they change
+            if let Some(args) = tracked {
+                track_reads(connection, &args);
+            }
+            frame
+        }
+        // reply with an error instead of dropping the connection on a malformed command
+        Err(e) => {
+            if let Some(name) = &name {
+                backend.stats.record_rejected_command(name);
+            }
+            // like redis, EXEC then discards the transaction the command was sent in
+            connection.fail_transaction();
+            SimpleError::new(format!("ERR {}", e)).into()
+        }
+    };
     Ok(RedisResponse { frame })
 }
 
+// parks the client until the command can be served, retrying it every time one of its
+// keys is written to. `index` is the database the client selected
+async fn block(cmd: &dyn BlockingCommand, backend: &Backend, index: usize) -> RespFrame {
+    let mut backend = backend.clone();
+    let mut waiter = backend.block_on_keys(cmd.keys());
+    // a timeout too far out to represent blocks like no timeout at all
+    let deadline = cmd
+        .timeout()
+        .and_then(|timeout| Instant::now().checked_add(timeout));
+    loop {
+        // SWAPDB moved the database away, the keys to wait on are those now in its place
+        if backend.db_index() != index {
+            if let Ok(selected) = backend.select(index) {
+                backend = selected;
+                waiter = backend.block_on_keys(cmd.keys());
+            }
+        }
+        let served = {
+            let _guard = backend.command_lock();
+            cmd.try_execute(&backend)
+        };
+        if let Some(frame) = served {
+            return frame;
+        }
+        match deadline {
+            Some(deadline) => {
+                if timeout_at(deadline, waiter.ready()).await.is_err() {
+                    return cmd.timed_out();
+                }
+            }
+            None => waiter.ready().await,
+        }
+    }
+}
+
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
 
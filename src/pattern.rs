@@ -0,0 +1,139 @@
+/// Redis-compatible glob-style matching, a port of `stringmatchlen`.
+///
+/// - `*` matches any sequence of bytes, `?` matches exactly one byte
+/// - `[abc]`, `[^abc]` and `[a-z]` match one byte from (or not from) a set
+/// - `\` escapes the following byte
+pub fn glob_match(pattern: &[u8], string: &[u8], nocase: bool) -> bool {
+    let eq = |a: u8, b: u8| {
+        if nocase {
+            a.eq_ignore_ascii_case(&b)
+        } else {
+            a == b
+        }
+    };
+    let (plen, slen) = (pattern.len(), string.len());
+    let (mut p, mut s) = (0, 0);
+    while p < plen && s < slen {
+        match pattern[p] {
+            b'*' => {
+                while p + 1 < plen && pattern[p + 1] == b'*' {
+                    p += 1;
+                }
+                if p + 1 == plen {
+                    return true;
+                }
+                return (s..=slen).any(|i| glob_match(&pattern[p + 1..], &string[i..], nocase));
+            }
+            b'?' => s += 1,
+            b'[' => {
+                p += 1;
+                let not = p < plen && pattern[p] == b'^';
+                if not {
+                    p += 1;
+                }
+                let mut matched = false;
+                loop {
+                    if p >= plen {
+                        // unterminated set, stop at the last byte of the pattern
+                        p = plen - 1;
+                        break;
+                    }
+                    if pattern[p] == b'\\' && p + 1 < plen {
+                        p += 1;
+                        if pattern[p] == string[s] {
+                            matched = true;
+                        }
+                    } else if pattern[p] == b']' {
+                        break;
+                    } else if p + 2 < plen && pattern[p + 1] == b'-' {
+                        let (mut start, mut end, mut c) = (pattern[p], pattern[p + 2], string[s]);
+                        if start > end {
+                            std::mem::swap(&mut start, &mut end);
+                        }
+                        if nocase {
+                            start = start.to_ascii_lowercase();
+                            end = end.to_ascii_lowercase();
+                            c = c.to_ascii_lowercase();
+                        }
+                        p += 2;
+                        if c >= start && c <= end {
+                            matched = true;
+                        }
+                    } else if eq(pattern[p], string[s]) {
+                        matched = true;
+                    }
+                    p += 1;
+                }
+                if not {
+                    matched = !matched;
+                }
+                if !matched {
+                    return false;
+                }
+                s += 1;
+            }
+            c => {
+                let c = if c == b'\\' && p + 1 < plen {
+                    p += 1;
+                    pattern[p]
+                } else {
+                    c
+                };
+                if !eq(c, string[s]) {
+                    return false;
+                }
+                s += 1;
+            }
+        }
+        p += 1;
+    }
+    // trailing stars also match the empty remainder
+    if s == slen {
+        while p < plen && pattern[p] == b'*' {
+            p += 1;
+        }
+    }
+    p == plen && s == slen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("*", "", true),
+            ("*", "hello", true),
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("h*llo", "heeeello", true),
+            ("h*llo", "hello world", false),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-b]llo", "hbllo", true),
+            ("h[b-a]llo", "hallo", true),
+            ("h[a-b]llo", "hcllo", false),
+            ("h\\*llo", "h*llo", true),
+            ("h\\*llo", "hello", false),
+            ("user:*:name", "user:1000:name", true),
+            ("user:*:name", "user:1000:email", false),
+            ("a*", "", false),
+            ("", "", true),
+        ];
+        for (pattern, string, expected) in cases {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), string.as_bytes(), false),
+                *expected,
+                "{} against {}",
+                pattern,
+                string
+            );
+        }
+        assert!(glob_match(b"HELLO", b"hello", true));
+        assert!(glob_match(b"h[A-Z]llo", b"hello", true));
+        assert!(!glob_match(b"HELLO", b"hello", false));
+    }
+}
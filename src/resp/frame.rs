@@ -1,6 +1,6 @@
 use crate::{
-    BulkString, RespArray, RespDecode, RespError, RespMap, RespNull, RespNullArray,
-    RespNullBulkString, RespSet, SimpleError, SimpleString,
+    format_score, BulkString, RespArray, RespDecode, RespError, RespMap, RespNull, RespNullArray,
+    RespNullBulkString, RespPush, RespSet, SimpleError, SimpleString,
 };
 use bytes::BytesMut;
 use enum_dispatch::enum_dispatch;
@@ -20,6 +20,7 @@ pub enum RespFrame {
     Double(f64),
     Map(RespMap),
     Set(RespSet),
+    Push(RespPush),
 }
 
 impl RespDecode for RespFrame {
@@ -82,6 +83,10 @@ impl RespDecode for RespFrame {
                 let frame = RespSet::decode(buf)?;
                 Ok(frame.into())
             }
+            Some(b'>') => {
+                let frame = RespPush::decode(buf)?;
+                Ok(frame.into())
+            }
             None => Err(RespError::NotComplete),
             _ => Err(RespError::InvalidFrameType(format!(
                 "Invalid frame type: {:?}",
@@ -103,11 +108,55 @@ impl RespDecode for RespFrame {
             Some(b',') => f64::expect_length(buf),
             Some(b'%') => RespMap::expect_length(buf),
             Some(b'~') => RespSet::expect_length(buf),
+            Some(b'>') => RespPush::expect_length(buf),
             _ => Err(RespError::NotComplete),
         }
     }
 }
 
+impl RespFrame {
+    /// Downgrades the frame to the types RESP2 clients understand: nulls become null bulk
+    /// strings, maps flat arrays of keys and values, sets and pushes arrays, booleans
+    /// integers and doubles bulk strings.
+    pub fn into_resp2(self) -> RespFrame {
+        match self {
+            RespFrame::Null(_) => RespNullBulkString.into(),
+            RespFrame::Boolean(value) => (value as i64).into(),
+            RespFrame::Double(value) => BulkString::new(format_score(value)).into(),
+            RespFrame::Array(array) => RespArray::new(
+                array
+                    .0
+                    .into_iter()
+                    .map(RespFrame::into_resp2)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Set(set) => RespArray::new(
+                set.0
+                    .into_iter()
+                    .map(RespFrame::into_resp2)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Push(push) => RespArray::new(
+                push.0
+                    .into_iter()
+                    .map(RespFrame::into_resp2)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            RespFrame::Map(map) => RespArray::new(
+                map.0
+                    .into_iter()
+                    .flat_map(|(key, value)| [BulkString::new(key).into(), value.into_resp2()])
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            frame => frame,
+        }
+    }
+}
+
 impl From<&str> for RespFrame {
     fn from(value: &str) -> Self {
         SimpleString(value.into()).into()
@@ -125,3 +174,49 @@ impl<const N: usize> From<&[u8; N]> for RespFrame {
         BulkString(s.to_vec()).into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_resp2() {
+        let mut map = RespMap::new();
+        map.insert("ok".to_string(), true.into());
+        map.insert("score".to_string(), 1.5.into());
+        map.insert(
+            "tags".to_string(),
+            RespSet::new(vec![RespNull.into()]).into(),
+        );
+        let frame: RespFrame = map.into();
+        assert_eq!(
+            frame.into_resp2(),
+            RespArray::new(vec![
+                BulkString::new("ok").into(),
+                1.into(),
+                BulkString::new("score").into(),
+                BulkString::new("1.5").into(),
+                BulkString::new("tags").into(),
+                RespArray::new(vec![RespNullBulkString.into()]).into(),
+            ])
+            .into()
+        );
+
+        // RESP2 clients receive pub/sub messages as plain arrays
+        let push: RespFrame = RespPush::new(vec![
+            BulkString::new("message").into(),
+            BulkString::new("news").into(),
+            RespNull.into(),
+        ])
+        .into();
+        assert_eq!(
+            push.into_resp2(),
+            RespArray::new(vec![
+                BulkString::new("message").into(),
+                BulkString::new("news").into(),
+                RespNullBulkString.into(),
+            ])
+            .into()
+        );
+    }
+}